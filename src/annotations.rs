@@ -0,0 +1,93 @@
+use anyhow::Result;
+use gtk::glib;
+
+use std::{
+    collections::HashMap,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use crate::config::APP_ID;
+
+/// Notes of each database as a mapping of key hashes to notes, keyed by
+/// database name
+type EnvNotes = HashMap<String, HashMap<String, String>>;
+
+/// Free-text notes attached to keys of an env.
+///
+/// These are stored in the user data directory rather than in the env, so
+/// they can be added to read-only envs and don't alter the data.
+#[derive(Debug, Default)]
+pub struct Annotations {
+    env_path: String,
+    notes: EnvNotes,
+}
+
+impl Annotations {
+    /// Loads the notes of the env at `env_path`.
+    pub fn load(env_path: &Path) -> Result<Self> {
+        let env_path = env_path.to_string_lossy().to_string();
+        let notes = read_all()?.remove(&env_path).unwrap_or_default();
+
+        Ok(Self { env_path, notes })
+    }
+
+    /// Returns the note attached to `key` of the database called `db_name`.
+    pub fn get(&self, db_name: &str, key: &[u8]) -> Option<&str> {
+        self.notes
+            .get(db_name)
+            .and_then(|notes| notes.get(&key_hash(key)))
+            .map(|note| note.as_str())
+    }
+
+    /// Attaches `note` to `key` of the database called `db_name`, or removes
+    /// the note if it is empty, then saves all notes of the env.
+    pub fn set(&mut self, db_name: &str, key: &[u8], note: &str) -> Result<()> {
+        if note.is_empty() {
+            if let Some(notes) = self.notes.get_mut(db_name) {
+                notes.remove(&key_hash(key));
+                if notes.is_empty() {
+                    self.notes.remove(db_name);
+                }
+            }
+        } else {
+            self.notes
+                .entry(db_name.to_string())
+                .or_default()
+                .insert(key_hash(key), note.to_string());
+        }
+
+        let mut all_notes = read_all()?;
+        if self.notes.is_empty() {
+            all_notes.remove(&self.env_path);
+        } else {
+            all_notes.insert(self.env_path.clone(), self.notes.clone());
+        }
+
+        let path = file_path();
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(&path, serde_json::to_vec_pretty(&all_notes)?)?;
+
+        Ok(())
+    }
+}
+
+/// Reads the notes of all envs, keyed by env path.
+fn read_all() -> Result<HashMap<String, EnvNotes>> {
+    match fs::read(file_path()) {
+        Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(HashMap::new()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn file_path() -> PathBuf {
+    glib::user_data_dir().join(APP_ID).join("annotations.json")
+}
+
+fn key_hash(key: &[u8]) -> String {
+    glib::compute_checksum_for_data(glib::ChecksumType::Sha256, key)
+        .unwrap()
+        .to_string()
+}
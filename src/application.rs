@@ -1,12 +1,33 @@
 use adw::subclass::prelude::*;
+use anyhow::{Context, Result};
 use gettextrs::gettext;
-use gtk::{gio, glib, prelude::*};
+use gtk::{gdk, gio, glib, glib::translate::IntoGlib, pango, prelude::*};
+
+use std::path::Path;
 
 use crate::{
     config::{APP_ID, PKGDATADIR, PROFILE, VERSION},
+    preferences_window::PreferencesWindow,
+    utils,
     window::Window,
 };
 
+/// Interface that lets other tools open envs and show keys in the viewer
+const DBUS_INTERFACE_XML: &str = r#"
+<node>
+  <interface name="io.github.seadve.LmdbViewer">
+    <method name="OpenEnvironment">
+      <arg type="s" name="path" direction="in"/>
+    </method>
+    <method name="ShowKey">
+      <arg type="s" name="path" direction="in"/>
+      <arg type="s" name="db" direction="in"/>
+      <arg type="ay" name="key" direction="in"/>
+    </method>
+  </interface>
+</node>
+"#;
+
 mod imp {
     use std::cell::OnceCell;
 
@@ -17,6 +38,8 @@ mod imp {
     #[derive(Debug, Default)]
     pub struct Application {
         pub(super) window: OnceCell<WeakRef<Window>>,
+        /// Kept so changes to the font are applied while the app runs
+        pub(super) font_settings: OnceCell<gio::Settings>,
     }
 
     #[glib::object_subclass]
@@ -45,15 +68,28 @@ mod imp {
             window.present();
         }
 
+        fn open(&self, files: &[gio::File], _hint: &str) {
+            let obj = self.obj();
+
+            obj.activate();
+
+            if let Some(file) = files.first() {
+                obj.open_file(file);
+            }
+        }
+
         fn startup(&self) {
             self.parent_startup();
 
             let obj = self.obj();
 
             gtk::Window::set_default_icon_name(APP_ID);
+            sourceview::init();
 
             obj.setup_gactions();
             obj.setup_accels();
+            obj.setup_dbus_interface();
+            obj.setup_font();
         }
     }
 
@@ -72,6 +108,7 @@ impl Application {
         glib::Object::builder()
             .property("application-id", APP_ID)
             .property("resource-base-path", "/io/github/seadve/LmdbViewer/")
+            .property("flags", gio::ApplicationFlags::HANDLES_OPEN)
             .build()
     }
 
@@ -79,6 +116,29 @@ impl Application {
         self.imp().window.get().unwrap().upgrade().unwrap()
     }
 
+    /// Opens the env containing `file`, which may be the env directory itself,
+    /// one of its files, or the data file of a single-file env.
+    fn open_file(&self, file: &gio::File) {
+        let window = self.main_window();
+
+        let file = file.clone();
+        glib::spawn_future_local(async move {
+            let env_path = file
+                .path()
+                .context("File has no path")
+                .and_then(|path| utils::env_path(&path));
+            let result = match env_path {
+                Ok(env_path) => window.load_env_or_copy(&env_path).await,
+                Err(err) => Err(err),
+            };
+
+            if let Err(err) = result {
+                tracing::error!("Failed to open `{}`: {:?}", file.uri(), &err);
+                window.add_error_toast(&gettext("Failed to open env"), &err);
+            }
+        });
+    }
+
     fn setup_gactions(&self) {
         let action_quit = gio::ActionEntry::builder("quit")
             .activate(move |app: &Self, _, _| {
@@ -88,17 +148,132 @@ impl Application {
             })
             .build();
 
+        let action_preferences = gio::ActionEntry::builder("preferences")
+            .activate(|app: &Self, _, _| {
+                let window = PreferencesWindow::new(&app.main_window());
+                window.present();
+            })
+            .build();
+
         let action_about = gio::ActionEntry::builder("about")
             .activate(|app: &Self, _, _| {
                 app.show_about_dialog();
             })
             .build();
-        self.add_action_entries([action_quit, action_about]);
+        self.add_action_entries([action_quit, action_preferences, action_about]);
     }
 
     fn setup_accels(&self) {
+        self.set_accels_for_action("app.preferences", &["<Control>comma"]);
         self.set_accels_for_action("app.quit", &["<Control>q"]);
         self.set_accels_for_action("window.close", &["<Control>w"]);
+        self.set_accels_for_action("win.show-command-palette", &["<Control><Shift>p"]);
+        self.set_accels_for_action("win.open-env", &["<Control>o"]);
+        self.set_accels_for_action("win.reload-db", &["F5"]);
+        self.set_accels_for_action("win.reload-env", &["<Control>r"]);
+        self.set_accels_for_action("win.print", &["<Control>p"]);
+        self.set_accels_for_action("win.search", &["<Control>f"]);
+        self.set_accels_for_action("win.go-to-key", &["<Control>g"]);
+        self.set_accels_for_action("win.go-to-row", &["<Control>l"]);
+        self.set_accels_for_action("win.undo", &["<Control>z"]);
+        self.set_accels_for_action("win.redo", &["<Control><Shift>z"]);
+        self.set_accels_for_action("win.switch-database", &["<Control>k"]);
+        self.set_accels_for_action("win.next-database", &["<Control>Page_Down"]);
+        self.set_accels_for_action("win.previous-database", &["<Control>Page_Up"]);
+    }
+
+    /// Applies the font picked in the preferences to keys and values, which
+    /// are all styled as monospace.
+    fn setup_font(&self) {
+        let Some(display) = gdk::Display::default() else {
+            return;
+        };
+
+        let provider = gtk::CssProvider::new();
+        gtk::style_context_add_provider_for_display(
+            &display,
+            &provider,
+            gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
+        );
+
+        let settings = gio::Settings::new(APP_ID);
+        let update_font = move |settings: &gio::Settings| {
+            let css = if settings.boolean("use-system-font") {
+                String::new()
+            } else {
+                let font_desc =
+                    pango::FontDescription::from_string(&settings.string("custom-font"));
+                font_css(&font_desc)
+            };
+            provider.load_from_string(&css);
+        };
+        update_font(&settings);
+        settings.connect_changed(None, move |settings, key| {
+            if matches!(key, "use-system-font" | "custom-font") {
+                update_font(settings);
+            }
+        });
+        self.imp().font_settings.set(settings).unwrap();
+    }
+
+    fn setup_dbus_interface(&self) {
+        let Some(connection) = self.dbus_connection() else {
+            return;
+        };
+
+        let interface_info = gio::DBusNodeInfo::for_xml(DBUS_INTERFACE_XML)
+            .unwrap()
+            .lookup_interface("io.github.seadve.LmdbViewer")
+            .unwrap();
+        let object_path = self.dbus_object_path().unwrap();
+
+        let result = connection.register_object(
+            &object_path,
+            &interface_info,
+            |_, _, _, _, method_name, parameters, invocation| {
+                // The closure must be `Send`, so get the app instead of capturing it
+                let app = gio::Application::default()
+                    .and_downcast::<Application>()
+                    .unwrap();
+
+                match app.handle_dbus_method_call(method_name, &parameters) {
+                    Ok(()) => invocation.return_value(None),
+                    Err(err) => {
+                        tracing::error!("Failed to handle `{}` call: {:?}", method_name, &err);
+                        invocation.return_error(gio::IOErrorEnum::Failed, &format!("{:#}", err));
+                    }
+                }
+            },
+            |_, _, _, _, _| unreachable!("interface has no properties"),
+            |_, _, _, _, _, _| unreachable!("interface has no properties"),
+        );
+
+        if let Err(err) = result {
+            tracing::warn!("Failed to register D-Bus interface: {:?}", &err);
+        }
+    }
+
+    fn handle_dbus_method_call(&self, method_name: &str, parameters: &glib::Variant) -> Result<()> {
+        self.activate();
+        let window = self.main_window();
+
+        match method_name {
+            "OpenEnvironment" => {
+                let (path,) = parameters
+                    .get::<(String,)>()
+                    .context("Invalid parameters")?;
+                window.load_env(Path::new(&path))?;
+            }
+            "ShowKey" => {
+                let (path, db_name, key) = parameters
+                    .get::<(String, String, Vec<u8>)>()
+                    .context("Invalid parameters")?;
+                window.show_key(Path::new(&path), &db_name, &key)?;
+            }
+            _ => anyhow::bail!("Unknown method `{}`", method_name),
+        }
+
+        Ok(())
     }
 
     fn show_about_dialog(&self) {
@@ -130,3 +305,29 @@ impl Default for Application {
         Self::new()
     }
 }
+
+/// Returns CSS that styles monospace text in `font_desc`.
+fn font_css(font_desc: &pango::FontDescription) -> String {
+    let mut declarations = Vec::new();
+    if let Some(family) = font_desc.family() {
+        declarations.push(format!("font-family: \"{}\";", family.replace('"', "\\\"")));
+    }
+    if font_desc.size() > 0 {
+        let size = font_desc.size() as f64 / pango::SCALE as f64;
+        let unit = if font_desc.is_size_absolute() {
+            "px"
+        } else {
+            "pt"
+        };
+        declarations.push(format!("font-size: {}{};", size, unit));
+    }
+    declarations.push(format!("font-weight: {};", font_desc.weight().into_glib()));
+    let style = match font_desc.style() {
+        pango::Style::Italic => "italic",
+        pango::Style::Oblique => "oblique",
+        _ => "normal",
+    };
+    declarations.push(format!("font-style: {};", style));
+
+    format!(".monospace {{ {} }}", declarations.join(" "))
+}
@@ -0,0 +1,173 @@
+use anyhow::{Context, Result};
+
+/// Size of the headers and data blocks of tar archives
+const TAR_BLOCK_LEN: usize = 512;
+
+/// Archive formats whose contents can be listed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Zip,
+    Tar,
+}
+
+impl ArchiveFormat {
+    pub fn detect(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(b"PK\x03\x04") || bytes.starts_with(b"PK\x05\x06") {
+            Some(Self::Zip)
+        } else if bytes.len() >= TAR_BLOCK_LEN && &bytes[257..262] == b"ustar" {
+            Some(Self::Tar)
+        } else {
+            None
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Zip => "ZIP",
+            Self::Tar => "tar",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Zip => "zip",
+            Self::Tar => "tar",
+        }
+    }
+
+    /// Lists the entries of the archive in `bytes`, in the order they are
+    /// stored.
+    pub fn list(self, bytes: &[u8]) -> Result<Vec<ArchiveEntry>> {
+        match self {
+            Self::Zip => list_zip(bytes),
+            Self::Tar => list_tar(bytes),
+        }
+    }
+}
+
+/// A file or directory in an archive.
+#[derive(Debug, Clone)]
+pub struct ArchiveEntry {
+    pub path: String,
+    /// Uncompressed size, in bytes
+    pub size: u64,
+    pub is_dir: bool,
+}
+
+/// Reads the central directory, which is found through the end of central
+/// directory record at the end of `bytes`.
+fn list_zip(bytes: &[u8]) -> Result<Vec<ArchiveEntry>> {
+    const EOCD_SIGNATURE: &[u8] = b"PK\x05\x06";
+    const EOCD_LEN: usize = 22;
+    const CENTRAL_HEADER_SIGNATURE: &[u8] = b"PK\x01\x02";
+    const CENTRAL_HEADER_LEN: usize = 46;
+
+    // The record is only followed by a comment of up to u16::MAX bytes
+    let search_start = bytes.len().saturating_sub(EOCD_LEN + u16::MAX as usize);
+    let eocd_offset = (search_start..=bytes.len().saturating_sub(EOCD_LEN))
+        .rev()
+        .find(|&offset| bytes[offset..].starts_with(EOCD_SIGNATURE))
+        .context("End of central directory not found")?;
+    let eocd = &bytes[eocd_offset..];
+
+    let n_entries = read_u16(eocd, 10)?;
+    let mut offset = read_u32(eocd, 16)? as usize;
+
+    let mut entries = Vec::with_capacity(n_entries as usize);
+    for _ in 0..n_entries {
+        let header = bytes
+            .get(offset..offset + CENTRAL_HEADER_LEN)
+            .context("Central directory is truncated")?;
+        anyhow::ensure!(
+            header.starts_with(CENTRAL_HEADER_SIGNATURE),
+            "Invalid central directory header at {}",
+            offset
+        );
+
+        let size = read_u32(header, 24)?;
+        let name_len = read_u16(header, 28)? as usize;
+        let extra_len = read_u16(header, 30)? as usize;
+        let comment_len = read_u16(header, 32)? as usize;
+
+        let name_start = offset + CENTRAL_HEADER_LEN;
+        let name = bytes
+            .get(name_start..name_start + name_len)
+            .context("Entry name is truncated")?;
+        let path = String::from_utf8_lossy(name).to_string();
+
+        entries.push(ArchiveEntry {
+            is_dir: path.ends_with('/'),
+            path,
+            size: size as u64,
+        });
+        offset = name_start + name_len + extra_len + comment_len;
+    }
+
+    Ok(entries)
+}
+
+/// Reads the header block preceding each entry's data, until the blocks of
+/// zeros that end the archive.
+fn list_tar(bytes: &[u8]) -> Result<Vec<ArchiveEntry>> {
+    let mut entries = Vec::new();
+    let mut offset = 0;
+    while let Some(header) = bytes.get(offset..offset + TAR_BLOCK_LEN) {
+        if header.iter().all(|&byte| byte == 0) {
+            break;
+        }
+
+        let size = parse_octal(&header[124..136])
+            .with_context(|| format!("Invalid entry size at {}", offset))?;
+        let name = nul_terminated(&header[0..100]);
+        let prefix = nul_terminated(&header[345..500]);
+        let path = if prefix.is_empty() {
+            String::from_utf8_lossy(name).to_string()
+        } else {
+            format!(
+                "{}/{}",
+                String::from_utf8_lossy(prefix),
+                String::from_utf8_lossy(name)
+            )
+        };
+
+        entries.push(ArchiveEntry {
+            is_dir: header[156] == b'5' || path.ends_with('/'),
+            path,
+            size,
+        });
+
+        let n_data_blocks = size.div_ceil(TAR_BLOCK_LEN as u64) as usize;
+        offset += TAR_BLOCK_LEN * (1 + n_data_blocks);
+    }
+
+    Ok(entries)
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> Result<u16> {
+    let bytes = bytes
+        .get(offset..offset + 2)
+        .context("Record is truncated")?;
+    Ok(u16::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32> {
+    let bytes = bytes
+        .get(offset..offset + 4)
+        .context("Record is truncated")?;
+    Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+/// Parses a number field of a tar header, which is octal digits padded with
+/// spaces or NULs.
+fn parse_octal(field: &[u8]) -> Option<u64> {
+    let text = std::str::from_utf8(field).ok()?;
+    let digits = text.trim_matches(|c| c == ' ' || c == '\0');
+    if digits.is_empty() {
+        return Some(0);
+    }
+    u64::from_str_radix(digits, 8).ok()
+}
+
+fn nul_terminated(field: &[u8]) -> &[u8] {
+    field.split(|&byte| byte == 0).next().unwrap_or_default()
+}
@@ -0,0 +1,130 @@
+use anyhow::{anyhow, Result};
+use gtk::glib;
+use heed::{types::ByteSlice, DatabaseFlags};
+
+use std::{
+    fmt,
+    fs::File,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use crate::{database, mdbx, snapshot::Snapshot, utils};
+
+/// Number of bytes at the start of a data file that are enough to tell its
+/// format from the magic number in its first meta page
+const SIGNATURE_LEN: u64 = 32;
+
+/// Formats of envs that can be opened, told apart by the signatures of their
+/// data files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackendKind {
+    Lmdb,
+    /// libmdbx, a fork of LMDB with an incompatible format, which is only
+    /// read
+    Mdbx,
+}
+
+impl BackendKind {
+    /// Detects the format of the data file at `data_path`, returning `None`
+    /// if it has neither signature.
+    pub fn detect(data_path: &Path) -> io::Result<Option<Self>> {
+        let mut signature = Vec::new();
+        File::open(data_path)?
+            .take(SIGNATURE_LEN)
+            .read_to_end(&mut signature)?;
+
+        Ok(Self::from_signature(&signature))
+    }
+
+    /// Returns the format of the data that starts with `bytes`, e.g., a value
+    /// that holds a nested env.
+    pub fn from_signature(bytes: &[u8]) -> Option<Self> {
+        if utils::is_lmdb_data(bytes) {
+            Some(Self::Lmdb)
+        } else if mdbx::is_mdbx_data(bytes) {
+            Some(Self::Mdbx)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the path of the data file of the env at `path`, which has the
+    /// name of its format in a directory, or is the path itself for an env
+    /// made of a single file.
+    pub fn data_path(path: &Path) -> PathBuf {
+        let mdbx_data_path = path.join(mdbx::DATA_FILE_NAME);
+        if path.is_dir() && mdbx_data_path.is_file() {
+            mdbx_data_path
+        } else {
+            utils::data_path(path)
+        }
+    }
+
+    pub fn display_name(self) -> &'static str {
+        match self {
+            Self::Lmdb => "LMDB",
+            Self::Mdbx => "MDBX",
+        }
+    }
+}
+
+/// Entries of a database read in a single txn, with its flags.
+pub struct DatabaseContents {
+    pub entries: Vec<(glib::Bytes, glib::Bytes)>,
+    pub flags: DatabaseFlags,
+    /// Id of the txn the entries were read in
+    pub txn_id: u64,
+}
+
+/// Reading of an env, whatever its format. LMDB envs can also be written
+/// and snapshotted, which is done on the `heed::Env` itself.
+pub trait Backend: fmt::Debug + Send + Sync {
+    fn kind(&self) -> BackendKind;
+
+    fn path(&self) -> &Path;
+
+    /// Returns the names of the databases, starting with `None` for the
+    /// unnamed one, like [`database::names`].
+    fn names(&self) -> Result<Vec<Option<String>>>;
+
+    /// Reads all entries of the database called `name`, in database order.
+    fn read_database(&self, name: Option<&str>) -> Result<DatabaseContents>;
+}
+
+impl Backend for heed::Env {
+    fn kind(&self) -> BackendKind {
+        BackendKind::Lmdb
+    }
+
+    fn path(&self) -> &Path {
+        heed::Env::path(self)
+    }
+
+    fn names(&self) -> Result<Vec<Option<String>>> {
+        database::names(self)
+    }
+
+    fn read_database(&self, name: Option<&str>) -> Result<DatabaseContents> {
+        let snapshot = Snapshot::latest(self)?;
+        let (entries, flags) = snapshot.read(|env, rtxn| {
+            let db = env
+                .open_database::<ByteSlice, ByteSlice>(rtxn, name)?
+                .ok_or_else(|| anyhow!("database not found"))?;
+            let entries = db
+                .iter(rtxn)?
+                .map(|item| {
+                    let (key, data) = item?;
+                    Ok::<_, heed::Error>((glib::Bytes::from(key), glib::Bytes::from(data)))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok((entries, database::read_flags(env, rtxn, name)?))
+        })?;
+
+        Ok(DatabaseContents {
+            entries,
+            flags,
+            txn_id: snapshot.txn_id(),
+        })
+    }
+}
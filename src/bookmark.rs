@@ -0,0 +1,67 @@
+use gtk::{gio, prelude::*};
+
+use std::{collections::HashMap, path::Path};
+
+use crate::config::APP_ID;
+
+/// Bookmarks of each env as `(db name, key, label)`, keyed by env path
+type EnvBookmarks = HashMap<String, Vec<(String, Option<Vec<u8>>, String)>>;
+
+/// A bookmarked database, or a key in it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bookmark {
+    pub db_name: String,
+    /// The bookmarked key, or `None` if the whole database is bookmarked
+    pub key: Option<Vec<u8>>,
+    /// User-provided label, which may be empty
+    pub label: String,
+}
+
+impl Bookmark {
+    /// Loads the bookmarks of the env at `env_path` from the settings.
+    pub fn load_all(env_path: &Path) -> Vec<Self> {
+        let settings = gio::Settings::new(APP_ID);
+
+        settings
+            .value("bookmarks")
+            .get::<EnvBookmarks>()
+            .unwrap()
+            .remove(env_path.to_string_lossy().as_ref())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(db_name, key, label)| Self {
+                db_name,
+                key,
+                label,
+            })
+            .collect()
+    }
+
+    /// Saves `bookmarks` as the bookmarks of the env at `env_path`.
+    pub fn save_all(env_path: &Path, bookmarks: &[Self]) {
+        let settings = gio::Settings::new(APP_ID);
+
+        let mut env_bookmarks = settings.value("bookmarks").get::<EnvBookmarks>().unwrap();
+        let env_path = env_path.to_string_lossy().to_string();
+
+        if bookmarks.is_empty() {
+            env_bookmarks.remove(&env_path);
+        } else {
+            let bookmarks = bookmarks
+                .iter()
+                .map(|bookmark| {
+                    (
+                        bookmark.db_name.clone(),
+                        bookmark.key.clone(),
+                        bookmark.label.clone(),
+                    )
+                })
+                .collect();
+            env_bookmarks.insert(env_path, bookmarks);
+        }
+
+        if let Err(err) = settings.set_value("bookmarks", &env_bookmarks.to_variant()) {
+            tracing::warn!("Failed to save bookmarks: {:?}", &err);
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+/// Returns the readings of the bytes at the start of `bytes` as numbers,
+/// a UUID, and IP addresses, like a hex editor's data inspector, as pairs of
+/// type names and values.
+pub fn inspect(bytes: &[u8]) -> Vec<(String, String)> {
+    let mut readings = Vec::new();
+
+    if let Some(&byte) = bytes.first() {
+        readings.push(("i8".to_string(), (byte as i8).to_string()));
+        readings.push(("u8".to_string(), byte.to_string()));
+    }
+
+    macro_rules! push_number {
+        ($($type:ty),*) => {
+            $(
+                if let Some(chunk) = bytes.get(..std::mem::size_of::<$type>()) {
+                    let chunk = chunk.try_into().unwrap();
+                    readings.push((
+                        format!("{} LE", stringify!($type)),
+                        <$type>::from_le_bytes(chunk).to_string(),
+                    ));
+                    readings.push((
+                        format!("{} BE", stringify!($type)),
+                        <$type>::from_be_bytes(chunk).to_string(),
+                    ));
+                }
+            )*
+        };
+    }
+    push_number!(i16, u16, i32, u32, i64, u64, f32, f64);
+
+    if let Some(chunk) = bytes.get(..4) {
+        let octets: [u8; 4] = chunk.try_into().unwrap();
+        readings.push(("IPv4".to_string(), Ipv4Addr::from(octets).to_string()));
+    }
+
+    if let Some(chunk) = bytes.get(..16) {
+        let octets: [u8; 16] = chunk.try_into().unwrap();
+        readings.push(("UUID".to_string(), uuid_string(&octets)));
+        readings.push(("IPv6".to_string(), Ipv6Addr::from(octets).to_string()));
+    }
+
+    readings
+}
+
+/// Formats `bytes` as a UUID in the usual 8-4-4-4-12 hex groups.
+fn uuid_string(bytes: &[u8; 16]) -> String {
+    let hex = bytes
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
@@ -0,0 +1,80 @@
+use gtk::{glib, pango};
+
+use crate::{decoder, non_printable_display};
+
+/// How the bytes of the key or value column are rendered.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, glib::Enum)]
+#[enum_type(name = "LvCellRendering")]
+pub enum CellRendering {
+    /// As text, following the display mode and encodings
+    #[default]
+    #[enum_value(name = "Text", nick = "text")]
+    Text,
+    #[enum_value(name = "Hexadecimal", nick = "hex")]
+    Hex,
+    /// As JSON, decoded with the built-in decoder of the detected format, or
+    /// as text if it has none
+    #[enum_value(name = "Decoded", nick = "decoded")]
+    Decoded,
+}
+
+impl CellRendering {
+    /// Renders `bytes`, or returns `None` if they should be rendered as text.
+    pub fn render(self, bytes: &[u8]) -> Option<String> {
+        match self {
+            Self::Text => None,
+            Self::Hex => Some(non_printable_display::to_hex(bytes)),
+            Self::Decoded => decoder::decode_detected(bytes).map(|decoded| decoded.to_string()),
+        }
+    }
+}
+
+/// How keys and values that don't fit their columns are wrapped.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, glib::Enum)]
+#[enum_type(name = "LvCellWrapMode")]
+pub enum CellWrapMode {
+    /// Each line of the text ellipsized at the end
+    #[default]
+    #[enum_value(name = "None", nick = "none")]
+    None,
+    #[enum_value(name = "Characters", nick = "char")]
+    Char,
+    #[enum_value(name = "Words", nick = "word")]
+    Word,
+    #[enum_value(name = "Words, Then Characters", nick = "word-char")]
+    WordChar,
+}
+
+impl CellWrapMode {
+    /// Wraps the text of `label` in this mode, ellipsizing it after
+    /// `max_lines` lines.
+    pub fn apply(self, label: &gtk::Label, max_lines: u32) {
+        let wrap_mode = match self {
+            Self::None => None,
+            Self::Char => Some(pango::WrapMode::Char),
+            Self::Word => Some(pango::WrapMode::Word),
+            Self::WordChar => Some(pango::WrapMode::WordChar),
+        };
+
+        label.set_wrap(wrap_mode.is_some());
+        label.set_lines(max_lines as i32);
+        if let Some(wrap_mode) = wrap_mode {
+            label.set_wrap_mode(wrap_mode);
+        }
+    }
+}
+
+/// Keeps the first `max_lines` lines of `text`, ending it with an ellipsis if
+/// any are dropped.
+pub fn clamp_lines(text: &str, max_lines: u32) -> String {
+    let mut lines = text.split('\n');
+    let mut clamped = lines
+        .by_ref()
+        .take(max_lines as usize)
+        .collect::<Vec<_>>()
+        .join("\n");
+    if lines.next().is_some() {
+        clamped.push('…');
+    }
+    clamped
+}
@@ -0,0 +1,226 @@
+use anyhow::{Context, Result};
+use gtk::{glib, prelude::*};
+use heed::{types::ByteSlice, DatabaseFlags, EnvFlags};
+
+use std::{
+    collections::HashMap,
+    fmt,
+    io::{self, Write},
+    path::Path,
+};
+
+use crate::{
+    database::{self, Database},
+    database_item::DatabaseItem,
+    export::{ExportEntry, ExportFormat},
+    key_display_mode::KeyDisplayMode,
+    non_printable_display::NonPrintableDisplay,
+    text_encoding::TextEncoding,
+    utils,
+};
+
+const USAGE: &str = "\
+Usage:
+  lmdb-viewer dump <env> [--db NAME] [--format json|csv|mdb]
+  lmdb-viewer stat <env>
+  lmdb-viewer get <env> <key> [--db NAME]";
+
+/// Invalid command-line arguments.
+#[derive(Debug)]
+struct UsageError(String);
+
+impl fmt::Display for UsageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}\n\n{}", self.0, USAGE)
+    }
+}
+
+impl std::error::Error for UsageError {}
+
+/// Runs the subcommand at the start of `args` without the GUI, or returns
+/// `None` if there is none.
+pub fn run(args: &[String]) -> Option<glib::ExitCode> {
+    let (command, args) = args.split_first()?;
+
+    let result = match command.as_str() {
+        "dump" => dump(args),
+        "stat" => stat(args),
+        "get" => get(args),
+        _ => return None,
+    };
+
+    let exit_code = match result {
+        Ok(()) => glib::ExitCode::SUCCESS,
+        Err(err) if err.is::<UsageError>() => {
+            eprintln!("{}", err);
+            glib::ExitCode::from(2)
+        }
+        Err(err) => {
+            eprintln!("Error: {:?}", err);
+            glib::ExitCode::FAILURE
+        }
+    };
+    Some(exit_code)
+}
+
+/// Writes all entries of a database to stdout.
+fn dump(args: &[String]) -> Result<()> {
+    let (positional, options) = parse_args(args, &["db", "format"])?;
+    let [env_path] = positional[..] else {
+        return Err(UsageError("Expected an env path".into()).into());
+    };
+    let format = match options.get("format") {
+        Some(name) => ExportFormat::from_name(name)
+            .ok_or_else(|| UsageError(format!("Unknown format `{}`", name)))?,
+        None => ExportFormat::Json,
+    };
+    let db_name = options.get("db").copied();
+
+    let env = open_env(env_path)?;
+    let db = Database::load(&env, db_name).context("Failed to load db")?;
+    let is_integer_key = db.flags().contains(DatabaseFlags::INTEGER_KEY);
+
+    let items = (0..db.n_items())
+        .map(|position| db.item(position).and_downcast::<DatabaseItem>().unwrap())
+        .collect::<Vec<_>>();
+    let keys = items.iter().map(|item| item.key()).collect::<Vec<_>>();
+    let values = items.iter().map(|item| item.data()).collect::<Vec<_>>();
+    let entries = keys
+        .iter()
+        .zip(&values)
+        .map(|(key, value)| ExportEntry {
+            key,
+            value,
+            key_text: match KeyDisplayMode::Auto.integer(key, is_integer_key) {
+                Some(integer) => integer.to_string(),
+                None => NonPrintableDisplay::Escape.render(key, TextEncoding::Utf8),
+            },
+            value_text: NonPrintableDisplay::Escape.render(value, TextEncoding::Utf8),
+        })
+        .collect::<Vec<_>>();
+
+    let bytes = format.serialize(db_name, db.flags(), &entries)?;
+    io::stdout().lock().write_all(&bytes)?;
+
+    Ok(())
+}
+
+/// Prints information about an env and the entry count of its databases.
+fn stat(args: &[String]) -> Result<()> {
+    let (positional, _) = parse_args(args, &[])?;
+    let [env_path] = positional[..] else {
+        return Err(UsageError("Expected an env path".into()).into());
+    };
+
+    let env = open_env(env_path)?;
+    let info = env.info();
+
+    let mut stdout = io::stdout().lock();
+    writeln!(stdout, "Path: {}", env.path().display())?;
+    writeln!(
+        stdout,
+        "Size on disk: {}",
+        glib::format_size(env.real_disk_size()?)
+    )?;
+    writeln!(
+        stdout,
+        "Map size: {}",
+        glib::format_size(info.map_size as u64)
+    )?;
+    writeln!(stdout, "Last page number: {}", info.last_page_number)?;
+    writeln!(stdout, "Last transaction id: {}", info.last_txn_id)?;
+    writeln!(
+        stdout,
+        "Readers: {}/{}",
+        info.number_of_readers, info.maximum_number_of_readers
+    )?;
+
+    let rtxn = env.read_txn()?;
+    // Keys of the unnamed db that aren't databases are left out
+    let (names, _) = database::scan_names(&env, &rtxn)?;
+
+    writeln!(stdout, "Databases:")?;
+    for name in names.iter().flatten() {
+        let Some(db) = env.open_database::<ByteSlice, ByteSlice>(&rtxn, Some(name))? else {
+            continue;
+        };
+        let flags = database::read_flags(&env, &rtxn, Some(name))?;
+        let flag_names = flags
+            .iter_names()
+            .map(|(flag_name, _)| flag_name)
+            .collect::<Vec<_>>();
+
+        write!(stdout, "  {}: {} entries", name, db.len(&rtxn)?)?;
+        if !flag_names.is_empty() {
+            write!(stdout, " ({})", flag_names.join(", "))?;
+        }
+        writeln!(stdout)?;
+    }
+
+    Ok(())
+}
+
+/// Writes the raw value of a key to stdout.
+fn get(args: &[String]) -> Result<()> {
+    let (positional, options) = parse_args(args, &["db"])?;
+    let [env_path, key] = positional[..] else {
+        return Err(UsageError("Expected an env path and a key".into()).into());
+    };
+    let db_name = options.get("db").copied();
+
+    let env = open_env(env_path)?;
+    let rtxn = env.read_txn()?;
+    let db = env
+        .open_database::<ByteSlice, ByteSlice>(&rtxn, db_name)?
+        .context("Database not found")?;
+    let value = db
+        .get(&rtxn, key.as_bytes())?
+        .with_context(|| format!("Key `{}` not found", key))?;
+
+    io::stdout().lock().write_all(value)?;
+
+    Ok(())
+}
+
+/// Opens the env at `path` read-only and without locking, so envs in read-only
+/// locations can be inspected.
+fn open_env(path: &str) -> Result<heed::Env> {
+    let env = utils::open_env_with_flags(Path::new(path), EnvFlags::READ_ONLY | EnvFlags::NO_LOCK)
+        .with_context(|| format!("Failed to open env at `{}`", path))?;
+    Ok(env)
+}
+
+/// Splits `args` into positional arguments and `--name value` or
+/// `--name=value` options, which must be one of `option_names`.
+fn parse_args<'a>(
+    args: &'a [String],
+    option_names: &[&str],
+) -> Result<(Vec<&'a str>, HashMap<&'a str, &'a str>)> {
+    let mut positional = Vec::new();
+    let mut options = HashMap::new();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let Some(option) = arg.strip_prefix("--") else {
+            positional.push(arg.as_str());
+            continue;
+        };
+
+        let (name, value) = match option.split_once('=') {
+            Some((name, value)) => (name, value),
+            None => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| UsageError(format!("Missing value for `--{}`", option)))?;
+                (option, value.as_str())
+            }
+        };
+
+        if !option_names.contains(&name) {
+            return Err(UsageError(format!("Unknown option `--{}`", name)).into());
+        }
+        options.insert(name, value);
+    }
+
+    Ok((positional, options))
+}
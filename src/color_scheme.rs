@@ -0,0 +1,112 @@
+use gtk::prelude::*;
+
+use std::ops::Range;
+
+/// Kinds of bytes, and the offsets of hex views, that are colored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorRole {
+    /// Offsets at the start of the lines of hex views
+    Offset,
+    /// Zero bytes
+    NullByte,
+    /// Bytes of printable ASCII characters
+    PrintableByte,
+    /// Bytes of ASCII whitespace and control characters
+    ControlByte,
+    /// Bytes outside of ASCII
+    HighByte,
+}
+
+impl ColorRole {
+    const ALL: [Self; 5] = [
+        Self::Offset,
+        Self::NullByte,
+        Self::PrintableByte,
+        Self::ControlByte,
+        Self::HighByte,
+    ];
+
+    pub fn for_byte(byte: u8) -> Self {
+        match byte {
+            0 => Self::NullByte,
+            _ if byte.is_ascii_graphic() || byte == b' ' => Self::PrintableByte,
+            _ if byte.is_ascii() => Self::ControlByte,
+            _ => Self::HighByte,
+        }
+    }
+
+    /// Name of the text tag that colors text of this role.
+    pub fn tag_name(self) -> &'static str {
+        match self {
+            Self::Offset => "color-offset",
+            Self::NullByte => "color-null-byte",
+            Self::PrintableByte => "color-printable-byte",
+            Self::ControlByte => "color-control-byte",
+            Self::HighByte => "color-high-byte",
+        }
+    }
+
+    /// Ids of the styles of style schemes whose colors this role takes, the
+    /// first the scheme has, as schemes have none for hex views.
+    fn style_ids(self) -> &'static [&'static str] {
+        match self {
+            Self::Offset => &["line-numbers", "def:comment"],
+            Self::NullByte => &["def:comment"],
+            Self::PrintableByte => &["def:string"],
+            Self::ControlByte => &["def:special-char", "def:constant"],
+            Self::HighByte => &["def:type", "def:keyword"],
+        }
+    }
+}
+
+/// Returns the installed style scheme with `id`, or the default one of the
+/// current style if there is none, e.g., if the picked one was uninstalled.
+pub fn style_scheme(id: &str) -> sourceview::StyleScheme {
+    let fallback_id = if adw::StyleManager::default().is_dark() {
+        "Adwaita-dark"
+    } else {
+        "Adwaita"
+    };
+
+    let manager = sourceview::StyleSchemeManager::default();
+    manager
+        .scheme(id)
+        .or_else(|| manager.scheme(fallback_id))
+        .expect("default style schemes must be installed")
+}
+
+/// Creates the tags of all roles in `tag_table` if needed, and sets their
+/// colors to the ones of `scheme`.
+pub fn apply(scheme: &sourceview::StyleScheme, tag_table: &gtk::TextTagTable) {
+    for role in ColorRole::ALL {
+        let tag = tag_table.lookup(role.tag_name()).unwrap_or_else(|| {
+            let tag = gtk::TextTag::new(Some(role.tag_name()));
+            tag_table.add(&tag);
+            tag
+        });
+
+        // Only the color is taken, as other attributes, such as the weight,
+        // may misalign the columns of hex views
+        let foreground = role
+            .style_ids()
+            .iter()
+            .filter_map(|style_id| scheme.style(style_id))
+            .find(|style| style.is_foreground_set())
+            .and_then(|style| style.foreground());
+        tag.set_foreground(foreground.as_deref());
+    }
+}
+
+/// Returns the ranges of consecutive `bytes` of the same role, with their
+/// roles.
+pub fn byte_runs(bytes: &[u8]) -> Vec<(ColorRole, Range<usize>)> {
+    let mut runs = Vec::<(ColorRole, Range<usize>)>::new();
+    for (index, &byte) in bytes.iter().enumerate() {
+        let role = ColorRole::for_byte(byte);
+        match runs.last_mut() {
+            Some((last_role, range)) if *last_role == role => range.end = index + 1,
+            _ => runs.push((role, index..index + 1)),
+        }
+    }
+    runs
+}
@@ -0,0 +1,354 @@
+use gtk::{
+    gdk, gio,
+    glib::{self, clone},
+    prelude::*,
+    subclass::prelude::*,
+};
+
+/// An action listed in a [`CommandPalette`].
+#[derive(Debug, Clone)]
+pub struct Command {
+    pub label: String,
+    /// Name of the action, including its prefix, such as `win.open-env`
+    pub action_name: String,
+    pub target: Option<glib::Variant>,
+    /// Shown next to the label, but not searched
+    pub detail: Option<String>,
+    /// Whether the command can be activated, as those that can't are still
+    /// listed
+    pub sensitive: bool,
+}
+
+impl Command {
+    pub fn new(label: &str, action_name: &str, target: Option<glib::Variant>) -> Self {
+        Self {
+            label: label.to_string(),
+            action_name: action_name.to_string(),
+            target,
+            detail: None,
+            sensitive: true,
+        }
+    }
+
+    pub fn with_detail(mut self, detail: String) -> Self {
+        self.detail = Some(detail);
+        self
+    }
+
+    pub fn insensitive(mut self) -> Self {
+        self.sensitive = false;
+        self
+    }
+
+    /// Returns the commands of the items of `menu`, including those in its
+    /// sections and submenus, with the labels of the submenus they are in
+    /// and `prefix` in front of theirs.
+    pub fn from_menu(menu: &impl IsA<gio::MenuModel>, prefix: Option<&str>) -> Vec<Self> {
+        let mut commands = Vec::new();
+        append_menu_commands(menu.upcast_ref(), prefix, &mut commands);
+        commands
+    }
+}
+
+mod imp {
+    use std::cell::RefCell;
+
+    use super::*;
+
+    #[derive(Debug, Default, gtk::CompositeTemplate)]
+    #[template(resource = "/io/github/seadve/LmdbViewer/ui/command_palette.ui")]
+    pub struct CommandPalette {
+        #[template_child]
+        pub(super) search_entry: TemplateChild<gtk::SearchEntry>,
+        #[template_child]
+        pub(super) scrolled_window: TemplateChild<gtk::ScrolledWindow>,
+        #[template_child]
+        pub(super) list_box: TemplateChild<gtk::ListBox>,
+
+        pub(super) commands: RefCell<Vec<Command>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for CommandPalette {
+        const NAME: &'static str = "LvCommandPalette";
+        type Type = super::CommandPalette;
+        type ParentType = gtk::Popover;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for CommandPalette {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let obj = self.obj();
+
+            self.search_entry
+                .connect_search_changed(clone!(@weak obj => move |_| {
+                    obj.update_rows();
+                }));
+            self.search_entry
+                .connect_activate(clone!(@weak obj => move |_| {
+                    if let Some(row) = obj.imp().list_box.selected_row() {
+                        row.activate();
+                    }
+                }));
+            self.search_entry
+                .connect_stop_search(clone!(@weak obj => move |_| {
+                    obj.popdown();
+                }));
+
+            // Lets the selected command be changed without leaving the entry
+            let key_controller = gtk::EventControllerKey::new();
+            key_controller.connect_key_pressed(
+                clone!(@weak obj => @default-return glib::Propagation::Proceed,
+                    move |_, keyval, _, _| {
+                        let offset = match keyval {
+                            gdk::Key::Up => -1,
+                            gdk::Key::Down => 1,
+                            _ => return glib::Propagation::Proceed,
+                        };
+                        obj.select_row_by_offset(offset);
+                        glib::Propagation::Stop
+                    }
+                ),
+            );
+            self.search_entry.add_controller(key_controller);
+
+            obj.connect_show(|obj| {
+                obj.imp().search_entry.grab_focus();
+            });
+
+            self.list_box
+                .connect_row_activated(clone!(@weak obj => move |_, _| {
+                    obj.popdown();
+                }));
+        }
+
+        fn dispose(&self) {
+            self.dispose_template();
+        }
+    }
+
+    impl WidgetImpl for CommandPalette {}
+    impl PopoverImpl for CommandPalette {}
+}
+
+glib::wrapper! {
+    /// A popover listing commands that activate actions, which can be
+    /// searched by typing any of the characters of their labels in order.
+    ///
+    /// The actions are looked up from the widget the popover is set on, so
+    /// it can list any of the actions available there.
+    pub struct CommandPalette(ObjectSubclass<imp::CommandPalette>)
+        @extends gtk::Widget, gtk::Popover;
+}
+
+impl CommandPalette {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    pub fn set_placeholder_text(&self, text: &str) {
+        self.imp().search_entry.set_placeholder_text(Some(text));
+    }
+
+    /// Lists `commands`, in order while not searching, with the shortcuts of
+    /// their actions if the popover is set on a widget of an application.
+    pub fn set_commands(&self, commands: Vec<Command>) {
+        let imp = self.imp();
+
+        imp.commands.replace(commands);
+        imp.search_entry.set_text("");
+        self.update_rows();
+    }
+
+    fn update_rows(&self) {
+        let imp = self.imp();
+
+        let query = imp.search_entry.text();
+        let commands = imp.commands.borrow();
+        let mut matches = commands
+            .iter()
+            .filter_map(|command| Some((fuzzy_score(&query, &command.label)?, command)))
+            .collect::<Vec<_>>();
+        // Stable, so commands matching as well stay in order
+        matches.sort_by_key(|(score, _)| -score);
+
+        let application = self
+            .parent()
+            .and_then(|parent| parent.root())
+            .and_downcast::<gtk::Window>()
+            .and_then(|window| window.application());
+
+        imp.list_box.remove_all();
+        for (_, command) in matches {
+            let label = gtk::Label::builder()
+                .label(&command.label)
+                .xalign(0.0)
+                .hexpand(true)
+                .ellipsize(gtk::pango::EllipsizeMode::End)
+                .build();
+            let hbox = gtk::Box::builder()
+                .spacing(12)
+                .margin_top(6)
+                .margin_bottom(6)
+                .margin_start(6)
+                .margin_end(6)
+                .build();
+            hbox.append(&label);
+
+            if let Some(detail) = &command.detail {
+                let detail_label = gtk::Label::builder()
+                    .label(detail)
+                    .css_classes(["dim-label", "numeric"])
+                    .build();
+                hbox.append(&detail_label);
+            }
+
+            let accel = application.as_ref().and_then(|application| {
+                let detailed_name =
+                    gio::Action::print_detailed_name(&command.action_name, command.target.as_ref());
+                application
+                    .accels_for_action(&detailed_name)
+                    .first()
+                    .and_then(gtk::accelerator_parse)
+            });
+            if let Some((key, modifier)) = accel {
+                let accel_label = gtk::Label::builder()
+                    .label(gtk::accelerator_get_label(key, modifier))
+                    .css_classes(["dim-label"])
+                    .build();
+                hbox.append(&accel_label);
+            }
+
+            let row = gtk::ListBoxRow::builder()
+                .child(&hbox)
+                .sensitive(command.sensitive)
+                .build();
+            // Actionable widgets are made sensitive when their action is enabled
+            if command.sensitive {
+                row.set_action_name(Some(&command.action_name));
+                row.set_action_target_value(command.target.as_ref());
+            }
+            imp.list_box.append(&row);
+        }
+
+        imp.list_box
+            .select_row(imp.list_box.row_at_index(0).as_ref());
+    }
+
+    /// Selects the row `offset` rows away from the selected one, stopping at
+    /// the first and last rows.
+    fn select_row_by_offset(&self, offset: i32) {
+        let list_box = &self.imp().list_box;
+
+        let Some(selected_row) = list_box.selected_row() else {
+            return;
+        };
+
+        if let Some(row) = list_box.row_at_index(selected_row.index() + offset) {
+            list_box.select_row(Some(&row));
+
+            if let Some(bounds) = row.compute_bounds(&list_box.get()) {
+                self.imp()
+                    .scrolled_window
+                    .vadjustment()
+                    .clamp_page(bounds.y() as f64, (bounds.y() + bounds.height()) as f64);
+            }
+        }
+    }
+}
+
+impl Default for CommandPalette {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn append_menu_commands(menu: &gio::MenuModel, prefix: Option<&str>, commands: &mut Vec<Command>) {
+    for index in 0..menu.n_items() {
+        let label = menu
+            .item_attribute_value(
+                index,
+                gio::MENU_ATTRIBUTE_LABEL,
+                Some(glib::VariantTy::STRING),
+            )
+            .and_then(|label| label.get::<String>())
+            .map(|label| strip_mnemonic(&label));
+        let label = match (prefix, label) {
+            (Some(prefix), Some(label)) => Some(format!("{} › {}", prefix, label)),
+            (Some(prefix), None) => Some(prefix.to_string()),
+            (None, label) => label,
+        };
+
+        let action_name = menu
+            .item_attribute_value(
+                index,
+                gio::MENU_ATTRIBUTE_ACTION,
+                Some(glib::VariantTy::STRING),
+            )
+            .and_then(|action_name| action_name.get::<String>());
+        if let (Some(label), Some(action_name)) = (&label, action_name) {
+            let target = menu.item_attribute_value(index, gio::MENU_ATTRIBUTE_TARGET, None);
+            commands.push(Command::new(label, &action_name, target));
+        }
+
+        if let Some(section) = menu.item_link(index, gio::MENU_LINK_SECTION) {
+            append_menu_commands(&section, label.as_deref(), commands);
+        }
+        if let Some(submenu) = menu.item_link(index, gio::MENU_LINK_SUBMENU) {
+            append_menu_commands(&submenu, label.as_deref(), commands);
+        }
+    }
+}
+
+/// Returns `label` without the underscores marking its mnemonic, keeping
+/// those escaped as double underscores.
+fn strip_mnemonic(label: &str) -> String {
+    let mut stripped = String::with_capacity(label.len());
+    let mut chars = label.chars();
+    while let Some(c) = chars.next() {
+        if c != '_' {
+            stripped.push(c);
+        } else if let Some(next) = chars.next() {
+            stripped.push(next);
+        }
+    }
+    stripped
+}
+
+/// Returns how well `query` matches `text`, if all of its characters other
+/// than spaces are in `text` in order, ignoring case. Characters that
+/// follow the previous one matched or start a word score higher.
+fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let text = text.to_lowercase().chars().collect::<Vec<_>>();
+
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous_match = None;
+    for c in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let offset = text[position..].iter().position(|&other| other == c)?;
+        let index = position + offset;
+
+        score += 1;
+        if previous_match.is_some_and(|previous| previous + 1 == index) {
+            score += 5;
+        }
+        if index == 0 || !text[index - 1].is_alphanumeric() {
+            score += 10;
+        }
+        score -= offset as i64;
+
+        previous_match = Some(index);
+        position = index + 1;
+    }
+
+    Some(score)
+}
@@ -0,0 +1,351 @@
+use adw::{prelude::*, subclass::prelude::*};
+use anyhow::{Context, Result};
+use gettextrs::gettext;
+use gtk::{
+    gio,
+    glib::{self, clone, closure},
+};
+use heed::Env;
+
+use std::path::Path;
+
+use crate::{
+    database::{self, Database},
+    database_item::DatabaseItem,
+    diff::{self, DiffKind},
+    diff_item::DiffItem,
+    error_details, utils,
+    value_diff_view::ValueDiffView,
+    window::Window,
+};
+
+mod imp {
+    use std::cell::{OnceCell, RefCell};
+
+    use super::*;
+
+    #[derive(Debug, Default, gtk::CompositeTemplate)]
+    #[template(resource = "/io/github/seadve/LmdbViewer/ui/compare_window.ui")]
+    pub struct CompareWindow {
+        #[template_child]
+        pub(super) toast_overlay: TemplateChild<adw::ToastOverlay>,
+        #[template_child]
+        pub(super) left_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) right_drop_down: TemplateChild<gtk::DropDown>,
+        #[template_child]
+        pub(super) stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        pub(super) column_view: TemplateChild<gtk::ColumnView>,
+        #[template_child]
+        pub(super) selection_model: TemplateChild<gtk::NoSelection>,
+
+        pub(super) left: OnceCell<(Env, String)>,
+        pub(super) right_env: RefCell<Option<Env>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for CompareWindow {
+        const NAME: &'static str = "LvCompareWindow";
+        type Type = super::CompareWindow;
+        type ParentType = adw::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+
+            klass.install_action_async("compare.open-env", None, |obj, _, _| async move {
+                if let Err(err) = obj.open_right_env().await {
+                    if !err
+                        .downcast_ref::<glib::Error>()
+                        .is_some_and(|error| error.matches(gtk::DialogError::Dismissed))
+                    {
+                        tracing::error!("Failed to open env to compare with: {:?}", &err);
+                        obj.add_error_toast(&gettext("Failed to open env"), &err);
+                    }
+                }
+            });
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for CompareWindow {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            self.obj().setup_view();
+        }
+    }
+
+    impl WidgetImpl for CompareWindow {}
+    impl WindowImpl for CompareWindow {}
+    impl AdwWindowImpl for CompareWindow {}
+}
+
+glib::wrapper! {
+    pub struct CompareWindow(ObjectSubclass<imp::CompareWindow>)
+        @extends gtk::Widget, gtk::Window, adw::Window;
+}
+
+impl CompareWindow {
+    /// Creates a window comparing the database called `db_name` in `env`
+    /// with another database, initially from the same env.
+    pub fn new(parent: &Window, env: &Env, db_name: &str) -> Self {
+        let this = glib::Object::builder::<Self>()
+            .property("transient-for", parent)
+            .build();
+
+        let imp = this.imp();
+        imp.left.set((env.clone(), db_name.to_string())).unwrap();
+        imp.left_label.set_label(&display_name(env.path(), db_name));
+        this.set_right_env(env.clone());
+
+        this
+    }
+
+    fn add_error_toast(&self, message: &str, err: &anyhow::Error) {
+        let toast = error_details::error_toast(self, message, err);
+        self.imp().toast_overlay.add_toast(toast);
+    }
+
+    fn main_window(&self) -> Window {
+        self.transient_for().and_downcast::<Window>().unwrap()
+    }
+
+    async fn open_right_env(&self) -> Result<()> {
+        let dialog = gtk::FileDialog::builder()
+            .title(gettext("Open Database to Compare With"))
+            .modal(true)
+            .build();
+
+        let folder = dialog.select_folder_future(Some(self)).await?;
+        let path = folder.path().expect("file must have a path");
+
+        let (left_env, _) = self.imp().left.get().unwrap();
+        let env = if path.canonicalize()? == left_env.path() {
+            left_env.clone()
+        } else {
+            utils::open_env(&path, false)
+                .with_context(|| format!("Failed to open env at `{}`", folder.uri()))?
+        };
+        self.set_right_env(env);
+
+        Ok(())
+    }
+
+    fn set_right_env(&self, env: Env) {
+        let imp = self.imp();
+
+        imp.right_drop_down.set_model(gio::ListModel::NONE);
+        imp.right_drop_down
+            .set_tooltip_text(Some(&env.path().display().to_string()));
+        imp.right_env.replace(Some(env.clone()));
+
+        match Database::load(&env, None) {
+            Ok(db) => imp.right_drop_down.set_model(Some(&db)),
+            Err(err) => {
+                tracing::error!("Failed to load unnamed db: {:?}", &err);
+                self.add_error_toast(&gettext("Failed to load databases"), &err);
+            }
+        }
+    }
+
+    /// Compares the left database with the one selected on the right in a
+    /// separate thread.
+    fn compare(&self) {
+        let imp = self.imp();
+
+        let Some(right_item) = imp
+            .right_drop_down
+            .selected_item()
+            .and_downcast::<DatabaseItem>()
+        else {
+            return;
+        };
+        let right_name = match database::name_from_bytes(&right_item.key()) {
+            Ok(right_name) => right_name.to_string(),
+            Err(err) => {
+                tracing::error!("Failed to compare databases: {:?}", &err);
+                self.add_error_toast(&gettext("Failed to compare databases"), &err);
+                return;
+            }
+        };
+        let right_env = imp.right_env.borrow().clone().unwrap();
+        let (left_env, left_name) = imp.left.get().unwrap().clone();
+
+        imp.selection_model.set_model(gio::ListModel::NONE);
+        imp.stack.set_visible_child_name("loading");
+
+        glib::spawn_future_local(clone!(@weak self as obj => async move {
+            let imp = obj.imp();
+
+            let differences = gio::spawn_blocking(move || {
+                diff::diff(&left_env, Some(&left_name), &right_env, Some(&right_name))
+            })
+            .await
+            .unwrap();
+
+            match differences {
+                Ok(differences) => {
+                    let items = differences
+                        .into_iter()
+                        .map(DiffItem::new)
+                        .collect::<Vec<_>>();
+                    let store = gio::ListStore::new::<DiffItem>();
+                    store.extend_from_slice(&items);
+
+                    imp.stack.set_visible_child_name(if store.n_items() == 0 {
+                        "empty"
+                    } else {
+                        "differences"
+                    });
+                    imp.selection_model.set_model(Some(&store));
+                }
+                Err(err) => {
+                    tracing::error!("Failed to compare databases: {:?}", &err);
+                    obj.add_error_toast(&gettext("Failed to compare databases"), &err);
+                    imp.stack.set_visible_child_name("empty");
+                }
+            }
+        }));
+    }
+
+    fn setup_view(&self) {
+        let imp = self.imp();
+
+        self.append_column(&gettext("Key"), |obj, item| {
+            let main_window = obj.main_window();
+            let text = main_window
+                .non_printable_display()
+                .render(&item.key(), main_window.key_encoding());
+            (text, item.kind().css_class())
+        });
+        self.append_column(&gettext("Left Value"), |obj, item| {
+            let text = item.left().map(|left| obj.value_text(&left));
+            let css_class = match item.kind() {
+                DiffKind::Added => None,
+                kind => kind.css_class(),
+            };
+            (text.unwrap_or_default(), css_class)
+        });
+        self.append_column(&gettext("Right Value"), |obj, item| {
+            let text = item.right().map(|right| obj.value_text(&right));
+            let css_class = match item.kind() {
+                DiffKind::Removed => None,
+                kind => kind.css_class(),
+            };
+            (text.unwrap_or_default(), css_class)
+        });
+
+        imp.column_view
+            .connect_activate(clone!(@weak self as obj => move |_, position| {
+                let item = obj
+                    .imp()
+                    .selection_model
+                    .item(position)
+                    .and_downcast::<DiffItem>()
+                    .unwrap();
+                obj.compare_values(&item);
+            }));
+
+        imp.right_drop_down
+            .set_expression(Some(&gtk::ClosureExpression::new::<glib::GString>(
+                &[] as &[gtk::Expression],
+                closure!(|list_item: DatabaseItem| {
+                    String::from_utf8_lossy(list_item.key().as_ref()).to_string()
+                }),
+            )));
+        imp.right_drop_down
+            .connect_selected_item_notify(clone!(@weak self as obj => move |_| {
+                obj.compare();
+            }));
+    }
+
+    /// Shows how the left value of a changed `item` differs from the right.
+    fn compare_values(&self, item: &DiffItem) {
+        let (Some(left), Some(right)) = (item.left(), item.right()) else {
+            return;
+        };
+
+        let main_window = self.main_window();
+        let key = item.key();
+        let (left_text, right_text) = main_window.value_diff_texts((&key, &left), (&key, &right));
+        let imp = self.imp();
+        // Values are only compared after comparing the databases, which fails
+        // if the name isn't valid UTF-8
+        let right_name = imp
+            .right_drop_down
+            .selected_item()
+            .and_downcast::<DatabaseItem>()
+            .and_then(|item| Some(database::name_from_bytes(&item.key()).ok()?.to_string()))
+            .unwrap_or_default();
+        let right_title = imp
+            .right_env
+            .borrow()
+            .as_ref()
+            .map(|env| display_name(env.path(), &right_name))
+            .unwrap_or_default();
+        ValueDiffView::present(
+            self,
+            (&imp.left_label.label(), &left_text),
+            (&right_title, &right_text),
+        );
+    }
+
+    /// Appends a column whose cells show the text and CSS class returned by
+    /// `cell_content` for each item.
+    fn append_column(
+        &self,
+        title: &str,
+        cell_content: impl Fn(&Self, &DiffItem) -> (String, Option<&'static str>) + 'static,
+    ) {
+        let factory = gtk::SignalListItemFactory::new();
+        factory.connect_setup(|_, list_item| {
+            let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
+            let label = gtk::Label::builder()
+                .xalign(0.0)
+                .ellipsize(gtk::pango::EllipsizeMode::End)
+                .single_line_mode(true)
+                .build();
+            list_item.set_child(Some(&label));
+        });
+        factory.connect_bind(clone!(@weak self as obj => move |_, list_item| {
+            let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
+            let label = list_item.child().and_downcast::<gtk::Label>().unwrap();
+            let item = list_item.item().and_downcast::<DiffItem>().unwrap();
+
+            let (text, css_class) = cell_content(&obj, &item);
+            label.set_label(&text);
+            label.set_css_classes(css_class.as_slice());
+        }));
+
+        let column = gtk::ColumnViewColumn::new(Some(title), Some(factory));
+        column.set_resizable(true);
+        column.set_expand(true);
+        self.imp().column_view.append_column(&column);
+    }
+
+    fn value_text(&self, value: &glib::Bytes) -> String {
+        let main_window = self.main_window();
+
+        let preview_len = value.len().min(main_window.preview_length() as usize);
+        let mut text = main_window
+            .non_printable_display()
+            .render(&value[..preview_len], main_window.value_encoding());
+        if preview_len < value.len() {
+            text.push('…');
+        }
+        text
+    }
+}
+
+/// Formats the name of the database `db_name` in the env at `path`.
+fn display_name(path: &Path, db_name: &str) -> String {
+    let env_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    format!("{} › {}", env_name, db_name)
+}
@@ -0,0 +1,63 @@
+use std::io::Read;
+
+use anyhow::{ensure, Result};
+use gtk::glib;
+
+/// Decompressed values larger than this are rejected, to guard against
+/// decompression bombs.
+const MAX_DECOMPRESSED_LEN: u64 = 256 * 1024 * 1024; // 256 MiB
+
+/// Compression formats that can be recognized by their magic bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+    Lz4,
+    Snappy,
+}
+
+impl Compression {
+    pub fn detect(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(&[0x1f, 0x8b]) {
+            Some(Self::Gzip)
+        } else if bytes.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(Self::Zstd)
+        } else if bytes.starts_with(&[0x04, 0x22, 0x4d, 0x18]) {
+            Some(Self::Lz4)
+        } else if bytes.starts_with(b"\xff\x06\x00\x00sNaPpY") {
+            Some(Self::Snappy)
+        } else {
+            None
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Gzip => "gzip",
+            Self::Zstd => "zstd",
+            Self::Lz4 => "LZ4",
+            Self::Snappy => "Snappy",
+        }
+    }
+
+    pub fn decompress(self, bytes: &[u8]) -> Result<Vec<u8>> {
+        let reader: Box<dyn Read + '_> = match self {
+            Self::Gzip => Box::new(flate2::read::GzDecoder::new(bytes)),
+            Self::Zstd => Box::new(zstd::stream::read::Decoder::new(bytes)?),
+            Self::Lz4 => Box::new(lz4_flex::frame::FrameDecoder::new(bytes)),
+            Self::Snappy => Box::new(snap::read::FrameDecoder::new(bytes)),
+        };
+
+        let mut decompressed = Vec::new();
+        reader
+            .take(MAX_DECOMPRESSED_LEN + 1)
+            .read_to_end(&mut decompressed)?;
+        ensure!(
+            decompressed.len() as u64 <= MAX_DECOMPRESSED_LEN,
+            "decompressed value exceeds {}",
+            glib::format_size(MAX_DECOMPRESSED_LEN)
+        );
+
+        Ok(decompressed)
+    }
+}
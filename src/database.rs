@@ -1,23 +1,60 @@
 use anyhow::{anyhow, Context, Result};
 use gtk::{gio, glib, prelude::*, subclass::prelude::*};
-use heed::types::ByteSlice;
+use heed::{types::ByteSlice, DatabaseFlags};
 use indexmap::IndexMap;
 
-use crate::database_item::DatabaseItem;
+use std::{
+    cmp::Ordering,
+    iter::Peekable,
+    ops::Bound,
+    sync::{
+        atomic::{self, AtomicBool, AtomicUsize},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::{
+    backend::{Backend, BackendKind},
+    database_item::DatabaseItem,
+    diff::DiffKind,
+    edit_history::Edit,
+    snapshot::Snapshot,
+};
 
 type Inner = heed::Database<ByteSlice, ByteSlice>;
 
+/// Number of entries read at a time when duplicating a database
+const DUPLICATE_BATCH_LEN: usize = 1000;
+
 mod imp {
-    use std::cell::{OnceCell, RefCell};
+    use std::cell::{Cell, OnceCell, RefCell};
 
     use super::*;
 
     #[derive(Default)]
     pub struct Database {
         pub(super) env: OnceCell<heed::Env>,
+        /// Env the items are read from if it isn't LMDB, in which case it
+        /// can only be read, and there is no env or snapshot
+        pub(super) backend: OnceCell<Arc<dyn Backend>>,
+        /// State the items were read from, pinned while they are shown
+        pub(super) snapshot: RefCell<Option<Snapshot>>,
+        /// Id of the txn the items were read in
+        pub(super) txn_id: Cell<u64>,
         pub(super) inner: OnceCell<Inner>,
-        pub(super) items: RefCell<IndexMap<glib::Bytes, DatabaseItem>>,
+        /// Items in database order, where a DUPSORT key has an item for each
+        /// of its values
+        pub(super) items: RefCell<Vec<DatabaseItem>>,
+        /// Items removed since the previous reload
+        pub(super) removed_items: RefCell<Vec<DatabaseItem>>,
+        /// Items merged with the removed ones, if changes are shown
+        pub(super) merged_items: RefCell<Option<Vec<DatabaseItem>>>,
+        pub(super) show_changes: Cell<bool>,
+        /// How long the latest load or reload took
+        pub(super) load_duration: Cell<Duration>,
         pub(super) name: OnceCell<Option<String>>,
+        pub(super) flags: OnceCell<DatabaseFlags>,
     }
 
     #[glib::object_subclass]
@@ -35,15 +72,24 @@ mod imp {
         }
 
         fn n_items(&self) -> u32 {
+            if let Some(merged_items) = self.merged_items.borrow().as_ref() {
+                return merged_items.len() as u32;
+            }
+
             self.items.borrow().len() as u32
         }
 
         fn item(&self, position: u32) -> Option<glib::Object> {
+            if let Some(merged_items) = self.merged_items.borrow().as_ref() {
+                return merged_items
+                    .get(position as usize)
+                    .map(|item| item.clone().upcast());
+            }
+
             self.items
                 .borrow()
-                .get_index(position as usize)
-                .map(|(_, v)| v.upcast_ref::<glib::Object>())
-                .cloned()
+                .get(position as usize)
+                .map(|item| item.clone().upcast())
         }
     }
 }
@@ -55,74 +101,918 @@ glib::wrapper! {
 
 impl Database {
     pub fn load(env: &heed::Env, name: Option<&str>) -> Result<Self> {
+        let _span = tracing::debug_span!("load_db", ?name).entered();
+        let start = Instant::now();
+
         let this = glib::Object::new::<Self>();
 
-        let rtxn = env.read_txn()?;
-        let db = env
-            .open_database(&rtxn, name)?
-            .ok_or_else(|| anyhow!("database not found"))?;
-        let items = db
-            .iter(&rtxn)?
-            .map(|item| {
-                let (key, data) = item?;
-                let key = glib::Bytes::from(key);
-                let data = glib::Bytes::from(data);
-                let item = DatabaseItem::new(&key, &data);
-                Ok::<_, heed::Error>((key, item))
-            })
-            .collect::<Result<IndexMap<_, _>, _>>()?;
+        let snapshot = Snapshot::latest(env)?;
+        // The handle was opened before the snapshot was pinned, so it
+        // outlives it
+        let (db, entries, flags) = snapshot.read(|env, rtxn| {
+            let db: Inner = env
+                .open_database(rtxn, name)?
+                .ok_or_else(|| anyhow!("database not found"))?;
+            let entries = db
+                .iter(rtxn)?
+                .map(|item| {
+                    let (key, data) = item?;
+                    Ok::<_, heed::Error>((glib::Bytes::from(key), glib::Bytes::from(data)))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let flags = read_flags(env, rtxn, name)?;
+            Ok((db, entries, flags))
+        })?;
+        let items = entries
+            .iter()
+            .map(|(key, data)| DatabaseItem::new(key, data))
+            .collect::<Vec<_>>();
 
         let imp = this.imp();
         imp.inner.set(db).unwrap();
         imp.env.set(env.clone()).unwrap();
+        imp.txn_id.set(snapshot.txn_id());
+        imp.snapshot.replace(Some(snapshot));
+        imp.items.replace(items);
+        imp.name.set(name.map(|s| s.to_string())).unwrap();
+        imp.flags.set(flags).unwrap();
+
+        let duration = start.elapsed();
+        imp.load_duration.set(duration);
+        tracing::debug!(n_entries = this.n_entries(), ?duration, "Loaded db");
+
+        Ok(this)
+    }
+
+    /// Like [`Self::load`], but reads the database through `backend`, for
+    /// envs that aren't LMDB. It can only be read, and has no snapshot.
+    pub fn load_from(backend: &Arc<dyn Backend>, name: Option<&str>) -> Result<Self> {
+        let _span = tracing::debug_span!("load_db", ?name, kind = ?backend.kind()).entered();
+        let start = Instant::now();
+
+        let this = glib::Object::new::<Self>();
+
+        let contents = backend.read_database(name)?;
+        let items = contents
+            .entries
+            .iter()
+            .map(|(key, data)| DatabaseItem::new(key, data))
+            .collect::<Vec<_>>();
+
+        let imp = this.imp();
+        imp.backend.set(backend.clone()).unwrap();
+        imp.txn_id.set(contents.txn_id);
         imp.items.replace(items);
         imp.name.set(name.map(|s| s.to_string())).unwrap();
+        imp.flags.set(contents.flags).unwrap();
+
+        let duration = start.elapsed();
+        imp.load_duration.set(duration);
+        tracing::debug!(n_entries = this.n_entries(), ?duration, "Loaded db");
 
         Ok(this)
     }
 
     pub fn reload(&self) -> Result<()> {
-        let env = self.env();
-        let db = self.inner();
+        let _span = tracing::debug_span!("reload_db", name = ?self.name()).entered();
+        let start = Instant::now();
+
+        if let Some(backend) = self.imp().backend.get() {
+            let contents = backend.read_database(self.name())?;
+            self.set_entries(contents.entries, None, contents.txn_id, start);
+            return Ok(());
+        }
+
+        let (entries, snapshot) = read_entries(self.env()?, self.inner())?;
+        let txn_id = snapshot.txn_id();
+        self.set_entries(entries, Some(snapshot), txn_id, start);
+
+        Ok(())
+    }
+
+    /// Like [`Self::reload`], but reads the entries in a separate thread, so
+    /// large databases don't block the UI.
+    pub async fn reload_in_background(&self) -> Result<()> {
+        let start = Instant::now();
+
+        let name = self.name().map(|name| name.to_string());
+        if let Some(backend) = self.imp().backend.get().cloned() {
+            let contents = gio::spawn_blocking(move || {
+                let _span = tracing::debug_span!("reload_db", ?name).entered();
+                backend.read_database(name.as_deref())
+            })
+            .await
+            .map_err(|_| anyhow!("reload thread panicked"))??;
+            self.set_entries(contents.entries, None, contents.txn_id, start);
+            return Ok(());
+        }
+
+        let env = self.env()?.clone();
+        let db = *self.inner();
+        let (entries, snapshot) = gio::spawn_blocking(move || {
+            let _span = tracing::debug_span!("reload_db", ?name).entered();
+            read_entries(&env, &db)
+        })
+        .await
+        .map_err(|_| anyhow!("reload thread panicked"))??;
+
+        let txn_id = snapshot.txn_id();
+        self.set_entries(entries, Some(snapshot), txn_id, start);
 
+        Ok(())
+    }
+
+    /// Replaces the items with ones for `entries`, read in the txn `txn_id`
+    /// and pinned by `snapshot` if there is one, marking what changed since
+    /// the previous reload, which started at `start`.
+    fn set_entries(
+        &self,
+        entries: Vec<(glib::Bytes, glib::Bytes)>,
+        snapshot: Option<Snapshot>,
+        txn_id: u64,
+        start: Instant,
+    ) {
         let prev_len = self.n_items();
 
         // TODO update only what changed
-        let rtxn = env.read_txn().context("Failed to create read txn")?;
-        let items = db
-            .iter(&rtxn)
-            .context("Failed to iter db")?
-            .map(|item| {
-                let (key, val) = item?;
-                let key = glib::Bytes::from(key);
-                let val = glib::Bytes::from(val);
-                let item = DatabaseItem::new(&key, &val);
-                Ok::<_, heed::Error>((key, item))
-            })
-            .collect::<Result<IndexMap<_, _>, _>>()
-            .context("Failed to collect db")?;
+        let items = entries
+            .iter()
+            .map(|(key, val)| DatabaseItem::new(key, val))
+            .collect::<Vec<_>>();
 
         let imp = self.imp();
-        imp.items.replace(items);
+        imp.snapshot.replace(snapshot);
+        imp.txn_id.set(txn_id);
+        let prev_items = imp.items.replace(items);
+
+        // Mark what changed since the previous reload. Items are matched by
+        // key and value first, as a DUPSORT key can have many values, then
+        // by key only, so an overwritten value is a change.
+        let mut prev_by_entry = IndexMap::<_, Vec<_>>::new();
+        for prev_item in prev_items {
+            prev_by_entry
+                .entry((prev_item.key(), prev_item.data()))
+                .or_default()
+                .push(prev_item);
+        }
+        let items = imp.items.borrow();
+        let mut unmatched_items = Vec::new();
+        for item in items.iter() {
+            let prev_item = prev_by_entry
+                .get_mut(&(item.key(), item.data()))
+                .and_then(|prev_items| prev_items.pop());
+            match prev_item {
+                Some(_) => item.set_change(DiffKind::Unchanged),
+                None => unmatched_items.push(item),
+            }
+        }
+        let mut prev_by_key = IndexMap::<_, Vec<_>>::new();
+        for prev_item in prev_by_entry.into_values().flatten() {
+            prev_by_key
+                .entry(prev_item.key())
+                .or_default()
+                .push(prev_item);
+        }
+        for item in unmatched_items {
+            let prev_item = prev_by_key
+                .get_mut(&item.key())
+                .and_then(|prev_items| prev_items.pop());
+            item.set_change(match prev_item {
+                Some(_) => DiffKind::Changed,
+                None => DiffKind::Added,
+            });
+        }
+        let removed_items = prev_by_key.into_values().flatten().collect::<Vec<_>>();
+        for item in &removed_items {
+            item.set_change(DiffKind::Removed);
+        }
+        drop(items);
+        imp.removed_items.replace(removed_items);
+        self.update_merged_items();
 
         let new_len = self.n_items();
 
-        dbg!(prev_len, new_len);
+        let duration = start.elapsed();
+        imp.load_duration.set(duration);
+        tracing::debug!(n_entries = self.n_entries(), ?duration, "Reloaded db");
 
         match new_len.cmp(&prev_len) {
             std::cmp::Ordering::Less => self.items_changed(0, new_len, prev_len),
             std::cmp::Ordering::Equal => self.items_changed(0, prev_len, prev_len),
             std::cmp::Ordering::Greater => self.items_changed(0, prev_len, new_len),
         }
+    }
 
-        Ok(())
+    /// Returns the items as they are, so they can be shown again with
+    /// [`Self::restore_items`] after a reload.
+    pub fn save_items(&self) -> SavedItems {
+        let imp = self.imp();
+        SavedItems {
+            items: imp
+                .items
+                .borrow()
+                .iter()
+                .map(|item| (item.clone(), item.change()))
+                .collect(),
+            removed_items: imp.removed_items.borrow().clone(),
+            snapshot: imp.snapshot.borrow().clone(),
+            txn_id: imp.txn_id.get(),
+        }
     }
 
-    fn env(&self) -> &heed::Env {
-        self.imp().env.get().unwrap()
+    /// Shows the `saved` items again, with the snapshot they were read from.
+    pub fn restore_items(&self, saved: SavedItems) {
+        let imp = self.imp();
+        let prev_len = self.n_items();
+
+        for (item, change) in &saved.items {
+            item.set_change(*change);
+        }
+        for item in &saved.removed_items {
+            item.set_change(DiffKind::Removed);
+        }
+        imp.items
+            .replace(saved.items.into_iter().map(|(item, _)| item).collect());
+        imp.removed_items.replace(saved.removed_items);
+        imp.snapshot.replace(saved.snapshot);
+        imp.txn_id.set(saved.txn_id);
+        self.update_merged_items();
+
+        self.items_changed(0, prev_len, self.n_items());
+    }
+
+    /// Writes `value` in place of `old_value` of `key` in its own write txn,
+    /// which only replaces that duplicate of a DUPSORT key, then reloads the
+    /// items. Returns the edit so it can be undone.
+    pub fn put(&self, key: &[u8], old_value: &[u8], value: &[u8]) -> Result<Edit> {
+        let mut wtxn = self
+            .env()?
+            .write_txn()
+            .context("Failed to create write txn")?;
+        let mut edit = Edit::default();
+        if self.flags().contains(DatabaseFlags::DUP_SORT) {
+            edit.write_duplicate(
+                &mut wtxn,
+                *self.inner(),
+                self.name(),
+                key,
+                old_value,
+                Some(value),
+            )?;
+        } else {
+            edit.write(&mut wtxn, *self.inner(), self.name(), key, Some(value))?;
+        }
+        wtxn.commit().context("Failed to commit write txn")?;
+
+        self.reload()?;
+
+        Ok(edit)
+    }
+
+    /// Deletes the entries of `items` in a single write txn, which are only
+    /// their own values of DUPSORT keys, then reloads the items. Returns the
+    /// edit so it can be undone.
+    pub fn delete(&self, items: &[DatabaseItem]) -> Result<Edit> {
+        let mut wtxn = self
+            .env()?
+            .write_txn()
+            .context("Failed to create write txn")?;
+        let is_dup_sort = self.flags().contains(DatabaseFlags::DUP_SORT);
+        let mut edit = Edit::default();
+        for item in items {
+            if is_dup_sort {
+                edit.write_duplicate(
+                    &mut wtxn,
+                    *self.inner(),
+                    self.name(),
+                    &item.key(),
+                    &item.data(),
+                    None,
+                )?;
+            } else {
+                edit.write(&mut wtxn, *self.inner(), self.name(), &item.key(), None)?;
+            }
+        }
+        wtxn.commit().context("Failed to commit write txn")?;
+
+        self.reload()?;
+
+        Ok(edit)
+    }
+
+    /// Returns the keys of the entries in `range`.
+    pub fn keys_in_range(&self, range: &KeyRange) -> Vec<glib::Bytes> {
+        let flags = self.flags();
+
+        let mut keys = self
+            .imp()
+            .items
+            .borrow()
+            .iter()
+            .map(|item| item.key())
+            .filter(|key| range.contains(flags, key))
+            .collect::<Vec<_>>();
+        // Deleting a DUPSORT key deletes all of its values
+        keys.dedup();
+        keys
+    }
+
+    /// Like [`Self::delete`], but deletes in a separate thread, adding to
+    /// `n_deleted` as each entry is deleted, so large batches don't block the
+    /// UI.
+    pub async fn delete_in_background(
+        &self,
+        keys: Vec<glib::Bytes>,
+        n_deleted: Arc<AtomicUsize>,
+    ) -> Result<Edit> {
+        let env = self.env()?.clone();
+        let db = *self.inner();
+        let name = self.name().map(|name| name.to_string());
+
+        let edit = gio::spawn_blocking(move || {
+            let mut wtxn = env.write_txn().context("Failed to create write txn")?;
+            let mut edit = Edit::default();
+            for key in &keys {
+                edit.write(&mut wtxn, db, name.as_deref(), key, None)?;
+                n_deleted.fetch_add(1, atomic::Ordering::Relaxed);
+            }
+            wtxn.commit().context("Failed to commit write txn")?;
+            Ok::<_, anyhow::Error>(edit)
+        })
+        .await
+        .map_err(|_| anyhow!("delete thread panicked"))??;
+
+        self.reload()?;
+
+        Ok(edit)
+    }
+
+    /// Walks every entry with a cursor in a background thread, counting the
+    /// entries read in `n_read`, and stops at the first error from LMDB, such
+    /// as a corrupted page.
+    pub async fn verify_in_background(&self, n_read: Arc<AtomicUsize>) -> Result<Verification> {
+        let env = self.env()?.clone();
+        let db = *self.inner();
+
+        gio::spawn_blocking(move || {
+            let rtxn = env.read_txn().context("Failed to create read txn")?;
+            let mut last_key = None;
+            let mut error = None;
+            match db.iter(&rtxn) {
+                Ok(iter) => {
+                    for item in iter {
+                        match item {
+                            Ok((key, _)) => {
+                                last_key = Some(key.to_vec());
+                                n_read.fetch_add(1, atomic::Ordering::Relaxed);
+                            }
+                            Err(err) => {
+                                error = Some(err);
+                                break;
+                            }
+                        }
+                    }
+                }
+                Err(err) => error = Some(err),
+            }
+            Ok(Verification {
+                n_read: n_read.load(atomic::Ordering::Relaxed),
+                failure: error.map(|error| VerifyFailure {
+                    last_key,
+                    message: error.to_string(),
+                }),
+            })
+        })
+        .await
+        .map_err(|_| anyhow!("verify thread panicked"))?
+    }
+
+    /// Moves the values of `key` to `new_key` in a single write txn, which
+    /// are all of its duplicates if the database is DUPSORT, failing if
+    /// `new_key` already exists, then updates the items in place. Returns the
+    /// edit so it can be undone.
+    pub fn rename(&self, key: &[u8], new_key: &[u8]) -> Result<Edit> {
+        let is_dup_sort = self.flags().contains(DatabaseFlags::DUP_SORT);
+
+        let mut wtxn = self
+            .env()?
+            .write_txn()
+            .context("Failed to create write txn")?;
+        let values = if is_dup_sort {
+            self.inner()
+                .get_duplicates(&wtxn, key)?
+                .context("Key not found")?
+                .map(|item| item.map(|(_, value)| value.to_vec()))
+                .collect::<Result<Vec<_>, _>>()
+                .context("Failed to read duplicates")?
+        } else {
+            vec![self
+                .inner()
+                .get(&wtxn, key)?
+                .context("Key not found")?
+                .to_vec()]
+        };
+        anyhow::ensure!(
+            self.inner().get(&wtxn, new_key)?.is_none(),
+            "New key already exists"
+        );
+        let mut edit = Edit::default();
+        for value in &values {
+            edit.write(&mut wtxn, *self.inner(), self.name(), new_key, Some(value))?;
+        }
+        edit.write(&mut wtxn, *self.inner(), self.name(), key, None)?;
+        wtxn.commit().context("Failed to commit write txn")?;
+
+        let imp = self.imp();
+
+        // Positions in the merged items can't be updated in place, and a
+        // DUPSORT key may have several items to move
+        if imp.show_changes.get() || is_dup_sort {
+            self.reload()?;
+            return Ok(edit);
+        }
+
+        let position = imp
+            .items
+            .borrow()
+            .iter()
+            .position(|item| item.key().as_ref() == key);
+        if let Some(position) = position {
+            imp.items.borrow_mut().remove(position);
+            self.items_changed(position as u32, 1, 0);
+        }
+
+        let position = {
+            let mut items = imp.items.borrow_mut();
+            let flags = self.flags();
+            let position =
+                items.partition_point(|item| compare_keys(flags, &item.key(), new_key).is_lt());
+            let value = values.into_iter().next().unwrap();
+            let item =
+                DatabaseItem::new(&glib::Bytes::from(new_key), &glib::Bytes::from_owned(value));
+            items.insert(position, item);
+            position
+        };
+        self.items_changed(position as u32, 0, 1);
+
+        Ok(edit)
+    }
+
+    /// Deletes all entries in a single write txn, keeping the database itself,
+    /// then reloads the items. Returns the edit so it can be undone.
+    pub fn clear(&self) -> Result<Edit> {
+        let mut wtxn = self
+            .env()?
+            .write_txn()
+            .context("Failed to create write txn")?;
+        let keys = self
+            .inner()
+            .iter(&wtxn)?
+            .map(|item| item.map(|(key, _)| key.to_vec()))
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to collect keys")?;
+        let mut edit = Edit::default();
+        for key in &keys {
+            edit.write(&mut wtxn, *self.inner(), self.name(), key, None)?;
+        }
+        wtxn.commit().context("Failed to commit write txn")?;
+
+        self.reload()?;
+
+        Ok(edit)
+    }
+
+    /// Sets whether items removed since the previous reload are included.
+    pub fn set_show_changes(&self, show_changes: bool) {
+        let imp = self.imp();
+
+        if show_changes == imp.show_changes.get() {
+            return;
+        }
+
+        let prev_len = self.n_items();
+        imp.show_changes.set(show_changes);
+        self.update_merged_items();
+        self.items_changed(0, prev_len, self.n_items());
+    }
+
+    /// Returns the items that were added, removed, or changed in the previous
+    /// reload.
+    pub fn changes(&self) -> Vec<DatabaseItem> {
+        let imp = self.imp();
+
+        imp.items
+            .borrow()
+            .iter()
+            .filter(|item| item.change() != DiffKind::Unchanged)
+            .chain(imp.removed_items.borrow().iter())
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the number of entries in the database, which excludes removed
+    /// items that are shown.
+    pub fn n_entries(&self) -> u32 {
+        self.imp().items.borrow().len() as u32
+    }
+
+    /// Returns how long the latest load or reload took.
+    pub fn load_duration(&self) -> Duration {
+        self.imp().load_duration.get()
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.imp().name.get().unwrap().as_deref()
+    }
+
+    pub fn flags(&self) -> DatabaseFlags {
+        *self.imp().flags.get().unwrap()
+    }
+
+    /// Returns the format of the env the database is of.
+    pub fn backend_kind(&self) -> BackendKind {
+        self.imp()
+            .backend
+            .get()
+            .map_or(BackendKind::Lmdb, |backend| backend.kind())
+    }
+
+    /// Returns the id of the txn the items were read in.
+    pub fn txn_id(&self) -> u64 {
+        self.imp().txn_id.get()
+    }
+
+    /// The state of the env the items were read from. As the items hold
+    /// their keys and values, anything shown from them, such as the details
+    /// of the selected entry, is of this snapshot. Only databases of LMDB
+    /// envs have one.
+    pub fn snapshot(&self) -> Result<Snapshot> {
+        self.imp()
+            .snapshot
+            .borrow()
+            .clone()
+            .ok_or_else(|| self.read_only_error())
+    }
+
+    fn update_merged_items(&self) {
+        let imp = self.imp();
+
+        let items = imp.items.borrow();
+        let removed_items = imp.removed_items.borrow();
+        let merged_items = (imp.show_changes.get() && !removed_items.is_empty()).then(|| {
+            MergeByKey {
+                flags: self.flags(),
+                a: items.iter().cloned().peekable(),
+                b: removed_items.iter().cloned().peekable(),
+            }
+            .collect()
+        });
+        imp.merged_items.replace(merged_items);
+    }
+
+    fn env(&self) -> Result<&heed::Env> {
+        self.imp().env.get().ok_or_else(|| self.read_only_error())
+    }
+
+    fn read_only_error(&self) -> anyhow::Error {
+        anyhow!(
+            "{} envs can only be browsed, not snapshotted or written",
+            self.backend_kind().display_name()
+        )
     }
 
     fn inner(&self) -> &Inner {
         self.imp().inner.get().unwrap()
     }
 }
+
+/// Reads all entries of `db` from the latest state of `env`, with the
+/// snapshot of that state.
+fn read_entries(
+    env: &heed::Env,
+    db: &Inner,
+) -> Result<(Vec<(glib::Bytes, glib::Bytes)>, Snapshot)> {
+    let snapshot = Snapshot::latest(env)?;
+    let entries = snapshot.read(|_, rtxn| {
+        db.iter(rtxn)
+            .context("Failed to iter db")?
+            .map(|item| {
+                let (key, val) = item?;
+                Ok::<_, heed::Error>((glib::Bytes::from(key), glib::Bytes::from(val)))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to collect db")
+    })?;
+    Ok((entries, snapshot))
+}
+
+/// Items of a database saved with [`Database::save_items`].
+pub struct SavedItems {
+    /// Items with how they changed since the reload before them
+    items: Vec<(DatabaseItem, DiffKind)>,
+    removed_items: Vec<DatabaseItem>,
+    /// Kept so the items can still be read from it
+    snapshot: Option<Snapshot>,
+    txn_id: u64,
+}
+
+/// Result of walking every entry of a database.
+#[derive(Debug)]
+pub struct Verification {
+    /// Number of entries read before the walk ended
+    pub n_read: usize,
+    /// Why the walk ended early, if it did
+    pub failure: Option<VerifyFailure>,
+}
+
+#[derive(Debug)]
+pub struct VerifyFailure {
+    /// The last key read before the error, or `None` if none was read
+    pub last_key: Option<Vec<u8>>,
+    pub message: String,
+}
+
+/// Keys selected for a batch delete.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyRange {
+    /// Keys starting with the bytes
+    Prefix(Vec<u8>),
+    /// Keys from `start` to `end`, both inclusive, where an empty bound is
+    /// unbounded
+    Between { start: Vec<u8>, end: Vec<u8> },
+}
+
+impl KeyRange {
+    /// Returns whether `key` is in the range, ordered as in a database with
+    /// `flags`.
+    pub fn contains(&self, flags: DatabaseFlags, key: &[u8]) -> bool {
+        match self {
+            Self::Prefix(prefix) => key.starts_with(prefix),
+            Self::Between { start, end } => {
+                (start.is_empty() || compare_keys(flags, start, key).is_le())
+                    && (end.is_empty() || compare_keys(flags, key, end).is_le())
+            }
+        }
+    }
+}
+
+/// Creates an empty database called `name` with `flags`, failing if one
+/// already exists.
+pub fn create(env: &heed::Env, name: &str, flags: DatabaseFlags) -> Result<()> {
+    let mut wtxn = env.write_txn().context("Failed to create write txn")?;
+
+    anyhow::ensure!(
+        env.open_database::<ByteSlice, ByteSlice>(&wtxn, Some(name))?
+            .is_none(),
+        "Database `{}` already exists",
+        name
+    );
+
+    env.database_options()
+        .types::<ByteSlice, ByteSlice>()
+        .name(name)
+        .flags(flags)
+        .create(&mut wtxn)
+        .context("Failed to create db")?;
+    wtxn.commit().context("Failed to commit write txn")?;
+
+    Ok(())
+}
+
+/// Creates a database called `new_name` with the flags of the one called
+/// `name`, then copies all of its entries in a single write txn, which is
+/// aborted, leaving nothing behind, if `is_cancelled` is set. Returns whether
+/// the copy was committed.
+///
+/// `n_copied` is incremented as entries are copied, so progress can be shown.
+pub fn duplicate(
+    env: &heed::Env,
+    name: &str,
+    new_name: &str,
+    n_copied: &AtomicUsize,
+    is_cancelled: &AtomicBool,
+) -> Result<bool> {
+    let mut wtxn = env.write_txn().context("Failed to create write txn")?;
+    let db = env
+        .open_database::<ByteSlice, ByteSlice>(&wtxn, Some(name))?
+        .with_context(|| format!("Database `{}` not found", name))?;
+    let flags = read_flags(env, &wtxn, Some(name))?;
+    anyhow::ensure!(
+        env.open_database::<ByteSlice, ByteSlice>(&wtxn, Some(new_name))?
+            .is_none(),
+        "Database `{}` already exists",
+        new_name
+    );
+    let new_db = env
+        .database_options()
+        .types::<ByteSlice, ByteSlice>()
+        .name(new_name)
+        .flags(flags)
+        .create(&mut wtxn)
+        .context("Failed to create db")?;
+
+    // The source is read in the write txn, which can't be borrowed by an
+    // iterator while putting, so it is copied in batches of whole keys
+    let mut last_key = None::<Vec<u8>>;
+    loop {
+        let start = last_key
+            .as_deref()
+            .map_or(Bound::Unbounded, Bound::Excluded);
+        let mut batch = Vec::<(Vec<u8>, Vec<u8>)>::new();
+        for item in db
+            .range(&wtxn, &(start, Bound::Unbounded))
+            .context("Failed to iter db")?
+        {
+            let (key, value) = item?;
+            // So all duplicates of a DUPSORT key are in the same batch
+            let is_new_key = batch.last().is_some_and(|(last_key, _)| last_key != key);
+            if batch.len() >= DUPLICATE_BATCH_LEN && is_new_key {
+                break;
+            }
+            batch.push((key.to_vec(), value.to_vec()));
+        }
+
+        if batch.is_empty() {
+            break;
+        }
+
+        for (key, value) in &batch {
+            if is_cancelled.load(atomic::Ordering::Relaxed) {
+                return Ok(false);
+            }
+
+            new_db
+                .put(&mut wtxn, key, value)
+                .context("Failed to put value")?;
+            n_copied.fetch_add(1, atomic::Ordering::Relaxed);
+        }
+        last_key = batch.pop().map(|(key, _)| key);
+    }
+    wtxn.commit().context("Failed to commit write txn")?;
+
+    Ok(true)
+}
+
+/// Returns the name of the database stored as the key `bytes` of the unnamed
+/// database, which can only be opened if it is valid UTF-8.
+pub fn name_from_bytes(bytes: &[u8]) -> Result<&str> {
+    std::str::from_utf8(bytes).with_context(|| {
+        format!(
+            "Database name `{}` is not valid UTF-8",
+            String::from_utf8_lossy(bytes)
+        )
+    })
+}
+
+/// Returns the names of the databases of `env`, starting with `None` for the
+/// unnamed one. Databases whose names aren't valid UTF-8 can't be opened, so
+/// they are returned by [`non_utf8_names`] instead.
+pub fn names(env: &heed::Env) -> Result<Vec<Option<String>>> {
+    let rtxn = env.read_txn()?;
+    let (names, _) = scan_names(env, &rtxn)?;
+    Ok(names)
+}
+
+/// Returns the names of the databases of `env` that aren't valid UTF-8, with
+/// the invalid bytes replaced, to list them as databases that can't be opened.
+pub fn non_utf8_names(env: &heed::Env) -> Result<Vec<String>> {
+    let rtxn = env.read_txn()?;
+    let (_, non_utf8_names) = scan_names(env, &rtxn)?;
+    Ok(non_utf8_names)
+}
+
+/// Returns the names of the databases of `env` as they are in `rtxn`, and the
+/// names that aren't valid UTF-8.
+pub fn scan_names(
+    env: &heed::Env,
+    rtxn: &heed::RoTxn,
+) -> Result<(Vec<Option<String>>, Vec<String>)> {
+    let unnamed_db = env
+        .open_database::<ByteSlice, ByteSlice>(rtxn, None)?
+        .context("Unnamed db not found")?;
+
+    let mut names = vec![None];
+    let mut non_utf8_names = Vec::new();
+    for item in unnamed_db.iter(rtxn)? {
+        let (key, value) = item?;
+        let Ok(name) = name_from_bytes(key) else {
+            // They can't be checked by opening them, so values that look like
+            // database records are taken as databases
+            if record_flags(value).is_some() {
+                non_utf8_names.push(String::from_utf8_lossy(key).into_owned());
+            }
+            continue;
+        };
+        // Keys of the unnamed database that aren't databases fail to open
+        if let Ok(Some(_)) = env.open_database::<ByteSlice, ByteSlice>(rtxn, Some(name)) {
+            names.push(Some(name.to_string()));
+        }
+    }
+
+    Ok((names, non_utf8_names))
+}
+
+/// Returns the number of entries of each of the named databases, by name in
+/// database order, and the names that aren't valid UTF-8, read in one txn.
+pub fn n_entries_by_name(env: &heed::Env) -> Result<(IndexMap<String, u64>, Vec<String>)> {
+    let rtxn = env.read_txn()?;
+    let (names, non_utf8_names) = scan_names(env, &rtxn)?;
+
+    let mut n_entries = IndexMap::new();
+    for name in names.into_iter().flatten() {
+        let db = env
+            .open_database::<ByteSlice, ByteSlice>(&rtxn, Some(&name))?
+            .context("Db not found")?;
+        n_entries.insert(name, db.len(&rtxn)?);
+    }
+
+    Ok((n_entries, non_utf8_names))
+}
+
+/// Reads the flags of the database called `name`. The unnamed database has
+/// none.
+pub fn read_flags(
+    env: &heed::Env,
+    rtxn: &heed::RoTxn,
+    name: Option<&str>,
+) -> Result<DatabaseFlags> {
+    let Some(name) = name else {
+        return Ok(DatabaseFlags::empty());
+    };
+
+    let main_db = env
+        .open_database::<ByteSlice, ByteSlice>(rtxn, None)?
+        .ok_or_else(|| anyhow!("unnamed database not found"))?;
+    let flags = main_db
+        .get(rtxn, name.as_bytes())?
+        .and_then(record_flags)
+        .unwrap_or_else(DatabaseFlags::empty);
+
+    Ok(flags)
+}
+
+/// Extracts the flags from a named database's `MDB_db` record, which is
+/// stored as its value in the unnamed database.
+fn record_flags(record: &[u8]) -> Option<DatabaseFlags> {
+    // md_pad: u32, md_flags: u16, md_depth: u16, then five size_t fields
+    const RECORD_LEN: usize = 4 + 2 + 2 + 5 * std::mem::size_of::<usize>();
+
+    if record.len() != RECORD_LEN {
+        return None;
+    }
+
+    let flags = u16::from_ne_bytes([record[4], record[5]]);
+    Some(DatabaseFlags::from_bits_truncate(flags as u32))
+}
+
+/// Compares keys the same way LMDB sorts them for a database with `flags`.
+pub fn compare_keys(flags: DatabaseFlags, a: &[u8], b: &[u8]) -> Ordering {
+    if flags.contains(DatabaseFlags::INTEGER_KEY) && a.len() == b.len() {
+        match a.len() {
+            4 => {
+                return u32::from_ne_bytes(a.try_into().unwrap())
+                    .cmp(&u32::from_ne_bytes(b.try_into().unwrap()))
+            }
+            8 => {
+                return u64::from_ne_bytes(a.try_into().unwrap())
+                    .cmp(&u64::from_ne_bytes(b.try_into().unwrap()))
+            }
+            _ => {}
+        }
+    }
+
+    if flags.contains(DatabaseFlags::REVERSE_KEY) {
+        a.iter().rev().cmp(b.iter().rev())
+    } else {
+        a.cmp(b)
+    }
+}
+
+/// Merges two iterators of items that are each sorted by key.
+struct MergeByKey<A: Iterator, B: Iterator> {
+    flags: DatabaseFlags,
+    a: Peekable<A>,
+    b: Peekable<B>,
+}
+
+impl<A, B> Iterator for MergeByKey<A, B>
+where
+    A: Iterator<Item = DatabaseItem>,
+    B: Iterator<Item = DatabaseItem>,
+{
+    type Item = DatabaseItem;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match (self.a.peek(), self.b.peek()) {
+            (Some(a), Some(b)) => {
+                if compare_keys(self.flags, &a.key(), &b.key()) == Ordering::Greater {
+                    self.b.next()
+                } else {
+                    self.a.next()
+                }
+            }
+            (Some(_), None) => self.a.next(),
+            (None, _) => self.b.next(),
+        }
+    }
+}
@@ -1,7 +1,9 @@
 use gtk::{glib, prelude::*, subclass::prelude::*};
 
+use crate::{diff::DiffKind, value_format::ValueFormat};
+
 mod imp {
-    use std::cell::OnceCell;
+    use std::cell::{Cell, OnceCell};
 
     use super::*;
 
@@ -12,6 +14,12 @@ mod imp {
         pub(super) key: OnceCell<glib::Bytes>,
         #[property(get, set, construct_only)]
         pub(super) data: OnceCell<glib::Bytes>,
+        /// How the item changed since the previous reload
+        #[property(get, set, builder(DiffKind::default()))]
+        pub(super) change: Cell<DiffKind>,
+
+        pub(super) checksum: OnceCell<u32>,
+        pub(super) format: OnceCell<ValueFormat>,
     }
 
     #[glib::object_subclass]
@@ -35,4 +43,21 @@ impl DatabaseItem {
             .property("data", data)
             .build()
     }
+
+    /// Returns the CRC32 of the data, computed on first use.
+    pub fn checksum(&self) -> u32 {
+        *self
+            .imp()
+            .checksum
+            .get_or_init(|| crc32fast::hash(self.data().as_ref()))
+    }
+
+    /// Returns the detected format of the data, detected on first use, as
+    /// detecting it can mean parsing the whole value.
+    pub fn format(&self) -> ValueFormat {
+        *self
+            .imp()
+            .format
+            .get_or_init(|| ValueFormat::detect(self.data().as_ref()))
+    }
 }
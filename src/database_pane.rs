@@ -0,0 +1,394 @@
+use anyhow::Result;
+use gtk::{
+    gio,
+    glib::{self, clone, closure},
+    prelude::*,
+    subclass::prelude::*,
+};
+use heed::Env;
+
+use crate::{
+    database::{self, Database},
+    database_item::DatabaseItem,
+    non_printable_display::NonPrintableDisplay,
+    text_encoding::TextEncoding,
+};
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use super::*;
+
+    #[derive(Debug, Default, glib::Properties, gtk::CompositeTemplate)]
+    #[properties(wrapper_type = super::DatabasePane)]
+    #[template(resource = "/io/github/seadve/LmdbViewer/ui/database_pane.ui")]
+    pub struct DatabasePane {
+        #[property(
+            get,
+            set = Self::set_key_encoding,
+            explicit_notify,
+            builder(TextEncoding::default())
+        )]
+        pub(super) key_encoding: Cell<TextEncoding>,
+        #[property(
+            get,
+            set = Self::set_value_encoding,
+            explicit_notify,
+            builder(TextEncoding::default())
+        )]
+        pub(super) value_encoding: Cell<TextEncoding>,
+        #[property(
+            get,
+            set = Self::set_non_printable_display,
+            explicit_notify,
+            builder(NonPrintableDisplay::default())
+        )]
+        pub(super) non_printable_display: Cell<NonPrintableDisplay>,
+        /// Maximum number of value bytes shown in a cell
+        #[property(get, set = Self::set_preview_length, explicit_notify)]
+        pub(super) preview_length: Cell<u32>,
+        /// Whether selecting a key scrolls the window's entries to it, and
+        /// [`super::DatabasePane::scroll_to_key`] is followed
+        #[property(get, set)]
+        pub(super) sync_scrolling: Cell<bool>,
+
+        #[template_child]
+        pub(super) drop_down: TemplateChild<gtk::DropDown>,
+        #[template_child]
+        pub(super) search_entry: TemplateChild<gtk::SearchEntry>,
+        #[template_child]
+        pub(super) column_view: TemplateChild<gtk::ColumnView>,
+        #[template_child]
+        pub(super) selection_model: TemplateChild<gtk::SingleSelection>,
+        #[template_child]
+        pub(super) filter_model: TemplateChild<gtk::FilterListModel>,
+
+        pub(super) env: RefCell<Option<Env>>,
+        /// Cells of the key and value columns, to update when how they are
+        /// shown changes
+        pub(super) key_cells: RefCell<Vec<glib::WeakRef<gtk::ListItem>>>,
+        pub(super) value_cells: RefCell<Vec<glib::WeakRef<gtk::ListItem>>>,
+        /// Whether the selection is being changed by
+        /// [`super::DatabasePane::scroll_to_key`], rather than by the user
+        pub(super) is_scrolling_to_key: Cell<bool>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for DatabasePane {
+        const NAME: &'static str = "LvDatabasePane";
+        type Type = super::DatabasePane;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for DatabasePane {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            self.obj().setup_view();
+        }
+
+        fn dispose(&self) {
+            self.dispose_template();
+        }
+    }
+
+    impl WidgetImpl for DatabasePane {}
+
+    impl DatabasePane {
+        fn set_key_encoding(&self, key_encoding: TextEncoding) {
+            if key_encoding == self.key_encoding.get() {
+                return;
+            }
+
+            self.key_encoding.set(key_encoding);
+
+            let obj = self.obj();
+            obj.update_cells();
+            obj.notify_key_encoding();
+        }
+
+        fn set_value_encoding(&self, value_encoding: TextEncoding) {
+            if value_encoding == self.value_encoding.get() {
+                return;
+            }
+
+            self.value_encoding.set(value_encoding);
+
+            let obj = self.obj();
+            obj.update_cells();
+            obj.notify_value_encoding();
+        }
+
+        fn set_non_printable_display(&self, non_printable_display: NonPrintableDisplay) {
+            if non_printable_display == self.non_printable_display.get() {
+                return;
+            }
+
+            self.non_printable_display.set(non_printable_display);
+
+            let obj = self.obj();
+            obj.update_cells();
+            obj.notify_non_printable_display();
+        }
+
+        fn set_preview_length(&self, preview_length: u32) {
+            if preview_length == self.preview_length.get() {
+                return;
+            }
+
+            self.preview_length.set(preview_length);
+
+            let obj = self.obj();
+            obj.update_cells();
+            obj.notify_preview_length();
+        }
+    }
+}
+
+glib::wrapper! {
+    /// A view of a database of the env, independent of the window's, to show
+    /// next to it.
+    pub struct DatabasePane(ObjectSubclass<imp::DatabasePane>)
+        @extends gtk::Widget;
+}
+
+impl DatabasePane {
+    /// Lists the databases of `env`, keeping the selected one if it is also
+    /// in `env`, or clears the pane if `env` is `None`.
+    pub fn set_env(&self, env: Option<&Env>) -> Result<()> {
+        let imp = self.imp();
+
+        let db_key = self.selected_db_key();
+
+        imp.env.replace(env.cloned());
+        imp.filter_model.set_model(gio::ListModel::NONE);
+
+        let Some(env) = env else {
+            imp.drop_down.set_model(gio::ListModel::NONE);
+            return Ok(());
+        };
+
+        let db = Database::load(env, None)?;
+        let position = db_key.and_then(|db_key| {
+            (0..db.n_items()).find(|&position| {
+                db.item(position)
+                    .and_downcast::<DatabaseItem>()
+                    .is_some_and(|item| item.key() == db_key)
+            })
+        });
+        imp.drop_down.set_model(Some(&db));
+        if let Some(position) = position {
+            imp.drop_down.set_selected(position);
+        }
+
+        Ok(())
+    }
+
+    /// Selects and scrolls to `key`, or to the first key after it if it isn't
+    /// shown, while scrolling is synchronized.
+    pub fn scroll_to_key(&self, key: &[u8]) {
+        let imp = self.imp();
+
+        if !self.sync_scrolling() {
+            return;
+        }
+
+        let model = &imp.selection_model;
+        let position = (0..model.n_items())
+            .find(|&position| {
+                model
+                    .item(position)
+                    .and_downcast::<DatabaseItem>()
+                    .is_some_and(|item| item.key().as_ref() >= key)
+            })
+            .unwrap_or_else(|| model.n_items().saturating_sub(1));
+        if model.n_items() == 0 || position == model.selected() {
+            return;
+        }
+
+        imp.is_scrolling_to_key.set(true);
+        imp.column_view
+            .scroll_to(position, None, gtk::ListScrollFlags::SELECT, None);
+        imp.is_scrolling_to_key.set(false);
+    }
+
+    /// Returns the raw name of the selected database, which may not be valid
+    /// UTF-8.
+    fn selected_db_key(&self) -> Option<glib::Bytes> {
+        self.imp()
+            .drop_down
+            .selected_item()
+            .and_downcast::<DatabaseItem>()
+            .map(|item| item.key())
+    }
+
+    fn load_selected_db(&self) {
+        let imp = self.imp();
+
+        let env = imp.env.borrow();
+        let (Some(env), Some(db_key)) = (env.as_ref(), self.selected_db_key()) else {
+            imp.filter_model.set_model(gio::ListModel::NONE);
+            return;
+        };
+
+        let db = database::name_from_bytes(&db_key)
+            .and_then(|db_name| Database::load(env, Some(db_name)));
+        match db {
+            Ok(db) => imp.filter_model.set_model(Some(&db)),
+            Err(err) => {
+                tracing::error!("Failed to load db in split view: {:?}", &err);
+                imp.filter_model.set_model(gio::ListModel::NONE);
+            }
+        }
+    }
+
+    fn key_text(&self, item: &DatabaseItem) -> String {
+        self.non_printable_display()
+            .render(&item.key(), self.key_encoding())
+    }
+
+    fn value_text(&self, item: &DatabaseItem) -> String {
+        let data = item.data();
+        let preview_length = (self.preview_length() as usize).min(data.len());
+        self.non_printable_display()
+            .render(&data[..preview_length], self.value_encoding())
+    }
+
+    fn update_cells(&self) {
+        let imp = self.imp();
+
+        for list_item in imp
+            .key_cells
+            .borrow()
+            .iter()
+            .filter_map(|cell| cell.upgrade())
+        {
+            self.update_cell(&list_item, true);
+        }
+        for list_item in imp
+            .value_cells
+            .borrow()
+            .iter()
+            .filter_map(|cell| cell.upgrade())
+        {
+            self.update_cell(&list_item, false);
+        }
+    }
+
+    fn update_cell(&self, list_item: &gtk::ListItem, is_key: bool) {
+        let Some(label) = list_item.child().and_downcast::<gtk::Label>() else {
+            return;
+        };
+
+        let text = list_item
+            .item()
+            .and_downcast::<DatabaseItem>()
+            .map(|item| {
+                if is_key {
+                    self.key_text(&item)
+                } else {
+                    self.value_text(&item)
+                }
+            })
+            .unwrap_or_default();
+        label.set_label(&text);
+    }
+
+    fn column_factory(&self, is_key: bool) -> gtk::SignalListItemFactory {
+        let factory = gtk::SignalListItemFactory::new();
+        factory.connect_setup(clone!(@weak self as obj => move |_, list_item| {
+            let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
+            let label = gtk::Label::builder()
+                .xalign(0.0)
+                .ellipsize(gtk::pango::EllipsizeMode::End)
+                .css_classes(["monospace"])
+                .build();
+            list_item.set_child(Some(&label));
+
+            let imp = obj.imp();
+            let cells = if is_key { &imp.key_cells } else { &imp.value_cells };
+            cells.borrow_mut().push(list_item.downgrade());
+        }));
+        factory.connect_bind(clone!(@weak self as obj => move |_, list_item| {
+            obj.update_cell(list_item.downcast_ref().unwrap(), is_key);
+        }));
+        factory.connect_teardown(clone!(@weak self as obj => move |_, list_item| {
+            let imp = obj.imp();
+            let cells = if is_key { &imp.key_cells } else { &imp.value_cells };
+            cells
+                .borrow_mut()
+                .retain(|cell| cell.upgrade().is_some_and(|cell| &cell != list_item));
+        }));
+        factory
+    }
+
+    fn setup_view(&self) {
+        let imp = self.imp();
+
+        imp.drop_down
+            .set_expression(Some(&gtk::ClosureExpression::new::<glib::GString>(
+                &[] as &[gtk::Expression],
+                closure!(|list_item: DatabaseItem| {
+                    String::from_utf8_lossy(list_item.key().as_ref()).to_string()
+                }),
+            )));
+        imp.drop_down
+            .connect_selected_item_notify(clone!(@weak self as obj => move |_| {
+                obj.load_selected_db();
+            }));
+
+        let filter = gtk::CustomFilter::new(clone!(@weak self as obj => @default-return true,
+            move |item| {
+                let text = obj.imp().search_entry.text().to_lowercase();
+                text.is_empty()
+                    || obj
+                        .key_text(item.downcast_ref::<DatabaseItem>().unwrap())
+                        .to_lowercase()
+                        .contains(&text)
+            }
+        ));
+        imp.filter_model.set_filter(Some(&filter));
+        imp.search_entry
+            .connect_search_changed(clone!(@weak filter => move |_| {
+                filter.changed(gtk::FilterChange::Different);
+            }));
+
+        imp.selection_model.connect_selected_item_notify(
+            clone!(@weak self as obj => move |selection_model| {
+                if !obj.sync_scrolling() || obj.imp().is_scrolling_to_key.get() {
+                    return;
+                }
+
+                let Some(item) = selection_model.selected_item().and_downcast::<DatabaseItem>()
+                else {
+                    return;
+                };
+                let key = item.key().to_vec();
+                if let Err(err) = obj.activate_action("win.scroll-to-key", Some(&key.to_variant()))
+                {
+                    tracing::warn!("Failed to scroll to key: {:?}", &err);
+                }
+            }),
+        );
+
+        let key_column = gtk::ColumnViewColumn::new(Some("Key"), Some(self.column_factory(true)));
+        key_column.set_resizable(true);
+        key_column.set_expand(true);
+        imp.column_view.append_column(&key_column);
+
+        let value_column =
+            gtk::ColumnViewColumn::new(Some("Value"), Some(self.column_factory(false)));
+        value_column.set_resizable(true);
+        value_column.set_expand(true);
+        imp.column_view.append_column(&value_column);
+    }
+}
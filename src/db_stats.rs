@@ -0,0 +1,92 @@
+use anyhow::{Context, Result};
+use heed::types::ByteSlice;
+
+use std::mem::size_of;
+
+use crate::database;
+
+/// Storage used by a named database.
+#[derive(Debug, Clone)]
+pub struct DbStats {
+    pub name: String,
+    pub n_entries: u64,
+    pub n_branch_pages: u64,
+    pub n_leaf_pages: u64,
+    pub n_overflow_pages: u64,
+    /// Total length of all keys
+    pub key_bytes: u64,
+    /// Total length of all values
+    pub value_bytes: u64,
+}
+
+impl DbStats {
+    /// Collects the stats of all named databases in `env`, which reads every
+    /// entry, so this is better done in a separate thread.
+    pub fn collect(env: &heed::Env) -> Result<Vec<Self>> {
+        let rtxn = env.read_txn().context("Failed to create read txn")?;
+        let main_db = env
+            .open_database::<ByteSlice, ByteSlice>(&rtxn, None)?
+            .context("Unnamed database not found")?;
+
+        let mut all_stats = Vec::new();
+        for item in main_db.iter(&rtxn)? {
+            let (key, record) = item?;
+            let Ok(name) = database::name_from_bytes(key) else {
+                continue;
+            };
+            let Some(record_fields) = record_fields(record) else {
+                continue;
+            };
+            let Some(db) = env.open_database::<ByteSlice, ByteSlice>(&rtxn, Some(name))? else {
+                continue;
+            };
+
+            let mut key_bytes = 0;
+            let mut value_bytes = 0;
+            for item in db.iter(&rtxn)? {
+                let (key, value) = item?;
+                key_bytes += key.len() as u64;
+                value_bytes += value.len() as u64;
+            }
+
+            let [n_branch_pages, n_leaf_pages, n_overflow_pages, n_entries] = record_fields;
+            all_stats.push(Self {
+                name: name.to_string(),
+                n_entries,
+                n_branch_pages,
+                n_leaf_pages,
+                n_overflow_pages,
+                key_bytes,
+                value_bytes,
+            });
+        }
+
+        Ok(all_stats)
+    }
+
+    pub fn n_pages(&self) -> u64 {
+        self.n_branch_pages + self.n_leaf_pages + self.n_overflow_pages
+    }
+}
+
+/// Extracts the branch, leaf and overflow page counts and the entry count
+/// from a named database's `MDB_db` record.
+fn record_fields(record: &[u8]) -> Option<[u64; 4]> {
+    // md_pad: u32, md_flags: u16, md_depth: u16, then five size_t fields
+    const FIELDS_OFFSET: usize = 4 + 2 + 2;
+    const RECORD_LEN: usize = FIELDS_OFFSET + 5 * size_of::<usize>();
+
+    if record.len() != RECORD_LEN {
+        return None;
+    }
+
+    let field = |index: usize| {
+        let offset = FIELDS_OFFSET + index * size_of::<usize>();
+        usize::from_ne_bytes(
+            record[offset..offset + size_of::<usize>()]
+                .try_into()
+                .unwrap(),
+        ) as u64
+    };
+    Some([field(0), field(1), field(2), field(3)])
+}
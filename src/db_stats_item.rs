@@ -0,0 +1,74 @@
+use gtk::{glib, prelude::*, subclass::prelude::*};
+
+use crate::db_stats::DbStats;
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use super::*;
+
+    #[derive(Debug, Default, glib::Properties)]
+    #[properties(wrapper_type = super::DbStatsItem)]
+    pub struct DbStatsItem {
+        #[property(get, set, construct_only)]
+        pub(super) name: RefCell<String>,
+        #[property(get, set, construct_only)]
+        pub(super) n_entries: Cell<u64>,
+        #[property(get, set, construct_only)]
+        pub(super) n_branch_pages: Cell<u64>,
+        #[property(get, set, construct_only)]
+        pub(super) n_leaf_pages: Cell<u64>,
+        #[property(get, set, construct_only)]
+        pub(super) n_overflow_pages: Cell<u64>,
+        #[property(get, set, construct_only)]
+        pub(super) key_bytes: Cell<u64>,
+        #[property(get, set, construct_only)]
+        pub(super) value_bytes: Cell<u64>,
+        /// Bytes taken by all pages of the database
+        #[property(get, set, construct_only)]
+        pub(super) size: Cell<u64>,
+        /// Size relative to the largest database, from 0 to 1
+        #[property(get, set, construct_only)]
+        pub(super) share: Cell<f64>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for DbStatsItem {
+        const NAME: &'static str = "LvDbStatsItem";
+        type Type = super::DbStatsItem;
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for DbStatsItem {}
+}
+
+glib::wrapper! {
+     pub struct DbStatsItem(ObjectSubclass<imp::DbStatsItem>);
+}
+
+impl DbStatsItem {
+    /// Creates an item of `stats`, where `max_size` is the size of the
+    /// largest database.
+    pub fn new(stats: &DbStats, page_size: u64, max_size: u64) -> Self {
+        let size = stats.n_pages() * page_size;
+
+        glib::Object::builder()
+            .property("name", &stats.name)
+            .property("n-entries", stats.n_entries)
+            .property("n-branch-pages", stats.n_branch_pages)
+            .property("n-leaf-pages", stats.n_leaf_pages)
+            .property("n-overflow-pages", stats.n_overflow_pages)
+            .property("key-bytes", stats.key_bytes)
+            .property("value-bytes", stats.value_bytes)
+            .property("size", size)
+            .property(
+                "share",
+                if max_size == 0 {
+                    0.0
+                } else {
+                    size as f64 / max_size as f64
+                },
+            )
+            .build()
+    }
+}
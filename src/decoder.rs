@@ -0,0 +1,268 @@
+use anyhow::{bail, ensure, Result};
+use gettextrs::gettext;
+use gtk::glib;
+use serde_json::{Map, Number, Value};
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{config::APP_ID, value_format::ValueFormat};
+
+mod plugin;
+mod protobuf;
+mod script;
+
+pub use self::protobuf::{load_schema as load_protobuf_schema, ProtobufWireFormat};
+use self::{plugin::Plugin, script::Script};
+
+/// Turns raw value bytes into a structured representation.
+pub trait Decoder {
+    /// Stable identifier used to persist the selected decoder.
+    fn id(&self) -> &str;
+
+    /// Human-readable name shown in the decoder selector.
+    fn name(&self) -> String;
+
+    fn decode(&self, bytes: &[u8]) -> Result<Value>;
+
+    /// Like [`Decoder::decode`], but also given the key of the value, for
+    /// decoders whose output depends on it.
+    fn decode_entry(&self, _key: &[u8], bytes: &[u8]) -> Result<Value> {
+        self.decode(bytes)
+    }
+}
+
+/// Decodes `bytes` with the built-in decoder of their detected format, or
+/// returns `None` if it has none or fails to decode.
+pub fn decode_detected(bytes: &[u8]) -> Option<Value> {
+    let decoder_id = ValueFormat::detect(bytes).decoder_id()?;
+    builtins()
+        .into_iter()
+        .find(|decoder| decoder.id() == decoder_id)?
+        .decode(bytes)
+        .ok()
+}
+
+/// Returns all decoders that ship with the app.
+pub fn builtins() -> Vec<Box<dyn Decoder>> {
+    vec![
+        Box::new(Json),
+        Box::new(MessagePack),
+        Box::new(Cbor),
+        Box::new(Bincode),
+        Box::new(Bson),
+        Box::new(ProtobufWireFormat),
+    ]
+}
+
+/// Returns the directory where users add decoders as scripts or plugins.
+pub fn user_decoders_dir() -> PathBuf {
+    glib::user_config_dir().join(APP_ID).join("decoders")
+}
+
+/// Loads the scripts and plugins in `dir`, sorted by file name.
+pub fn load_user_decoders(dir: &Path) -> Vec<Box<dyn Decoder>> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut paths = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    paths
+        .iter()
+        .filter_map(|path| {
+            let decoder = match path.extension()?.to_str()? {
+                script::EXTENSION => Script::load(path).map(|s| Box::new(s) as Box<dyn Decoder>),
+                plugin::EXTENSION => Plugin::load(path).map(|p| Box::new(p) as Box<dyn Decoder>),
+                _ => return None,
+            };
+            decoder
+                .inspect_err(|err| {
+                    tracing::warn!("Failed to load decoder `{}`: {:?}", path.display(), err);
+                })
+                .ok()
+        })
+        .collect()
+}
+
+/// Returns whether `decoder` was loaded from the user decoders directory.
+pub fn is_user_decoder(decoder: &dyn Decoder) -> bool {
+    decoder.id().starts_with(script::ID_PREFIX) || decoder.id().starts_with(plugin::ID_PREFIX)
+}
+
+pub struct Json;
+
+impl Decoder for Json {
+    fn id(&self) -> &str {
+        "json"
+    }
+
+    fn name(&self) -> String {
+        gettext("JSON")
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Value> {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+}
+
+pub struct MessagePack;
+
+impl Decoder for MessagePack {
+    fn id(&self) -> &str {
+        "msgpack"
+    }
+
+    fn name(&self) -> String {
+        gettext("MessagePack")
+    }
+
+    fn decode(&self, mut bytes: &[u8]) -> Result<Value> {
+        let value = rmpv::decode::read_value(&mut bytes)?;
+        ensure!(bytes.is_empty(), "{} trailing bytes", bytes.len());
+        Ok(msgpack_to_json(value))
+    }
+}
+
+pub struct Cbor;
+
+impl Decoder for Cbor {
+    fn id(&self) -> &str {
+        "cbor"
+    }
+
+    fn name(&self) -> String {
+        gettext("CBOR")
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Value> {
+        let value = ciborium::from_reader::<ciborium::Value, _>(bytes)?;
+        Ok(cbor_to_json(value))
+    }
+}
+
+/// Bincode is not self-describing, so without a schema only the most common
+/// top-level shape, a length-prefixed string, can be recovered.
+pub struct Bincode;
+
+impl Decoder for Bincode {
+    fn id(&self) -> &str {
+        "bincode"
+    }
+
+    fn name(&self) -> String {
+        gettext("Bincode")
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Value> {
+        match bincode::deserialize::<String>(bytes) {
+            Ok(string) if bincode::serialized_size(&string)? == bytes.len() as u64 => {
+                Ok(Value::String(string))
+            }
+            _ => bail!("value is not a bincode string; other bincode values need a schema"),
+        }
+    }
+}
+
+pub struct Bson;
+
+impl Decoder for Bson {
+    fn id(&self) -> &str {
+        "bson"
+    }
+
+    fn name(&self) -> String {
+        gettext("BSON")
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Value> {
+        let document = bson::Document::from_reader(bytes)?;
+        Ok(bson::Bson::Document(document).into_relaxed_extjson())
+    }
+}
+
+fn bytes_to_json(bytes: &[u8]) -> Value {
+    Value::String(bytes.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+fn map_key(key: Value) -> String {
+    match key {
+        Value::String(string) => string,
+        other => other.to_string(),
+    }
+}
+
+fn msgpack_to_json(value: rmpv::Value) -> Value {
+    match value {
+        rmpv::Value::Nil => Value::Null,
+        rmpv::Value::Boolean(boolean) => Value::Bool(boolean),
+        rmpv::Value::Integer(integer) => integer
+            .as_u64()
+            .map(Number::from)
+            .or_else(|| integer.as_i64().map(Number::from))
+            .map_or(Value::Null, Value::Number),
+        rmpv::Value::F32(float) => {
+            Number::from_f64(float as f64).map_or(Value::Null, Value::Number)
+        }
+        rmpv::Value::F64(float) => Number::from_f64(float).map_or(Value::Null, Value::Number),
+        rmpv::Value::String(string) => match string.into_str() {
+            Some(string) => Value::String(string),
+            None => Value::Null,
+        },
+        rmpv::Value::Binary(bytes) => bytes_to_json(&bytes),
+        rmpv::Value::Array(values) => {
+            Value::Array(values.into_iter().map(msgpack_to_json).collect())
+        }
+        rmpv::Value::Map(entries) => Value::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| (map_key(msgpack_to_json(key)), msgpack_to_json(value)))
+                .collect::<Map<_, _>>(),
+        ),
+        rmpv::Value::Ext(kind, bytes) => {
+            let mut object = Map::new();
+            object.insert("ext".to_string(), Value::from(kind));
+            object.insert("data".to_string(), bytes_to_json(&bytes));
+            Value::Object(object)
+        }
+    }
+}
+
+fn cbor_to_json(value: ciborium::Value) -> Value {
+    match value {
+        ciborium::Value::Null => Value::Null,
+        ciborium::Value::Bool(boolean) => Value::Bool(boolean),
+        ciborium::Value::Integer(integer) => {
+            let integer = i128::from(integer);
+            u64::try_from(integer)
+                .map(Number::from)
+                .or_else(|_| i64::try_from(integer).map(Number::from))
+                .map_or_else(|_| Value::String(integer.to_string()), Value::Number)
+        }
+        ciborium::Value::Float(float) => Number::from_f64(float).map_or(Value::Null, Value::Number),
+        ciborium::Value::Text(string) => Value::String(string),
+        ciborium::Value::Bytes(bytes) => bytes_to_json(&bytes),
+        ciborium::Value::Array(values) => {
+            Value::Array(values.into_iter().map(cbor_to_json).collect())
+        }
+        ciborium::Value::Map(entries) => Value::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| (map_key(cbor_to_json(key)), cbor_to_json(value)))
+                .collect::<Map<_, _>>(),
+        ),
+        ciborium::Value::Tag(tag, value) => {
+            let mut object = Map::new();
+            object.insert("tag".to_string(), Value::from(tag));
+            object.insert("value".to_string(), cbor_to_json(*value));
+            Value::Object(object)
+        }
+        _ => Value::Null,
+    }
+}
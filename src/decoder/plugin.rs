@@ -0,0 +1,136 @@
+use std::{fs, path::Path};
+
+use anyhow::{anyhow, bail, Context, Result};
+use gettextrs::gettext;
+use serde_json::Value;
+use wasmi::{Config, Engine, Linker, Module, StoreLimits, StoreLimitsBuilder};
+use wasmi_wasi::{WasiCtx, WasiCtxBuilder};
+
+use super::Decoder;
+
+/// Prefix of the ids of plugin decoders, followed by the plugin name
+pub const ID_PREFIX: &str = "plugin:";
+
+/// File extension of decoder plugins
+pub const EXTENSION: &str = "wasm";
+
+/// Limits that keep a runaway or malicious plugin from freezing the app
+const MAX_FUEL: u64 = 100_000_000;
+const MAX_MEMORY_SIZE: usize = 256 * 1024 * 1024;
+
+/// Decodes values with a user-supplied WebAssembly module targeting WASI.
+///
+/// The module must export its `memory` and these functions:
+/// - `alloc(len: i32) -> i32`, returning a pointer to `len` writable bytes
+/// - `decode(key_ptr: i32, key_len: i32, value_ptr: i32, value_len: i32) -> i64`,
+///   returning the pointer to its UTF-8 output in the high 32 bits and its
+///   length in the low 32 bits
+///
+/// The output is the decoded value as JSON, or an error message if it is not
+/// valid JSON. Each value is decoded in a fresh instance that has no access
+/// to files, the environment, or the network.
+pub struct Plugin {
+    id: String,
+    name: String,
+    engine: Engine,
+    /// The compiled module, or the error that made it fail to compile, which
+    /// is shown when decoding
+    module: Result<Module, String>,
+}
+
+impl Plugin {
+    pub fn load(path: &Path) -> Result<Self> {
+        let name = path
+            .file_stem()
+            .context("Plugin has no file name")?
+            .to_string_lossy()
+            .to_string();
+        let bytes =
+            fs::read(path).with_context(|| format!("Failed to read `{}`", path.display()))?;
+
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, bytes).map_err(|err| err.to_string());
+
+        Ok(Self {
+            id: format!("{}{}", ID_PREFIX, name),
+            name,
+            engine,
+            module,
+        })
+    }
+}
+
+impl Decoder for Plugin {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> String {
+        format!("{} ({})", self.name, gettext("Plugin"))
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Value> {
+        self.decode_entry(&[], bytes)
+    }
+
+    fn decode_entry(&self, key: &[u8], bytes: &[u8]) -> Result<Value> {
+        let module = self.module.as_ref().map_err(|err| anyhow!("{}", err))?;
+
+        let mut store = wasmi::Store::new(
+            &self.engine,
+            State {
+                wasi: WasiCtxBuilder::new().build(),
+                limits: StoreLimitsBuilder::new()
+                    .memory_size(MAX_MEMORY_SIZE)
+                    .build(),
+            },
+        );
+        store.limiter(|state| &mut state.limits);
+        store.set_fuel(MAX_FUEL)?;
+
+        let mut linker = Linker::<State>::new(&self.engine);
+        wasmi_wasi::add_to_linker(&mut linker, |state| &mut state.wasi)?;
+        let instance = linker.instantiate_and_start(&mut store, module)?;
+
+        // Reactor modules need to be initialized before their exports are used
+        if let Ok(initialize) = instance.get_typed_func::<(), ()>(&store, "_initialize") {
+            initialize.call(&mut store, ())?;
+        }
+
+        let memory = instance
+            .get_memory(&store, "memory")
+            .context("Plugin does not export `memory`")?;
+        let alloc = instance.get_typed_func::<i32, i32>(&store, "alloc")?;
+        let decode = instance.get_typed_func::<(i32, i32, i32, i32), i64>(&store, "decode")?;
+
+        let mut write_input = |input: &[u8]| -> Result<(i32, i32)> {
+            let len = i32::try_from(input.len()).context("Input is too large")?;
+            let ptr = alloc.call(&mut store, len)?;
+            memory.write(&mut store, ptr as u32 as usize, input)?;
+            Ok((ptr, len))
+        };
+        let (key_ptr, key_len) = write_input(key)?;
+        let (value_ptr, value_len) = write_input(bytes)?;
+
+        let output = decode.call(&mut store, (key_ptr, key_len, value_ptr, value_len))?;
+        let output_ptr = (output as u64 >> 32) as usize;
+        let output_len = (output as u64 & u32::MAX as u64) as usize;
+        let output = memory
+            .data(&store)
+            .get(output_ptr..output_ptr + output_len)
+            .context("Plugin output is out of bounds")?;
+
+        match serde_json::from_slice(output) {
+            Ok(value) => Ok(value),
+            Err(_) => bail!("{}", String::from_utf8_lossy(output)),
+        }
+    }
+}
+
+/// Data of the store a plugin runs in
+struct State {
+    wasi: WasiCtx,
+    limits: StoreLimits,
+}
@@ -0,0 +1,186 @@
+use std::{fs, path::Path};
+
+use anyhow::{bail, Context, Result};
+use gettextrs::gettext;
+use prost_reflect::{DescriptorPool, DynamicMessage, MessageDescriptor, SerializeOptions};
+use serde_json::{json, Value};
+
+use super::{bytes_to_json, Decoder};
+
+/// Decodes values as a message type from a user-supplied schema.
+pub struct Protobuf {
+    id: String,
+    message: MessageDescriptor,
+}
+
+impl Protobuf {
+    fn new(message: MessageDescriptor) -> Self {
+        Self {
+            id: format!("protobuf:{}", message.full_name()),
+            message,
+        }
+    }
+}
+
+impl Decoder for Protobuf {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> String {
+        format!("{} ({})", self.message.full_name(), gettext("Protobuf"))
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Value> {
+        let message = match DynamicMessage::decode(self.message.clone(), bytes) {
+            Ok(message) => message,
+            Err(err) => {
+                return Ok(json!({
+                    "decode_error": err.to_string(),
+                    "wire_format": wire_format_to_json(bytes)?,
+                }))
+            }
+        };
+
+        let mut value = message
+            .serialize_with_options(serde_json::value::Serializer, &SerializeOptions::new())?;
+
+        let mut unknown_bytes = Vec::new();
+        for field in message.unknown_fields() {
+            field.encode(&mut unknown_bytes);
+        }
+        if !unknown_bytes.is_empty() {
+            if let Value::Object(ref mut object) = value {
+                object.insert(
+                    "unknown_fields".to_string(),
+                    wire_format_to_json(&unknown_bytes)?,
+                );
+            }
+        }
+
+        Ok(value)
+    }
+}
+
+/// Shows the raw wire-format structure of a protobuf message, without a schema.
+pub struct ProtobufWireFormat;
+
+impl Decoder for ProtobufWireFormat {
+    fn id(&self) -> &str {
+        "protobuf-wire-format"
+    }
+
+    fn name(&self) -> String {
+        gettext("Protobuf (Wire Format)")
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Value> {
+        wire_format_to_json(bytes)
+    }
+}
+
+/// Loads a `.proto` file, or a compiled descriptor set, and returns a decoder
+/// for every message type it defines.
+pub fn load_schema(path: &Path) -> Result<Vec<Box<dyn Decoder>>> {
+    let pool = if path
+        .extension()
+        .is_some_and(|extension| extension == "proto")
+    {
+        let parent = path.parent().context("Schema has no parent directory")?;
+        let file_name = path.file_name().context("Schema has no file name")?;
+        protox::Compiler::new([parent])?
+            .include_imports(true)
+            .open_file(file_name)?
+            .descriptor_pool()
+    } else {
+        let bytes =
+            fs::read(path).with_context(|| format!("Failed to read `{}`", path.display()))?;
+        DescriptorPool::decode(bytes.as_slice())?
+    };
+
+    Ok(pool
+        .all_messages()
+        .filter(|message| message.package_name() != "google.protobuf")
+        .map(|message| Box::new(Protobuf::new(message)) as Box<dyn Decoder>)
+        .collect())
+}
+
+fn wire_format_to_json(bytes: &[u8]) -> Result<Value> {
+    match parse_wire_format(bytes) {
+        Some(fields) => Ok(Value::Array(fields)),
+        None => bail!("value is not in protobuf wire format"),
+    }
+}
+
+fn parse_wire_format(mut bytes: &[u8]) -> Option<Vec<Value>> {
+    let mut fields = Vec::new();
+
+    while !bytes.is_empty() {
+        let tag = read_varint(&mut bytes)?;
+        let number = tag >> 3;
+
+        if number == 0 {
+            return None;
+        }
+
+        let (wire_type, value) = match tag & 0b111 {
+            0 => ("varint", json!(read_varint(&mut bytes)?)),
+            1 => {
+                let value = u64::from_le_bytes(take(&mut bytes, 8)?.try_into().unwrap());
+                ("i64", json!(value))
+            }
+            2 => {
+                let len = usize::try_from(read_varint(&mut bytes)?).ok()?;
+                let value = take(&mut bytes, len)?;
+                let value = if let Some(fields) =
+                    parse_wire_format(value).filter(|fields| !fields.is_empty())
+                {
+                    Value::Array(fields)
+                } else if let Ok(string) = std::str::from_utf8(value) {
+                    Value::String(string.to_string())
+                } else {
+                    bytes_to_json(value)
+                };
+                ("len", value)
+            }
+            5 => {
+                let value = u32::from_le_bytes(take(&mut bytes, 4)?.try_into().unwrap());
+                ("i32", json!(value))
+            }
+            _ => return None,
+        };
+
+        fields.push(json!({
+            "field": number,
+            "wire_type": wire_type,
+            "value": value,
+        }));
+    }
+
+    Some(fields)
+}
+
+fn read_varint(bytes: &mut &[u8]) -> Option<u64> {
+    let mut value = 0;
+
+    for (index, byte) in bytes.iter().enumerate().take(10) {
+        value |= ((byte & 0x7f) as u64) << (7 * index);
+
+        if byte & 0x80 == 0 {
+            *bytes = &bytes[index + 1..];
+            return Some(value);
+        }
+    }
+
+    None
+}
+
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+    if bytes.len() < len {
+        return None;
+    }
+
+    let (taken, rest) = bytes.split_at(len);
+    *bytes = rest;
+    Some(taken)
+}
@@ -0,0 +1,94 @@
+use std::{fs, path::Path};
+
+use anyhow::{anyhow, Context, Result};
+use gettextrs::gettext;
+use rhai::{module_resolvers::DummyModuleResolver, Blob, Dynamic, Engine, Scope, AST};
+use serde_json::Value;
+
+use super::Decoder;
+
+/// Prefix of the ids of script decoders, followed by the script name
+pub const ID_PREFIX: &str = "script:";
+
+/// File extension of decoder scripts
+pub const EXTENSION: &str = "rhai";
+
+/// Name of the function a script must define, taking the value bytes as a
+/// blob and returning a string, or anything representable as JSON
+const DECODE_FN_NAME: &str = "decode";
+
+/// Limits that keep a runaway or malicious script from freezing the app
+const MAX_OPERATIONS: u64 = 10_000_000;
+const MAX_CALL_LEVELS: usize = 64;
+const MAX_EXPR_DEPTH: usize = 64;
+const MAX_COLLECTION_LEN: usize = 1024 * 1024;
+
+/// Decodes values with a user-written Rhai script.
+pub struct Script {
+    id: String,
+    name: String,
+    engine: Engine,
+    /// The compiled script, or the error that made it fail to compile, which
+    /// is shown when decoding so it can be fixed
+    ast: Result<AST, String>,
+}
+
+impl Script {
+    pub fn load(path: &Path) -> Result<Self> {
+        let name = path
+            .file_stem()
+            .context("Script has no file name")?
+            .to_string_lossy()
+            .to_string();
+        let source = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read `{}`", path.display()))?;
+
+        let engine = sandboxed_engine();
+        let ast = engine.compile(source).map_err(|err| err.to_string());
+
+        Ok(Self {
+            id: format!("{}{}", ID_PREFIX, name),
+            name,
+            engine,
+            ast,
+        })
+    }
+}
+
+impl Decoder for Script {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> String {
+        format!("{} ({})", self.name, gettext("Script"))
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Value> {
+        let ast = self.ast.as_ref().map_err(|err| anyhow!("{}", err))?;
+
+        // Rhai errors are not `Send`, so they are turned to strings
+        let decoded = self
+            .engine
+            .call_fn::<Dynamic>(&mut Scope::new(), ast, DECODE_FN_NAME, (Blob::from(bytes),))
+            .map_err(|err| anyhow!("{}", err))?;
+        rhai::serde::from_dynamic(&decoded).map_err(|err| anyhow!("{}", err))
+    }
+}
+
+/// Returns an engine that can only compute: it cannot import modules, its
+/// output only goes to the debug log, and it stops scripts that run too long
+/// or use too much memory.
+fn sandboxed_engine() -> Engine {
+    let mut engine = Engine::new();
+    engine.set_module_resolver(DummyModuleResolver::new());
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.set_max_call_levels(MAX_CALL_LEVELS);
+    engine.set_max_expr_depths(MAX_EXPR_DEPTH, MAX_EXPR_DEPTH);
+    engine.set_max_string_size(MAX_COLLECTION_LEN);
+    engine.set_max_array_size(MAX_COLLECTION_LEN);
+    engine.set_max_map_size(MAX_COLLECTION_LEN);
+    engine.on_print(|text| tracing::debug!("Script printed: {}", text));
+    engine.on_debug(|text, _, _| tracing::debug!("Script debugged: {}", text));
+    engine
+}
@@ -0,0 +1,1763 @@
+use adw::prelude::*;
+use anyhow::{Context, Result};
+use gettextrs::gettext;
+use gtk::{
+    gdk, gio,
+    glib::{self, clone},
+    pango,
+    subclass::prelude::*,
+};
+use sourceview::prelude::*;
+
+use serde_json::Value;
+
+use std::{cell::RefCell, fs, path::Path, rc::Rc};
+
+use crate::{
+    application::Application,
+    archive::{ArchiveEntry, ArchiveFormat},
+    byte_inspector, color_scheme,
+    compression::Compression,
+    config::APP_ID,
+    database_item::DatabaseItem,
+    decoder::{self, Decoder},
+    error_details,
+    hex_editor::{self, HexEditor},
+    key_history::KeyVersion,
+    non_printable_display::{self, NonPrintableDisplay},
+    text_encoding::TextEncoding,
+    timestamp,
+    utils::{self, gettext_f, ngettext_f},
+    value_diff_view::ValueDiffView,
+    value_format::ValueFormat,
+    window::Window,
+};
+
+/// Decoder id that picks a decoder based on the detected value format
+const AUTOMATIC_DECODER_ID: &str = "";
+const PLAIN_TEXT_DECODER_ID: &str = "text";
+
+/// Number of selector entries preceding the actual decoders
+const N_SPECIAL_DECODERS: u32 = 2;
+
+/// Number of bytes of text inserted into the view per main loop iteration
+const TEXT_CHUNK_LEN: usize = 64 * 1024;
+
+/// Number of matches highlighted when finding in the value, beyond which the
+/// rest are ignored
+const MAX_N_FIND_MATCHES: usize = 10_000;
+
+/// Number of characters of text beyond which it is not colored, as tagging
+/// every token would be too slow
+const MAX_COLORED_TEXT_LEN: usize = 1024 * 1024;
+
+/// Number of entries listed when showing the contents of an archive
+const MAX_N_LISTED_ARCHIVE_ENTRIES: usize = 1000;
+
+/// A way to open the value, picked in [`DetailPane::open_value_with`].
+#[derive(Clone)]
+enum OpenWith {
+    ArchiveContents(ArchiveFormat),
+    Env,
+    App(gio::AppInfo),
+}
+
+mod imp {
+    use std::cell::{Cell, OnceCell, RefCell};
+
+    use super::*;
+
+    #[derive(Default, glib::Properties, gtk::CompositeTemplate)]
+    #[properties(wrapper_type = super::DetailPane)]
+    #[template(resource = "/io/github/seadve/LmdbViewer/ui/detail_pane.ui")]
+    pub struct DetailPane {
+        #[property(get, set = Self::set_item, explicit_notify, nullable)]
+        pub(super) item: RefCell<Option<DatabaseItem>>,
+        /// Id of the selected decoder, or empty to pick one automatically
+        #[property(get, set = Self::set_decoder_id, explicit_notify)]
+        pub(super) decoder_id: RefCell<String>,
+        /// Note attached to the item's key
+        #[property(get, set = Self::set_note, explicit_notify)]
+        pub(super) note: RefCell<String>,
+        /// Encoding used when the value is shown as text
+        #[property(
+            get,
+            set = Self::set_encoding,
+            explicit_notify,
+            builder(TextEncoding::default())
+        )]
+        pub(super) encoding: Cell<TextEncoding>,
+        #[property(
+            get,
+            set = Self::set_non_printable_display,
+            explicit_notify,
+            builder(NonPrintableDisplay::default())
+        )]
+        pub(super) non_printable_display: Cell<NonPrintableDisplay>,
+        /// Whether the value can be edited with the hex editor
+        #[property(get, set = Self::set_can_edit, explicit_notify)]
+        pub(super) can_edit: Cell<bool>,
+        /// Width of tabs in the text, in spaces
+        #[property(get, set = Self::set_tab_width, explicit_notify)]
+        pub(super) tab_width: Cell<u32>,
+        /// Whether spaces, tabs, and line breaks are marked in the text
+        #[property(get, set = Self::set_show_whitespace, explicit_notify)]
+        pub(super) show_whitespace: Cell<bool>,
+        /// Id of the style scheme used when the style is light
+        #[property(get, set = Self::set_light_color_scheme, explicit_notify)]
+        pub(super) light_color_scheme: RefCell<String>,
+        /// Id of the style scheme used when the style is dark
+        #[property(get, set = Self::set_dark_color_scheme, explicit_notify)]
+        pub(super) dark_color_scheme: RefCell<String>,
+
+        #[template_child]
+        pub(super) decoder_drop_down: TemplateChild<gtk::DropDown>,
+        #[template_child]
+        pub(super) decoder_names: TemplateChild<gtk::StringList>,
+        #[template_child]
+        pub(super) format_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) note_entry: TemplateChild<gtk::Entry>,
+        #[template_child]
+        pub(super) compression_box: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub(super) decompress_spinner: TemplateChild<gtk::Spinner>,
+        #[template_child]
+        pub(super) compression_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) decompress_button: TemplateChild<gtk::ToggleButton>,
+        #[template_child]
+        pub(super) image_box: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub(super) image_picture: TemplateChild<gtk::Picture>,
+        #[template_child]
+        pub(super) error_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) edit_button: TemplateChild<gtk::ToggleButton>,
+        #[template_child]
+        pub(super) find_bar: TemplateChild<gtk::SearchBar>,
+        #[template_child]
+        pub(super) find_entry: TemplateChild<gtk::SearchEntry>,
+        #[template_child]
+        pub(super) find_matches_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) value_stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        pub(super) text_view: TemplateChild<sourceview::View>,
+        #[template_child]
+        pub(super) hex_editor: TemplateChild<HexEditor>,
+        #[template_child]
+        pub(super) cancel_edit_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub(super) save_edit_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub(super) inspector_expander: TemplateChild<gtk::Expander>,
+        #[template_child]
+        pub(super) inspector_grid: TemplateChild<gtk::Grid>,
+        #[template_child]
+        pub(super) versions_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub(super) timestamps_expander: TemplateChild<gtk::Expander>,
+        #[template_child]
+        pub(super) timestamps_grid: TemplateChild<gtk::Grid>,
+
+        pub(super) decoders: RefCell<Vec<Box<dyn Decoder>>>,
+        pub(super) decoder_drop_down_handler_id: OnceCell<glib::SignalHandlerId>,
+        pub(super) user_decoders_monitor: OnceCell<gio::FileMonitor>,
+        pub(super) image_bytes: RefCell<Option<glib::Bytes>>,
+        /// Item whose value was decompressed, with the decompressed value or
+        /// why it couldn't be decompressed
+        pub(super) decompressed: RefCell<Option<(DatabaseItem, Result<glib::Bytes, String>)>>,
+        /// Values the item's key was seen with before, oldest first
+        pub(super) versions: RefCell<Vec<KeyVersion>>,
+        pub(super) text_source_id: RefCell<Option<glib::SourceId>>,
+        /// Whether the text is a decoded value as JSON, whose syntax is colored
+        pub(super) is_json: Cell<bool>,
+        pub(super) dark_handler_id: RefCell<Option<glib::SignalHandlerId>>,
+        /// Character offsets of the start and end of the matches of the find
+        /// query in the text
+        pub(super) find_matches: RefCell<Vec<(i32, i32)>>,
+        pub(super) current_find_match: Cell<Option<usize>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for DetailPane {
+        const NAME: &'static str = "LvDetailPane";
+        type Type = super::DetailPane;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            HexEditor::ensure_type();
+            sourceview::View::ensure_type();
+
+            klass.bind_template();
+
+            klass.install_action_async(
+                "detail-pane.load-protobuf-schema",
+                None,
+                |obj, _, _| async move {
+                    if let Err(err) = obj.load_protobuf_schema().await {
+                        if !err
+                            .downcast_ref::<glib::Error>()
+                            .is_some_and(|error| error.matches(gtk::DialogError::Dismissed))
+                        {
+                            tracing::error!("Failed to load protobuf schema: {:?}", &err);
+                            obj.add_error_toast(&gettext("Failed to load schema"), &err);
+                        }
+                    }
+                },
+            );
+
+            klass.install_action_async(
+                "detail-pane.open-user-decoders-folder",
+                None,
+                |obj, _, _| async move {
+                    if let Err(err) = obj.open_user_decoders_folder().await {
+                        tracing::error!("Failed to open user decoders folder: {:?}", &err);
+                        obj.add_error_toast(&gettext("Failed to open decoders folder"), &err);
+                    }
+                },
+            );
+
+            klass.install_action("detail-pane.find", None, |obj, _, _| {
+                let imp = obj.imp();
+                imp.find_bar.set_search_mode(true);
+                imp.find_entry.grab_focus();
+            });
+
+            klass.install_action("detail-pane.find-next", None, |obj, _, _| {
+                obj.go_to_find_match(true);
+            });
+
+            klass.install_action("detail-pane.find-previous", None, |obj, _, _| {
+                obj.go_to_find_match(false);
+            });
+
+            // Only while the pane has focus, so it wins over searching keys
+            klass.add_binding_action(
+                gdk::Key::f,
+                gdk::ModifierType::CONTROL_MASK,
+                "detail-pane.find",
+                None,
+            );
+
+            klass.install_action_async(
+                "detail-pane.open-value-with",
+                None,
+                |obj, _, _| async move {
+                    if let Err(err) = obj.open_value_with().await {
+                        tracing::error!("Failed to open value: {:?}", &err);
+                        obj.add_error_toast(&gettext("Failed to open value"), &err);
+                    }
+                },
+            );
+
+            klass.install_action("detail-pane.open-image", None, |obj, _, _| {
+                obj.open_image();
+            });
+
+            klass.install_action_async("detail-pane.save-image", None, |obj, _, _| async move {
+                if let Err(err) = obj.save_image().await {
+                    if !err
+                        .downcast_ref::<glib::Error>()
+                        .is_some_and(|error| error.matches(gtk::DialogError::Dismissed))
+                    {
+                        tracing::error!("Failed to save image: {:?}", &err);
+                        obj.add_error_toast(&gettext("Failed to save image"), &err);
+                    }
+                }
+            });
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for DetailPane {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let obj = self.obj();
+
+            self.decoder_names.append(&gettext("Automatic"));
+            self.decoder_names.append(&gettext("Plain Text"));
+
+            let handler_id = self.decoder_drop_down.connect_selected_notify(
+                clone!(@weak obj => move |drop_down| {
+                    obj.set_decoder_id(obj.decoder_id_at(drop_down.selected()));
+                }),
+            );
+            self.decoder_drop_down_handler_id.set(handler_id).unwrap();
+
+            obj.action_set_enabled("detail-pane.open-value-with", false);
+
+            self.note_entry
+                .connect_activate(clone!(@weak obj => move |entry| {
+                    obj.set_note(entry.text().trim());
+                }));
+            let focus_controller = gtk::EventControllerFocus::new();
+            focus_controller.connect_leave(clone!(@weak obj => move |_| {
+                obj.set_note(obj.imp().note_entry.text().trim());
+            }));
+            self.note_entry.add_controller(focus_controller);
+
+            self.decompress_button
+                .connect_toggled(clone!(@weak obj => move |_| {
+                    obj.update_text();
+                }));
+
+            self.edit_button
+                .connect_toggled(clone!(@weak obj => move |_| {
+                    obj.update_editing();
+                }));
+            self.cancel_edit_button
+                .connect_clicked(clone!(@weak obj => move |_| {
+                    obj.imp().edit_button.set_active(false);
+                }));
+            self.save_edit_button
+                .connect_clicked(clone!(@weak obj => move |_| {
+                    obj.save_edit();
+                }));
+
+            self.text_view
+                .buffer()
+                .connect_mark_set(clone!(@weak obj => move |_, _, mark| {
+                    if matches!(mark.name().as_deref(), Some("insert" | "selection_bound")) {
+                        obj.update_inspector();
+                        obj.update_timestamps();
+                    }
+                }));
+            let tag_table = self.text_view.buffer().tag_table();
+            tag_table.add(
+                &gtk::TextTag::builder()
+                    .name("find-match")
+                    .background_rgba(&gdk::RGBA::new(0.96, 0.83, 0.18, 0.4))
+                    .build(),
+            );
+            tag_table.add(
+                &gtk::TextTag::builder()
+                    .name("current-find-match")
+                    .background_rgba(&gdk::RGBA::new(1.0, 0.47, 0.0, 0.6))
+                    .build(),
+            );
+            tag_table.add(&gtk::TextTag::new(Some("whitespace")));
+
+            self.find_bar
+                .connect_search_mode_enabled_notify(clone!(@weak obj => move |_| {
+                    obj.update_find_matches();
+                }));
+            self.find_entry
+                .connect_search_changed(clone!(@weak obj => move |_| {
+                    obj.update_find_matches();
+                }));
+            self.find_entry
+                .connect_activate(clone!(@weak obj => move |_| {
+                    obj.go_to_find_match(true);
+                }));
+            self.find_entry
+                .connect_next_match(clone!(@weak obj => move |_| {
+                    obj.go_to_find_match(true);
+                }));
+            self.find_entry
+                .connect_previous_match(clone!(@weak obj => move |_| {
+                    obj.go_to_find_match(false);
+                }));
+            self.find_entry
+                .connect_stop_search(clone!(@weak obj => move |_| {
+                    let imp = obj.imp();
+                    imp.find_bar.set_search_mode(false);
+                    imp.text_view.grab_focus();
+                }));
+
+            self.inspector_expander
+                .connect_expanded_notify(clone!(@weak obj => move |_| {
+                    obj.update_inspector();
+                }));
+            self.timestamps_expander
+                .connect_expanded_notify(clone!(@weak obj => move |_| {
+                    obj.update_timestamps();
+                }));
+
+            obj.add_decoders(decoder::builtins());
+
+            let settings = gio::Settings::new(APP_ID);
+            settings.bind("detail-encoding", &*obj, "encoding").build();
+            settings
+                .bind("non-printable-display", &*obj, "non-printable-display")
+                .build();
+            settings
+                .bind("value-wrap-mode", &*self.text_view, "wrap-mode")
+                .build();
+            settings
+                .bind("monospace-font", &*self.text_view, "monospace")
+                .build();
+            settings.bind("tab-width", &*obj, "tab-width").build();
+            settings
+                .bind("show-whitespace", &*obj, "show-whitespace")
+                .build();
+            settings
+                .bind("light-color-scheme", &*obj, "light-color-scheme")
+                .build();
+            settings
+                .bind("dark-color-scheme", &*obj, "dark-color-scheme")
+                .build();
+
+            let dark_handler_id =
+                adw::StyleManager::default().connect_dark_notify(clone!(@weak obj => move |_| {
+                    obj.update_color_scheme();
+                }));
+            self.dark_handler_id.replace(Some(dark_handler_id));
+            obj.update_color_scheme();
+
+            for path in settings.strv("protobuf-schemas") {
+                match decoder::load_protobuf_schema(Path::new(path.as_str())) {
+                    Ok(decoders) => obj.add_decoders(decoders),
+                    Err(err) => {
+                        tracing::warn!("Failed to load protobuf schema `{}`: {:?}", path, &err)
+                    }
+                }
+            }
+
+            obj.reload_user_decoders();
+            match gio::File::for_path(decoder::user_decoders_dir())
+                .monitor_directory(gio::FileMonitorFlags::WATCH_MOVES, gio::Cancellable::NONE)
+            {
+                Ok(monitor) => {
+                    monitor.connect_changed(clone!(@weak obj => move |_, _, _, event| {
+                        // Only reload once a file is fully written
+                        if !matches!(
+                            event,
+                            gio::FileMonitorEvent::Changed | gio::FileMonitorEvent::AttributeChanged
+                        ) {
+                            obj.reload_user_decoders();
+                        }
+                    }));
+                    self.user_decoders_monitor.set(monitor).unwrap();
+                }
+                Err(err) => tracing::warn!("Failed to monitor user decoders: {:?}", &err),
+            }
+        }
+
+        fn dispose(&self) {
+            if let Some(handler_id) = self.dark_handler_id.take() {
+                adw::StyleManager::default().disconnect(handler_id);
+            }
+
+            self.dispose_template();
+        }
+    }
+
+    impl WidgetImpl for DetailPane {}
+
+    impl DetailPane {
+        fn set_item(&self, item: Option<DatabaseItem>) {
+            if item == *self.item.borrow() {
+                return;
+            }
+
+            self.note_entry.set_sensitive(item.is_some());
+            let obj = self.obj();
+            obj.action_set_enabled("detail-pane.open-value-with", item.is_some());
+            self.item.replace(item);
+            self.edit_button.set_active(false);
+
+            obj.update_text();
+            obj.update_inspector();
+            obj.update_timestamps();
+            obj.update_versions();
+            obj.notify_item();
+        }
+
+        fn set_decoder_id(&self, decoder_id: String) {
+            if decoder_id == *self.decoder_id.borrow() {
+                return;
+            }
+
+            let obj = self.obj();
+
+            let position = obj.decoder_position(&decoder_id);
+            self.decoder_id.replace(decoder_id);
+            self.decoder_drop_down.set_selected(position);
+
+            obj.update_text();
+            obj.notify_decoder_id();
+        }
+
+        fn set_note(&self, note: String) {
+            // Also discard uncommitted edits when the note is unchanged
+            if self.note_entry.text() != note {
+                self.note_entry.set_text(&note);
+            }
+
+            if note == *self.note.borrow() {
+                return;
+            }
+
+            self.note.replace(note);
+
+            self.obj().notify_note();
+        }
+
+        fn set_encoding(&self, encoding: TextEncoding) {
+            if encoding == self.encoding.get() {
+                return;
+            }
+
+            self.encoding.set(encoding);
+
+            let obj = self.obj();
+            obj.update_text();
+            obj.notify_encoding();
+        }
+
+        fn set_non_printable_display(&self, non_printable_display: NonPrintableDisplay) {
+            if non_printable_display == self.non_printable_display.get() {
+                return;
+            }
+
+            self.non_printable_display.set(non_printable_display);
+
+            let obj = self.obj();
+            obj.update_text();
+            obj.notify_non_printable_display();
+        }
+
+        fn set_tab_width(&self, tab_width: u32) {
+            if tab_width == self.tab_width.get() {
+                return;
+            }
+
+            self.tab_width.set(tab_width);
+
+            let obj = self.obj();
+            obj.update_tabs();
+            obj.notify_tab_width();
+        }
+
+        fn set_show_whitespace(&self, show_whitespace: bool) {
+            if show_whitespace == self.show_whitespace.get() {
+                return;
+            }
+
+            self.show_whitespace.set(show_whitespace);
+
+            let obj = self.obj();
+            obj.update_whitespace_tag();
+            obj.notify_show_whitespace();
+        }
+
+        fn set_light_color_scheme(&self, light_color_scheme: String) {
+            if light_color_scheme == *self.light_color_scheme.borrow() {
+                return;
+            }
+
+            self.light_color_scheme.replace(light_color_scheme);
+
+            let obj = self.obj();
+            obj.update_color_scheme();
+            obj.notify_light_color_scheme();
+        }
+
+        fn set_dark_color_scheme(&self, dark_color_scheme: String) {
+            if dark_color_scheme == *self.dark_color_scheme.borrow() {
+                return;
+            }
+
+            self.dark_color_scheme.replace(dark_color_scheme);
+
+            let obj = self.obj();
+            obj.update_color_scheme();
+            obj.notify_dark_color_scheme();
+        }
+
+        fn set_can_edit(&self, can_edit: bool) {
+            if can_edit == self.can_edit.get() {
+                return;
+            }
+
+            self.can_edit.set(can_edit);
+
+            if !can_edit {
+                self.edit_button.set_active(false);
+            }
+
+            self.obj().notify_can_edit();
+        }
+    }
+}
+
+glib::wrapper! {
+    pub struct DetailPane(ObjectSubclass<imp::DetailPane>)
+        @extends gtk::Widget,
+        @implements gtk::Accessible;
+}
+
+impl DetailPane {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    /// Decodes the value `bytes` of `key` with the selected decoder, or
+    /// returns `None` if it has none or fails to decode.
+    pub fn decode(&self, key: &[u8], bytes: &[u8]) -> Option<Value> {
+        self.decode_with_selected(key, bytes, ValueFormat::detect(bytes))?
+            .ok()
+    }
+
+    /// Returns the texts the values `before` and `after` of the given keys
+    /// are compared by, which are their decoded values if both can be
+    /// decoded, their text if both are UTF-8, or else their hex dumps.
+    pub fn value_diff_texts(
+        &self,
+        (before_key, before): (&[u8], &[u8]),
+        (after_key, after): (&[u8], &[u8]),
+    ) -> (String, String) {
+        let decoded_text = |value: Value| match value {
+            Value::String(text) => text,
+            value => serde_json::to_string_pretty(&value).unwrap(),
+        };
+        if let (Some(before), Some(after)) = (
+            self.decode(before_key, before),
+            self.decode(after_key, after),
+        ) {
+            return (decoded_text(before), decoded_text(after));
+        }
+
+        match (std::str::from_utf8(before), std::str::from_utf8(after)) {
+            (Ok(before), Ok(after)) => (before.to_string(), after.to_string()),
+            _ => (hex_editor::hex_dump(before), hex_editor::hex_dump(after)),
+        }
+    }
+
+    /// Sets the values the item's key was seen with, oldest first, which are
+    /// listed if they differ from the current one.
+    pub fn set_versions(&self, versions: Vec<KeyVersion>) {
+        self.imp().versions.replace(versions);
+        self.update_versions();
+    }
+
+    /// Decodes like [`Self::decode`], picking a decoder by `format` if the
+    /// selected one is automatic.
+    fn decode_with_selected(
+        &self,
+        key: &[u8],
+        bytes: &[u8],
+        format: ValueFormat,
+    ) -> Option<Result<Value>> {
+        let decoder_id = match self.decoder_id() {
+            decoder_id if decoder_id == AUTOMATIC_DECODER_ID => {
+                format.decoder_id().unwrap_or_default().to_string()
+            }
+            decoder_id => decoder_id,
+        };
+        self.imp()
+            .decoders
+            .borrow()
+            .iter()
+            .find(|decoder| decoder.id() == decoder_id)
+            .map(|decoder| decoder.decode_entry(key, bytes))
+    }
+
+    /// Adds decoders to the selector, replacing existing ones with the same id.
+    fn add_decoders(&self, new_decoders: Vec<Box<dyn Decoder>>) {
+        self.update_decoders(|decoders| {
+            decoders.retain(|decoder| {
+                !new_decoders
+                    .iter()
+                    .any(|new_decoder| new_decoder.id() == decoder.id())
+            });
+            decoders.extend(new_decoders);
+        });
+    }
+
+    /// Replaces the user decoders with the ones currently in the user
+    /// decoders folder, so edits to scripts and plugins apply without
+    /// restarting.
+    fn reload_user_decoders(&self) {
+        let user_decoders = decoder::load_user_decoders(&decoder::user_decoders_dir());
+        self.update_decoders(|decoders| {
+            decoders.retain(|decoder| !decoder::is_user_decoder(decoder.as_ref()));
+            decoders.extend(user_decoders);
+        });
+    }
+
+    /// Applies `f` to the decoders, then updates the selector and the text.
+    fn update_decoders(&self, f: impl FnOnce(&mut Vec<Box<dyn Decoder>>)) {
+        let imp = self.imp();
+
+        let names = {
+            let mut decoders = imp.decoders.borrow_mut();
+            f(&mut decoders);
+            decoders
+                .iter()
+                .map(|decoder| decoder.name())
+                .collect::<Vec<_>>()
+        };
+
+        let position = self.decoder_position(&self.decoder_id());
+
+        let handler_id = imp.decoder_drop_down_handler_id.get().unwrap();
+        imp.decoder_drop_down.block_signal(handler_id);
+        imp.decoder_names.splice(
+            N_SPECIAL_DECODERS,
+            imp.decoder_names.n_items() - N_SPECIAL_DECODERS,
+            &names.iter().map(|name| name.as_str()).collect::<Vec<_>>(),
+        );
+        imp.decoder_drop_down.set_selected(position);
+        imp.decoder_drop_down.unblock_signal(handler_id);
+
+        self.update_text();
+    }
+
+    /// Returns the position of the decoder in the selector, falling back
+    /// to automatic for unknown decoders.
+    fn decoder_position(&self, decoder_id: &str) -> u32 {
+        if decoder_id == PLAIN_TEXT_DECODER_ID {
+            return 1;
+        }
+
+        self.imp()
+            .decoders
+            .borrow()
+            .iter()
+            .position(|decoder| decoder.id() == decoder_id)
+            .map_or(0, |index| index as u32 + N_SPECIAL_DECODERS)
+    }
+
+    fn decoder_id_at(&self, position: u32) -> String {
+        match position {
+            0 => AUTOMATIC_DECODER_ID.to_string(),
+            1 => PLAIN_TEXT_DECODER_ID.to_string(),
+            _ => self
+                .imp()
+                .decoders
+                .borrow()
+                .get((position - N_SPECIAL_DECODERS) as usize)
+                .map(|decoder| decoder.id().to_string())
+                .unwrap_or_default(),
+        }
+    }
+
+    async fn load_protobuf_schema(&self) -> Result<()> {
+        let filter = gtk::FileFilter::new();
+        filter.set_name(Some(&gettext("Protobuf Schemas")));
+        filter.add_suffix("proto");
+        filter.add_suffix("desc");
+        filter.add_suffix("pb");
+        filter.add_suffix("binpb");
+
+        let filters = gio::ListStore::new::<gtk::FileFilter>();
+        filters.append(&filter);
+
+        let dialog = gtk::FileDialog::builder()
+            .title(gettext("Load Protobuf Schema"))
+            .filters(&filters)
+            .modal(true)
+            .build();
+
+        let file = dialog
+            .open_future(self.root().and_downcast_ref::<gtk::Window>())
+            .await?;
+        let path = file.path().context("File must have a path")?;
+
+        let decoders = decoder::load_protobuf_schema(&path)
+            .with_context(|| format!("Failed to load schema at `{}`", path.display()))?;
+        self.add_decoders(decoders);
+
+        let settings = gio::Settings::new(APP_ID);
+        let mut paths = settings.strv("protobuf-schemas");
+        let path = path.to_string_lossy();
+        if !paths.contains(path.as_ref()) {
+            paths.push(path.as_ref().into());
+            settings.set_strv("protobuf-schemas", paths)?;
+        }
+
+        Ok(())
+    }
+
+    async fn open_user_decoders_folder(&self) -> Result<()> {
+        let dir = decoder::user_decoders_dir();
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create `{}`", dir.display()))?;
+
+        gtk::FileLauncher::new(Some(&gio::File::for_path(&dir)))
+            .launch_future(self.root().and_downcast_ref::<gtk::Window>())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Offers the ways the value can be opened, as an archive or env nested
+    /// in it, or with an app that handles its content type.
+    async fn open_value_with(&self) -> Result<()> {
+        let Some(item) = self.item() else {
+            return Ok(());
+        };
+        let data = item.data();
+
+        let (content_type, _) = gio::content_type_guess(None::<&Path>, &data);
+        let archive_format = ArchiveFormat::detect(&data);
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("Open Value With"))
+            .body(gio::content_type_get_description(&content_type))
+            .transient_for(self.root().and_downcast_ref::<gtk::Window>().unwrap())
+            .modal(true)
+            .default_response("cancel")
+            .close_response("cancel")
+            .build();
+        dialog.add_response("cancel", &gettext("_Cancel"));
+
+        let list_box = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(["boxed-list"])
+            .build();
+        let choice = Rc::new(RefCell::new(None));
+        let add_row = |title: &str, icon: Option<gio::Icon>, open_with: OpenWith| {
+            let row = adw::ActionRow::builder()
+                .title(title)
+                .use_markup(false)
+                .activatable(true)
+                .build();
+            let image = gtk::Image::from_gicon(
+                &icon.unwrap_or_else(|| gio::ThemedIcon::new("application-x-executable").upcast()),
+            );
+            image.set_icon_size(gtk::IconSize::Large);
+            row.add_prefix(&image);
+            row.connect_activated(clone!(@weak dialog, @strong choice => move |_| {
+                choice.replace(Some(open_with.clone()));
+                dialog.close();
+            }));
+            list_box.append(&row);
+        };
+
+        if let Some(format) = archive_format {
+            add_row(
+                &gettext_f("{format} Archive Contents", &[("format", format.name())]),
+                Some(gio::ThemedIcon::new("package-x-generic").upcast()),
+                OpenWith::ArchiveContents(format),
+            );
+        }
+        if utils::is_lmdb_data(&data) {
+            add_row(
+                &gettext("Nested Environment in New Window"),
+                Some(gio::ThemedIcon::new("drive-harddisk-symbolic").upcast()),
+                OpenWith::Env,
+            );
+        }
+        for app_info in gio::AppInfo::all_for_type(&content_type) {
+            add_row(
+                &app_info.display_name(),
+                app_info.icon(),
+                OpenWith::App(app_info),
+            );
+        }
+
+        if list_box.first_child().is_some() {
+            let scrolled_window = gtk::ScrolledWindow::builder()
+                .hscrollbar_policy(gtk::PolicyType::Never)
+                .propagate_natural_height(true)
+                .max_content_height(360)
+                .child(&list_box)
+                .build();
+            dialog.set_extra_child(Some(&scrolled_window));
+        } else {
+            dialog.set_body(&gettext_f(
+                "No apps can open values of type “{type}”",
+                &[("type", &gio::content_type_get_description(&content_type))],
+            ));
+        }
+
+        dialog.choose_future().await;
+
+        let Some(open_with) = choice.take() else {
+            return Ok(());
+        };
+        match open_with {
+            OpenWith::ArchiveContents(format) => {
+                let entries = format.list(&data)?;
+                self.show_archive_contents(format, &entries).await;
+            }
+            OpenWith::Env => {
+                let dir = utils::write_to_temp_dir(&data, "data.mdb")?;
+                let app = self
+                    .root()
+                    .and_downcast::<gtk::Window>()
+                    .and_then(|window| window.application())
+                    .and_downcast::<Application>()
+                    .context("No application")?;
+                let window = Window::new(&app);
+                window.present();
+                window.load_env(&dir)?;
+            }
+            OpenWith::App(app_info) => {
+                let extension = archive_format
+                    .map(|format| format.extension())
+                    .or_else(|| ValueFormat::detect(&data).image_extension());
+                let file_name = match extension {
+                    Some(extension) => format!("value.{}", extension),
+                    None => "value".to_string(),
+                };
+                let dir = utils::write_to_temp_dir(&data, &file_name)?;
+                app_info.launch(
+                    &[gio::File::for_path(dir.join(&file_name))],
+                    Some(&self.display().app_launch_context()),
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn show_archive_contents(&self, format: ArchiveFormat, entries: &[ArchiveEntry]) {
+        let n_entries = entries.len() as u32;
+        let mut body = ngettext_f(
+            "{n} entry",
+            "{n} entries",
+            n_entries,
+            &[("n", &n_entries.to_string())],
+        );
+        if entries.len() > MAX_N_LISTED_ARCHIVE_ENTRIES {
+            body = gettext_f(
+                "{entries}, showing the first {n_shown}",
+                &[
+                    ("entries", &body),
+                    ("n_shown", &MAX_N_LISTED_ARCHIVE_ENTRIES.to_string()),
+                ],
+            );
+        }
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext_f(
+                "{format} Archive Contents",
+                &[("format", format.name())],
+            ))
+            .body(body)
+            .transient_for(self.root().and_downcast_ref::<gtk::Window>().unwrap())
+            .modal(true)
+            .default_response("close")
+            .close_response("close")
+            .build();
+        dialog.add_response("close", &gettext("_Close"));
+
+        if !entries.is_empty() {
+            let list_box = gtk::ListBox::builder()
+                .selection_mode(gtk::SelectionMode::None)
+                .css_classes(["boxed-list"])
+                .build();
+            for entry in entries.iter().take(MAX_N_LISTED_ARCHIVE_ENTRIES) {
+                let row = adw::ActionRow::builder()
+                    .title(&entry.path)
+                    .use_markup(false)
+                    .build();
+                if entry.is_dir {
+                    row.add_prefix(&gtk::Image::from_icon_name("folder-symbolic"));
+                } else {
+                    row.add_prefix(&gtk::Image::from_icon_name("text-x-generic-symbolic"));
+                    row.set_subtitle(&glib::format_size(entry.size));
+                }
+                list_box.append(&row);
+            }
+            let scrolled_window = gtk::ScrolledWindow::builder()
+                .hscrollbar_policy(gtk::PolicyType::Never)
+                .propagate_natural_height(true)
+                .max_content_height(360)
+                .child(&list_box)
+                .build();
+            dialog.set_extra_child(Some(&scrolled_window));
+        }
+
+        dialog.choose_future().await;
+    }
+
+    fn open_image(&self) {
+        let Some(texture) = self.imp().image_picture.paintable() else {
+            return;
+        };
+
+        let picture = gtk::Picture::builder()
+            .paintable(&texture)
+            .can_shrink(false)
+            .build();
+        let scrolled_window = gtk::ScrolledWindow::builder().child(&picture).build();
+
+        let toolbar_view = adw::ToolbarView::new();
+        toolbar_view.add_top_bar(&adw::HeaderBar::new());
+        toolbar_view.set_content(Some(&scrolled_window));
+
+        let window = adw::Window::builder()
+            .title(gettext("Image"))
+            .default_width(texture.intrinsic_width().clamp(360, 1200))
+            .default_height(texture.intrinsic_height().clamp(240, 900))
+            .content(&toolbar_view)
+            .modal(true)
+            .build();
+        window.set_transient_for(self.root().and_downcast_ref::<gtk::Window>());
+        window.present();
+    }
+
+    async fn save_image(&self) -> Result<()> {
+        let Some(bytes) = self.imp().image_bytes.borrow().clone() else {
+            return Ok(());
+        };
+
+        let extension = ValueFormat::detect(&bytes)
+            .image_extension()
+            .context("Value is not an image")?;
+
+        let dialog = gtk::FileDialog::builder()
+            .title(gettext("Save Image"))
+            .initial_name(format!("image.{}", extension))
+            .modal(true)
+            .build();
+
+        let file = dialog
+            .save_future(self.root().and_downcast_ref::<gtk::Window>())
+            .await?;
+        file.replace_contents_future(bytes, None, false, gio::FileCreateFlags::NONE)
+            .await
+            .map_err(|(_, err)| err)?;
+
+        Ok(())
+    }
+
+    /// Shows the hex editor with the raw value while the edit button is
+    /// active, discarding unsaved edits otherwise.
+    fn update_editing(&self) {
+        let imp = self.imp();
+
+        if imp.edit_button.is_active() {
+            let data = self.item().map(|item| item.data().to_vec());
+            imp.hex_editor.set_bytes(&data.unwrap_or_default());
+            imp.value_stack.set_visible_child_name("hex-editor");
+            imp.hex_editor.grab_focus();
+        } else {
+            imp.value_stack.set_visible_child_name("text");
+        }
+    }
+
+    /// Shows `message` in a toast that can show the details of `err`, in the
+    /// toast overlay the pane is in, which is that of the window it is
+    /// opened in on its own too.
+    fn add_error_toast(&self, message: &str, err: &anyhow::Error) {
+        let (Some(window), Some(toast_overlay)) = (
+            self.root().and_downcast::<gtk::Window>(),
+            self.ancestor(adw::ToastOverlay::static_type())
+                .and_downcast::<adw::ToastOverlay>(),
+        ) else {
+            return;
+        };
+        toast_overlay.add_toast(error_details::error_toast(&window, message, err));
+    }
+
+    /// Puts the edited bytes as the item's value through the window.
+    fn save_edit(&self) {
+        let imp = self.imp();
+
+        let bytes = imp.hex_editor.bytes();
+        imp.edit_button.set_active(false);
+
+        if let Err(err) = self.activate_action("win.set-value", Some(&bytes.to_variant())) {
+            tracing::error!("Failed to activate set-value action: {:?}", &err);
+        }
+    }
+
+    fn update_image(&self, data: &[u8], format: ValueFormat) {
+        let imp = self.imp();
+
+        let texture = format.image_extension().and_then(|_| {
+            let bytes = glib::Bytes::from(data);
+            match gdk::Texture::from_bytes(&bytes) {
+                Ok(texture) => Some((texture, bytes)),
+                Err(err) => {
+                    tracing::debug!("Failed to load image: {:?}", err);
+                    None
+                }
+            }
+        });
+
+        imp.image_box.set_visible(texture.is_some());
+
+        if let Some((texture, bytes)) = texture {
+            imp.image_picture.set_paintable(Some(&texture));
+            imp.image_bytes.replace(Some(bytes));
+        } else {
+            imp.image_picture.set_paintable(gdk::Paintable::NONE);
+            imp.image_bytes.replace(None);
+        }
+    }
+
+    /// Decompresses the value of `item` in the background, as it can take a
+    /// while for large values, and shows it once done.
+    fn decompress(&self, item: &DatabaseItem, compression: Compression) {
+        let imp = self.imp();
+
+        imp.decompress_spinner.set_visible(true);
+        imp.compression_label.set_label(&gettext_f(
+            "Decompressing from {compression}…",
+            &[("compression", compression.name())],
+        ));
+        imp.is_json.set(false);
+        self.set_text(String::new());
+        imp.format_label.set_label("");
+        imp.error_label.set_visible(false);
+        self.update_image(&[], ValueFormat::Empty);
+
+        glib::spawn_future_local(clone!(@weak self as obj, @strong item => async move {
+            let data = item.data();
+            let result = gio::spawn_blocking(move || compression.decompress(data.as_ref()))
+                .await
+                .unwrap();
+
+            // The item was changed while decompressing
+            if obj.item().as_ref() != Some(&item) {
+                return;
+            }
+
+            if let Err(err) = &result {
+                tracing::debug!("Failed to decompress value: {:?}", err);
+            }
+            obj.imp().decompressed.replace(Some((
+                item,
+                result
+                    .map(glib::Bytes::from_owned)
+                    .map_err(|err| err.to_string()),
+            )));
+            obj.update_text();
+        }));
+    }
+
+    fn update_text(&self) {
+        let imp = self.imp();
+
+        let Some(item) = self.item() else {
+            imp.is_json.set(false);
+            self.set_text(String::new());
+            imp.format_label.set_label("");
+            imp.compression_box.set_visible(false);
+            imp.error_label.set_visible(false);
+            self.update_image(&[], ValueFormat::Empty);
+            self.reset_property(gtk::AccessibleProperty::Description);
+            return;
+        };
+
+        if imp
+            .decompressed
+            .borrow()
+            .as_ref()
+            .is_some_and(|(decompressed_item, _)| *decompressed_item != item)
+        {
+            imp.decompressed.replace(None);
+        }
+
+        let raw_data = item.data();
+        let compression = Compression::detect(raw_data.as_ref());
+        imp.compression_box.set_visible(compression.is_some());
+        imp.decompress_spinner.set_visible(false);
+
+        let data = match compression {
+            Some(compression) if imp.decompress_button.is_active() => {
+                let decompressed = imp
+                    .decompressed
+                    .borrow()
+                    .as_ref()
+                    .map(|(_, result)| result.clone());
+                match decompressed {
+                    Some(Ok(decompressed)) => {
+                        imp.compression_label.set_label(&gettext_f(
+                            "Decompressed from {compression}: {compressed_size} → {size}",
+                            &[
+                                ("compression", compression.name()),
+                                ("compressed_size", &glib::format_size(raw_data.len() as u64)),
+                                ("size", &glib::format_size(decompressed.len() as u64)),
+                            ],
+                        ));
+                        decompressed
+                    }
+                    Some(Err(err)) => {
+                        imp.compression_label.set_label(&gettext_f(
+                            "Failed to decompress: {error}",
+                            &[("error", &err)],
+                        ));
+                        raw_data
+                    }
+                    None => {
+                        self.decompress(&item, compression);
+                        return;
+                    }
+                }
+            }
+            Some(compression) => {
+                imp.compression_label.set_label(&gettext_f(
+                    "Compressed with {compression} ({size})",
+                    &[
+                        ("compression", compression.name()),
+                        ("size", &glib::format_size(raw_data.len() as u64)),
+                    ],
+                ));
+                raw_data
+            }
+            None => raw_data,
+        };
+
+        let raw_text = || self.non_printable_display().render(&data, self.encoding());
+
+        let format = ValueFormat::detect(data.as_ref());
+        imp.format_label.set_label(&format.label());
+
+        self.update_image(&data, format);
+
+        let decoded = self.decode_with_selected(item.key().as_ref(), &data, format);
+
+        let mut description = gettext_f(
+            "Key {key}, {format} value of {size}",
+            &[
+                (
+                    "key",
+                    &self
+                        .non_printable_display()
+                        .render(&item.key(), TextEncoding::default()),
+                ),
+                ("format", &format.label()),
+                ("size", &glib::format_size(data.len() as u64)),
+            ],
+        );
+        if let Some(Ok(value)) = &decoded {
+            description = gettext_f(
+                "{description}, decoded as {type}",
+                &[
+                    ("description", &description),
+                    ("type", &value_type_name(value)),
+                ],
+            );
+        }
+        self.update_property(&[gtk::accessible::Property::Description(&description)]);
+
+        match decoded {
+            // Strings are shown as is, so user decoders can render values freely
+            Some(Ok(Value::String(string))) => {
+                imp.is_json.set(false);
+                self.set_text(string);
+                imp.error_label.set_visible(false);
+            }
+            Some(Ok(value)) => {
+                imp.is_json.set(true);
+                self.set_text(serde_json::to_string_pretty(&value).unwrap());
+                imp.error_label.set_visible(false);
+            }
+            Some(Err(err)) => {
+                imp.is_json.set(false);
+                self.set_text(raw_text());
+                imp.error_label
+                    .set_label(&format!("{}: {}", gettext("Failed to decode"), err));
+                imp.error_label.set_visible(true);
+            }
+            None => {
+                imp.is_json.set(false);
+                self.set_text(raw_text());
+                imp.error_label.set_visible(false);
+            }
+        }
+    }
+
+    /// Lists the values that the selected bytes, or the bytes from the cursor
+    /// in a hex dump or from the start otherwise, can be read as.
+    fn update_inspector(&self) {
+        let imp = self.imp();
+
+        if !imp.inspector_expander.is_expanded() {
+            return;
+        }
+
+        let readings = match self.selected_bytes() {
+            Some(bytes) => byte_inspector::inspect(&bytes),
+            None => self
+                .item()
+                .map(|item| {
+                    let data = item.data();
+                    let start = self.hex_cursor_position(data.len()).unwrap_or(0);
+                    byte_inspector::inspect(&data[start..])
+                })
+                .unwrap_or_default(),
+        };
+
+        fill_grid(
+            &imp.inspector_grid,
+            readings
+                .into_iter()
+                .map(|(type_name, value)| (type_name, value, None))
+                .collect(),
+            &gettext("No bytes to inspect"),
+        );
+    }
+
+    /// Lists the previous versions of the item's value, newest first, each
+    /// with a button to compare it with the current value.
+    fn update_versions(&self) {
+        let imp = self.imp();
+
+        imp.versions_list.remove_all();
+
+        let Some(item) = self.item() else {
+            return;
+        };
+        let data = item.data();
+        for version in imp.versions.borrow().iter().rev() {
+            if version.value == data {
+                continue;
+            }
+
+            let title = gettext_f("Transaction {id}", &[("id", &version.txn_id.to_string())]);
+            let row = adw::ActionRow::builder()
+                .title(&title)
+                .subtitle(format!(
+                    "{} · {}",
+                    version.seen_at.format("%X").unwrap_or_default(),
+                    glib::format_size(version.value.len() as u64)
+                ))
+                .build();
+            let button = gtk::Button::builder()
+                .label(gettext("Compare"))
+                .valign(gtk::Align::Center)
+                .build();
+            button.connect_clicked(
+                clone!(@weak self as obj, @weak item, @strong version => move |_| {
+                    let Some(window) = obj.root().and_downcast::<gtk::Window>() else {
+                        return;
+                    };
+                    let key = item.key();
+                    let (before_text, after_text) =
+                        obj.value_diff_texts((&key, &version.value), (&key, &item.data()));
+                    ValueDiffView::present(
+                        &window,
+                        (&title, &before_text),
+                        (&gettext("Current"), &after_text),
+                    );
+                }),
+            );
+            row.add_suffix(&button);
+            row.set_activatable_widget(Some(&button));
+            imp.versions_list.append(&row);
+        }
+    }
+
+    /// Lists the times that the selected text, or the whole value if nothing
+    /// is selected, can be read as.
+    fn update_timestamps(&self) {
+        let imp = self.imp();
+
+        if !imp.timestamps_expander.is_expanded() {
+            return;
+        }
+
+        let timestamps = match self.selected_bytes() {
+            Some(bytes) => timestamp::interpret(&bytes),
+            None => self
+                .item()
+                .map(|item| timestamp::interpret(&item.data()))
+                .unwrap_or_default(),
+        };
+
+        fill_grid(
+            &imp.timestamps_grid,
+            timestamps
+                .iter()
+                .map(|timestamp| {
+                    (
+                        timestamp.label.clone(),
+                        timestamp.to_local_string(),
+                        timestamp
+                            .date_time
+                            .format_iso8601()
+                            .ok()
+                            .map(|string| string.to_string()),
+                    )
+                })
+                .collect(),
+            &gettext("Not a time in a known format"),
+        );
+    }
+
+    /// Returns the bytes of the selected text, or `None` if nothing is
+    /// selected.
+    fn selected_bytes(&self) -> Option<Vec<u8>> {
+        let buffer = self.imp().text_view.buffer();
+        let (start, end) = buffer.selection_bounds()?;
+
+        if let Some(data) = self
+            .item()
+            .map(|item| item.data())
+            .filter(|data| self.is_hex_dump(data.len()))
+        {
+            // Each byte takes two digits and a separating space, and bytes
+            // with a digit in the selection are included
+            let start = (start.offset() as usize + 1) / 3;
+            let end = (end.offset() as usize).div_ceil(3);
+            return Some(data[start..end.min(data.len())].to_vec());
+        }
+
+        Some(non_printable_display::unrender(
+            &buffer.text(&start, &end, false),
+        ))
+    }
+
+    /// Returns the index of the byte at the cursor if the view shows a hex
+    /// dump of a value of `len` bytes.
+    fn hex_cursor_position(&self, len: usize) -> Option<usize> {
+        if !self.is_hex_dump(len) {
+            return None;
+        }
+
+        let buffer = self.imp().text_view.buffer();
+        let offset = buffer.iter_at_mark(&buffer.get_insert()).offset() as usize;
+        Some((offset / 3).min(len - 1))
+    }
+
+    /// Returns whether the view shows a hex dump of a value of `len` bytes.
+    fn is_hex_dump(&self, len: usize) -> bool {
+        let buffer = self.imp().text_view.buffer();
+
+        // Each byte takes two digits and a separating space
+        self.non_printable_display() == NonPrintableDisplay::Hex
+            && len > 0
+            && buffer.char_count() as usize == len * 3 - 1
+    }
+
+    /// Sets the width of tabs in the text to the width of `tab-width` spaces
+    /// in its font.
+    fn update_tabs(&self) {
+        let imp = self.imp();
+
+        let space = " ".repeat(self.tab_width() as usize);
+        let (width, _) = imp.text_view.create_pango_layout(Some(&space)).pixel_size();
+        let mut tabs = pango::TabArray::new(1, true);
+        tabs.set_tab(0, pango::TabAlign::Left, width);
+        imp.text_view.set_tabs(&tabs);
+    }
+
+    fn update_whitespace_tag(&self) {
+        let imp = self.imp();
+
+        let tag = imp
+            .text_view
+            .buffer()
+            .tag_table()
+            .lookup("whitespace")
+            .unwrap();
+        tag.set_show_spaces(if self.show_whitespace() {
+            pango::ShowFlags::SPACES | pango::ShowFlags::LINE_BREAKS | pango::ShowFlags::IGNORABLES
+        } else {
+            pango::ShowFlags::NONE
+        });
+    }
+
+    /// Colors the text and the hex editor with the style scheme of the
+    /// current style.
+    fn update_color_scheme(&self) {
+        let imp = self.imp();
+
+        let scheme_id = if adw::StyleManager::default().is_dark() {
+            self.dark_color_scheme()
+        } else {
+            self.light_color_scheme()
+        };
+        let scheme = color_scheme::style_scheme(&scheme_id);
+        let buffer = self.source_buffer();
+        buffer.set_style_scheme(Some(&scheme));
+        color_scheme::apply(&scheme, &buffer.tag_table());
+        imp.hex_editor.set_color_scheme(Some(scheme));
+    }
+
+    fn source_buffer(&self) -> sourceview::Buffer {
+        self.imp()
+            .text_view
+            .buffer()
+            .downcast::<sourceview::Buffer>()
+            .unwrap()
+    }
+
+    /// Marks the whitespace of the text if enabled, and colors the syntax of
+    /// decoded values, or the bytes of hex dumps by their kind.
+    fn color_text(&self) {
+        let imp = self.imp();
+
+        let buffer = self.source_buffer();
+        buffer.apply_tag_by_name("whitespace", &buffer.start_iter(), &buffer.end_iter());
+        if buffer.char_count() as usize > MAX_COLORED_TEXT_LEN {
+            return;
+        }
+
+        if imp.is_json.get() {
+            buffer.set_language(
+                sourceview::LanguageManager::default()
+                    .language("json")
+                    .as_ref(),
+            );
+            return;
+        }
+
+        let Some(item) = self
+            .item()
+            .filter(|item| self.is_hex_dump(item.data().len()))
+        else {
+            return;
+        };
+
+        // Each byte takes two digits and a separating space
+        for (role, range) in color_scheme::byte_runs(&item.data())
+            .into_iter()
+            .map(|(role, range)| (role, range.start * 3..range.end * 3 - 1))
+        {
+            buffer.apply_tag_by_name(
+                role.tag_name(),
+                &buffer.iter_at_offset(range.start as i32),
+                &buffer.iter_at_offset(range.end as i32),
+            );
+        }
+    }
+
+    /// Highlights the matches of the find query in the text, case
+    /// insensitively. In hex dumps, queries of hex digits only match whole
+    /// bytes, regardless of spacing.
+    fn update_find_matches(&self) {
+        let imp = self.imp();
+
+        let buffer = imp.text_view.buffer();
+        let (start, end) = buffer.bounds();
+        buffer.remove_tag_by_name("find-match", &start, &end);
+        buffer.remove_tag_by_name("current-find-match", &start, &end);
+
+        let query = imp.find_entry.text();
+        let mut matches = Vec::new();
+
+        if imp.find_bar.is_search_mode() && !query.is_empty() {
+            let hex_pattern = self
+                .item()
+                .filter(|item| self.is_hex_dump(item.data().len()))
+                .and_then(|_| hex_pattern(&query));
+            let query = hex_pattern.as_deref().unwrap_or(&query);
+
+            let mut iter = buffer.start_iter();
+            while let Some((match_start, match_end)) =
+                iter.forward_search(query, gtk::TextSearchFlags::CASE_INSENSITIVE, None)
+            {
+                if hex_pattern.is_some() && match_start.offset() % 3 != 0 {
+                    iter = match_start;
+                    iter.forward_char();
+                    continue;
+                }
+
+                buffer.apply_tag_by_name("find-match", &match_start, &match_end);
+                matches.push((match_start.offset(), match_end.offset()));
+                if matches.len() == MAX_N_FIND_MATCHES {
+                    break;
+                }
+                iter = match_end;
+            }
+        }
+
+        imp.find_matches.replace(matches);
+        imp.current_find_match.set(None);
+        self.update_find_matches_label();
+    }
+
+    /// Selects the next match of the find query after the current one, or
+    /// the previous one if `forward` is false, wrapping around at either end.
+    fn go_to_find_match(&self, forward: bool) {
+        let imp = self.imp();
+
+        let matches = imp.find_matches.borrow();
+        let n_matches = matches.len();
+        if n_matches == 0 {
+            return;
+        }
+
+        let index = match (imp.current_find_match.get(), forward) {
+            (None, true) => 0,
+            (None, false) => n_matches - 1,
+            (Some(index), true) => (index + 1) % n_matches,
+            (Some(index), false) => (index + n_matches - 1) % n_matches,
+        };
+
+        let buffer = imp.text_view.buffer();
+        let (start, end) = buffer.bounds();
+        buffer.remove_tag_by_name("current-find-match", &start, &end);
+
+        let (start_offset, end_offset) = matches[index];
+        let mut start = buffer.iter_at_offset(start_offset);
+        let end = buffer.iter_at_offset(end_offset);
+        buffer.apply_tag_by_name("current-find-match", &start, &end);
+        buffer.select_range(&start, &end);
+        imp.text_view
+            .scroll_to_iter(&mut start, 0.1, false, 0.0, 0.0);
+
+        drop(matches);
+        imp.current_find_match.set(Some(index));
+        self.update_find_matches_label();
+    }
+
+    fn update_find_matches_label(&self) {
+        let imp = self.imp();
+
+        let n_matches = imp.find_matches.borrow().len();
+        let label = match imp.current_find_match.get() {
+            _ if imp.find_entry.text().is_empty() => String::new(),
+            _ if n_matches == 0 => gettext("No matches"),
+            Some(index) => gettext_f(
+                "{current} of {n}",
+                &[
+                    ("current", &(index + 1).to_string()),
+                    ("n", &n_matches.to_string()),
+                ],
+            ),
+            None => ngettext_f(
+                "{n} match",
+                "{n} matches",
+                n_matches as u32,
+                &[("n", &n_matches.to_string())],
+            ),
+        };
+        imp.find_matches_label.set_label(&label);
+    }
+
+    /// Replaces the text in the view, inserting huge texts a chunk at a time
+    /// so the UI stays responsive while they load.
+    fn set_text(&self, text: String) {
+        let imp = self.imp();
+
+        if let Some(source_id) = imp.text_source_id.take() {
+            source_id.remove();
+        }
+
+        // Measured again in case the font changed since
+        self.update_tabs();
+
+        // Highlighted once all of the text is in, rather than every chunk
+        let buffer = self.source_buffer();
+        buffer.set_language(None);
+        let first_chunk = next_chunk(&text, 0);
+        buffer.set_text(first_chunk);
+
+        let mut offset = first_chunk.len();
+        if offset == text.len() {
+            self.color_text();
+            self.update_find_matches();
+            return;
+        }
+
+        let source_id = glib::idle_add_local(
+            clone!(@weak self as obj => @default-return glib::ControlFlow::Break, move || {
+                let buffer = obj.imp().text_view.buffer();
+                let chunk = next_chunk(&text, offset);
+                buffer.insert(&mut buffer.end_iter(), chunk);
+                offset += chunk.len();
+
+                if offset == text.len() {
+                    obj.imp().text_source_id.replace(None);
+                    obj.color_text();
+                    obj.update_find_matches();
+                    glib::ControlFlow::Break
+                } else {
+                    glib::ControlFlow::Continue
+                }
+            }),
+        );
+        imp.text_source_id.replace(Some(source_id));
+    }
+}
+
+impl Default for DetailPane {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the name of the kind of `value`, as announced by screen readers.
+fn value_type_name(value: &Value) -> String {
+    match value {
+        Value::Null => gettext("null"),
+        Value::Bool(_) => gettext("boolean"),
+        Value::Number(_) => gettext("number"),
+        Value::String(_) => gettext("string"),
+        Value::Array(elements) => ngettext_f(
+            "array of {n} item",
+            "array of {n} items",
+            elements.len() as u32,
+            &[("n", &elements.len().to_string())],
+        ),
+        Value::Object(members) => ngettext_f(
+            "object with {n} field",
+            "object with {n} fields",
+            members.len() as u32,
+            &[("n", &members.len().to_string())],
+        ),
+    }
+}
+
+/// Replaces the rows of `grid` with `rows` of titles, values, and optional
+/// tooltips, or a label with `empty_text` if there are none.
+fn fill_grid(grid: &gtk::Grid, rows: Vec<(String, String, Option<String>)>, empty_text: &str) {
+    while let Some(child) = grid.first_child() {
+        grid.remove(&child);
+    }
+
+    if rows.is_empty() {
+        let label = gtk::Label::builder()
+            .label(empty_text)
+            .xalign(0.0)
+            .css_classes(["dim-label"])
+            .build();
+        grid.attach(&label, 0, 0, 1, 1);
+        return;
+    }
+
+    for (row, (title, value, tooltip)) in rows.into_iter().enumerate() {
+        let title_label = gtk::Label::builder()
+            .label(title)
+            .xalign(1.0)
+            .css_classes(["dim-label"])
+            .build();
+        let value_label = gtk::Label::builder()
+            .label(value)
+            .xalign(0.0)
+            .wrap(true)
+            .wrap_mode(gtk::pango::WrapMode::WordChar)
+            .selectable(true)
+            .build();
+        value_label.set_tooltip_text(tooltip.as_deref());
+        grid.attach(&title_label, 0, row as i32, 1, 1);
+        grid.attach(&value_label, 1, row as i32, 1, 1);
+    }
+}
+
+/// Returns `query` as it appears in a hex dump if it only has hex digits and
+/// whitespace, with the digits of each byte separated by a space.
+fn hex_pattern(query: &str) -> Option<String> {
+    let digits = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<Vec<_>>();
+    if digits.is_empty() || digits.len() % 2 != 0 || !digits.iter().all(char::is_ascii_hexdigit) {
+        return None;
+    }
+
+    Some(
+        digits
+            .chunks(2)
+            .map(|byte| byte.iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+/// Returns up to `TEXT_CHUNK_LEN` bytes of `text` starting at `offset`, without
+/// splitting a character.
+fn next_chunk(text: &str, offset: usize) -> &str {
+    let mut end = (offset + TEXT_CHUNK_LEN).min(text.len());
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[offset..end]
+}
@@ -0,0 +1,135 @@
+use anyhow::{anyhow, ensure, Result};
+use gtk::glib;
+use heed::{types::ByteSlice, DatabaseFlags, Env};
+
+use std::cmp::Ordering;
+
+use crate::database;
+
+/// How an entry differs between two databases.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, glib::Enum)]
+#[enum_type(name = "LvDiffKind")]
+pub enum DiffKind {
+    /// Same in both databases
+    #[default]
+    Unchanged,
+    /// Only in the right database
+    Added,
+    /// Only in the left database
+    Removed,
+    /// In both, but with different values
+    Changed,
+}
+
+impl DiffKind {
+    /// Returns the style class used to highlight entries of this kind.
+    pub fn css_class(self) -> Option<&'static str> {
+        match self {
+            Self::Unchanged => None,
+            Self::Added => Some("diff-added"),
+            Self::Removed => Some("diff-removed"),
+            Self::Changed => Some("diff-changed"),
+        }
+    }
+}
+
+/// An entry that differs between two databases.
+#[derive(Debug)]
+pub struct Difference {
+    pub kind: DiffKind,
+    pub key: Vec<u8>,
+    pub left: Option<Vec<u8>>,
+    pub right: Option<Vec<u8>>,
+}
+
+/// Finds the entries that differ between two databases by walking both in
+/// key order at once, so neither has to be loaded into memory in full.
+pub fn diff(
+    left_env: &Env,
+    left_name: Option<&str>,
+    right_env: &Env,
+    right_name: Option<&str>,
+) -> Result<Vec<Difference>> {
+    // Without MDB_NOTLS, a thread can only have one read txn per env
+    let left_rtxn = left_env.read_txn()?;
+    let right_rtxn_owned;
+    let right_rtxn = if left_env.path() == right_env.path() {
+        &left_rtxn
+    } else {
+        right_rtxn_owned = right_env.read_txn()?;
+        &right_rtxn_owned
+    };
+
+    let left_flags = database::read_flags(left_env, &left_rtxn, left_name)?;
+    let right_flags = database::read_flags(right_env, right_rtxn, right_name)?;
+    let key_order_flags = DatabaseFlags::REVERSE_KEY | DatabaseFlags::INTEGER_KEY;
+    ensure!(
+        left_flags & key_order_flags == right_flags & key_order_flags,
+        "databases sort their keys differently"
+    );
+
+    let left_db = left_env
+        .open_database::<ByteSlice, ByteSlice>(&left_rtxn, left_name)?
+        .ok_or_else(|| anyhow!("left database not found"))?;
+    let right_db = right_env
+        .open_database::<ByteSlice, ByteSlice>(right_rtxn, right_name)?
+        .ok_or_else(|| anyhow!("right database not found"))?;
+
+    let mut left_iter = left_db.iter(&left_rtxn)?;
+    let mut right_iter = right_db.iter(right_rtxn)?;
+
+    let mut left = left_iter.next().transpose()?;
+    let mut right = right_iter.next().transpose()?;
+
+    let mut differences = Vec::new();
+
+    loop {
+        let ordering = match (left, right) {
+            (None, None) => break,
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some((left_key, _)), Some((right_key, _))) => {
+                database::compare_keys(left_flags, left_key, right_key)
+            }
+        };
+
+        match ordering {
+            Ordering::Less => {
+                let (key, value) = left.unwrap();
+                differences.push(Difference {
+                    kind: DiffKind::Removed,
+                    key: key.to_vec(),
+                    left: Some(value.to_vec()),
+                    right: None,
+                });
+                left = left_iter.next().transpose()?;
+            }
+            Ordering::Greater => {
+                let (key, value) = right.unwrap();
+                differences.push(Difference {
+                    kind: DiffKind::Added,
+                    key: key.to_vec(),
+                    left: None,
+                    right: Some(value.to_vec()),
+                });
+                right = right_iter.next().transpose()?;
+            }
+            Ordering::Equal => {
+                let (key, left_value) = left.unwrap();
+                let (_, right_value) = right.unwrap();
+                if left_value != right_value {
+                    differences.push(Difference {
+                        kind: DiffKind::Changed,
+                        key: key.to_vec(),
+                        left: Some(left_value.to_vec()),
+                        right: Some(right_value.to_vec()),
+                    });
+                }
+                left = left_iter.next().transpose()?;
+                right = right_iter.next().transpose()?;
+            }
+        }
+    }
+
+    Ok(differences)
+}
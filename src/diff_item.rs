@@ -0,0 +1,48 @@
+use gtk::{glib, prelude::*, subclass::prelude::*};
+
+use crate::diff::{DiffKind, Difference};
+
+mod imp {
+    use std::cell::{Cell, OnceCell, RefCell};
+
+    use super::*;
+
+    #[derive(Debug, Default, glib::Properties)]
+    #[properties(wrapper_type = super::DiffItem)]
+    pub struct DiffItem {
+        #[property(get, set, construct_only, builder(DiffKind::default()))]
+        pub(super) kind: Cell<DiffKind>,
+        #[property(get, set, construct_only)]
+        pub(super) key: OnceCell<glib::Bytes>,
+        /// Value in the left database, if any
+        #[property(get, set, construct_only, nullable)]
+        pub(super) left: RefCell<Option<glib::Bytes>>,
+        /// Value in the right database, if any
+        #[property(get, set, construct_only, nullable)]
+        pub(super) right: RefCell<Option<glib::Bytes>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for DiffItem {
+        const NAME: &'static str = "LvDiffItem";
+        type Type = super::DiffItem;
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for DiffItem {}
+}
+
+glib::wrapper! {
+     pub struct DiffItem(ObjectSubclass<imp::DiffItem>);
+}
+
+impl DiffItem {
+    pub fn new(difference: Difference) -> Self {
+        glib::Object::builder()
+            .property("kind", difference.kind)
+            .property("key", glib::Bytes::from_owned(difference.key))
+            .property("left", difference.left.map(glib::Bytes::from_owned))
+            .property("right", difference.right.map(glib::Bytes::from_owned))
+            .build()
+    }
+}
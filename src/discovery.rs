@@ -0,0 +1,141 @@
+use gtk::glib;
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{backend::BackendKind, utils};
+
+/// How deep under the cache directory envs are looked for
+const MAX_CACHE_DEPTH: usize = 3;
+
+/// An env found where an app is known to keep one.
+#[derive(Debug, Clone)]
+pub struct KnownLocation {
+    pub app_name: String,
+    pub path: PathBuf,
+}
+
+/// Where an app keeps its envs.
+struct Probe {
+    app_name: &'static str,
+    /// Candidate env directories, where `~` is the home directory and `*`
+    /// matches any directory name
+    patterns: &'static [&'static str],
+}
+
+const PROBES: &[Probe] = &[
+    Probe {
+        app_name: "Monero",
+        patterns: &[
+            "~/.bitmonero/lmdb",
+            "~/.bitmonero/testnet/lmdb",
+            "~/.bitmonero/stagenet/lmdb",
+        ],
+    },
+    Probe {
+        app_name: "OpenLDAP",
+        patterns: &[
+            "/var/lib/ldap",
+            "/var/lib/openldap",
+            "/var/lib/openldap-data",
+            "/var/lib/openldap/openldap-data",
+        ],
+    },
+    Probe {
+        app_name: "Firefox",
+        patterns: &["~/.mozilla/firefox/*/security_state"],
+    },
+    Probe {
+        app_name: "Thunderbird",
+        patterns: &["~/.thunderbird/*/security_state"],
+    },
+];
+
+/// Looks for envs at the locations of known apps and under the cache
+/// directory, which may take a while.
+pub fn discover() -> Vec<KnownLocation> {
+    let mut locations = Vec::new();
+
+    for probe in PROBES {
+        for pattern in probe.patterns {
+            for path in expand(pattern) {
+                if is_env(&path) {
+                    locations.push(KnownLocation {
+                        app_name: probe.app_name.to_string(),
+                        path,
+                    });
+                }
+            }
+        }
+    }
+
+    let cache_dir = glib::user_cache_dir();
+    let mut cache_env_paths = Vec::new();
+    find_envs(&cache_dir, MAX_CACHE_DEPTH, &mut cache_env_paths);
+    for path in cache_env_paths {
+        // Name it after the app that owns the cache
+        let app_name = path
+            .strip_prefix(&cache_dir)
+            .ok()
+            .and_then(|relative_path| relative_path.iter().next())
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        locations.push(KnownLocation { app_name, path });
+    }
+
+    locations
+}
+
+/// Returns the existing directories matching `pattern`.
+fn expand(pattern: &str) -> Vec<PathBuf> {
+    let mut paths = vec![PathBuf::from("/")];
+
+    for component in pattern.split('/').filter(|component| !component.is_empty()) {
+        paths = match component {
+            "~" => vec![glib::home_dir()],
+            "*" => paths
+                .iter()
+                .filter_map(|path| fs::read_dir(path).ok())
+                .flatten()
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.is_dir())
+                .collect(),
+            _ => paths
+                .iter()
+                .map(|path| path.join(component))
+                .filter(|path| path.is_dir())
+                .collect(),
+        };
+    }
+
+    paths
+}
+
+/// Adds the envs in `dir` and its subdirectories, up to `depth` levels deep,
+/// to `paths`.
+fn find_envs(dir: &Path, depth: usize, paths: &mut Vec<PathBuf>) {
+    if is_env(dir) {
+        paths.push(dir.to_path_buf());
+        return;
+    }
+
+    if depth == 0 {
+        return;
+    }
+
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+            find_envs(&entry.path(), depth - 1, paths);
+        }
+    }
+}
+
+fn is_env(dir: &Path) -> bool {
+    BackendKind::data_path(dir).is_file() && utils::env_path(dir).is_ok()
+}
@@ -0,0 +1,80 @@
+use anyhow::{Context, Result};
+use heed::types::ByteSlice;
+
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+};
+
+use crate::snapshot::Snapshot;
+
+/// Keys whose values are byte-identical.
+#[derive(Debug, Clone)]
+pub struct DuplicateGroup {
+    pub keys: Vec<Vec<u8>>,
+    /// Length of the shared value
+    pub value_len: u64,
+}
+
+impl DuplicateGroup {
+    /// Bytes that would be saved if the value were stored only once
+    pub fn wasted_bytes(&self) -> u64 {
+        self.value_len * (self.keys.len() as u64 - 1)
+    }
+}
+
+/// Finds the keys of the database called `db_name` that share non-empty
+/// values, with the groups that waste the most bytes first.
+///
+/// Values are first grouped by hash so only the keys and hashes are kept in
+/// memory, then the values of keys with the same hash are compared.
+pub fn find(snapshot: &Snapshot, db_name: Option<&str>) -> Result<Vec<DuplicateGroup>> {
+    snapshot.read(|env, rtxn| {
+        let db = env
+            .open_database::<ByteSlice, ByteSlice>(rtxn, db_name)?
+            .context("Database not found")?;
+
+        let mut keys_by_hash = HashMap::<(u64, usize), Vec<Vec<u8>>>::new();
+        for item in db.iter(rtxn)? {
+            let (key, value) = item?;
+            if value.is_empty() {
+                continue;
+            }
+
+            let mut hasher = DefaultHasher::new();
+            value.hash(&mut hasher);
+            keys_by_hash
+                .entry((hasher.finish(), value.len()))
+                .or_default()
+                .push(key.to_vec());
+        }
+
+        let mut groups = Vec::new();
+        for ((_, value_len), keys) in keys_by_hash {
+            if keys.len() < 2 {
+                continue;
+            }
+
+            // Split by the actual value in case of hash collisions
+            let mut keys_by_value = HashMap::<&[u8], Vec<Vec<u8>>>::new();
+            for key in keys {
+                let value = db.get(rtxn, &key)?.context("Key disappeared")?;
+                keys_by_value.entry(value).or_default().push(key);
+            }
+
+            groups.extend(
+                keys_by_value
+                    .into_values()
+                    .filter(|keys| keys.len() > 1)
+                    .map(|keys| DuplicateGroup {
+                        keys,
+                        value_len: value_len as u64,
+                    }),
+            );
+        }
+
+        groups.sort_by_key(|group| std::cmp::Reverse(group.wasted_bytes()));
+
+        Ok(groups)
+    })
+}
@@ -0,0 +1,218 @@
+use anyhow::{Context, Result};
+use heed::types::ByteSlice;
+
+use std::ops::Bound;
+
+/// Maximum number of edits that can be undone
+const MAX_N_EDITS: usize = 100;
+
+/// The values of a key before and after an edit, where none means the key
+/// doesn't exist and more than one are the duplicates of a DUPSORT key.
+#[derive(Debug, Clone)]
+struct KeyChange {
+    db_name: Option<String>,
+    key: Vec<u8>,
+    before: Vec<Vec<u8>>,
+    after: Vec<Vec<u8>>,
+}
+
+/// A write operation, recorded as how it changed each key so it can be
+/// reverted by writing back the previous values.
+#[derive(Debug, Default, Clone)]
+pub struct Edit {
+    changes: Vec<KeyChange>,
+}
+
+impl Edit {
+    /// Puts `value` under `key` of `db`, which adds a duplicate if `db` is
+    /// DUPSORT, or deletes the key with all of its values if `value` is
+    /// `None`, recording the previous values as read from `wtxn`.
+    pub fn write(
+        &mut self,
+        wtxn: &mut heed::RwTxn<'_>,
+        db: heed::Database<ByteSlice, ByteSlice>,
+        db_name: Option<&str>,
+        key: &[u8],
+        value: Option<&[u8]>,
+    ) -> Result<()> {
+        self.record(wtxn, db, db_name, key, |wtxn| {
+            match value {
+                Some(value) => db.put(wtxn, key, value).context("Failed to put value")?,
+                None => {
+                    db.delete(wtxn, key).context("Failed to delete entry")?;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Like [`Self::write`], but only replaces or deletes `old_value` of
+    /// `key`, leaving its other duplicates if `db` is DUPSORT.
+    pub fn write_duplicate(
+        &mut self,
+        wtxn: &mut heed::RwTxn<'_>,
+        db: heed::Database<ByteSlice, ByteSlice>,
+        db_name: Option<&str>,
+        key: &[u8],
+        old_value: &[u8],
+        value: Option<&[u8]>,
+    ) -> Result<()> {
+        self.record(wtxn, db, db_name, key, |wtxn| {
+            db.delete_one_duplicate(wtxn, key, old_value)
+                .context("Failed to delete value")?;
+            if let Some(value) = value {
+                db.put(wtxn, key, value).context("Failed to put value")?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Runs `write` on `key`, recording its values before and after.
+    fn record(
+        &mut self,
+        wtxn: &mut heed::RwTxn<'_>,
+        db: heed::Database<ByteSlice, ByteSlice>,
+        db_name: Option<&str>,
+        key: &[u8],
+        write: impl FnOnce(&mut heed::RwTxn<'_>) -> Result<()>,
+    ) -> Result<()> {
+        let before = read_values(wtxn, db, key)?;
+        write(wtxn)?;
+        let after = read_values(wtxn, db, key)?;
+
+        self.changes.push(KeyChange {
+            db_name: db_name.map(|name| name.to_string()),
+            key: key.to_vec(),
+            before,
+            after,
+        });
+
+        Ok(())
+    }
+
+    /// Appends the changes of `other`, which was applied after this edit.
+    pub fn extend(&mut self, other: Edit) {
+        self.changes.extend(other.changes);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Writes back the values before the edit in a single write txn.
+    fn revert(&self, env: &heed::Env) -> Result<()> {
+        write_values(
+            env,
+            self.changes
+                .iter()
+                .rev()
+                .map(|change| (change, change.before.as_slice())),
+        )
+    }
+
+    /// Writes the values after the edit again in a single write txn.
+    fn reapply(&self, env: &heed::Env) -> Result<()> {
+        write_values(
+            env,
+            self.changes
+                .iter()
+                .map(|change| (change, change.after.as_slice())),
+        )
+    }
+}
+
+/// Edits that can be undone and redone, most recent last.
+#[derive(Debug, Default)]
+pub struct EditHistory {
+    undo_stack: Vec<Edit>,
+    redo_stack: Vec<Edit>,
+}
+
+impl EditHistory {
+    /// Records an applied edit, which makes undone edits no longer redoable.
+    pub fn push(&mut self, edit: Edit) {
+        if edit.is_empty() {
+            return;
+        }
+
+        self.redo_stack.clear();
+        self.undo_stack.push(edit);
+
+        if self.undo_stack.len() > MAX_N_EDITS {
+            self.undo_stack.remove(0);
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Reverts the most recent edit in `env` with a compensating write txn.
+    pub fn undo(&mut self, env: &heed::Env) -> Result<()> {
+        let edit = self.undo_stack.last().context("Nothing to undo")?;
+        edit.revert(env)?;
+
+        let edit = self.undo_stack.pop().unwrap();
+        self.redo_stack.push(edit);
+
+        Ok(())
+    }
+
+    /// Applies the most recently undone edit to `env` again.
+    pub fn redo(&mut self, env: &heed::Env) -> Result<()> {
+        let edit = self.redo_stack.last().context("Nothing to redo")?;
+        edit.reapply(env)?;
+
+        let edit = self.redo_stack.pop().unwrap();
+        self.undo_stack.push(edit);
+
+        Ok(())
+    }
+
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+}
+
+/// Sets each key to exactly its values, deleting it if there are none, in a
+/// single write txn.
+fn write_values<'a>(
+    env: &heed::Env,
+    values: impl Iterator<Item = (&'a KeyChange, &'a [Vec<u8>])>,
+) -> Result<()> {
+    let mut wtxn = env.write_txn().context("Failed to create write txn")?;
+    for (change, values) in values {
+        let db = env
+            .open_database::<ByteSlice, ByteSlice>(&wtxn, change.db_name.as_deref())?
+            .with_context(|| format!("Database `{:?}` not found", change.db_name))?;
+        db.delete(&mut wtxn, &change.key)
+            .context("Failed to delete entry")?;
+        for value in values {
+            db.put(&mut wtxn, &change.key, value)
+                .context("Failed to put value")?;
+        }
+    }
+    wtxn.commit().context("Failed to commit write txn")?;
+
+    Ok(())
+}
+
+/// Returns the values of `key` in `db`, which are all of its duplicates if
+/// `db` is DUPSORT.
+fn read_values(
+    txn: &heed::RoTxn,
+    db: heed::Database<ByteSlice, ByteSlice>,
+    key: &[u8],
+) -> Result<Vec<Vec<u8>>> {
+    // A range over a single key walks its duplicates with `MDB_NEXT`, which,
+    // unlike `get_duplicates`, also works on databases without DUPSORT
+    db.range(txn, &(Bound::Included(key), Bound::Included(key)))?
+        .map(|item| item.map(|(_, value)| value.to_vec()))
+        .collect::<Result<Vec<_>, _>>()
+        .context("Failed to read values")
+}
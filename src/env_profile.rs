@@ -0,0 +1,104 @@
+use gtk::{gio, glib, prelude::*};
+
+use std::{collections::HashMap, fs, path::Path};
+
+use crate::config::APP_ID;
+
+/// Display settings that are remembered for each env
+pub const DISPLAY_SETTINGS: &[&str] = &[
+    "key-display-mode",
+    "key-encoding",
+    "key-rendering",
+    "value-encoding",
+    "value-rendering",
+    "non-printable-display",
+];
+
+/// The view of an env when it was last closed, restored when it is reopened.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct EnvProfile {
+    pub selected_db: Option<String>,
+    /// Values of the [`DISPLAY_SETTINGS`], keyed by settings key
+    pub display_settings: HashMap<String, String>,
+    /// Widths of the resized columns, keyed by column id
+    pub column_widths: HashMap<String, i32>,
+    /// Ids of the hidden columns
+    pub hidden_columns: Vec<String>,
+    /// Paths of the decoded fields shown as columns, keyed by database name
+    pub pinned_fields: HashMap<String, Vec<String>>,
+    pub map_size: Option<u64>,
+}
+
+impl EnvProfile {
+    /// Loads the profile of the env at `env_path` from the settings.
+    pub fn load(env_path: &Path) -> Self {
+        let settings = gio::Settings::new(APP_ID);
+
+        let Some(fields) = settings
+            .value("env-profiles")
+            .get::<HashMap<String, HashMap<String, glib::Variant>>>()
+            .unwrap()
+            .remove(&profile_key(env_path))
+        else {
+            return Self::default();
+        };
+
+        Self {
+            selected_db: field(&fields, "selected-db"),
+            display_settings: field(&fields, "display-settings").unwrap_or_default(),
+            column_widths: field(&fields, "column-widths").unwrap_or_default(),
+            hidden_columns: field(&fields, "hidden-columns").unwrap_or_default(),
+            pinned_fields: field(&fields, "pinned-fields").unwrap_or_default(),
+            map_size: field(&fields, "map-size"),
+        }
+    }
+
+    /// Saves this as the profile of the env at `env_path`.
+    pub fn save(&self, env_path: &Path) {
+        let settings = gio::Settings::new(APP_ID);
+
+        let mut fields = HashMap::new();
+        if let Some(selected_db) = &self.selected_db {
+            fields.insert("selected-db", selected_db.to_variant());
+        }
+        fields.insert("display-settings", self.display_settings.to_variant());
+        fields.insert("column-widths", self.column_widths.to_variant());
+        fields.insert("hidden-columns", self.hidden_columns.to_variant());
+        fields.insert("pinned-fields", self.pinned_fields.to_variant());
+        if let Some(map_size) = self.map_size {
+            fields.insert("map-size", map_size.to_variant());
+        }
+
+        let mut profiles = settings
+            .value("env-profiles")
+            .get::<HashMap<String, HashMap<String, glib::Variant>>>()
+            .unwrap();
+        profiles.insert(
+            profile_key(env_path),
+            fields
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value))
+                .collect(),
+        );
+
+        if let Err(err) = settings.set_value("env-profiles", &profiles.to_variant()) {
+            tracing::warn!("Failed to save env profile: {:?}", &err);
+        }
+    }
+}
+
+/// Returns the field called `name`, or `None` if it is missing or has a
+/// different type.
+fn field<T: glib::FromVariant>(fields: &HashMap<String, glib::Variant>, name: &str) -> Option<T> {
+    fields.get(name).and_then(|value| value.get())
+}
+
+/// Returns the canonical form of `env_path`, so the same env has the same
+/// profile however its path is written.
+fn profile_key(env_path: &Path) -> String {
+    fs::canonicalize(env_path)
+        .as_deref()
+        .unwrap_or(env_path)
+        .to_string_lossy()
+        .to_string()
+}
@@ -0,0 +1,125 @@
+use anyhow::{Context, Result};
+use gtk::glib;
+use heed::{types::ByteSlice, DatabaseFlags};
+
+use std::{
+    path::Path,
+    sync::atomic::{self, AtomicUsize},
+};
+
+use crate::{database, utils};
+
+/// Number of entries written per write txn
+const BATCH_LEN: usize = 10_000;
+
+/// Part of an env to copy to a new env.
+#[derive(Debug)]
+pub enum Subset {
+    /// Some entries of the database called `db_name`
+    Entries {
+        db_name: Option<String>,
+        entries: Vec<(glib::Bytes, glib::Bytes)>,
+    },
+    /// All entries of the databases with these names, where `None` is the
+    /// unnamed one
+    Databases(Vec<Option<String>>),
+}
+
+/// Creates a new env in `dir` with only `subset` of `env`, keeping the flags
+/// of the copied databases. `n_copied` is incremented after each batch of
+/// entries, so progress can be shown.
+pub fn copy_to_new_env(
+    env: &heed::Env,
+    subset: Subset,
+    dir: &Path,
+    n_copied: &AtomicUsize,
+) -> Result<()> {
+    let new_env = utils::create_env(dir, env.info().map_size)?;
+    let rtxn = env.read_txn()?;
+
+    match subset {
+        Subset::Entries { db_name, entries } => {
+            let flags = database::read_flags(env, &rtxn, db_name.as_deref())?;
+            let new_db = create_db(&new_env, db_name.as_deref(), flags)?;
+            put_all(
+                &new_env,
+                new_db,
+                entries
+                    .iter()
+                    .map(|(key, value)| Ok((key.as_ref(), value.as_ref()))),
+                n_copied,
+            )?;
+        }
+        Subset::Databases(db_names) => {
+            let (all_db_names, _) = database::scan_names(env, &rtxn)?;
+            for db_name in db_names {
+                let db = env
+                    .open_database::<ByteSlice, ByteSlice>(&rtxn, db_name.as_deref())?
+                    .with_context(|| format!("Database `{:?}` not found", db_name))?;
+                let flags = database::read_flags(env, &rtxn, db_name.as_deref())?;
+                let new_db = create_db(&new_env, db_name.as_deref(), flags)?;
+
+                // The records of named databases in the unnamed one are made
+                // when creating them, so they must not be copied
+                let entries = db.iter(&rtxn)?.filter(|item| {
+                    db_name.is_some()
+                        || !item.as_ref().is_ok_and(|(key, _)| {
+                            all_db_names
+                                .iter()
+                                .flatten()
+                                .any(|name| name.as_bytes() == *key)
+                        })
+                });
+                put_all(&new_env, new_db, entries.map(|item| Ok(item?)), n_copied)?;
+            }
+        }
+    }
+
+    new_env.force_sync()?;
+
+    Ok(())
+}
+
+/// Creates the database called `name` in `env` with `flags`, or opens the
+/// unnamed one.
+fn create_db(
+    env: &heed::Env,
+    name: Option<&str>,
+    flags: DatabaseFlags,
+) -> Result<heed::Database<ByteSlice, ByteSlice>> {
+    let mut wtxn = env.write_txn()?;
+
+    let mut options = env.database_options().types::<ByteSlice, ByteSlice>();
+    if let Some(name) = name {
+        options.name(name).flags(flags);
+    }
+    let db = options.create(&mut wtxn)?;
+    wtxn.commit()?;
+
+    Ok(db)
+}
+
+/// Puts `entries` in `db` of `env`, committing every [`BATCH_LEN`] entries.
+fn put_all<'a>(
+    env: &heed::Env,
+    db: heed::Database<ByteSlice, ByteSlice>,
+    entries: impl Iterator<Item = Result<(&'a [u8], &'a [u8])>>,
+    n_copied: &AtomicUsize,
+) -> Result<()> {
+    let mut entries = entries.peekable();
+
+    while entries.peek().is_some() {
+        let mut wtxn = env.write_txn()?;
+        let mut n_batch_copied = 0;
+        for item in entries.by_ref().take(BATCH_LEN) {
+            let (key, value) = item?;
+            db.put(&mut wtxn, key, value)?;
+            n_batch_copied += 1;
+        }
+        wtxn.commit()?;
+
+        n_copied.fetch_add(n_batch_copied, atomic::Ordering::Relaxed);
+    }
+
+    Ok(())
+}
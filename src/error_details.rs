@@ -0,0 +1,75 @@
+use adw::prelude::*;
+use gettextrs::gettext;
+use gtk::glib::{self, clone};
+
+use crate::config::VERSION;
+
+/// Creates a toast with `message` and a button that shows the chain of
+/// causes of `err` in a dialog over `parent`.
+pub fn error_toast(
+    parent: &impl IsA<gtk::Window>,
+    message: &str,
+    err: &anyhow::Error,
+) -> adw::Toast {
+    let toast = adw::Toast::builder()
+        .title(message)
+        .button_label(gettext("Show Details"))
+        .build();
+
+    let parent = parent.upcast_ref::<gtk::Window>();
+    let message = message.to_string();
+    let details = format!("{:?}", err);
+    toast.connect_button_clicked(clone!(@weak parent => move |_| {
+        glib::spawn_future_local(clone!(@strong message, @strong details => async move {
+            show_error_details(&parent, &message, &details).await;
+        }));
+    }));
+
+    toast
+}
+
+/// Shows `message` with the error `details`, which can be copied to be
+/// included in bug reports.
+async fn show_error_details(parent: &gtk::Window, message: &str, details: &str) {
+    let text_view = gtk::TextView::builder()
+        .editable(false)
+        .monospace(true)
+        .wrap_mode(gtk::WrapMode::WordChar)
+        .top_margin(6)
+        .bottom_margin(6)
+        .left_margin(6)
+        .right_margin(6)
+        .build();
+    text_view.buffer().set_text(details);
+    let scrolled_window = gtk::ScrolledWindow::builder()
+        .hscrollbar_policy(gtk::PolicyType::Never)
+        .min_content_height(120)
+        .max_content_height(360)
+        .propagate_natural_height(true)
+        .css_classes(["card"])
+        .child(&text_view)
+        .build();
+
+    let dialog = adw::MessageDialog::builder()
+        .heading(message)
+        .body(gettext(
+            "The error below can be copied to include in a bug report.",
+        ))
+        .transient_for(parent)
+        .modal(true)
+        .extra_child(&scrolled_window)
+        .default_response("close")
+        .close_response("close")
+        .build();
+    dialog.add_responses(&[
+        ("copy", &gettext("_Copy Details")),
+        ("close", &gettext("_Close")),
+    ]);
+
+    if dialog.choose_future().await == "copy" {
+        parent.clipboard().set_text(&format!(
+            "{}\n\nVersion: {}\n\n{}",
+            message, VERSION, details
+        ));
+    }
+}
@@ -0,0 +1,365 @@
+use anyhow::{Context, Result};
+use heed::{types::ByteSlice, DatabaseFlags};
+
+use std::{
+    fmt::Write,
+    fs::File,
+    io,
+    path::{Path, PathBuf},
+    sync::atomic::{self, AtomicBool, AtomicUsize},
+};
+
+use crate::{
+    database, non_printable_display::NonPrintableDisplay, snapshot::Snapshot,
+    text_encoding::TextEncoding,
+};
+
+/// Flags of a database with the names `mdb_load` reads from `mdb_dump`
+/// headers, in the order `mdb_dump` writes them
+const MDB_DUMP_FLAGS: [(DatabaseFlags, &str); 6] = [
+    (DatabaseFlags::REVERSE_KEY, "reversekey"),
+    (DatabaseFlags::DUP_SORT, "dupsort"),
+    (DatabaseFlags::INTEGER_KEY, "integerkey"),
+    (DatabaseFlags::DUP_FIXED, "dupfixed"),
+    (DatabaseFlags::INTEGER_DUP, "integerdup"),
+    (DatabaseFlags::REVERSE_DUP, "reversedup"),
+];
+
+/// A key-value pair to export, with how its key and value are displayed.
+pub struct ExportEntry<'a> {
+    pub key: &'a [u8],
+    pub value: &'a [u8],
+    pub key_text: String,
+    pub value_text: String,
+}
+
+/// File format entries can be exported to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Array of objects with the key and value text
+    Json,
+    /// `key,value` rows of the key and value text
+    Csv,
+    /// `mdb_dump` output of the raw bytes, which `mdb_load` can import
+    MdbDump,
+}
+
+impl ExportFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "json" => Some(Self::Json),
+            "csv" => Some(Self::Csv),
+            "mdb" => Some(Self::MdbDump),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+            Self::MdbDump => "txt",
+        }
+    }
+
+    /// Serializes `entries` of the database called `db_name`, which has
+    /// `flags`.
+    pub fn serialize(
+        self,
+        db_name: Option<&str>,
+        flags: DatabaseFlags,
+        entries: &[ExportEntry<'_>],
+    ) -> Result<Vec<u8>> {
+        let mut writer = ExportWriter::new(self, db_name, flags, Vec::new())?;
+        for entry in entries {
+            writer.write_entry(entry)?;
+        }
+        writer.finish()
+    }
+}
+
+/// Writes entries in an export format one at a time, so they don't all have
+/// to be collected first.
+pub struct ExportWriter<W> {
+    format: ExportFormat,
+    writer: W,
+    n_written: usize,
+    /// Database of the following entries, if they are of many databases
+    combined_db_name: Option<String>,
+    /// Whether an `mdb_dump` section is started and has to be ended
+    is_section_open: bool,
+}
+
+impl<W: io::Write> ExportWriter<W> {
+    /// Writes the header of the export of the database called `db_name`,
+    /// which has `flags`.
+    pub fn new(
+        format: ExportFormat,
+        db_name: Option<&str>,
+        flags: DatabaseFlags,
+        mut writer: W,
+    ) -> Result<Self> {
+        match format {
+            ExportFormat::Json => write!(writer, "[")?,
+            ExportFormat::Csv => write!(writer, "key,value\r\n")?,
+            ExportFormat::MdbDump => write_mdb_dump_header(&mut writer, db_name, flags)?,
+        }
+
+        Ok(Self {
+            format,
+            writer,
+            n_written: 0,
+            combined_db_name: None,
+            is_section_open: format == ExportFormat::MdbDump,
+        })
+    }
+
+    /// Like [`Self::new`], but for the entries of many databases, each
+    /// started with [`Self::start_db`]. Entries get a field with the name of
+    /// their database, or, in `mdb_dump` output, a section per database.
+    pub fn new_combined(format: ExportFormat, mut writer: W) -> Result<Self> {
+        match format {
+            ExportFormat::Json => write!(writer, "[")?,
+            ExportFormat::Csv => write!(writer, "db,key,value\r\n")?,
+            ExportFormat::MdbDump => {}
+        }
+
+        Ok(Self {
+            format,
+            writer,
+            n_written: 0,
+            combined_db_name: Some(String::new()),
+            is_section_open: false,
+        })
+    }
+
+    /// Makes the following entries be of the database called `db_name`, or
+    /// the unnamed one if `None`, which has `flags`, in a writer created with
+    /// [`Self::new_combined`].
+    pub fn start_db(&mut self, db_name: Option<&str>, flags: DatabaseFlags) -> Result<()> {
+        if self.format == ExportFormat::MdbDump {
+            if self.is_section_open {
+                writeln!(self.writer, "DATA=END")?;
+            }
+            write_mdb_dump_header(&mut self.writer, db_name, flags)?;
+            self.is_section_open = true;
+        }
+        self.combined_db_name = Some(db_name.unwrap_or_default().to_string());
+
+        Ok(())
+    }
+
+    pub fn write_entry(&mut self, entry: &ExportEntry<'_>) -> Result<()> {
+        match self.format {
+            ExportFormat::Json => {
+                let row = match &self.combined_db_name {
+                    Some(db_name) => serde_json::json!({
+                        "db": db_name,
+                        "key": entry.key_text,
+                        "value": entry.value_text,
+                    }),
+                    None => serde_json::json!({ "key": entry.key_text, "value": entry.value_text }),
+                };
+                // Indent the row as an element of a pretty-printed array
+                let row = serde_json::to_string_pretty(&row)?.replace('\n', "\n  ");
+                let separator = if self.n_written == 0 { "" } else { "," };
+                write!(self.writer, "{}\n  {}", separator, row)?;
+            }
+            ExportFormat::Csv => {
+                if let Some(db_name) = &self.combined_db_name {
+                    write!(self.writer, "{},", csv_field(db_name))?;
+                }
+                write!(
+                    self.writer,
+                    "{},{}\r\n",
+                    csv_field(&entry.key_text),
+                    csv_field(&entry.value_text)
+                )?;
+            }
+            ExportFormat::MdbDump => {
+                writeln!(self.writer, " {}", hex(entry.key))?;
+                writeln!(self.writer, " {}", hex(entry.value))?;
+            }
+        }
+
+        self.n_written += 1;
+
+        Ok(())
+    }
+
+    /// Writes the footer, then returns the underlying writer.
+    pub fn finish(mut self) -> Result<W> {
+        match self.format {
+            ExportFormat::Json if self.n_written == 0 => write!(self.writer, "]")?,
+            ExportFormat::Json => write!(self.writer, "\n]")?,
+            ExportFormat::Csv => {}
+            ExportFormat::MdbDump if self.is_section_open => writeln!(self.writer, "DATA=END")?,
+            ExportFormat::MdbDump => {}
+        }
+        self.writer.flush()?;
+
+        Ok(self.writer)
+    }
+}
+
+/// Writes the `mdb_dump` header of a section with the entries of the
+/// database called `db_name`, with its flags so `mdb_load` creates it with
+/// them.
+fn write_mdb_dump_header(
+    writer: &mut impl io::Write,
+    db_name: Option<&str>,
+    flags: DatabaseFlags,
+) -> Result<()> {
+    write!(writer, "VERSION=3\nformat=bytevalue\n")?;
+    if let Some(db_name) = db_name {
+        writeln!(writer, "database={}", db_name)?;
+    }
+    writeln!(writer, "type=btree")?;
+    for (flag, name) in MDB_DUMP_FLAGS {
+        if flags.contains(flag) {
+            writeln!(writer, "{}=1", name)?;
+        }
+    }
+    writeln!(writer, "HEADER=END")?;
+
+    Ok(())
+}
+
+/// Where the entries of many databases are exported to.
+#[derive(Debug, Clone)]
+pub enum ExportDestination {
+    /// One file with the entries of all databases
+    Combined(PathBuf),
+    /// A directory with a file for each database, named after it
+    Directory(PathBuf),
+}
+
+/// How the entries of many databases are exported, which is done in a
+/// separate thread, away from how the window shows them.
+#[derive(Debug, Clone, Copy)]
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    pub non_printable_display: NonPrintableDisplay,
+    pub key_encoding: TextEncoding,
+    pub value_encoding: TextEncoding,
+}
+
+/// Writes the entries of the databases called `db_names` to `destination`
+/// with `options`, stopping early once `is_cancelled` is set.
+///
+/// `n_total` is set to the number of entries to write, and `n_written` is
+/// incremented after each entry, so progress can be shown.
+pub fn export_dbs(
+    snapshot: &Snapshot,
+    db_names: &[Option<String>],
+    destination: &ExportDestination,
+    options: ExportOptions,
+    n_total: &AtomicUsize,
+    n_written: &AtomicUsize,
+    is_cancelled: &AtomicBool,
+) -> Result<()> {
+    let ExportOptions {
+        format,
+        non_printable_display,
+        key_encoding,
+        value_encoding,
+    } = options;
+
+    snapshot.read(|env, rtxn| {
+        let mut dbs = Vec::new();
+        for name in db_names {
+            let db = env
+                .open_database::<ByteSlice, ByteSlice>(rtxn, name.as_deref())?
+                .with_context(|| format!("Database `{:?}` not found", name))?;
+            n_total.fetch_add(db.len(rtxn)? as usize, atomic::Ordering::Relaxed);
+            let flags = database::read_flags(env, rtxn, name.as_deref())?;
+            dbs.push((name.as_deref(), db, flags));
+        }
+
+        let create_file = |path: &Path| {
+            File::create(path)
+                .map(io::BufWriter::new)
+                .with_context(|| format!("Failed to create `{}`", path.display()))
+        };
+        let mut combined_writer = match destination {
+            ExportDestination::Combined(path) => {
+                Some(ExportWriter::new_combined(format, create_file(path)?)?)
+            }
+            ExportDestination::Directory(_) => None,
+        };
+
+        for (name, db, flags) in dbs {
+            let mut file_writer = None;
+            let writer = match (&mut combined_writer, destination) {
+                (Some(writer), _) => {
+                    writer.start_db(name, flags)?;
+                    writer
+                }
+                (None, ExportDestination::Directory(dir)) => {
+                    let path = dir.join(format!("{}.{}", file_stem(name), format.extension()));
+                    let writer = ExportWriter::new(format, name, flags, create_file(&path)?)?;
+                    file_writer.insert(writer)
+                }
+                (None, ExportDestination::Combined(_)) => unreachable!(),
+            };
+
+            for item in db.iter(rtxn)? {
+                if is_cancelled.load(atomic::Ordering::Relaxed) {
+                    break;
+                }
+
+                let (key, value) = item?;
+                writer.write_entry(&ExportEntry {
+                    key,
+                    value,
+                    key_text: non_printable_display.render(key, key_encoding),
+                    value_text: non_printable_display.render(value, value_encoding),
+                })?;
+                n_written.fetch_add(1, atomic::Ordering::Relaxed);
+            }
+
+            if let Some(writer) = file_writer {
+                writer.finish()?;
+            }
+            if is_cancelled.load(atomic::Ordering::Relaxed) {
+                break;
+            }
+        }
+
+        if let Some(writer) = combined_writer {
+            writer.finish()?;
+        }
+
+        Ok(())
+    })
+}
+
+/// Returns a file name without extension for the export of the database
+/// called `db_name`, with the characters that can't be in file names
+/// replaced.
+fn file_stem(db_name: Option<&str>) -> String {
+    match db_name {
+        Some(db_name) if !db_name.is_empty() && db_name != "." && db_name != ".." => db_name
+            .chars()
+            .map(|c| if c == '/' || c == '\0' { '_' } else { c })
+            .collect(),
+        _ => "unnamed".to_string(),
+    }
+}
+
+/// Quotes `text` if it contains characters special to CSV.
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        write!(hex, "{:02x}", byte).unwrap();
+    }
+    hex
+}
@@ -0,0 +1,246 @@
+use anyhow::{Context, Result};
+use heed::types::ByteSlice;
+
+use std::sync::{
+    atomic::{self, AtomicBool, AtomicUsize},
+    Mutex,
+};
+
+use crate::{
+    non_printable_display::NonPrintableDisplay, query::Query, snapshot::Snapshot,
+    text_encoding::TextEncoding,
+};
+
+/// A key found by a scan, with the text it matched on.
+#[derive(Debug, Clone)]
+pub struct Match {
+    /// Name of the database of the key, for scans spanning the env
+    pub db_name: Option<String>,
+    pub key: Vec<u8>,
+    pub key_text: String,
+}
+
+/// State of a scan, shared between the thread running it and the one showing
+/// its results.
+#[derive(Debug, Default)]
+pub struct ScanProgress {
+    n_scanned: AtomicUsize,
+    /// Databases done by scans spanning the env
+    n_scanned_dbs: AtomicUsize,
+    /// Database being scanned by scans spanning the env
+    current_db: Mutex<Option<String>>,
+    /// Matches found since they were last taken
+    new_matches: Mutex<Vec<Match>>,
+    is_cancelled: AtomicBool,
+}
+
+impl ScanProgress {
+    pub fn n_scanned(&self) -> usize {
+        self.n_scanned.load(atomic::Ordering::Relaxed)
+    }
+
+    pub fn n_scanned_dbs(&self) -> usize {
+        self.n_scanned_dbs.load(atomic::Ordering::Relaxed)
+    }
+
+    pub fn current_db(&self) -> Option<String> {
+        self.current_db.lock().unwrap().clone()
+    }
+
+    /// Returns the matches found since the last call.
+    pub fn take_new_matches(&self) -> Vec<Match> {
+        std::mem::take(&mut *self.new_matches.lock().unwrap())
+    }
+
+    /// Makes the scan stop at the next entry.
+    pub fn cancel(&self) {
+        self.is_cancelled.store(true, atomic::Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.is_cancelled.load(atomic::Ordering::Relaxed)
+    }
+}
+
+/// Walks the database called `db_name` with a cursor, reporting the keys
+/// whose text, rendered with `non_printable_display` in `encoding`, contains
+/// `query` regardless of case.
+///
+/// Only the matches are kept, so this works on databases too large to load.
+pub fn scan(
+    snapshot: &Snapshot,
+    db_name: Option<&str>,
+    query: &str,
+    non_printable_display: NonPrintableDisplay,
+    encoding: TextEncoding,
+    progress: &ScanProgress,
+) -> Result<()> {
+    snapshot.read(|env, rtxn| {
+        let db = env
+            .open_database::<ByteSlice, ByteSlice>(rtxn, db_name)?
+            .context("Database not found")?;
+
+        let scanner = Scanner {
+            query: query.to_lowercase(),
+            non_printable_display,
+            key_encoding: encoding,
+            value_encoding: None,
+        };
+        scanner.scan_db(rtxn, db, None, progress)
+    })
+}
+
+/// Walks the databases called `db_names` one after another in the same txn,
+/// like [`scan`], but also reporting the keys with a value whose text, in
+/// `value_encoding`, contains `query`.
+pub fn scan_env(
+    snapshot: &Snapshot,
+    db_names: &[String],
+    query: &str,
+    non_printable_display: NonPrintableDisplay,
+    key_encoding: TextEncoding,
+    value_encoding: TextEncoding,
+    progress: &ScanProgress,
+) -> Result<()> {
+    snapshot.read(|env, rtxn| {
+        let scanner = Scanner {
+            query: query.to_lowercase(),
+            non_printable_display,
+            key_encoding,
+            value_encoding: Some(value_encoding),
+        };
+        for db_name in db_names {
+            if progress.is_cancelled() {
+                break;
+            }
+
+            progress.current_db.lock().unwrap().replace(db_name.clone());
+            let db = env
+                .open_database::<ByteSlice, ByteSlice>(rtxn, Some(db_name))?
+                .with_context(|| format!("Database `{}` not found", db_name))?;
+            scanner.scan_db(rtxn, db, Some(db_name), progress)?;
+            progress
+                .n_scanned_dbs
+                .fetch_add(1, atomic::Ordering::Relaxed);
+        }
+        progress.current_db.lock().unwrap().take();
+
+        Ok(())
+    })
+}
+
+/// Walks the database called `db_name` with a cursor, counting the entries
+/// that match `query`, if any, and whose key text, rendered like in [`scan`],
+/// contains `text` regardless of case. Decoded values aren't available, so
+/// `query` must not test them.
+///
+/// Nothing is kept but the count, which is partial if cancelled.
+pub fn count(
+    snapshot: &Snapshot,
+    db_name: Option<&str>,
+    query: Option<&Query>,
+    text: &str,
+    non_printable_display: NonPrintableDisplay,
+    encoding: TextEncoding,
+    progress: &ScanProgress,
+) -> Result<u64> {
+    snapshot.read(|env, rtxn| {
+        let db = env
+            .open_database::<ByteSlice, ByteSlice>(rtxn, db_name)?
+            .context("Database not found")?;
+
+        let text = text.to_lowercase();
+        let mut n_matching = 0;
+        for item in db.iter(rtxn)? {
+            if progress.is_cancelled() {
+                break;
+            }
+
+            let (key, value) = item?;
+            progress.n_scanned.fetch_add(1, atomic::Ordering::Relaxed);
+
+            let is_match = query.is_none_or(|query| query.matches(key, value, || None))
+                && (text.is_empty()
+                    || non_printable_display
+                        .render(key, encoding)
+                        .to_lowercase()
+                        .contains(&text));
+            if is_match {
+                n_matching += 1;
+            }
+        }
+
+        Ok(n_matching)
+    })
+}
+
+/// Returns the total number of entries in the databases called `db_names`,
+/// which is quick as LMDB keeps count.
+pub fn count_entries(snapshot: &Snapshot, db_names: &[String]) -> Result<u64> {
+    snapshot.read(|env, rtxn| {
+        let mut n_entries = 0;
+        for db_name in db_names {
+            if let Some(db) = env.open_database::<ByteSlice, ByteSlice>(rtxn, Some(db_name))? {
+                n_entries += db.len(rtxn)?;
+            }
+        }
+
+        Ok(n_entries)
+    })
+}
+
+struct Scanner {
+    /// Lowercase query
+    query: String,
+    non_printable_display: NonPrintableDisplay,
+    key_encoding: TextEncoding,
+    /// Encoding of the values, which are only matched if given
+    value_encoding: Option<TextEncoding>,
+}
+
+impl Scanner {
+    fn scan_db(
+        &self,
+        rtxn: &heed::RoTxn,
+        db: heed::Database<ByteSlice, ByteSlice>,
+        db_name: Option<&str>,
+        progress: &ScanProgress,
+    ) -> Result<()> {
+        let mut prev_key = None::<&[u8]>;
+        let mut prev_key_matched = false;
+        for item in db.iter(rtxn)? {
+            if progress.is_cancelled() {
+                break;
+            }
+
+            let (key, value) = item?;
+            progress.n_scanned.fetch_add(1, atomic::Ordering::Relaxed);
+
+            // Duplicates of DUPSORT databases share their key, which is only
+            // matched once, by the key or any of its values
+            let is_duplicate = prev_key == Some(key);
+            prev_key = Some(key);
+            if is_duplicate && (prev_key_matched || self.value_encoding.is_none()) {
+                continue;
+            }
+
+            let key_text = self.non_printable_display.render(key, self.key_encoding);
+            prev_key_matched = key_text.to_lowercase().contains(&self.query)
+                || self.value_encoding.is_some_and(|encoding| {
+                    self.non_printable_display
+                        .render(value, encoding)
+                        .to_lowercase()
+                        .contains(&self.query)
+                });
+            if prev_key_matched {
+                progress.new_matches.lock().unwrap().push(Match {
+                    db_name: db_name.map(str::to_owned),
+                    key: key.to_vec(),
+                    key_text,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
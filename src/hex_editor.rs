@@ -0,0 +1,474 @@
+use gettextrs::gettext;
+use gtk::{
+    gdk,
+    glib::{self, clone},
+    pango,
+    prelude::*,
+    subclass::prelude::*,
+};
+
+use std::fmt::Write;
+
+use crate::{
+    color_scheme::{self, ColorRole},
+    utils::gettext_f,
+};
+
+const BYTES_PER_LINE: usize = 16;
+/// Column of the first hex byte, after the offset and two spaces
+const HEX_START: i32 = 10;
+/// Column of the first ASCII character, after the hex bytes and two spaces
+const ASCII_START: i32 = HEX_START + BYTES_PER_LINE as i32 * 3 - 1 + 2;
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use super::*;
+
+    #[derive(Debug, Default, glib::Properties, gtk::CompositeTemplate)]
+    #[properties(wrapper_type = super::HexEditor)]
+    #[template(resource = "/io/github/seadve/LmdbViewer/ui/hex_editor.ui")]
+    pub struct HexEditor {
+        /// Whether typing replaces bytes rather than inserting them
+        #[property(get, set = Self::set_overwrite, explicit_notify)]
+        pub(super) overwrite: Cell<bool>,
+        /// Style scheme whose colors the offsets and the bytes take, by
+        /// their kind
+        #[property(get, set = Self::set_color_scheme, explicit_notify, nullable)]
+        pub(super) color_scheme: RefCell<Option<sourceview::StyleScheme>>,
+        /// Whether the bytes were edited since they were set
+        #[property(get)]
+        pub(super) modified: Cell<bool>,
+
+        #[template_child]
+        pub(super) text_view: TemplateChild<gtk::TextView>,
+        #[template_child]
+        pub(super) status_label: TemplateChild<gtk::Label>,
+
+        pub(super) bytes: RefCell<Vec<u8>>,
+        /// Index of the byte at the cursor, which is the length of the bytes
+        /// when appending
+        pub(super) cursor: Cell<usize>,
+        /// Whether the next hex digit goes into the low nibble of the byte
+        pub(super) is_low_nibble: Cell<bool>,
+        /// Whether typing goes to the ASCII pane rather than the hex pane
+        pub(super) is_ascii_focused: Cell<bool>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for HexEditor {
+        const NAME: &'static str = "LvHexEditor";
+        type Type = super::HexEditor;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for HexEditor {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let obj = self.obj();
+
+            self.overwrite.set(true);
+
+            let buffer = self.text_view.buffer();
+            buffer.create_tag(
+                Some("cursor"),
+                &[("background-rgba", &gdk::RGBA::new(0.21, 0.52, 0.89, 0.3))],
+            );
+            buffer.create_tag(Some("active"), &[("underline", &pango::Underline::Single)]);
+            if let Some(color_scheme) = obj.color_scheme() {
+                color_scheme::apply(&color_scheme, &buffer.tag_table());
+            }
+
+            let key_controller = gtk::EventControllerKey::new();
+            key_controller.set_propagation_phase(gtk::PropagationPhase::Capture);
+            key_controller.connect_key_pressed(clone!(
+                @weak obj => @default-return glib::Propagation::Proceed,
+                move |_, key, _, state| obj.handle_key_pressed(key, state)
+            ));
+            self.text_view.add_controller(key_controller);
+
+            let gesture = gtk::GestureClick::new();
+            gesture.connect_released(clone!(@weak obj => move |_, _, x, y| {
+                obj.handle_click(x, y);
+            }));
+            self.text_view.add_controller(gesture);
+
+            obj.render();
+        }
+
+        fn dispose(&self) {
+            self.dispose_template();
+        }
+    }
+
+    impl WidgetImpl for HexEditor {
+        fn grab_focus(&self) -> bool {
+            self.text_view.grab_focus()
+        }
+    }
+
+    impl HexEditor {
+        fn set_overwrite(&self, overwrite: bool) {
+            if overwrite == self.overwrite.get() {
+                return;
+            }
+
+            self.overwrite.set(overwrite);
+
+            let obj = self.obj();
+            obj.update_cursor();
+            obj.notify_overwrite();
+        }
+
+        fn set_color_scheme(&self, color_scheme: Option<sourceview::StyleScheme>) {
+            if color_scheme == *self.color_scheme.borrow() {
+                return;
+            }
+
+            if let Some(color_scheme) = &color_scheme {
+                color_scheme::apply(color_scheme, &self.text_view.buffer().tag_table());
+            }
+            self.color_scheme.replace(color_scheme);
+
+            let obj = self.obj();
+            obj.notify_color_scheme();
+        }
+    }
+}
+
+glib::wrapper! {
+    pub struct HexEditor(ObjectSubclass<imp::HexEditor>)
+        @extends gtk::Widget;
+}
+
+impl HexEditor {
+    /// Replaces the edited bytes, which are then unmodified.
+    pub fn set_bytes(&self, bytes: &[u8]) {
+        let imp = self.imp();
+
+        imp.bytes.replace(bytes.to_vec());
+        imp.cursor.set(0);
+        imp.is_low_nibble.set(false);
+        self.set_modified(false);
+        self.render();
+    }
+
+    pub fn bytes(&self) -> Vec<u8> {
+        self.imp().bytes.borrow().clone()
+    }
+
+    fn set_modified(&self, modified: bool) {
+        if modified == self.imp().modified.get() {
+            return;
+        }
+
+        self.imp().modified.set(modified);
+        self.notify_modified();
+    }
+
+    fn handle_key_pressed(&self, key: gdk::Key, state: gdk::ModifierType) -> glib::Propagation {
+        let imp = self.imp();
+
+        let len = imp.bytes.borrow().len();
+        let cursor = imp.cursor.get();
+        let has_control = state.contains(gdk::ModifierType::CONTROL_MASK);
+
+        match key {
+            gdk::Key::Left => self.move_cursor(cursor.saturating_sub(1)),
+            gdk::Key::Right => self.move_cursor(cursor + 1),
+            gdk::Key::Up => self.move_cursor(cursor.saturating_sub(BYTES_PER_LINE)),
+            gdk::Key::Down => self.move_cursor(cursor + BYTES_PER_LINE),
+            gdk::Key::Page_Up => self.move_cursor(cursor.saturating_sub(16 * BYTES_PER_LINE)),
+            gdk::Key::Page_Down => self.move_cursor(cursor + 16 * BYTES_PER_LINE),
+            gdk::Key::Home if has_control => self.move_cursor(0),
+            gdk::Key::End if has_control => self.move_cursor(len),
+            gdk::Key::Home => self.move_cursor(cursor - cursor % BYTES_PER_LINE),
+            gdk::Key::End => {
+                self.move_cursor(cursor - cursor % BYTES_PER_LINE + BYTES_PER_LINE - 1)
+            }
+            gdk::Key::Tab | gdk::Key::ISO_Left_Tab => {
+                imp.is_ascii_focused.set(!imp.is_ascii_focused.get());
+                imp.is_low_nibble.set(false);
+                self.update_cursor();
+            }
+            gdk::Key::Insert => self.set_overwrite(!self.overwrite()),
+            gdk::Key::BackSpace if cursor > 0 => {
+                imp.bytes.borrow_mut().remove(cursor - 1);
+                imp.cursor.set(cursor - 1);
+                imp.is_low_nibble.set(false);
+                self.handle_edit(None);
+            }
+            gdk::Key::Delete if cursor < len => {
+                imp.bytes.borrow_mut().remove(cursor);
+                imp.is_low_nibble.set(false);
+                self.handle_edit(None);
+            }
+            _ if has_control => return glib::Propagation::Proceed,
+            _ => match key.to_unicode() {
+                Some(c) if imp.is_ascii_focused.get() && (' '..='~').contains(&c) => {
+                    self.write_byte(c as u8);
+                }
+                Some(c) if !imp.is_ascii_focused.get() && c.is_ascii_hexdigit() => {
+                    self.write_nibble(c.to_digit(16).unwrap() as u8);
+                }
+                _ => return glib::Propagation::Proceed,
+            },
+        }
+
+        glib::Propagation::Stop
+    }
+
+    /// Replaces or inserts `byte` at the cursor, then moves past it.
+    fn write_byte(&self, byte: u8) {
+        let imp = self.imp();
+
+        let cursor = imp.cursor.get();
+        let is_insert = !self.overwrite() || cursor == imp.bytes.borrow().len();
+        if is_insert {
+            imp.bytes.borrow_mut().insert(cursor, byte);
+        } else {
+            imp.bytes.borrow_mut()[cursor] = byte;
+        }
+        imp.cursor.set(cursor + 1);
+
+        self.handle_edit((!is_insert).then_some(cursor));
+    }
+
+    /// Replaces the high nibble of the byte at the cursor with `nibble`, or
+    /// inserts a byte with it, then replaces the low nibble on the next one.
+    fn write_nibble(&self, nibble: u8) {
+        let imp = self.imp();
+
+        let cursor = imp.cursor.get();
+        if imp.is_low_nibble.get() {
+            let mut bytes = imp.bytes.borrow_mut();
+            bytes[cursor] = bytes[cursor] & 0xf0 | nibble;
+            drop(bytes);
+
+            imp.cursor.set(cursor + 1);
+            imp.is_low_nibble.set(false);
+            self.handle_edit(Some(cursor));
+            return;
+        }
+
+        let is_insert = !self.overwrite() || cursor == imp.bytes.borrow().len();
+        if is_insert {
+            imp.bytes.borrow_mut().insert(cursor, nibble << 4);
+        } else {
+            let mut bytes = imp.bytes.borrow_mut();
+            bytes[cursor] = bytes[cursor] & 0x0f | nibble << 4;
+        }
+        imp.is_low_nibble.set(true);
+
+        self.handle_edit((!is_insert).then_some(cursor));
+    }
+
+    /// Re-renders after an edit, only the line of the byte at `index` if
+    /// only that byte changed.
+    fn handle_edit(&self, index: Option<usize>) {
+        match index {
+            Some(index) => self.render_line(index / BYTES_PER_LINE),
+            None => self.render(),
+        }
+        self.set_modified(true);
+    }
+
+    fn move_cursor(&self, cursor: usize) {
+        let imp = self.imp();
+
+        imp.cursor.set(cursor.min(imp.bytes.borrow().len()));
+        imp.is_low_nibble.set(false);
+        self.update_cursor();
+    }
+
+    /// Moves the cursor to the byte under `x` and `y` in either pane.
+    fn handle_click(&self, x: f64, y: f64) {
+        let imp = self.imp();
+
+        let (x, y) =
+            imp.text_view
+                .window_to_buffer_coords(gtk::TextWindowType::Widget, x as i32, y as i32);
+        let Some(iter) = imp.text_view.iter_at_location(x, y) else {
+            return;
+        };
+
+        let column = iter.line_offset();
+        let (index_in_line, is_ascii_focused) = if column >= ASCII_START {
+            ((column - ASCII_START) as usize, true)
+        } else if column >= HEX_START {
+            (((column - HEX_START) / 3) as usize, false)
+        } else {
+            (0, imp.is_ascii_focused.get())
+        };
+
+        imp.is_ascii_focused.set(is_ascii_focused);
+        self.move_cursor(
+            iter.line() as usize * BYTES_PER_LINE + index_in_line.min(BYTES_PER_LINE - 1),
+        );
+        imp.text_view.grab_focus();
+    }
+
+    fn render(&self) {
+        let imp = self.imp();
+
+        let bytes = imp.bytes.borrow();
+        let n_lines = bytes.len() / BYTES_PER_LINE + 1;
+        let text = (0..n_lines)
+            .map(|line| line_text(&bytes, line))
+            .collect::<Vec<_>>()
+            .join("\n");
+        drop(bytes);
+
+        imp.text_view.buffer().set_text(&text);
+        for line in 0..n_lines {
+            self.color_line(line);
+        }
+        self.update_cursor();
+    }
+
+    fn render_line(&self, line: usize) {
+        let imp = self.imp();
+
+        let buffer = imp.text_view.buffer();
+        let (Some(mut start), Some(mut end)) = (
+            buffer.iter_at_line(line as i32),
+            buffer.iter_at_line(line as i32),
+        ) else {
+            self.render();
+            return;
+        };
+        if !end.ends_line() {
+            end.forward_to_line_end();
+        }
+        buffer.delete(&mut start, &mut end);
+        buffer.insert(&mut start, &line_text(&imp.bytes.borrow(), line));
+
+        self.color_line(line);
+        self.update_cursor();
+    }
+
+    /// Colors the offset of `line` and its bytes in both panes by their kind.
+    fn color_line(&self, line: usize) {
+        let imp = self.imp();
+
+        let buffer = imp.text_view.buffer();
+        let apply_tag = |role: ColorRole, start: i32, end: i32| {
+            if let (Some(start), Some(end)) = (
+                buffer.iter_at_line_offset(line as i32, start),
+                buffer.iter_at_line_offset(line as i32, end),
+            ) {
+                buffer.apply_tag_by_name(role.tag_name(), &start, &end);
+            }
+        };
+        apply_tag(ColorRole::Offset, 0, HEX_START - 2);
+
+        let bytes = imp.bytes.borrow();
+        let start = (line * BYTES_PER_LINE).min(bytes.len());
+        let chunk = &bytes[start..(start + BYTES_PER_LINE).min(bytes.len())];
+        for (role, range) in color_scheme::byte_runs(chunk) {
+            let (start, end) = (range.start as i32, range.end as i32);
+            apply_tag(role, HEX_START + start * 3, HEX_START + end * 3 - 1);
+            apply_tag(role, ASCII_START + start, ASCII_START + end);
+        }
+    }
+
+    /// Highlights the byte at the cursor in both panes, underlining where
+    /// typing goes, and scrolls to it.
+    fn update_cursor(&self) {
+        let imp = self.imp();
+
+        let buffer = imp.text_view.buffer();
+        buffer.remove_tag_by_name("cursor", &buffer.start_iter(), &buffer.end_iter());
+        buffer.remove_tag_by_name("active", &buffer.start_iter(), &buffer.end_iter());
+
+        let cursor = imp.cursor.get();
+        let line = (cursor / BYTES_PER_LINE) as i32;
+        let index_in_line = (cursor % BYTES_PER_LINE) as i32;
+        let hex_column = HEX_START + index_in_line * 3;
+        let ascii_column = ASCII_START + index_in_line;
+
+        let apply_tag = |name: &str, column: i32, len: i32| {
+            if let (Some(start), Some(end)) = (
+                buffer.iter_at_line_offset(line, column),
+                buffer.iter_at_line_offset(line, column + len),
+            ) {
+                buffer.apply_tag_by_name(name, &start, &end);
+            }
+        };
+        apply_tag("cursor", hex_column, 2);
+        apply_tag("cursor", ascii_column, 1);
+        if imp.is_ascii_focused.get() {
+            apply_tag("active", ascii_column, 1);
+        } else {
+            apply_tag("active", hex_column + imp.is_low_nibble.get() as i32, 1);
+        }
+
+        if let Some(iter) = buffer.iter_at_line_offset(line, hex_column) {
+            let mark = buffer.create_mark(None, &iter, true);
+            imp.text_view.scroll_mark_onscreen(&mark);
+            buffer.delete_mark(&mark);
+        }
+
+        imp.status_label.set_label(&gettext_f(
+            "Offset {offset} of {len} · {mode}",
+            &[
+                ("offset", &format!("{:#x}", cursor)),
+                ("len", &format!("{:#x}", imp.bytes.borrow().len())),
+                (
+                    "mode",
+                    &if self.overwrite() {
+                        gettext("Overwrite")
+                    } else {
+                        gettext("Insert")
+                    },
+                ),
+            ],
+        ));
+    }
+}
+
+/// Renders all of `bytes` in lines like the editor shows them.
+pub fn hex_dump(bytes: &[u8]) -> String {
+    (0..bytes.len().div_ceil(BYTES_PER_LINE))
+        .map(|line| line_text(bytes, line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Renders `line` of `bytes` as its offset, hex bytes, and ASCII characters,
+/// with non-printable ones as dots.
+fn line_text(bytes: &[u8], line: usize) -> String {
+    let start = (line * BYTES_PER_LINE).min(bytes.len());
+    let chunk = &bytes[start..(start + BYTES_PER_LINE).min(bytes.len())];
+
+    let mut text = format!("{:08x}  ", line * BYTES_PER_LINE);
+    for index in 0..BYTES_PER_LINE {
+        if index > 0 {
+            text.push(' ');
+        }
+        match chunk.get(index) {
+            Some(byte) => write!(text, "{:02x}", byte).unwrap(),
+            None => text.push_str("  "),
+        }
+    }
+    text.push_str("  ");
+    text.extend(chunk.iter().map(|&byte| {
+        if byte.is_ascii_graphic() || byte == b' ' {
+            byte as char
+        } else {
+            '.'
+        }
+    }));
+
+    text
+}
@@ -0,0 +1,168 @@
+use gettextrs::gettext;
+use gtk::{gio, glib, prelude::*};
+use serde_json::Value;
+
+use std::{cell::OnceCell, collections::HashMap, path::Path};
+
+use crate::{
+    config::APP_ID,
+    query::{ParseError, Query},
+};
+
+/// Highlight rules of each env as `(query, color id, badge)`, keyed by env path
+type EnvHighlightRules = HashMap<String, Vec<(String, String, String)>>;
+
+/// Colors that rows matching a highlight rule can be shown in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum HighlightColor {
+    #[default]
+    Yellow,
+    Red,
+    Green,
+    Blue,
+    Purple,
+}
+
+impl HighlightColor {
+    pub const ALL: [Self; 5] = [
+        Self::Yellow,
+        Self::Red,
+        Self::Green,
+        Self::Blue,
+        Self::Purple,
+    ];
+
+    /// Stable identifier used to persist the color.
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::Yellow => "yellow",
+            Self::Red => "red",
+            Self::Green => "green",
+            Self::Blue => "blue",
+            Self::Purple => "purple",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|color| color.id() == id)
+    }
+
+    pub fn label(self) -> String {
+        match self {
+            Self::Yellow => gettext("Yellow"),
+            Self::Red => gettext("Red"),
+            Self::Green => gettext("Green"),
+            Self::Blue => gettext("Blue"),
+            Self::Purple => gettext("Purple"),
+        }
+    }
+
+    pub fn css_class(self) -> &'static str {
+        match self {
+            Self::Yellow => "highlight-yellow",
+            Self::Red => "highlight-red",
+            Self::Green => "highlight-green",
+            Self::Blue => "highlight-blue",
+            Self::Purple => "highlight-purple",
+        }
+    }
+}
+
+/// Rows matching a query, shown in a color and with an optional badge.
+#[derive(Debug, Clone)]
+pub struct HighlightRule {
+    /// Text of the query, as entered
+    pub query_text: String,
+    pub query: Query,
+    pub color: HighlightColor,
+    /// Short text shown next to the key, which may be empty
+    pub badge: String,
+}
+
+impl HighlightRule {
+    pub fn new(query_text: &str, color: HighlightColor, badge: &str) -> Result<Self, ParseError> {
+        Ok(Self {
+            query_text: query_text.to_string(),
+            query: Query::parse(query_text)?,
+            color,
+            badge: badge.to_string(),
+        })
+    }
+
+    /// Loads the highlight rules of the env at `env_path` from the settings,
+    /// skipping those that are no longer valid.
+    pub fn load_all(env_path: &Path) -> Vec<Self> {
+        let settings = gio::Settings::new(APP_ID);
+
+        settings
+            .value("highlight-rules")
+            .get::<EnvHighlightRules>()
+            .unwrap()
+            .remove(env_path.to_string_lossy().as_ref())
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|(query_text, color_id, badge)| {
+                let color = HighlightColor::from_id(&color_id).unwrap_or_default();
+                Self::new(&query_text, color, &badge)
+                    .inspect_err(|err| {
+                        tracing::warn!("Skipping invalid highlight rule: {}", err);
+                    })
+                    .ok()
+            })
+            .collect()
+    }
+
+    /// Saves `rules` as the highlight rules of the env at `env_path`.
+    pub fn save_all(env_path: &Path, rules: &[Self]) {
+        let settings = gio::Settings::new(APP_ID);
+
+        let mut env_rules = settings
+            .value("highlight-rules")
+            .get::<EnvHighlightRules>()
+            .unwrap();
+        let env_path = env_path.to_string_lossy().to_string();
+
+        if rules.is_empty() {
+            env_rules.remove(&env_path);
+        } else {
+            let rules = rules
+                .iter()
+                .map(|rule| {
+                    (
+                        rule.query_text.clone(),
+                        rule.color.id().to_string(),
+                        rule.badge.clone(),
+                    )
+                })
+                .collect();
+            env_rules.insert(env_path, rules);
+        }
+
+        if let Err(err) = settings.set_value("highlight-rules", &env_rules.to_variant()) {
+            tracing::warn!("Failed to save highlight rules: {:?}", &err);
+        }
+    }
+}
+
+/// The highlight rules of an env, in order of precedence.
+#[derive(Debug, Default, Clone, glib::Boxed)]
+#[boxed_type(name = "LvHighlightRules")]
+pub struct HighlightRules(pub Vec<HighlightRule>);
+
+impl HighlightRules {
+    /// Returns the first rule that the entry with `key` and `value` matches,
+    /// where `decode` returns the decoded value.
+    pub fn find(
+        &self,
+        key: &[u8],
+        value: &[u8],
+        decode: impl Fn() -> Option<Value>,
+    ) -> Option<&HighlightRule> {
+        // Shared so the value is decoded at most once for all rules
+        let decoded = OnceCell::new();
+        self.0.iter().find(|rule| {
+            rule.query
+                .matches(key, value, || decoded.get_or_init(&decode).clone())
+        })
+    }
+}
@@ -0,0 +1,626 @@
+use anyhow::{anyhow, Context, Result};
+use heed::types::ByteSlice;
+use serde_json::Value;
+
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Read},
+    path::Path,
+    sync::{
+        atomic::{self, AtomicUsize},
+        Arc,
+    },
+};
+
+use crate::edit_history::Edit;
+
+/// Number of entries written per write txn
+const BATCH_LEN: usize = 10_000;
+
+/// Key and value of an entry read from a file
+pub type Entry = (Vec<u8>, Vec<u8>);
+
+/// File format entries can be imported from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    /// Rows of comma-separated fields, quoted as RFC 4180 does
+    Csv,
+    /// One JSON object per line
+    JsonLines,
+    /// Array of JSON objects, as exported
+    Json,
+}
+
+impl ImportFormat {
+    /// Guesses the format from the extension of `path`, defaulting to CSV.
+    pub fn for_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("jsonl" | "ndjson") => Self::JsonLines,
+            Some("json") => Self::Json,
+            _ => Self::Csv,
+        }
+    }
+}
+
+/// How the text of a field is turned into the bytes of a key or value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldEncoding {
+    /// UTF-8 bytes of the text as is
+    Text,
+    Hex,
+    Base64,
+}
+
+impl FieldEncoding {
+    pub fn decode(self, text: &str) -> Result<Vec<u8>> {
+        match self {
+            Self::Text => Ok(text.as_bytes().to_vec()),
+            Self::Hex => decode_hex(text),
+            Self::Base64 => decode_base64(text),
+        }
+    }
+}
+
+/// Where the keys and values are in the rows of a file, and how to decode
+/// them.
+#[derive(Debug, Clone)]
+pub struct ImportOptions {
+    pub format: ImportFormat,
+    /// Whether the first row of a CSV file names its columns
+    pub has_header: bool,
+    /// Name of the key field of JSON objects or CSV columns, or the number of
+    /// a CSV column, starting from 1
+    pub key_column: String,
+    pub value_column: String,
+    pub key_encoding: FieldEncoding,
+    pub value_encoding: FieldEncoding,
+}
+
+/// Reads entries from a CSV or JSON Lines file one row at a time, or from
+/// a JSON array, which is read whole when the file is opened.
+pub struct EntryReader {
+    reader: BufReader<CountingReader<File>>,
+    options: ImportOptions,
+    /// Indices of the key and value columns of CSV rows
+    column_indices: Option<(usize, usize)>,
+    /// Elements of a JSON array that are yet to be read
+    json_rows: Option<std::vec::IntoIter<Value>>,
+    n_rows: usize,
+}
+
+impl EntryReader {
+    /// Opens the file at `path`, reading the header of CSV files if there is
+    /// one. The bytes read so far are counted in `n_read`.
+    pub fn open(path: &Path, options: ImportOptions, n_read: Arc<AtomicUsize>) -> Result<Self> {
+        let file =
+            File::open(path).with_context(|| format!("Failed to open `{}`", path.display()))?;
+        let mut this = Self {
+            reader: BufReader::new(CountingReader {
+                inner: file,
+                n_read,
+            }),
+            options,
+            column_indices: None,
+            json_rows: None,
+            n_rows: 0,
+        };
+
+        if this.options.format == ImportFormat::Json {
+            let rows = serde_json::from_reader::<_, Vec<Value>>(&mut this.reader)
+                .context("File is not a JSON array")?;
+            this.json_rows = Some(rows.into_iter());
+        }
+
+        if this.options.format == ImportFormat::Csv {
+            let header = if this.options.has_header {
+                this.read_csv_record()?.unwrap_or_default()
+            } else {
+                Vec::new()
+            };
+            let column_index = |column: &str| {
+                header
+                    .iter()
+                    .position(|name| name == column)
+                    .or_else(|| {
+                        column
+                            .parse::<usize>()
+                            .ok()
+                            .filter(|&number| number > 0)
+                            .map(|number| number - 1)
+                    })
+                    .with_context(|| format!("No column `{}`", column))
+            };
+            this.column_indices = Some((
+                column_index(&this.options.key_column)?,
+                column_index(&this.options.value_column)?,
+            ));
+        }
+
+        Ok(this)
+    }
+
+    /// Returns the key and value of the next row, or `None` at the end of
+    /// the file. Errors name the row they are on, after which reading can
+    /// go on.
+    pub fn next_entry(&mut self) -> Result<Option<Entry>> {
+        let (key, value) = match self.options.format {
+            ImportFormat::Csv => {
+                let Some(record) = self.read_csv_record()? else {
+                    return Ok(None);
+                };
+                self.n_rows += 1;
+                let (key_index, value_index) = self.column_indices.unwrap();
+                let field = |index: usize| {
+                    record
+                        .get(index)
+                        .cloned()
+                        .with_context(|| format!("Row {} has no column {}", self.n_rows, index + 1))
+                };
+                (field(key_index)?, field(value_index)?)
+            }
+            ImportFormat::JsonLines => {
+                let line = loop {
+                    let mut line = String::new();
+                    if self.reader.read_line(&mut line)? == 0 {
+                        return Ok(None);
+                    }
+                    self.n_rows += 1;
+                    if !line.trim().is_empty() {
+                        break line;
+                    }
+                };
+                let object = serde_json::from_str::<Value>(&line)
+                    .with_context(|| format!("Row {} is not valid JSON", self.n_rows))?;
+                self.json_fields(&object)?
+            }
+            ImportFormat::Json => {
+                let Some(object) = self.json_rows.as_mut().unwrap().next() else {
+                    return Ok(None);
+                };
+                self.n_rows += 1;
+                self.json_fields(&object)?
+            }
+        };
+
+        let key = self
+            .options
+            .key_encoding
+            .decode(&key)
+            .with_context(|| format!("Invalid key on row {}", self.n_rows))?;
+        let value = self
+            .options
+            .value_encoding
+            .decode(&value)
+            .with_context(|| format!("Invalid value on row {}", self.n_rows))?;
+        anyhow::ensure!(!key.is_empty(), "Empty key on row {}", self.n_rows);
+
+        Ok(Some((key, value)))
+    }
+
+    /// Returns the key and value fields of the JSON object of the current row,
+    /// with values that aren't strings as JSON.
+    fn json_fields(&self, object: &Value) -> Result<(String, String)> {
+        let field = |name: &str| match object.get(name) {
+            Some(Value::String(text)) => Ok(text.clone()),
+            Some(value) => Ok(value.to_string()),
+            None => Err(anyhow!("Row {} has no field `{}`", self.n_rows, name)),
+        };
+        Ok((
+            field(&self.options.key_column)?,
+            field(&self.options.value_column)?,
+        ))
+    }
+
+    /// Reads the fields of the next CSV record, which may span lines if a
+    /// quoted field has line breaks.
+    fn read_csv_record(&mut self) -> Result<Option<Vec<String>>> {
+        let mut fields = Vec::new();
+        let mut field = String::new();
+        let mut in_quotes = false;
+        let mut has_record = false;
+
+        loop {
+            let mut line = String::new();
+            if self.reader.read_line(&mut line)? == 0 {
+                anyhow::ensure!(
+                    !in_quotes,
+                    "Unterminated quoted field at the end of the file"
+                );
+                break;
+            }
+            if !has_record && !in_quotes && line.trim_end_matches(['\r', '\n']).is_empty() {
+                continue;
+            }
+            has_record = true;
+
+            let mut chars = line.chars().peekable();
+            while let Some(c) = chars.next() {
+                match c {
+                    '"' if in_quotes && chars.peek() == Some(&'"') => {
+                        chars.next();
+                        field.push('"');
+                    }
+                    '"' => in_quotes = !in_quotes,
+                    ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+                    '\r' | '\n' if !in_quotes => {}
+                    _ => field.push(c),
+                }
+            }
+
+            if !in_quotes {
+                break;
+            }
+        }
+
+        if !has_record {
+            return Ok(None);
+        }
+        fields.push(field);
+        Ok(Some(fields))
+    }
+}
+
+/// Reads the first `n_rows` entries of the file at `path` without writing
+/// anything, so the options can be checked before importing.
+pub fn preview(path: &Path, options: ImportOptions, n_rows: usize) -> Result<Vec<Result<Entry>>> {
+    let mut reader = EntryReader::open(path, options, Arc::default())?;
+    let mut entries = Vec::new();
+    while entries.len() < n_rows {
+        match reader.next_entry() {
+            Ok(Some(entry)) => entries.push(Ok(entry)),
+            Ok(None) => break,
+            Err(err) => entries.push(Err(err)),
+        }
+    }
+    Ok(entries)
+}
+
+/// An import that stopped, either at the end of the file or at the first
+/// invalid row.
+pub struct Import {
+    /// The writes of the committed batches, which can be undone
+    pub edit: Edit,
+    pub n_imported: usize,
+    /// Why the import stopped early, in which case the batch with the invalid
+    /// row isn't written
+    pub error: Option<anyhow::Error>,
+}
+
+/// Writes the entries of the file at `path` to the database called
+/// `db_name`, committing a write txn every [`BATCH_LEN`] entries.
+///
+/// The bytes of the file read so far are counted in `n_read`, so progress
+/// can be shown.
+pub fn import(
+    env: &heed::Env,
+    db_name: Option<&str>,
+    path: &Path,
+    options: ImportOptions,
+    n_read: Arc<AtomicUsize>,
+) -> Result<Import> {
+    let db = {
+        let rtxn = env.read_txn()?;
+        env.open_database::<ByteSlice, ByteSlice>(&rtxn, db_name)?
+            .with_context(|| format!("Database `{:?}` not found", db_name))?
+    };
+
+    let mut reader = EntryReader::open(path, options, n_read)?;
+    let mut import = Import {
+        edit: Edit::default(),
+        n_imported: 0,
+        error: None,
+    };
+
+    loop {
+        let mut wtxn = env.write_txn().context("Failed to create write txn")?;
+        let mut batch = Edit::default();
+        let mut n_batch_imported = 0;
+        let mut is_done = false;
+        while n_batch_imported < BATCH_LEN {
+            match reader.next_entry() {
+                Ok(Some((key, value))) => {
+                    batch.write(&mut wtxn, db, db_name, &key, Some(&value))?;
+                    n_batch_imported += 1;
+                }
+                Ok(None) => {
+                    is_done = true;
+                    break;
+                }
+                Err(err) => {
+                    import.error = Some(err);
+                    return Ok(import);
+                }
+            }
+        }
+        wtxn.commit().context("Failed to commit write txn")?;
+        import.edit.extend(batch);
+        import.n_imported += n_batch_imported;
+
+        if is_done {
+            return Ok(import);
+        }
+    }
+}
+
+/// Counts the bytes read through it.
+struct CountingReader<R> {
+    inner: R,
+    n_read: Arc<AtomicUsize>,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n_read = self.inner.read(buf)?;
+        self.n_read.fetch_add(n_read, atomic::Ordering::Relaxed);
+        Ok(n_read)
+    }
+}
+
+fn decode_hex(text: &str) -> Result<Vec<u8>> {
+    let digits = text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| {
+            c.to_digit(16)
+                .with_context(|| format!("`{}` is not a hex digit", c))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    anyhow::ensure!(digits.len() % 2 == 0, "Odd number of hex digits");
+    Ok(digits
+        .chunks(2)
+        .map(|pair| (pair[0] * 16 + pair[1]) as u8)
+        .collect())
+}
+
+/// Decodes standard or URL-safe base64, with or without padding.
+fn decode_base64(text: &str) -> Result<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(text.len() / 4 * 3);
+    let mut buffer = 0u32;
+    let mut n_bits = 0;
+    for c in text.chars().filter(|c| !c.is_whitespace() && *c != '=') {
+        let sextet = match c {
+            'A'..='Z' => c as u32 - 'A' as u32,
+            'a'..='z' => c as u32 - 'a' as u32 + 26,
+            '0'..='9' => c as u32 - '0' as u32 + 52,
+            '+' | '-' => 62,
+            '/' | '_' => 63,
+            _ => anyhow::bail!("`{}` is not a base64 character", c),
+        };
+        buffer = (buffer << 6) | sextet;
+        n_bits += 6;
+        if n_bits >= 8 {
+            n_bits -= 8;
+            bytes.push((buffer >> n_bits) as u8);
+            buffer &= (1 << n_bits) - 1;
+        }
+    }
+    anyhow::ensure!(n_bits < 6, "Truncated base64");
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::utils;
+
+    fn default_options(format: ImportFormat) -> ImportOptions {
+        ImportOptions {
+            format,
+            has_header: true,
+            key_column: "key".into(),
+            value_column: "value".into(),
+            key_encoding: FieldEncoding::Text,
+            value_encoding: FieldEncoding::Text,
+        }
+    }
+
+    /// Reads the entries of a file with `contents`, with the errors of the
+    /// invalid rows formatted with their context.
+    fn read(contents: &str, options: ImportOptions) -> Result<Vec<Result<Entry, String>>> {
+        let path = utils::write_to_temp_dir(contents.as_bytes(), "entries")?.join("entries");
+        let entries = preview(&path, options, 100)?;
+        Ok(entries
+            .into_iter()
+            .map(|entry| entry.map_err(|err| format!("{:#}", err)))
+            .collect())
+    }
+
+    fn entry(key: &str, value: &str) -> Result<Entry, String> {
+        Ok((key.into(), value.into()))
+    }
+
+    #[test]
+    fn guesses_format_from_extension() {
+        assert_eq!(
+            ImportFormat::for_path(Path::new("a.json")),
+            ImportFormat::Json
+        );
+        assert_eq!(
+            ImportFormat::for_path(Path::new("a.jsonl")),
+            ImportFormat::JsonLines
+        );
+        assert_eq!(
+            ImportFormat::for_path(Path::new("a.ndjson")),
+            ImportFormat::JsonLines
+        );
+        assert_eq!(
+            ImportFormat::for_path(Path::new("a.csv")),
+            ImportFormat::Csv
+        );
+        assert_eq!(ImportFormat::for_path(Path::new("a")), ImportFormat::Csv);
+    }
+
+    #[test]
+    fn decodes_hex_fields() {
+        assert_eq!(
+            FieldEncoding::Hex.decode("de ad\tBE EF").unwrap(),
+            [0xde, 0xad, 0xbe, 0xef]
+        );
+        assert!(FieldEncoding::Hex.decode("").unwrap().is_empty());
+        assert_eq!(
+            FieldEncoding::Hex.decode("0g").unwrap_err().to_string(),
+            "`g` is not a hex digit"
+        );
+        assert_eq!(
+            FieldEncoding::Hex.decode("abc").unwrap_err().to_string(),
+            "Odd number of hex digits"
+        );
+    }
+
+    #[test]
+    fn decodes_base64_fields() {
+        assert_eq!(FieldEncoding::Base64.decode("aGVsbG8=").unwrap(), b"hello");
+        assert_eq!(FieldEncoding::Base64.decode("aGVsbG8").unwrap(), b"hello");
+        assert_eq!(FieldEncoding::Base64.decode("+/8=").unwrap(), [0xfb, 0xff]);
+        assert_eq!(FieldEncoding::Base64.decode("-_8").unwrap(), [0xfb, 0xff]);
+        assert_eq!(
+            FieldEncoding::Base64.decode("aG*").unwrap_err().to_string(),
+            "`*` is not a base64 character"
+        );
+        assert_eq!(
+            FieldEncoding::Base64
+                .decode("aGVsb")
+                .unwrap_err()
+                .to_string(),
+            "Truncated base64"
+        );
+    }
+
+    #[test]
+    fn reads_csv() {
+        let contents =
+            "\r\nkey,value\r\na,1\r\n\"b,c\",\"say \"\"hi\"\"\"\r\n\"multi\nline\",2\r\n";
+        assert_eq!(
+            read(contents, default_options(ImportFormat::Csv)).unwrap(),
+            [
+                entry("a", "1"),
+                entry("b,c", "say \"hi\""),
+                entry("multi\nline", "2"),
+            ]
+        );
+
+        let options = ImportOptions {
+            has_header: false,
+            key_column: "2".into(),
+            value_column: "1".into(),
+            ..default_options(ImportFormat::Csv)
+        };
+        assert_eq!(
+            read("1,a\n2,b\n", options).unwrap(),
+            [entry("a", "1"), entry("b", "2")]
+        );
+    }
+
+    #[test]
+    fn reports_invalid_csv() {
+        let options = ImportOptions {
+            key_column: "id".into(),
+            ..default_options(ImportFormat::Csv)
+        };
+        assert_eq!(
+            read("key,value\n", options).unwrap_err().to_string(),
+            "No column `id`"
+        );
+
+        let options = ImportOptions {
+            has_header: false,
+            key_column: "0".into(),
+            ..default_options(ImportFormat::Csv)
+        };
+        assert_eq!(
+            read("a,1\n", options).unwrap_err().to_string(),
+            "No column `0`"
+        );
+
+        assert_eq!(
+            read(
+                "key,value\na\n,1\nb,2\n",
+                default_options(ImportFormat::Csv)
+            )
+            .unwrap(),
+            [
+                Err("Row 1 has no column 2".into()),
+                Err("Empty key on row 2".into()),
+                entry("b", "2"),
+            ]
+        );
+        assert_eq!(
+            read("key,value\n\"a,1\n", default_options(ImportFormat::Csv)).unwrap(),
+            [Err(
+                "Unterminated quoted field at the end of the file".into()
+            )]
+        );
+    }
+
+    #[test]
+    fn reads_json_lines() {
+        let contents = concat!(
+            "{\"key\": \"a\", \"value\": {\"n\": 1}}\n",
+            "\n",
+            "{\"key\": \"b\"\n",
+            "{\"key\": \"c\"}\n",
+            "{\"key\": \"d\", \"value\": null}\n",
+        );
+        assert_eq!(
+            read(contents, default_options(ImportFormat::JsonLines)).unwrap(),
+            [
+                entry("a", "{\"n\":1}"),
+                Err(
+                    "Row 3 is not valid JSON: EOF while parsing an object at line 2 column 0"
+                        .into()
+                ),
+                Err("Row 4 has no field `value`".into()),
+                entry("d", "null"),
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_json_arrays() {
+        let contents = r#"[{"key": "a", "value": "1"}, {"key": "b", "value": 2}, 3]"#;
+        assert_eq!(
+            read(contents, default_options(ImportFormat::Json)).unwrap(),
+            [
+                entry("a", "1"),
+                entry("b", "2"),
+                Err("Row 3 has no field `key`".into()),
+            ]
+        );
+
+        let error = read(r#"{"key": "a"}"#, default_options(ImportFormat::Json)).unwrap_err();
+        assert_eq!(error.to_string(), "File is not a JSON array");
+        assert!(read("[", default_options(ImportFormat::Json)).is_err());
+    }
+
+    #[test]
+    fn reports_invalid_encoded_fields() {
+        let options = ImportOptions {
+            key_encoding: FieldEncoding::Hex,
+            value_encoding: FieldEncoding::Base64,
+            ..default_options(ImportFormat::JsonLines)
+        };
+        let contents = concat!(
+            "{\"key\": \"6b\", \"value\": \"dg==\"}\n",
+            "{\"key\": \"zz\", \"value\": \"\"}\n",
+            "{\"key\": \"6b\", \"value\": \"d\"}\n",
+        );
+        assert_eq!(
+            read(contents, options).unwrap(),
+            [
+                entry("k", "v"),
+                Err("Invalid key on row 2: `z` is not a hex digit".into()),
+                Err("Invalid value on row 3: Truncated base64".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn previews_first_rows() {
+        let path = utils::write_to_temp_dir(b"key,value\na,1\nb,2\n", "entries")
+            .unwrap()
+            .join("entries");
+        let entries = preview(&path, default_options(ImportFormat::Csv), 1).unwrap();
+        assert_eq!(entries.len(), 1);
+    }
+}
@@ -0,0 +1,34 @@
+use gtk::glib;
+use heed::DatabaseFlags;
+
+/// Order in which keys are listed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, glib::Enum)]
+#[enum_type(name = "LvKeyCollation")]
+pub enum KeyCollation {
+    /// The database's own order, which follows `MDB_REVERSEKEY` and
+    /// `MDB_INTEGERKEY`
+    #[default]
+    #[enum_value(name = "Database Order", nick = "auto")]
+    Auto,
+    #[enum_value(name = "Bytewise", nick = "bytewise")]
+    Bytewise,
+    /// Bytewise, starting from the last byte, like `MDB_REVERSEKEY`
+    #[enum_value(name = "Reverse Bytewise", nick = "reverse")]
+    Reverse,
+    /// Native-endian integers, like `MDB_INTEGERKEY`
+    #[enum_value(name = "Numeric", nick = "numeric")]
+    Numeric,
+}
+
+impl KeyCollation {
+    /// Returns the flags of a database whose keys LMDB sorts in this order,
+    /// given the flags of the actual database.
+    pub fn flags(self, db_flags: DatabaseFlags) -> DatabaseFlags {
+        match self {
+            Self::Auto => db_flags & (DatabaseFlags::INTEGER_KEY | DatabaseFlags::REVERSE_KEY),
+            Self::Bytewise => DatabaseFlags::empty(),
+            Self::Reverse => DatabaseFlags::REVERSE_KEY,
+            Self::Numeric => DatabaseFlags::INTEGER_KEY,
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use gtk::glib;
+
+/// How keys are rendered in the key column.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, glib::Enum)]
+#[enum_type(name = "LvKeyDisplayMode")]
+pub enum KeyDisplayMode {
+    /// Native-endian integers for `MDB_INTEGERKEY` databases, text otherwise
+    #[default]
+    #[enum_value(name = "Automatic", nick = "auto")]
+    Auto,
+    #[enum_value(name = "Text", nick = "text")]
+    Text,
+    #[enum_value(name = "Little Endian Integer", nick = "little-endian")]
+    LittleEndian,
+    #[enum_value(name = "Big Endian Integer", nick = "big-endian")]
+    BigEndian,
+}
+
+impl KeyDisplayMode {
+    /// Decodes `key` as an integer, or returns `None` if it should be shown as
+    /// text instead.
+    pub fn integer(self, key: &[u8], is_integer_key: bool) -> Option<u64> {
+        match self {
+            Self::Auto if is_integer_key => decode_integer(key, cfg!(target_endian = "big")),
+            Self::Auto | Self::Text => None,
+            Self::LittleEndian => decode_integer(key, false),
+            Self::BigEndian => decode_integer(key, true),
+        }
+    }
+}
+
+/// Decodes 4 or 8 bytes as an unsigned integer.
+fn decode_integer(bytes: &[u8], big_endian: bool) -> Option<u64> {
+    match (bytes.len(), big_endian) {
+        (4, false) => Some(u32::from_le_bytes(bytes.try_into().unwrap()) as u64),
+        (4, true) => Some(u32::from_be_bytes(bytes.try_into().unwrap()) as u64),
+        (8, false) => Some(u64::from_le_bytes(bytes.try_into().unwrap())),
+        (8, true) => Some(u64::from_be_bytes(bytes.try_into().unwrap())),
+        _ => None,
+    }
+}
@@ -0,0 +1,119 @@
+use anyhow::Result;
+use gtk::glib;
+use heed::types::ByteSlice;
+use indexmap::IndexMap;
+
+use std::collections::VecDeque;
+
+use crate::snapshot::Snapshot;
+
+/// Number of values kept per key
+const MAX_VERSIONS: usize = 20;
+/// Number of keys whose values are kept, dropping the least recently
+/// inspected first
+const MAX_KEYS: usize = 100;
+
+/// A value a key was seen with.
+#[derive(Debug, Clone)]
+pub struct KeyVersion {
+    pub value: glib::Bytes,
+    /// Id of the txn the value was read at
+    pub txn_id: u64,
+    pub seen_at: glib::DateTime,
+}
+
+/// Values seen for inspected keys during the session, so their changes can
+/// be looked back at.
+#[derive(Debug, Default)]
+pub struct KeyHistory {
+    /// Versions by database name and key, oldest first, ordered from the
+    /// least to the most recently inspected key
+    versions: IndexMap<(Option<String>, Vec<u8>), VecDeque<KeyVersion>>,
+}
+
+impl KeyHistory {
+    /// Starts keeping the values of `key` of the database called `db_name`,
+    /// or makes it the most recently inspected one, recording `value`.
+    pub fn inspect(&mut self, db_name: Option<&str>, key: &[u8], value: &glib::Bytes, txn_id: u64) {
+        let id = (db_name.map(str::to_owned), key.to_vec());
+        let versions = self.versions.shift_remove(&id).unwrap_or_default();
+        self.versions.insert(id, versions);
+        if self.versions.len() > MAX_KEYS {
+            self.versions.shift_remove_index(0);
+        }
+
+        self.record(db_name, key, value, txn_id);
+    }
+
+    /// Records `value` of `key`, read at the txn `txn_id`, if the key is kept
+    /// and the value changed since the last one recorded.
+    pub fn record(&mut self, db_name: Option<&str>, key: &[u8], value: &glib::Bytes, txn_id: u64) {
+        let Some(versions) = self
+            .versions
+            .get_mut(&(db_name.map(str::to_owned), key.to_vec()))
+        else {
+            return;
+        };
+
+        if let Some(latest) = versions.back() {
+            // Older snapshots can be shown again, but aren't newer versions
+            if txn_id <= latest.txn_id || *value == latest.value {
+                return;
+            }
+        }
+
+        versions.push_back(KeyVersion {
+            value: value.clone(),
+            txn_id,
+            seen_at: glib::DateTime::now_local().unwrap(),
+        });
+        if versions.len() > MAX_VERSIONS {
+            versions.pop_front();
+        }
+    }
+
+    /// Records the values of the kept keys of the database called `db_name`
+    /// as they are in `snapshot`.
+    pub fn record_snapshot(&mut self, snapshot: &Snapshot, db_name: Option<&str>) -> Result<()> {
+        let keys = self
+            .versions
+            .keys()
+            .filter(|(name, _)| name.as_deref() == db_name)
+            .map(|(_, key)| key.clone())
+            .collect::<Vec<_>>();
+        if keys.is_empty() {
+            return Ok(());
+        }
+
+        let values = snapshot.read(|env, rtxn| {
+            // A deleted database has no values to record
+            let Some(db) = env.open_database::<ByteSlice, ByteSlice>(rtxn, db_name)? else {
+                return Ok(Vec::new());
+            };
+            let mut values = Vec::new();
+            for key in keys {
+                if let Some(value) = db.get(rtxn, &key)? {
+                    values.push((key, glib::Bytes::from(value)));
+                }
+            }
+            Ok(values)
+        })?;
+        for (key, value) in values {
+            self.record(db_name, &key, &value, snapshot.txn_id());
+        }
+
+        Ok(())
+    }
+
+    /// Returns the values seen for `key`, oldest first.
+    pub fn versions(&self, db_name: Option<&str>, key: &[u8]) -> Vec<KeyVersion> {
+        self.versions
+            .get(&(db_name.map(str::to_owned), key.to_vec()))
+            .map(|versions| versions.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn clear(&mut self) {
+        self.versions.clear();
+    }
+}
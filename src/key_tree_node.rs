@@ -0,0 +1,160 @@
+use gtk::{gio, glib, prelude::*, subclass::prelude::*};
+
+use std::rc::Rc;
+
+use crate::{non_printable_display::NonPrintableDisplay, text_encoding::TextEncoding};
+
+/// Keys grouped into a tree by a separator.
+#[derive(Debug)]
+pub struct KeyTree {
+    /// All keys, sorted bytewise so keys with the same prefix are adjacent
+    pub keys: Vec<glib::Bytes>,
+    pub separator: Vec<u8>,
+    pub non_printable_display: NonPrintableDisplay,
+    pub encoding: TextEncoding,
+}
+
+impl KeyTree {
+    fn render(&self, segment: &[u8]) -> String {
+        self.non_printable_display.render(segment, self.encoding)
+    }
+}
+
+mod imp {
+    use std::cell::{Cell, OnceCell, RefCell};
+
+    use super::*;
+
+    #[derive(Debug, Default, glib::Properties)]
+    #[properties(wrapper_type = super::KeyTreeNode)]
+    pub struct KeyTreeNode {
+        /// The segment of the key after its parent's prefix
+        #[property(get, set, construct_only)]
+        pub(super) label: RefCell<String>,
+        /// Number of keys under this node, which is 1 for a key
+        #[property(get, set, construct_only)]
+        pub(super) n_keys: Cell<u32>,
+        /// Whether this is a prefix rather than a key
+        #[property(get, set, construct_only)]
+        pub(super) is_prefix: Cell<bool>,
+
+        pub(super) tree: OnceCell<Rc<KeyTree>>,
+        /// Range of the keys under this node in the tree's keys
+        pub(super) range: Cell<(usize, usize)>,
+        /// Length of the prefix shared by the keys under this node
+        pub(super) prefix_len: Cell<usize>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for KeyTreeNode {
+        const NAME: &'static str = "LvKeyTreeNode";
+        type Type = super::KeyTreeNode;
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for KeyTreeNode {}
+}
+
+glib::wrapper! {
+     pub struct KeyTreeNode(ObjectSubclass<imp::KeyTreeNode>);
+}
+
+impl KeyTreeNode {
+    fn new(
+        tree: &Rc<KeyTree>,
+        label: &str,
+        is_prefix: bool,
+        range: (usize, usize),
+        prefix_len: usize,
+    ) -> Self {
+        let this = glib::Object::builder::<Self>()
+            .property("label", label)
+            .property("n-keys", (range.1 - range.0) as u32)
+            .property("is-prefix", is_prefix)
+            .build();
+
+        let imp = this.imp();
+        imp.tree.set(tree.clone()).unwrap();
+        imp.range.set(range);
+        imp.prefix_len.set(prefix_len);
+
+        this
+    }
+
+    /// Returns the top-level nodes of `tree`.
+    pub fn roots(tree: KeyTree) -> gio::ListStore {
+        let n_keys = tree.keys.len();
+        children(&Rc::new(tree), (0, n_keys), 0)
+    }
+
+    /// Returns the key of this node, or `None` if it is a prefix.
+    pub fn key(&self) -> Option<glib::Bytes> {
+        if self.is_prefix() {
+            return None;
+        }
+
+        let imp = self.imp();
+        let (start, _) = imp.range.get();
+        Some(imp.tree.get().unwrap().keys[start].clone())
+    }
+
+    /// Returns the child nodes of this prefix, or `None` if it is a key.
+    pub fn children(&self) -> Option<gio::ListStore> {
+        if !self.is_prefix() {
+            return None;
+        }
+
+        let imp = self.imp();
+        Some(children(
+            imp.tree.get().unwrap(),
+            imp.range.get(),
+            imp.prefix_len.get(),
+        ))
+    }
+}
+
+/// Groups the keys in `range`, which share a prefix of `prefix_len` bytes, by
+/// their next segment.
+fn children(tree: &Rc<KeyTree>, range: (usize, usize), prefix_len: usize) -> gio::ListStore {
+    let store = gio::ListStore::new::<KeyTreeNode>();
+
+    let (mut start, end) = range;
+    let mut nodes = Vec::new();
+    while start < end {
+        let rest = &tree.keys[start][prefix_len..];
+        let separator_position = (!tree.separator.is_empty())
+            .then(|| {
+                rest.windows(tree.separator.len())
+                    .position(|window| window == tree.separator)
+            })
+            .flatten();
+
+        let node = match separator_position {
+            Some(position) => {
+                // Everything starting with this segment and the separator
+                let child_prefix_len = prefix_len + position + tree.separator.len();
+                let child_prefix = &tree.keys[start][..child_prefix_len];
+                let child_end = start
+                    + tree.keys[start..end].partition_point(|key| key.starts_with(child_prefix));
+                let node = KeyTreeNode::new(
+                    tree,
+                    &tree.render(&rest[..position]),
+                    true,
+                    (start, child_end),
+                    child_prefix_len,
+                );
+                start = child_end;
+                node
+            }
+            None => {
+                let node = KeyTreeNode::new(tree, &tree.render(rest), false, (start, start + 1), 0);
+                start += 1;
+                node
+            }
+        };
+        nodes.push(node);
+    }
+
+    store.extend_from_slice(&nodes);
+    store
+}
@@ -0,0 +1,135 @@
+use gtk::{
+    gio,
+    glib::{self, clone},
+    prelude::*,
+    subclass::prelude::*,
+};
+
+use crate::key_tree_node::{KeyTree, KeyTreeNode};
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, gtk::CompositeTemplate)]
+    #[template(resource = "/io/github/seadve/LmdbViewer/ui/key_tree_pane.ui")]
+    pub struct KeyTreePane {
+        #[template_child]
+        pub(super) list_view: TemplateChild<gtk::ListView>,
+        #[template_child]
+        pub(super) selection_model: TemplateChild<gtk::NoSelection>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for KeyTreePane {
+        const NAME: &'static str = "LvKeyTreePane";
+        type Type = super::KeyTreePane;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for KeyTreePane {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            self.obj().setup_view();
+        }
+
+        fn dispose(&self) {
+            self.dispose_template();
+        }
+    }
+
+    impl WidgetImpl for KeyTreePane {}
+}
+
+glib::wrapper! {
+    pub struct KeyTreePane(ObjectSubclass<imp::KeyTreePane>)
+        @extends gtk::Widget;
+}
+
+impl KeyTreePane {
+    /// Shows `tree`, with all prefixes collapsed.
+    pub fn set_tree(&self, tree: KeyTree) {
+        let tree_model = gtk::TreeListModel::new(KeyTreeNode::roots(tree), false, false, |item| {
+            let node = item.downcast_ref::<KeyTreeNode>().unwrap();
+            node.children().map(|children| children.upcast())
+        });
+        self.imp().selection_model.set_model(Some(&tree_model));
+    }
+
+    /// Removes the shown tree.
+    pub fn clear(&self) {
+        self.imp().selection_model.set_model(gio::ListModel::NONE);
+    }
+
+    fn setup_view(&self) {
+        let imp = self.imp();
+
+        let factory = gtk::SignalListItemFactory::new();
+        factory.connect_setup(|_, list_item| {
+            let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
+            let label = gtk::Label::builder()
+                .xalign(0.0)
+                .hexpand(true)
+                .ellipsize(gtk::pango::EllipsizeMode::End)
+                .build();
+            let count_label = gtk::Label::builder().css_classes(["dim-label"]).build();
+            let hbox = gtk::Box::builder().spacing(6).build();
+            hbox.append(&label);
+            hbox.append(&count_label);
+            let expander = gtk::TreeExpander::builder().child(&hbox).build();
+            list_item.set_child(Some(&expander));
+        });
+        factory.connect_bind(|_, list_item| {
+            let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
+            let expander = list_item
+                .child()
+                .and_downcast::<gtk::TreeExpander>()
+                .unwrap();
+            let hbox = expander.child().and_downcast::<gtk::Box>().unwrap();
+            let label = hbox.first_child().and_downcast::<gtk::Label>().unwrap();
+            let count_label = hbox.last_child().and_downcast::<gtk::Label>().unwrap();
+            let row = list_item.item().and_downcast::<gtk::TreeListRow>().unwrap();
+            let node = row.item().and_downcast::<KeyTreeNode>().unwrap();
+
+            expander.set_list_row(Some(&row));
+            label.set_label(&node.label());
+            label.set_tooltip_text(Some(&node.label()));
+            count_label.set_label(&node.n_keys().to_string());
+            count_label.set_visible(node.is_prefix());
+        });
+        imp.list_view.set_factory(Some(&factory));
+
+        imp.list_view
+            .connect_activate(clone!(@weak self as obj => move |_, position| {
+                let imp = obj.imp();
+
+                let Some(row) = imp
+                    .selection_model
+                    .item(position)
+                    .and_downcast::<gtk::TreeListRow>()
+                else {
+                    return;
+                };
+                let node = row.item().and_downcast::<KeyTreeNode>().unwrap();
+
+                match node.key() {
+                    Some(key) => {
+                        if let Err(err) =
+                            obj.activate_action("win.select-key", Some(&key.to_variant()))
+                        {
+                            tracing::error!("Failed to activate select-key: {:?}", &err);
+                        }
+                    }
+                    None => row.set_expanded(!row.is_expanded()),
+                }
+            }));
+    }
+}
@@ -0,0 +1,145 @@
+use anyhow::{Context, Result};
+use heed::types::ByteSlice;
+
+/// Number of unchanged characters kept around the changed part of a value
+const CONTEXT_LEN: usize = 16;
+
+/// A key whose value is checked for changes, with the value it had when
+/// last checked, or `None` if it didn't exist.
+#[derive(Debug, Clone)]
+struct KeyWatch {
+    db_name: Option<String>,
+    key: Vec<u8>,
+    value: Option<Vec<u8>>,
+}
+
+/// A change of the value of a watched key.
+#[derive(Debug, Clone)]
+pub struct WatchedChange {
+    pub db_name: Option<String>,
+    pub key: Vec<u8>,
+    /// Value before the change, or `None` if the key was added
+    pub before: Option<Vec<u8>>,
+    /// Value after the change, or `None` if the key was deleted
+    pub after: Option<Vec<u8>>,
+}
+
+/// Keys of an env that are watched for changes.
+#[derive(Debug, Default)]
+pub struct KeyWatches {
+    watches: Vec<KeyWatch>,
+    /// Id of the txn the values were last read at
+    txn_id: Option<u64>,
+}
+
+impl KeyWatches {
+    pub fn is_empty(&self) -> bool {
+        self.watches.is_empty()
+    }
+
+    pub fn contains(&self, db_name: Option<&str>, key: &[u8]) -> bool {
+        self.watches
+            .iter()
+            .any(|watch| watch.db_name.as_deref() == db_name && watch.key == key)
+    }
+
+    /// Starts watching `key` of the database called `db_name`, from its
+    /// current value in `env`.
+    pub fn add(&mut self, env: &heed::Env, db_name: Option<&str>, key: &[u8]) -> Result<()> {
+        if self.contains(db_name, key) {
+            return Ok(());
+        }
+
+        let value = read_value(env, db_name, key)?;
+        self.watches.push(KeyWatch {
+            db_name: db_name.map(|db_name| db_name.to_string()),
+            key: key.to_vec(),
+            value,
+        });
+
+        Ok(())
+    }
+
+    pub fn remove(&mut self, db_name: Option<&str>, key: &[u8]) {
+        self.watches
+            .retain(|watch| watch.db_name.as_deref() != db_name || watch.key != key);
+    }
+
+    pub fn clear(&mut self) {
+        self.watches.clear();
+        self.txn_id = None;
+    }
+
+    /// Reads the watched keys again if `env` was written to since the last
+    /// poll, returning the ones whose value changed.
+    pub fn poll(&mut self, env: &heed::Env) -> Result<Vec<WatchedChange>> {
+        let txn_id = env.info().last_txn_id as u64;
+        if self.txn_id == Some(txn_id) {
+            return Ok(Vec::new());
+        }
+        self.txn_id = Some(txn_id);
+
+        let mut changes = Vec::new();
+        for watch in &mut self.watches {
+            let value = read_value(env, watch.db_name.as_deref(), &watch.key)?;
+            if value != watch.value {
+                let before = std::mem::replace(&mut watch.value, value.clone());
+                changes.push(WatchedChange {
+                    db_name: watch.db_name.clone(),
+                    key: watch.key.clone(),
+                    before,
+                    after: value,
+                });
+            }
+        }
+
+        Ok(changes)
+    }
+}
+
+fn read_value(env: &heed::Env, db_name: Option<&str>, key: &[u8]) -> Result<Option<Vec<u8>>> {
+    let rtxn = env.read_txn().context("Failed to create read txn")?;
+    // A deleted database reads as a deleted key
+    let Some(db) = env.open_database::<ByteSlice, ByteSlice>(&rtxn, db_name)? else {
+        return Ok(None);
+    };
+    Ok(db.get(&rtxn, key)?.map(|value| value.to_vec()))
+}
+
+/// Returns the parts of `before` and `after` that differ, with a few of the
+/// characters around them, so a change in a long value can be told at a
+/// glance.
+pub fn changed_parts(before: &str, after: &str) -> (String, String) {
+    let before = before.chars().collect::<Vec<_>>();
+    let after = after.chars().collect::<Vec<_>>();
+
+    let prefix_len = before
+        .iter()
+        .zip(&after)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let max_suffix_len = before.len().min(after.len()) - prefix_len;
+    let suffix_len = before
+        .iter()
+        .rev()
+        .zip(after.iter().rev())
+        .take(max_suffix_len)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let excerpt = |chars: &[char]| {
+        let start = prefix_len.saturating_sub(CONTEXT_LEN);
+        let end = (chars.len() - suffix_len + CONTEXT_LEN).min(chars.len());
+        let mut excerpt = String::new();
+        if start > 0 {
+            excerpt.push('…');
+        }
+        excerpt.extend(&chars[start..end]);
+        if end < chars.len() {
+            excerpt.push('…');
+        }
+        excerpt
+    };
+
+    (excerpt(&before), excerpt(&after))
+}
@@ -0,0 +1,100 @@
+use adw::{prelude::*, subclass::prelude::*};
+use gettextrs::gettext;
+use gtk::{
+    gio,
+    glib::{self, clone},
+};
+
+use crate::{discovery, window::Window};
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, gtk::CompositeTemplate)]
+    #[template(resource = "/io/github/seadve/LmdbViewer/ui/known_locations_window.ui")]
+    pub struct KnownLocationsWindow {
+        #[template_child]
+        pub(super) stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        pub(super) list_box: TemplateChild<gtk::ListBox>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for KnownLocationsWindow {
+        const NAME: &'static str = "LvKnownLocationsWindow";
+        type Type = super::KnownLocationsWindow;
+        type ParentType = adw::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for KnownLocationsWindow {}
+    impl WidgetImpl for KnownLocationsWindow {}
+    impl WindowImpl for KnownLocationsWindow {}
+    impl AdwWindowImpl for KnownLocationsWindow {}
+}
+
+glib::wrapper! {
+    pub struct KnownLocationsWindow(ObjectSubclass<imp::KnownLocationsWindow>)
+        @extends gtk::Widget, gtk::Window, adw::Window;
+}
+
+impl KnownLocationsWindow {
+    /// Creates a window listing the envs of known apps on the system, which
+    /// opens the activated one in `parent`.
+    pub fn new(parent: &Window) -> Self {
+        let this = glib::Object::builder::<Self>()
+            .property("transient-for", parent)
+            .build();
+
+        this.load_locations(parent);
+
+        this
+    }
+
+    /// Discovers the envs in a separate thread.
+    fn load_locations(&self, parent: &Window) {
+        let imp = self.imp();
+
+        imp.stack.set_visible_child_name("loading");
+
+        glib::spawn_future_local(clone!(@weak self as obj, @weak parent => async move {
+            let imp = obj.imp();
+
+            let locations = gio::spawn_blocking(discovery::discover).await.unwrap();
+
+            for location in locations {
+                let row = adw::ActionRow::builder()
+                    .title(glib::markup_escape_text(&location.app_name))
+                    .subtitle(glib::markup_escape_text(&location.path.display().to_string()))
+                    .activatable(true)
+                    .build();
+                row.add_suffix(&gtk::Image::from_icon_name("go-next-symbolic"));
+                row.connect_activated(clone!(@weak obj, @weak parent => move |_| {
+                    obj.close();
+
+                    let path = location.path.clone();
+                    glib::spawn_future_local(clone!(@weak parent => async move {
+                        if let Err(err) = parent.load_env_or_copy(&path).await {
+                            tracing::error!("Failed to open `{}`: {:?}", path.display(), &err);
+                            parent.add_error_toast(&gettext("Failed to open env"), &err);
+                        }
+                    }));
+                }));
+                imp.list_box.append(&row);
+            }
+
+            imp.stack.set_visible_child_name(if imp.list_box.row_at_index(0).is_some() {
+                "locations"
+            } else {
+                "empty"
+            });
+        }));
+    }
+}
@@ -0,0 +1,96 @@
+/// Max number of line pairs compared to find the fewest changes, beyond
+/// which all lines between the common start and end are shown as changed
+const MAX_COMPARED_PAIRS: usize = 4_000_000;
+
+/// How a line differs between two texts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineChange {
+    Unchanged,
+    /// Only in the text before
+    Removed,
+    /// Only in the text after
+    Added,
+}
+
+/// Returns the lines of `before` and `after` in order, with how each one
+/// changed, keeping as many lines unchanged as possible.
+pub fn diff<'a>(before: &'a str, after: &'a str) -> Vec<(LineChange, &'a str)> {
+    let before = before.lines().collect::<Vec<_>>();
+    let after = after.lines().collect::<Vec<_>>();
+
+    let prefix_len = before
+        .iter()
+        .zip(&after)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let max_suffix_len = before.len().min(after.len()) - prefix_len;
+    let suffix_len = before
+        .iter()
+        .rev()
+        .zip(after.iter().rev())
+        .take(max_suffix_len)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let before_middle = &before[prefix_len..before.len() - suffix_len];
+    let after_middle = &after[prefix_len..after.len() - suffix_len];
+
+    let mut lines = Vec::with_capacity(before.len().max(after.len()));
+    lines.extend(
+        before[..prefix_len]
+            .iter()
+            .map(|line| (LineChange::Unchanged, *line)),
+    );
+    if before_middle.len() * after_middle.len() <= MAX_COMPARED_PAIRS {
+        diff_middle(before_middle, after_middle, &mut lines);
+    } else {
+        lines.extend(
+            before_middle
+                .iter()
+                .map(|line| (LineChange::Removed, *line)),
+        );
+        lines.extend(after_middle.iter().map(|line| (LineChange::Added, *line)));
+    }
+    lines.extend(
+        before[before.len() - suffix_len..]
+            .iter()
+            .map(|line| (LineChange::Unchanged, *line)),
+    );
+
+    lines
+}
+
+/// Appends the lines of `before` and `after` to `lines`, keeping their
+/// longest common subsequence unchanged.
+fn diff_middle<'a>(before: &[&'a str], after: &[&'a str], lines: &mut Vec<(LineChange, &'a str)>) {
+    let n_columns = after.len() + 1;
+    // Length of the longest common subsequence of `before[i..]` and
+    // `after[j..]`, at `i * n_columns + j`
+    let mut lcs_lens = vec![0u32; (before.len() + 1) * n_columns];
+    for i in (0..before.len()).rev() {
+        for j in (0..after.len()).rev() {
+            lcs_lens[i * n_columns + j] = if before[i] == after[j] {
+                lcs_lens[(i + 1) * n_columns + j + 1] + 1
+            } else {
+                lcs_lens[(i + 1) * n_columns + j].max(lcs_lens[i * n_columns + j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < before.len() && j < after.len() {
+        if before[i] == after[j] {
+            lines.push((LineChange::Unchanged, before[i]));
+            i += 1;
+            j += 1;
+        } else if lcs_lens[(i + 1) * n_columns + j] >= lcs_lens[i * n_columns + j + 1] {
+            lines.push((LineChange::Removed, before[i]));
+            i += 1;
+        } else {
+            lines.push((LineChange::Added, after[j]));
+            j += 1;
+        }
+    }
+    lines.extend(before[i..].iter().map(|line| (LineChange::Removed, *line)));
+    lines.extend(after[j..].iter().map(|line| (LineChange::Added, *line)));
+}
@@ -1,7 +1,69 @@
+mod annotations;
 mod application;
+mod archive;
+mod backend;
+mod bookmark;
+mod byte_inspector;
+mod cell_rendering;
+mod cli;
+mod color_scheme;
+mod command_palette;
+mod compare_window;
+mod compression;
 mod config;
 mod database;
 mod database_item;
+mod database_pane;
+mod db_stats;
+mod db_stats_item;
+mod decoder;
+mod detail_pane;
+mod diff;
+mod diff_item;
+mod discovery;
+mod duplicates;
+mod edit_history;
+mod env_profile;
+mod env_subset;
+mod error_details;
+mod export;
+mod full_search;
+mod hex_editor;
+mod highlight_rule;
+mod import;
+mod key_collation;
+mod key_display_mode;
+mod key_history;
+mod key_tree_node;
+mod key_tree_pane;
+mod key_watch;
+mod known_locations_window;
+mod line_diff;
+mod mdbx;
+mod meta_page;
+mod monitor_pane;
+mod non_printable_display;
+mod page;
+mod parquet_export;
+mod pending_changes;
+mod pending_changes_pane;
+mod preferences_window;
+mod print_report;
+mod query;
+mod remote;
+mod saved_search;
+mod search_results_pane;
+mod settings_bundle;
+mod size_distribution;
+mod snapshot;
+mod space_usage;
+mod sqlite_export;
+mod statistics_window;
+mod text_encoding;
+mod timestamp;
+mod utils;
+mod value_diff_view;
+mod value_format;
 mod window;
 
 use gettextrs::{gettext, LocaleCategory};
@@ -19,6 +81,11 @@ fn main() -> glib::ExitCode {
     gettextrs::bindtextdomain(GETTEXT_PACKAGE, LOCALEDIR).expect("Unable to bind the text domain");
     gettextrs::textdomain(GETTEXT_PACKAGE).expect("Unable to switch to the text domain");
 
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if let Some(exit_code) = cli::run(&args) {
+        return exit_code;
+    }
+
     glib::set_application_name(&gettext("LMDB Viewer"));
 
     let res = gio::Resource::load(RESOURCES_FILE).expect("Could not load gresource file");
@@ -0,0 +1,386 @@
+use anyhow::{anyhow, Context, Result};
+use gtk::glib;
+use heed::DatabaseFlags;
+use libloading::Library;
+
+use std::{
+    ffi::{c_char, c_int, c_uint, c_void, CStr, CString},
+    fmt, io,
+    os::unix::ffi::OsStrExt,
+    path::{Path, PathBuf},
+    ptr,
+    sync::OnceLock,
+};
+
+use crate::{
+    backend::{Backend, BackendKind, DatabaseContents},
+    database,
+};
+
+/// Name of the data file in the directory of an env
+pub const DATA_FILE_NAME: &str = "mdbx.dat";
+
+/// Name of the lock file in the directory of an env
+pub const LOCK_FILE_NAME: &str = "mdbx.lck";
+
+/// Suffix MDBX adds to the path of an env made of a single data file to name
+/// its lock file
+pub const LOCK_SUFFIX: &str = "-lck";
+
+/// Size of the start of a data file that holds the MDBX magic number, after
+/// the page header of txnid (u64), then dupfix_ksize, flags (u16), lower
+/// (u32) and pgno (u32)
+const MDBX_HEADER_LEN: usize = 8 + 2 * 2 + 4 + 4 + 8;
+
+/// Magic number in the upper 56 bits of `magic_and_version` of meta pages
+const MDBX_MAGIC: u64 = 0x59659DBDEF4C11;
+
+/// Names libmdbx is looked up by. It is loaded when an MDBX env is first
+/// opened rather than linked, so LMDB envs can be opened without it.
+const LIBRARY_NAMES: &[&str] = &["libmdbx.so", "libmdbx.so.0"];
+
+/// Number of named dbs that can be opened, as they are all listed
+const MAX_DBS: u64 = 1024;
+
+const MDBX_SUCCESS: c_int = 0;
+const MDBX_NOTFOUND: c_int = -30798;
+
+const MDBX_NOSUBDIR: c_uint = 0x4000;
+const MDBX_RDONLY: c_uint = 0x20000;
+/// Takes the flags of an existing env or db instead of failing on others
+const MDBX_ACCEDE: c_uint = 0x40000000;
+
+const MDBX_OPT_MAX_DB: c_int = 0;
+
+const MDBX_FIRST: c_int = 0;
+const MDBX_NEXT: c_int = 8;
+
+type RawEnv = *mut c_void;
+type RawTxn = *mut c_void;
+type RawCursor = *mut c_void;
+type Dbi = c_uint;
+
+/// `MDBX_val`, which is laid out as `struct iovec`
+#[repr(C)]
+struct Val {
+    base: *mut c_void,
+    len: usize,
+}
+
+impl Val {
+    fn empty() -> Self {
+        Self {
+            base: ptr::null_mut(),
+            len: 0,
+        }
+    }
+
+    /// # Safety
+    ///
+    /// The value must have been filled by MDBX in a txn that is still open.
+    unsafe fn to_bytes(&self) -> glib::Bytes {
+        if self.len == 0 {
+            return glib::Bytes::from_static(&[]);
+        }
+
+        glib::Bytes::from(std::slice::from_raw_parts(self.base as *const u8, self.len))
+    }
+}
+
+/// Functions of the loaded libmdbx, as declared in `mdbx.h`.
+struct Api {
+    env_create: unsafe extern "C" fn(*mut RawEnv) -> c_int,
+    env_set_option: unsafe extern "C" fn(RawEnv, c_int, u64) -> c_int,
+    env_open: unsafe extern "C" fn(RawEnv, *const c_char, c_uint, libc::mode_t) -> c_int,
+    env_close_ex: unsafe extern "C" fn(RawEnv, bool) -> c_int,
+    txn_begin_ex: unsafe extern "C" fn(RawEnv, RawTxn, c_uint, *mut RawTxn, *mut c_void) -> c_int,
+    txn_id: unsafe extern "C" fn(RawTxn) -> u64,
+    txn_abort: unsafe extern "C" fn(RawTxn) -> c_int,
+    dbi_open: unsafe extern "C" fn(RawTxn, *const c_char, c_uint, *mut Dbi) -> c_int,
+    dbi_flags_ex: unsafe extern "C" fn(RawTxn, Dbi, *mut c_uint, *mut c_uint) -> c_int,
+    cursor_open: unsafe extern "C" fn(RawTxn, Dbi, *mut RawCursor) -> c_int,
+    cursor_get: unsafe extern "C" fn(RawCursor, *mut Val, *mut Val, c_int) -> c_int,
+    cursor_close: unsafe extern "C" fn(RawCursor),
+    strerror: unsafe extern "C" fn(c_int) -> *const c_char,
+    _library: Library,
+}
+
+impl Api {
+    fn load() -> Result<Self> {
+        let library = LIBRARY_NAMES
+            .iter()
+            // SAFETY: libmdbx only sets up its own state when loaded
+            .find_map(|name| unsafe { Library::new(name) }.ok())
+            .context("libmdbx is not installed")?;
+
+        // SAFETY: The types are the ones of the declarations in `mdbx.h`, and
+        // the pointers are kept with the library they point into
+        unsafe {
+            Ok(Self {
+                env_create: *library.get(b"mdbx_env_create\0")?,
+                env_set_option: *library.get(b"mdbx_env_set_option\0")?,
+                env_open: *library.get(b"mdbx_env_open\0")?,
+                env_close_ex: *library.get(b"mdbx_env_close_ex\0")?,
+                txn_begin_ex: *library.get(b"mdbx_txn_begin_ex\0")?,
+                txn_id: *library.get(b"mdbx_txn_id\0")?,
+                txn_abort: *library.get(b"mdbx_txn_abort\0")?,
+                dbi_open: *library.get(b"mdbx_dbi_open\0")?,
+                dbi_flags_ex: *library.get(b"mdbx_dbi_flags_ex\0")?,
+                cursor_open: *library.get(b"mdbx_cursor_open\0")?,
+                cursor_get: *library.get(b"mdbx_cursor_get\0")?,
+                cursor_close: *library.get(b"mdbx_cursor_close\0")?,
+                strerror: *library.get(b"mdbx_strerror\0")?,
+                _library: library,
+            })
+        }
+    }
+
+    fn check(&self, code: c_int) -> Result<()> {
+        if code == MDBX_SUCCESS {
+            Ok(())
+        } else {
+            Err(self.error(code))
+        }
+    }
+
+    fn error(&self, code: c_int) -> anyhow::Error {
+        // Positive codes are errno values, which are kept as I/O errors, so
+        // a copy can be offered if permission is denied
+        if code > 0 {
+            return io::Error::from_raw_os_error(code).into();
+        }
+
+        // SAFETY: MDBX returns a static string for any code
+        let message = unsafe { CStr::from_ptr((self.strerror)(code)) };
+        anyhow!("{} ({})", message.to_string_lossy(), code)
+    }
+}
+
+/// Returns libmdbx, loading it the first time.
+fn api() -> Result<&'static Api> {
+    static API: OnceLock<Result<Api, String>> = OnceLock::new();
+
+    API.get_or_init(|| Api::load().map_err(|err| format!("{:#}", err)))
+        .as_ref()
+        .map_err(|err| anyhow!("Failed to load libmdbx, which MDBX envs need: {}", err))
+}
+
+/// Returns whether `bytes` start like an MDBX data file.
+pub fn is_mdbx_data(bytes: &[u8]) -> bool {
+    bytes
+        .get(MDBX_HEADER_LEN - 8..MDBX_HEADER_LEN)
+        .is_some_and(|magic| u64::from_ne_bytes(magic.try_into().unwrap()) >> 8 == MDBX_MAGIC)
+}
+
+/// An MDBX env, which is always opened read-only.
+pub struct Env {
+    api: &'static Api,
+    raw: RawEnv,
+    path: PathBuf,
+}
+
+// SAFETY: MDBX envs can be used from any thread, and txns are only used in
+// the thread that began them, within a single call
+unsafe impl Send for Env {}
+unsafe impl Sync for Env {}
+
+impl fmt::Debug for Env {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Env")
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Env {
+    /// Opens the env at `path`, a directory or the data file of an env made
+    /// of a single file, read-only.
+    pub fn open(path: &Path) -> Result<Self> {
+        let api = api()?;
+        let c_path = CString::new(path.as_os_str().as_bytes())?;
+
+        let mut raw = ptr::null_mut();
+        // SAFETY: `raw` is set to a new env on success
+        api.check(unsafe { (api.env_create)(&mut raw) })?;
+        // Closed when dropped if opening it fails below
+        let env = Self {
+            api,
+            raw,
+            path: path.to_path_buf(),
+        };
+
+        // SAFETY: The env is valid, and not open yet
+        api.check(unsafe { (api.env_set_option)(raw, MDBX_OPT_MAX_DB, MAX_DBS) })?;
+
+        let mut flags = MDBX_RDONLY | MDBX_ACCEDE;
+        if path.is_file() {
+            flags |= MDBX_NOSUBDIR;
+        }
+        // SAFETY: The env is valid and `c_path` outlives the call
+        api.check(unsafe { (api.env_open)(raw, c_path.as_ptr(), flags, 0) })
+            .with_context(|| format!("Failed to open MDBX env `{}`", path.display()))?;
+
+        Ok(env)
+    }
+
+    fn read_txn(&self) -> Result<RoTxn<'_>> {
+        let mut raw = ptr::null_mut();
+        // SAFETY: The env is open, and `raw` is set to a new txn on success
+        self.api
+            .check(unsafe {
+                (self.api.txn_begin_ex)(
+                    self.raw,
+                    ptr::null_mut(),
+                    MDBX_RDONLY,
+                    &mut raw,
+                    ptr::null_mut(),
+                )
+            })
+            .context("Failed to create read txn")?;
+
+        Ok(RoTxn { env: self, raw })
+    }
+}
+
+impl Drop for Env {
+    fn drop(&mut self) {
+        // SAFETY: Txns borrow the env, so none are left
+        let code = unsafe { (self.api.env_close_ex)(self.raw, false) };
+        if let Err(err) = self.api.check(code) {
+            tracing::warn!("Failed to close MDBX env: {:?}", err);
+        }
+    }
+}
+
+impl Backend for Env {
+    fn kind(&self) -> BackendKind {
+        BackendKind::Mdbx
+    }
+
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn names(&self) -> Result<Vec<Option<String>>> {
+        let rtxn = self.read_txn()?;
+        let unnamed_dbi = rtxn.open_dbi(None)?.context("Unnamed db not found")?;
+
+        let mut names = vec![None];
+        for (key, _) in rtxn.entries(unnamed_dbi)? {
+            // Like with LMDB, only valid UTF-8 names can be opened, and keys
+            // of the unnamed database that aren't databases fail to open
+            let Ok(name) = database::name_from_bytes(&key) else {
+                continue;
+            };
+            if let Ok(Some(_)) = rtxn.open_dbi(Some(name)) {
+                names.push(Some(name.to_string()));
+            }
+        }
+
+        Ok(names)
+    }
+
+    fn read_database(&self, name: Option<&str>) -> Result<DatabaseContents> {
+        let rtxn = self.read_txn()?;
+        let dbi = rtxn
+            .open_dbi(name)?
+            .ok_or_else(|| anyhow!("database not found"))?;
+
+        Ok(DatabaseContents {
+            entries: rtxn.entries(dbi).context("Failed to collect db")?,
+            flags: rtxn.flags(dbi)?,
+            txn_id: rtxn.id(),
+        })
+    }
+}
+
+/// A read-only txn, aborted when dropped.
+struct RoTxn<'env> {
+    env: &'env Env,
+    raw: RawTxn,
+}
+
+impl RoTxn<'_> {
+    fn id(&self) -> u64 {
+        // SAFETY: The txn is open
+        unsafe { (self.env.api.txn_id)(self.raw) }
+    }
+
+    /// Opens the handle of the database called `name`, returning `None` if
+    /// there is none.
+    fn open_dbi(&self, name: Option<&str>) -> Result<Option<Dbi>> {
+        let api = self.env.api;
+
+        let c_name = name.map(CString::new).transpose()?;
+        let mut dbi = 0;
+        // SAFETY: The txn is open and `c_name` outlives the call
+        let code = unsafe {
+            (api.dbi_open)(
+                self.raw,
+                c_name.as_ref().map_or(ptr::null(), |name| name.as_ptr()),
+                MDBX_ACCEDE,
+                &mut dbi,
+            )
+        };
+        if code == MDBX_NOTFOUND {
+            return Ok(None);
+        }
+        api.check(code)?;
+
+        Ok(Some(dbi))
+    }
+
+    fn flags(&self, dbi: Dbi) -> Result<DatabaseFlags> {
+        let mut flags = 0;
+        let mut state = 0;
+        // SAFETY: The txn is open and `dbi` was opened in it
+        self.env
+            .api
+            .check(unsafe { (self.env.api.dbi_flags_ex)(self.raw, dbi, &mut flags, &mut state) })?;
+
+        // MDBX keeps the values of LMDB's database flags
+        Ok(DatabaseFlags::from_bits_truncate(flags))
+    }
+
+    /// Reads all entries of `dbi` in database order, with an entry for each
+    /// value of a DUPSORT key.
+    fn entries(&self, dbi: Dbi) -> Result<Vec<(glib::Bytes, glib::Bytes)>> {
+        let api = self.env.api;
+
+        let mut cursor = ptr::null_mut();
+        // SAFETY: The txn is open and `dbi` was opened in it
+        api.check(unsafe { (api.cursor_open)(self.raw, dbi, &mut cursor) })?;
+
+        let mut entries = Vec::new();
+        let mut op = MDBX_FIRST;
+        let result = loop {
+            let mut key = Val::empty();
+            let mut data = Val::empty();
+            // SAFETY: The cursor is open, and fills the values on success
+            match unsafe { (api.cursor_get)(cursor, &mut key, &mut data, op) } {
+                MDBX_SUCCESS => {
+                    // SAFETY: The values point into the map while the txn is
+                    // open, and are copied before it ends
+                    entries.push(unsafe { (key.to_bytes(), data.to_bytes()) });
+                    op = MDBX_NEXT;
+                }
+                MDBX_NOTFOUND => break Ok(entries),
+                code => break Err(api.error(code)),
+            }
+        };
+        // SAFETY: The cursor is open and not used after this
+        unsafe { (api.cursor_close)(cursor) };
+
+        result
+    }
+}
+
+impl Drop for RoTxn<'_> {
+    fn drop(&mut self) {
+        // SAFETY: The txn is open and not used after this
+        let code = unsafe { (self.env.api.txn_abort)(self.raw) };
+        if let Err(err) = self.env.api.check(code) {
+            tracing::warn!("Failed to abort MDBX read txn: {:?}", err);
+        }
+    }
+}
@@ -0,0 +1,255 @@
+use anyhow::Result;
+
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    mem::size_of,
+    path::Path,
+};
+
+/// Magic number at the start of every meta page
+const MAGIC: u32 = 0xBEEFC0DE;
+
+/// Page number of a database record with no root, i.e., an empty database
+const INVALID_PAGE: u64 = usize::MAX as u64;
+
+// Page header: pgno (size_t), then pad, flags, lower and upper (u16)
+const META_OFFSET: usize = size_of::<usize>() + 4 * 2;
+// Meta: magic and version (u32), address (pointer) and map size (size_t)
+const MAP_SIZE_OFFSET: usize = META_OFFSET + 4 * 2 + size_of::<usize>();
+const FREE_DB_OFFSET: usize = MAP_SIZE_OFFSET + size_of::<usize>();
+// md_pad: u32, md_flags: u16, md_depth: u16, then five size_t fields
+const RECORD_LEN: usize = 4 + 2 + 2 + 5 * size_of::<usize>();
+const MAIN_DB_OFFSET: usize = FREE_DB_OFFSET + RECORD_LEN;
+// Both database records, then the last page number and txn id (size_t)
+const LAST_PAGE_OFFSET: usize = MAIN_DB_OFFSET + RECORD_LEN;
+const TXN_ID_OFFSET: usize = LAST_PAGE_OFFSET + size_of::<usize>();
+const META_LEN: usize = TXN_ID_OFFSET + size_of::<usize>();
+
+/// One of the two meta pages at the start of a data file, which LMDB writes
+/// alternately on commit. The one with the latest txn id is current, and the
+/// other one is the previous snapshot.
+#[derive(Debug, Clone, Copy)]
+pub struct MetaPage {
+    /// Whether the page starts with the LMDB magic number, without which the
+    /// rest is meaningless
+    pub has_magic: bool,
+    pub version: u32,
+    pub map_size: u64,
+    /// The `MDB_db` record of the freelist database
+    pub free_db: DbRecord,
+    /// The `MDB_db` record of the unnamed database
+    pub main_db: DbRecord,
+    /// Last page used in the data file
+    pub last_page: u64,
+    /// Id of the txn that wrote the page
+    pub txn_id: u64,
+}
+
+/// An `MDB_db` record in a meta page.
+#[derive(Debug, Clone, Copy)]
+pub struct DbRecord {
+    /// Padding, which holds the page size in the freelist record
+    pub pad: u32,
+    pub depth: u16,
+    pub n_branch_pages: u64,
+    pub n_leaf_pages: u64,
+    pub n_overflow_pages: u64,
+    pub n_entries: u64,
+    /// Root page, or `None` if the database is empty
+    pub root: Option<u64>,
+}
+
+impl DbRecord {
+    pub fn n_pages(&self) -> u64 {
+        self.n_branch_pages + self.n_leaf_pages + self.n_overflow_pages
+    }
+
+    fn parse(meta: &[u8], offset: usize) -> Self {
+        let field = |index| read_usize(meta, offset + 8 + index * size_of::<usize>());
+        let root = field(4);
+        Self {
+            pad: read_u32(meta, offset),
+            depth: u16::from_ne_bytes(meta[offset + 6..offset + 8].try_into().unwrap()),
+            n_branch_pages: field(0),
+            n_leaf_pages: field(1),
+            n_overflow_pages: field(2),
+            n_entries: field(3),
+            root: (root != INVALID_PAGE).then_some(root),
+        }
+    }
+}
+
+impl MetaPage {
+    /// Page size, which LMDB keeps in the padding of the freelist record
+    pub fn page_size(&self) -> u64 {
+        self.free_db.pad as u64
+    }
+
+    fn parse(meta: &[u8; META_LEN]) -> Self {
+        Self {
+            has_magic: read_u32(meta, META_OFFSET) == MAGIC,
+            version: read_u32(meta, META_OFFSET + 4),
+            map_size: read_usize(meta, MAP_SIZE_OFFSET),
+            free_db: DbRecord::parse(meta, FREE_DB_OFFSET),
+            main_db: DbRecord::parse(meta, MAIN_DB_OFFSET),
+            last_page: read_usize(meta, LAST_PAGE_OFFSET),
+            txn_id: read_usize(meta, TXN_ID_OFFSET),
+        }
+    }
+}
+
+/// Reads both meta pages of the data file at `path`. The second one is found
+/// with the page size in the first one, as LMDB does.
+pub fn read_meta_pages(path: &Path) -> Result<[MetaPage; 2]> {
+    let mut file = File::open(path)?;
+    let mut first_meta = [0; META_LEN];
+    file.read_exact(&mut first_meta)?;
+    let first = MetaPage::parse(&first_meta);
+
+    anyhow::ensure!(first.has_magic, "First meta page has no LMDB magic number");
+    anyhow::ensure!(first.page_size() > 0, "Invalid page size");
+
+    let mut second_meta = [0; META_LEN];
+    file.seek(SeekFrom::Start(first.page_size()))?;
+    file.read_exact(&mut second_meta)?;
+
+    Ok([first, MetaPage::parse(&second_meta)])
+}
+
+/// Returns the index of the current one of `meta_pages`, which is the one
+/// LMDB would pick.
+pub fn current_index(meta_pages: &[MetaPage; 2]) -> usize {
+    if meta_pages[1].txn_id > meta_pages[0].txn_id {
+        1
+    } else {
+        0
+    }
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_ne_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_usize(bytes: &[u8], offset: usize) -> u64 {
+    usize::from_ne_bytes(
+        bytes[offset..offset + size_of::<usize>()]
+            .try_into()
+            .unwrap(),
+    ) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::path::PathBuf;
+
+    use crate::utils;
+
+    const PAGE_SIZE: usize = 256;
+
+    fn set_usize(meta: &mut [u8], offset: usize, value: usize) {
+        meta[offset..offset + size_of::<usize>()].copy_from_slice(&value.to_ne_bytes());
+    }
+
+    /// Returns a meta page written by txn `txn_id`, with a main database of
+    /// `n_entries` entries rooted at page 5, or an empty one if there are
+    /// none.
+    fn meta(txn_id: usize, n_entries: usize) -> [u8; META_LEN] {
+        let mut meta = [0; META_LEN];
+        meta[META_OFFSET..META_OFFSET + 4].copy_from_slice(&MAGIC.to_ne_bytes());
+        meta[META_OFFSET + 4..META_OFFSET + 8].copy_from_slice(&1u32.to_ne_bytes());
+        set_usize(&mut meta, MAP_SIZE_OFFSET, 1 << 20);
+        meta[FREE_DB_OFFSET..FREE_DB_OFFSET + 4].copy_from_slice(&(PAGE_SIZE as u32).to_ne_bytes());
+        set_usize(
+            &mut meta,
+            FREE_DB_OFFSET + 8 + 4 * size_of::<usize>(),
+            usize::MAX,
+        );
+
+        let field_offset = |index: usize| MAIN_DB_OFFSET + 8 + index * size_of::<usize>();
+        if n_entries > 0 {
+            meta[MAIN_DB_OFFSET + 6..MAIN_DB_OFFSET + 8].copy_from_slice(&2u16.to_ne_bytes());
+            set_usize(&mut meta, field_offset(0), 1);
+            set_usize(&mut meta, field_offset(1), 3);
+            set_usize(&mut meta, field_offset(3), n_entries);
+            set_usize(&mut meta, field_offset(4), 5);
+        } else {
+            set_usize(&mut meta, field_offset(4), usize::MAX);
+        }
+
+        set_usize(&mut meta, LAST_PAGE_OFFSET, 9);
+        set_usize(&mut meta, TXN_ID_OFFSET, txn_id);
+        meta
+    }
+
+    /// Writes a data file starting with the meta pages `first` and `second`.
+    fn data_file(first: &[u8], second: &[u8]) -> PathBuf {
+        let mut bytes = first.to_vec();
+        bytes.resize(PAGE_SIZE, 0);
+        bytes.extend_from_slice(second);
+        bytes.resize(PAGE_SIZE * 2, 0);
+        utils::write_to_temp_dir(&bytes, "data.mdb")
+            .unwrap()
+            .join("data.mdb")
+    }
+
+    #[test]
+    fn reads_both_meta_pages() {
+        let meta_pages = read_meta_pages(&data_file(&meta(4, 0), &meta(5, 12))).unwrap();
+
+        let [first, second] = meta_pages;
+        assert!(first.has_magic && second.has_magic);
+        assert_eq!(first.version, 1);
+        assert_eq!(first.map_size, 1 << 20);
+        assert_eq!(first.page_size(), PAGE_SIZE as u64);
+        assert_eq!(first.free_db.root, None);
+        assert_eq!(first.main_db.root, None);
+        assert_eq!(first.txn_id, 4);
+
+        assert_eq!(second.main_db.root, Some(5));
+        assert_eq!(second.main_db.depth, 2);
+        assert_eq!(second.main_db.n_entries, 12);
+        assert_eq!(second.main_db.n_pages(), 4);
+        assert_eq!(second.last_page, 9);
+        assert_eq!(second.txn_id, 5);
+
+        assert_eq!(current_index(&meta_pages), 1);
+        assert_eq!(current_index(&[second, first]), 0);
+    }
+
+    #[test]
+    fn fails_without_magic_or_page_size() {
+        let mut no_magic = meta(1, 0);
+        no_magic[META_OFFSET] ^= 0xff;
+        let error = read_meta_pages(&data_file(&no_magic, &meta(2, 0))).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "First meta page has no LMDB magic number"
+        );
+
+        let mut no_page_size = meta(1, 0);
+        no_page_size[FREE_DB_OFFSET..FREE_DB_OFFSET + 4].fill(0);
+        let error = read_meta_pages(&data_file(&no_page_size, &meta(2, 0))).unwrap_err();
+        assert_eq!(error.to_string(), "Invalid page size");
+    }
+
+    #[test]
+    fn fails_on_truncated_files() {
+        let first = meta(1, 0);
+
+        let path = utils::write_to_temp_dir(&first[..META_LEN - 1], "data.mdb")
+            .unwrap()
+            .join("data.mdb");
+        assert!(read_meta_pages(&path).is_err());
+
+        // The second meta page is cut off
+        let mut bytes = first.to_vec();
+        bytes.resize(PAGE_SIZE + META_LEN - 1, 0);
+        let path = utils::write_to_temp_dir(&bytes, "data.mdb")
+            .unwrap()
+            .join("data.mdb");
+        assert!(read_meta_pages(&path).is_err());
+    }
+}
@@ -0,0 +1,106 @@
+use adw::prelude::*;
+use gettextrs::gettext;
+use gtk::{glib, subclass::prelude::*};
+
+use crate::diff::DiffKind;
+
+/// Maximum number of changes kept in the feed
+const MAX_N_ROWS: u32 = 500;
+
+mod imp {
+    use std::cell::Cell;
+
+    use super::*;
+
+    #[derive(Debug, Default, gtk::CompositeTemplate)]
+    #[template(resource = "/io/github/seadve/LmdbViewer/ui/monitor_pane.ui")]
+    pub struct MonitorPane {
+        #[template_child]
+        pub(super) watched_prefix_entry: TemplateChild<gtk::Entry>,
+        #[template_child]
+        pub(super) list_box: TemplateChild<gtk::ListBox>,
+
+        pub(super) n_rows: Cell<u32>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MonitorPane {
+        const NAME: &'static str = "LvMonitorPane";
+        type Type = super::MonitorPane;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+
+            klass.install_action("monitor-pane.clear", None, |obj, _, _| {
+                obj.clear();
+            });
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for MonitorPane {
+        fn dispose(&self) {
+            self.dispose_template();
+        }
+    }
+
+    impl WidgetImpl for MonitorPane {}
+}
+
+glib::wrapper! {
+    pub struct MonitorPane(ObjectSubclass<imp::MonitorPane>)
+        @extends gtk::Widget;
+}
+
+impl MonitorPane {
+    /// Adds a change to the top of the feed, dropping the oldest one if it
+    /// is full.
+    pub fn add_change(&self, kind: DiffKind, key_text: &str) {
+        let imp = self.imp();
+
+        let time = glib::DateTime::now_local()
+            .and_then(|now| now.format("%X"))
+            .unwrap_or_default();
+        let kind_label = match kind {
+            DiffKind::Unchanged => return,
+            DiffKind::Added => gettext("Added"),
+            DiffKind::Removed => gettext("Removed"),
+            DiffKind::Changed => gettext("Changed"),
+        };
+
+        let row = adw::ActionRow::builder()
+            .title(key_text)
+            .subtitle(format!("{} · {}", time, kind_label))
+            .use_markup(false)
+            .build();
+        if let Some(css_class) = kind.css_class() {
+            row.add_css_class(css_class);
+        }
+        imp.list_box.prepend(&row);
+
+        if imp.n_rows.get() == MAX_N_ROWS {
+            let last_row = imp.list_box.row_at_index(MAX_N_ROWS as i32).unwrap();
+            imp.list_box.remove(&last_row);
+        } else {
+            imp.n_rows.set(imp.n_rows.get() + 1);
+        }
+    }
+
+    /// Returns the key prefix to notify about, or an empty string if none.
+    pub fn watched_prefix(&self) -> glib::GString {
+        self.imp().watched_prefix_entry.text()
+    }
+
+    fn clear(&self) {
+        let imp = self.imp();
+
+        while let Some(row) = imp.list_box.row_at_index(0) {
+            imp.list_box.remove(&row);
+        }
+        imp.n_rows.set(0);
+    }
+}
@@ -0,0 +1,119 @@
+use gtk::glib;
+
+use std::fmt::Write;
+
+use crate::text_encoding::TextEncoding;
+
+/// How bytes that can't be shown as text are rendered.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, glib::Enum)]
+#[enum_type(name = "LvNonPrintableDisplay")]
+pub enum NonPrintableDisplay {
+    /// `\x00`-style escapes
+    #[default]
+    #[enum_value(name = "Escape Sequences", nick = "escape")]
+    Escape,
+    /// Unicode control pictures (e.g., ␀) for C0 controls, escapes otherwise
+    #[enum_value(name = "Control Pictures", nick = "control-pictures")]
+    ControlPictures,
+    /// The whole value as hex if it contains anything non-printable
+    #[enum_value(name = "Hexadecimal", nick = "hex")]
+    Hex,
+}
+
+impl NonPrintableDisplay {
+    /// Renders `bytes` as text in `encoding`, making non-printable characters
+    /// and invalid sequences visible.
+    pub fn render(self, bytes: &[u8], encoding: TextEncoding) -> String {
+        let mut text = String::with_capacity(bytes.len());
+        let mut has_non_printable = false;
+
+        if encoding == TextEncoding::Utf8 {
+            // Escape the offending bytes themselves rather than U+FFFD.
+            for chunk in bytes.utf8_chunks() {
+                has_non_printable |= self.push_escaped(&mut text, chunk.valid());
+
+                for byte in chunk.invalid() {
+                    has_non_printable = true;
+                    write!(text, "\\x{:02x}", byte).unwrap();
+                }
+            }
+        } else {
+            has_non_printable = self.push_escaped(&mut text, &encoding.decode(bytes));
+        }
+
+        if self == Self::Hex && has_non_printable {
+            return to_hex(bytes);
+        }
+
+        text
+    }
+
+    /// Appends `str` to `text` with non-printable characters escaped, returning
+    /// whether there were any.
+    fn push_escaped(self, text: &mut String, str: &str) -> bool {
+        let mut has_non_printable = false;
+
+        for c in str.chars() {
+            if is_printable(c) {
+                text.push(c);
+                continue;
+            }
+
+            has_non_printable = true;
+
+            match (self, c as u32) {
+                (Self::ControlPictures, code @ 0x00..=0x1f) => {
+                    text.push(char::from_u32(0x2400 + code).unwrap());
+                }
+                (Self::ControlPictures, 0x7f) => text.push('\u{2421}'),
+                (_, code @ 0x00..=0xff) => write!(text, "\\x{:02x}", code).unwrap(),
+                (_, code) => write!(text, "\\u{{{:x}}}", code).unwrap(),
+            }
+        }
+
+        has_non_printable
+    }
+}
+
+/// Parses `text` rendered by [`NonPrintableDisplay::render`] back to bytes,
+/// undoing `\xHH` escapes. Hex dumps are told apart by the view showing them
+/// rather than by their text, which may as well be escaped text.
+pub fn unrender(text: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(index) = rest.find("\\x") {
+        bytes.extend_from_slice(&rest.as_bytes()[..index]);
+        let escaped = rest
+            .get(index + 2..index + 4)
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                bytes.push(byte);
+                rest = &rest[index + 4..];
+            }
+            None => {
+                bytes.extend_from_slice(b"\\x");
+                rest = &rest[index + 2..];
+            }
+        }
+    }
+    bytes.extend_from_slice(rest.as_bytes());
+
+    bytes
+}
+
+fn is_printable(c: char) -> bool {
+    matches!(c, '\n' | '\t') || !(c.is_control() || c == char::REPLACEMENT_CHARACTER)
+}
+
+/// Renders `bytes` as space-separated hex pairs.
+pub fn to_hex(bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(bytes.len() * 3);
+    for (index, byte) in bytes.iter().enumerate() {
+        if index > 0 {
+            hex.push(' ');
+        }
+        write!(hex, "{:02x}", byte).unwrap();
+    }
+    hex
+}
@@ -0,0 +1,488 @@
+use anyhow::{Context, Result};
+
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    mem::size_of,
+    path::Path,
+};
+
+// Page header: pgno (size_t), then pad, flags, lower and upper (u16)
+const HEADER_LEN: usize = size_of::<usize>() + 4 * 2;
+// Node header: lo and hi (u16), which hold the data size or child page
+// number, then flags and key size (u16)
+const NODE_HEADER_LEN: usize = 2 * 4;
+
+const BRANCH: u16 = 0x01;
+const LEAF: u16 = 0x02;
+const OVERFLOW: u16 = 0x04;
+const META: u16 = 0x08;
+const LEAF2: u16 = 0x20;
+
+/// Names of the page flags, from `mdb.c`
+const FLAG_NAMES: &[(u16, &str)] = &[
+    (BRANCH, "P_BRANCH"),
+    (LEAF, "P_LEAF"),
+    (OVERFLOW, "P_OVERFLOW"),
+    (META, "P_META"),
+    (0x10, "P_DIRTY"),
+    (LEAF2, "P_LEAF2"),
+    (0x40, "P_SUBP"),
+    (0x4000, "P_LOOSE"),
+    (0x8000, "P_KEEP"),
+];
+
+/// Names of the node flags, from `mdb.c`
+const NODE_FLAG_NAMES: &[(u16, &str)] = &[
+    (0x01, "F_BIGDATA"),
+    (0x02, "F_SUBDATA"),
+    (0x04, "F_DUPDATA"),
+];
+
+/// What a page holds, from its flags
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageKind {
+    Branch,
+    Leaf,
+    /// Leaf of the fixed size values of a `DUP_FIXED` database, which are
+    /// packed without node headers
+    Leaf2,
+    /// First page of a value too large for a leaf
+    Overflow,
+    Meta,
+    Unknown,
+}
+
+/// A page of a data file, read as is, without following anything it points
+/// to.
+#[derive(Debug, Clone)]
+pub struct Page {
+    bytes: Vec<u8>,
+}
+
+impl Page {
+    /// Reads page `number` of the data file at `path`, which has pages of
+    /// `page_size` bytes.
+    pub fn read(path: &Path, page_size: u64, number: u64) -> Result<Self> {
+        anyhow::ensure!(page_size as usize > HEADER_LEN, "Invalid page size");
+
+        let mut file = File::open(path)?;
+        let n_pages = file.metadata()?.len() / page_size;
+        anyhow::ensure!(
+            number < n_pages,
+            "Page {} is past the end of the data file, which has {} pages",
+            number,
+            n_pages
+        );
+
+        let mut bytes = vec![0; page_size as usize];
+        file.seek(SeekFrom::Start(number * page_size))?;
+        file.read_exact(&mut bytes)?;
+
+        Ok(Self { bytes })
+    }
+
+    /// Page number in the header, which is that of the page unless the page
+    /// is corrupted
+    pub fn number(&self) -> u64 {
+        read_usize(&self.bytes, 0)
+    }
+
+    pub fn flags(&self) -> u16 {
+        read_u16(&self.bytes, size_of::<usize>() + 2)
+    }
+
+    /// Names of the set flags, with unknown bits in hexadecimal.
+    pub fn flag_names(&self) -> Vec<String> {
+        flag_names(self.flags(), FLAG_NAMES)
+    }
+
+    pub fn kind(&self) -> PageKind {
+        let flags = self.flags();
+        if flags & META != 0 {
+            PageKind::Meta
+        } else if flags & OVERFLOW != 0 {
+            PageKind::Overflow
+        } else if flags & BRANCH != 0 {
+            PageKind::Branch
+        } else if flags & LEAF2 != 0 {
+            PageKind::Leaf2
+        } else if flags & LEAF != 0 {
+            PageKind::Leaf
+        } else {
+            PageKind::Unknown
+        }
+    }
+
+    /// Offset of the end of the node pointers, or of the packed keys of
+    /// [`PageKind::Leaf2`] pages
+    pub fn lower(&self) -> u16 {
+        read_u16(&self.bytes, size_of::<usize>() + 4)
+    }
+
+    /// Offset of the start of the nodes, which are laid out from the end
+    pub fn upper(&self) -> u16 {
+        read_u16(&self.bytes, size_of::<usize>() + 6)
+    }
+
+    /// Bytes between the node pointers and the nodes
+    pub fn free_space(&self) -> u16 {
+        self.upper().saturating_sub(self.lower())
+    }
+
+    /// Number of pages the value of an overflow page spans, including it
+    pub fn n_overflow_pages(&self) -> u32 {
+        read_u32(&self.bytes, size_of::<usize>() + 4)
+    }
+
+    /// Size of the keys of [`PageKind::Leaf2`] pages, which LMDB keeps in the
+    /// header's padding
+    pub fn leaf2_key_size(&self) -> u16 {
+        read_u16(&self.bytes, size_of::<usize>())
+    }
+
+    pub fn n_keys(&self) -> usize {
+        match self.kind() {
+            PageKind::Branch | PageKind::Leaf | PageKind::Leaf2 => {
+                (self.lower() as usize)
+                    .min(self.bytes.len())
+                    .saturating_sub(HEADER_LEN)
+                    / 2
+            }
+            PageKind::Overflow | PageKind::Meta | PageKind::Unknown => 0,
+        }
+    }
+
+    /// Returns the nodes of a branch or leaf page in order, or the keys of a
+    /// [`PageKind::Leaf2`] page, located through the pointers after the
+    /// header.
+    pub fn nodes(&self) -> Result<Vec<Node<'_>>> {
+        if self.kind() == PageKind::Leaf2 {
+            let key_size = self.leaf2_key_size() as usize;
+            return (0..self.n_keys())
+                .map(|index| {
+                    let offset = HEADER_LEN + index * key_size;
+                    let key = self
+                        .bytes
+                        .get(offset..offset + key_size)
+                        .with_context(|| format!("Key {} is past the end of the page", index))?;
+                    Ok(Node {
+                        offset,
+                        flags: 0,
+                        key,
+                        value: NodeValue::None,
+                    })
+                })
+                .collect();
+        }
+
+        (0..self.n_keys())
+            .map(|index| {
+                let offset = read_u16(&self.bytes, HEADER_LEN + index * 2) as usize;
+                self.node_at(offset)
+                    .with_context(|| format!("Invalid node {} at {}", index, offset))
+            })
+            .collect()
+    }
+
+    fn node_at(&self, offset: usize) -> Result<Node<'_>> {
+        let header = self
+            .bytes
+            .get(offset..offset + NODE_HEADER_LEN)
+            .context("Node header is past the end of the page")?;
+        // lo and hi are in host byte order, so together they read as a u32
+        let lo_hi = read_u32(header, 0) as u64;
+        let flags = read_u16(header, 4);
+        let key_size = read_u16(header, 6) as usize;
+
+        let key_start = offset + NODE_HEADER_LEN;
+        let key = self
+            .bytes
+            .get(key_start..key_start + key_size)
+            .context("Key is past the end of the page")?;
+
+        let data_start = key_start + key_size;
+        let value = if self.kind() == PageKind::Branch {
+            // Branch nodes have no flags, so they hold the top bits instead
+            let high = if size_of::<usize>() > 4 {
+                (flags as u64) << 32
+            } else {
+                0
+            };
+            NodeValue::Child(lo_hi | high)
+        } else if flags & 0x01 != 0 {
+            let page = read_usize(
+                self.bytes
+                    .get(data_start..data_start + size_of::<usize>())
+                    .context("Overflow page number is past the end of the page")?,
+                0,
+            );
+            NodeValue::Overflow {
+                page,
+                len: lo_hi as u32,
+            }
+        } else {
+            let data = self
+                .bytes
+                .get(data_start..data_start + lo_hi as usize)
+                .context("Data is past the end of the page")?;
+            NodeValue::Data(data)
+        };
+
+        Ok(Node {
+            offset,
+            flags: if self.kind() == PageKind::Branch {
+                0
+            } else {
+                flags
+            },
+            key,
+            value,
+        })
+    }
+}
+
+/// A key of a page, with what it points to.
+#[derive(Debug, Clone)]
+pub struct Node<'a> {
+    /// Offset of the node in the page
+    pub offset: usize,
+    pub flags: u16,
+    pub key: &'a [u8],
+    pub value: NodeValue<'a>,
+}
+
+impl Node<'_> {
+    /// Names of the set flags, with unknown bits in hexadecimal.
+    pub fn flag_names(&self) -> Vec<String> {
+        flag_names(self.flags, NODE_FLAG_NAMES)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum NodeValue<'a> {
+    /// Page number of the child of a branch node
+    Child(u64),
+    /// Data stored in the node, which is a database record if
+    /// `F_SUBDATA` is set
+    Data(&'a [u8]),
+    /// Data stored on overflow pages starting at `page`
+    Overflow { page: u64, len: u32 },
+    /// Nothing, for the keys of [`PageKind::Leaf2`] pages
+    None,
+}
+
+fn flag_names(flags: u16, names: &[(u16, &str)]) -> Vec<String> {
+    let mut flag_names = names
+        .iter()
+        .filter(|(flag, _)| flags & flag != 0)
+        .map(|(_, name)| name.to_string())
+        .collect::<Vec<_>>();
+    let unknown = names.iter().fold(flags, |flags, (flag, _)| flags & !flag);
+    if unknown != 0 {
+        flag_names.push(format!("{:#06x}", unknown));
+    }
+    flag_names
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_ne_bytes(bytes[offset..offset + 2].try_into().unwrap())
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_ne_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_usize(bytes: &[u8], offset: usize) -> u64 {
+    usize::from_ne_bytes(
+        bytes[offset..offset + size_of::<usize>()]
+            .try_into()
+            .unwrap(),
+    ) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::utils;
+
+    const PAGE_SIZE: usize = 256;
+
+    fn set_u16(bytes: &mut [u8], offset: usize, value: u16) {
+        bytes[offset..offset + 2].copy_from_slice(&value.to_ne_bytes());
+    }
+
+    /// Returns page 3 with `flags`, holding nodes with the given flags, keys,
+    /// and data, which are laid out from the end as LMDB does.
+    fn page(flags: u16, nodes: &[(u16, &[u8], &[u8])]) -> Page {
+        let mut bytes = vec![0; PAGE_SIZE];
+        bytes[..size_of::<usize>()].copy_from_slice(&3usize.to_ne_bytes());
+        set_u16(&mut bytes, size_of::<usize>() + 2, flags);
+
+        let mut upper = PAGE_SIZE;
+        for (index, (node_flags, key, data)) in nodes.iter().enumerate() {
+            upper -= NODE_HEADER_LEN + key.len() + data.len();
+            bytes[upper..upper + 4].copy_from_slice(&(data.len() as u32).to_ne_bytes());
+            set_u16(&mut bytes, upper + 4, *node_flags);
+            set_u16(&mut bytes, upper + 6, key.len() as u16);
+            let key_start = upper + NODE_HEADER_LEN;
+            bytes[key_start..key_start + key.len()].copy_from_slice(key);
+            bytes[key_start + key.len()..key_start + key.len() + data.len()].copy_from_slice(data);
+            set_u16(&mut bytes, HEADER_LEN + index * 2, upper as u16);
+        }
+        set_u16(
+            &mut bytes,
+            size_of::<usize>() + 4,
+            (HEADER_LEN + nodes.len() * 2) as u16,
+        );
+        set_u16(&mut bytes, size_of::<usize>() + 6, upper as u16);
+
+        Page { bytes }
+    }
+
+    fn node_offset(page: &Page, index: usize) -> usize {
+        read_u16(&page.bytes, HEADER_LEN + index * 2) as usize
+    }
+
+    #[test]
+    fn reads_leaf_nodes() {
+        let page = page(LEAF, &[(0, b"a", b"1"), (0x04, b"bc", b"234")]);
+        assert_eq!(page.number(), 3);
+        assert_eq!(page.kind(), PageKind::Leaf);
+        assert_eq!(page.flag_names(), ["P_LEAF"]);
+        assert_eq!(page.n_keys(), 2);
+        assert_eq!(
+            page.free_space() as usize,
+            PAGE_SIZE - 2 * NODE_HEADER_LEN - 7 - (HEADER_LEN + 4)
+        );
+
+        let nodes = page.nodes().unwrap();
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[0].key, b"a");
+        assert!(matches!(nodes[0].value, NodeValue::Data(b"1")));
+        assert_eq!(nodes[1].key, b"bc");
+        assert!(matches!(nodes[1].value, NodeValue::Data(b"234")));
+        assert_eq!(nodes[1].flag_names(), ["F_DUPDATA"]);
+    }
+
+    #[test]
+    fn reads_overflow_nodes() {
+        let page = page(LEAF, &[(0x01, b"k", &42usize.to_ne_bytes())]);
+        let nodes = page.nodes().unwrap();
+        assert!(matches!(
+            nodes[0].value,
+            NodeValue::Overflow { page: 42, len } if len as usize == size_of::<usize>()
+        ));
+        assert_eq!(nodes[0].flag_names(), ["F_BIGDATA"]);
+    }
+
+    #[test]
+    fn reads_branch_children() {
+        // Branch nodes keep the top bits of the child page number in their
+        // flags
+        let mut page = page(BRANCH, &[(0x0001, b"k", b"")]);
+        let offset = node_offset(&page, 0);
+        page.bytes[offset..offset + 4].copy_from_slice(&5u32.to_ne_bytes());
+
+        let nodes = page.nodes().unwrap();
+        let expected = if size_of::<usize>() > 4 {
+            1 << 32 | 5
+        } else {
+            5
+        };
+        assert!(matches!(nodes[0].value, NodeValue::Child(child) if child == expected));
+        assert_eq!(nodes[0].flags, 0);
+    }
+
+    #[test]
+    fn reads_leaf2_keys() {
+        let mut page = page(LEAF2, &[]);
+        set_u16(&mut page.bytes, size_of::<usize>(), 3);
+        set_u16(
+            &mut page.bytes,
+            size_of::<usize>() + 4,
+            (HEADER_LEN + 2 * 2) as u16,
+        );
+        page.bytes[HEADER_LEN..HEADER_LEN + 6].copy_from_slice(b"abcdef");
+
+        assert_eq!(page.kind(), PageKind::Leaf2);
+        let keys = page
+            .nodes()
+            .unwrap()
+            .into_iter()
+            .map(|node| node.key)
+            .collect::<Vec<_>>();
+        assert_eq!(keys, [b"abc", b"def"]);
+    }
+
+    #[test]
+    fn reads_page_kinds_and_unknown_flags() {
+        let mut overflow = page(OVERFLOW, &[]);
+        overflow.bytes[size_of::<usize>() + 4..size_of::<usize>() + 8]
+            .copy_from_slice(&7u32.to_ne_bytes());
+        assert_eq!(overflow.kind(), PageKind::Overflow);
+        assert_eq!(overflow.n_overflow_pages(), 7);
+        assert_eq!(overflow.n_keys(), 0);
+
+        assert_eq!(page(META, &[]).kind(), PageKind::Meta);
+        let unknown = page(0x0100, &[]);
+        assert_eq!(unknown.kind(), PageKind::Unknown);
+        assert_eq!(unknown.flag_names(), ["0x0100"]);
+        assert!(unknown.nodes().unwrap().is_empty());
+    }
+
+    #[test]
+    fn fails_on_nodes_past_the_end() {
+        let error_of = |page: &Page| format!("{:#}", page.nodes().unwrap_err());
+
+        let mut header_past_end = page(LEAF, &[(0, b"a", b"1")]);
+        set_u16(
+            &mut header_past_end.bytes,
+            HEADER_LEN,
+            (PAGE_SIZE - 4) as u16,
+        );
+        assert!(error_of(&header_past_end).contains("Node header is past the end"));
+
+        let mut key_past_end = page(LEAF, &[(0, b"a", b"1")]);
+        let offset = node_offset(&key_past_end, 0);
+        set_u16(&mut key_past_end.bytes, offset + 6, PAGE_SIZE as u16);
+        assert!(error_of(&key_past_end).contains("Key is past the end"));
+
+        let mut data_past_end = page(LEAF, &[(0, b"a", b"1")]);
+        let offset = node_offset(&data_past_end, 0);
+        data_past_end.bytes[offset..offset + 4].copy_from_slice(&(PAGE_SIZE as u32).to_ne_bytes());
+        assert!(error_of(&data_past_end).contains("Data is past the end"));
+
+        // The node ends with the page, leaving no room for the page number
+        let overflow_past_end = page(LEAF, &[(0x01, b"k", b"")]);
+        assert!(error_of(&overflow_past_end).contains("Overflow page number is past the end"));
+
+        let mut leaf2_past_end = page(LEAF2, &[]);
+        set_u16(
+            &mut leaf2_past_end.bytes,
+            size_of::<usize>(),
+            PAGE_SIZE as u16,
+        );
+        set_u16(
+            &mut leaf2_past_end.bytes,
+            size_of::<usize>() + 4,
+            (HEADER_LEN + 2) as u16,
+        );
+        assert!(error_of(&leaf2_past_end).contains("Key 0 is past the end"));
+    }
+
+    #[test]
+    fn fails_to_read_truncated_files() {
+        let dir = utils::write_to_temp_dir(&[0; PAGE_SIZE + 10], "data.mdb").unwrap();
+        let path = dir.join("data.mdb");
+
+        assert!(Page::read(&path, PAGE_SIZE as u64, 0).is_ok());
+        let error = Page::read(&path, PAGE_SIZE as u64, 1).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Page 1 is past the end of the data file, which has 1 pages"
+        );
+        assert!(Page::read(&path, HEADER_LEN as u64, 0).is_err());
+    }
+}
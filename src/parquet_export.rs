@@ -0,0 +1,134 @@
+use anyhow::{Context, Result};
+use heed::types::ByteSlice;
+use parquet::{
+    basic::Compression,
+    data_type::{ByteArray, ByteArrayType, DataType, Int64Type},
+    file::{
+        properties::WriterProperties,
+        writer::{SerializedFileWriter, SerializedRowGroupWriter},
+    },
+    schema::parser::parse_message_type,
+};
+
+use std::{
+    fs,
+    path::Path,
+    sync::{
+        atomic::{self, AtomicUsize},
+        Arc,
+    },
+};
+
+use crate::{decoder, snapshot::Snapshot};
+
+/// Number of rows per row group, which are written one at a time
+const ROW_GROUP_LEN: usize = 50_000;
+
+const SCHEMA: &str = "
+message entry {
+    required binary key;
+    required binary value;
+    required int64 key_len;
+    required int64 value_len;
+}";
+
+const SCHEMA_WITH_DECODED: &str = "
+message entry {
+    required binary key;
+    required binary value;
+    required int64 key_len;
+    required int64 value_len;
+    optional binary decoded (STRING);
+}";
+
+/// Writes the entries of the database called `db_name` to a Parquet file at
+/// `path`, with the raw key and value and their lengths as columns.
+///
+/// If `include_decoded` is set, a `decoded` column holds each value as JSON,
+/// decoded with the built-in decoder of its detected format, or null if it
+/// has none or fails to decode. `n_written` is incremented after each row
+/// group, so progress can be shown.
+pub fn export(
+    snapshot: &Snapshot,
+    db_name: Option<&str>,
+    path: &Path,
+    include_decoded: bool,
+    n_written: &AtomicUsize,
+) -> Result<()> {
+    let schema = parse_message_type(if include_decoded {
+        SCHEMA_WITH_DECODED
+    } else {
+        SCHEMA
+    })?;
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+
+    let file =
+        fs::File::create(path).with_context(|| format!("Failed to create `{}`", path.display()))?;
+    let mut writer = SerializedFileWriter::new(file, Arc::new(schema), Arc::new(properties))?;
+
+    snapshot.read(|env, rtxn| {
+        let db = env
+            .open_database::<ByteSlice, ByteSlice>(rtxn, db_name)?
+            .context("Database not found")?;
+
+        let mut entries = db.iter(rtxn)?.peekable();
+        while entries.peek().is_some() {
+            let mut keys = Vec::new();
+            let mut values = Vec::new();
+            let mut key_lens = Vec::new();
+            let mut value_lens = Vec::new();
+            let mut decoded = Vec::new();
+            let mut decoded_def_levels = Vec::new();
+
+            for item in entries.by_ref().take(ROW_GROUP_LEN) {
+                let (key, value) = item?;
+
+                keys.push(ByteArray::from(key.to_vec()));
+                values.push(ByteArray::from(value.to_vec()));
+                key_lens.push(key.len() as i64);
+                value_lens.push(value.len() as i64);
+
+                if include_decoded {
+                    // Nulls are only marked by a definition level of 0
+                    match decoder::decode_detected(value) {
+                        Some(json) => {
+                            decoded.push(ByteArray::from(json.to_string().into_bytes()));
+                            decoded_def_levels.push(1);
+                        }
+                        None => decoded_def_levels.push(0),
+                    }
+                }
+            }
+
+            let mut row_group = writer.next_row_group()?;
+            write_column::<ByteArrayType>(&mut row_group, &keys, None)?;
+            write_column::<ByteArrayType>(&mut row_group, &values, None)?;
+            write_column::<Int64Type>(&mut row_group, &key_lens, None)?;
+            write_column::<Int64Type>(&mut row_group, &value_lens, None)?;
+            if include_decoded {
+                write_column::<ByteArrayType>(&mut row_group, &decoded, Some(&decoded_def_levels))?;
+            }
+            row_group.close()?;
+
+            n_written.fetch_add(keys.len(), atomic::Ordering::Relaxed);
+        }
+
+        writer.close()?;
+
+        Ok(())
+    })
+}
+
+/// Writes `values` as the next column of `row_group`.
+fn write_column<T: DataType>(
+    row_group: &mut SerializedRowGroupWriter<'_, fs::File>,
+    values: &[T::T],
+    def_levels: Option<&[i16]>,
+) -> Result<()> {
+    let mut column = row_group.next_column()?.context("Missing column")?;
+    column.typed::<T>().write_batch(values, def_levels, None)?;
+    column.close()?;
+    Ok(())
+}
@@ -0,0 +1,199 @@
+use anyhow::{Context, Result};
+use gtk::glib;
+use heed::types::ByteSlice;
+use serde_json::{json, Value};
+
+use std::{
+    fmt::Write,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use crate::{config::APP_ID, edit_history::Edit, import::FieldEncoding};
+
+/// An edit that is staged rather than written right away.
+///
+/// `duplicate` is the value of a DUPSORT key that is replaced or deleted,
+/// where `None` puts another duplicate or deletes all of them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PendingChange {
+    Put {
+        key: Vec<u8>,
+        value: Vec<u8>,
+        duplicate: Option<Vec<u8>>,
+    },
+    Delete {
+        key: Vec<u8>,
+        duplicate: Option<Vec<u8>>,
+    },
+}
+
+impl PendingChange {
+    pub fn key(&self) -> &[u8] {
+        match self {
+            Self::Put { key, .. } | Self::Delete { key, .. } => key,
+        }
+    }
+
+    pub fn duplicate(&self) -> Option<&[u8]> {
+        match self {
+            Self::Put { duplicate, .. } | Self::Delete { duplicate, .. } => duplicate.as_deref(),
+        }
+    }
+}
+
+/// Edits staged across the databases of an env, in the order they were made.
+#[derive(Debug, Default)]
+pub struct PendingChanges {
+    /// Changes with the name of the database they apply to
+    changes: Vec<(Option<String>, PendingChange)>,
+}
+
+impl PendingChanges {
+    pub fn push(&mut self, db_name: Option<&str>, change: PendingChange) {
+        self.changes
+            .push((db_name.map(|name| name.to_string()), change));
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Option<&str>, &PendingChange)> {
+        self.changes
+            .iter()
+            .map(|(db_name, change)| (db_name.as_deref(), change))
+    }
+
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.changes.clear();
+    }
+
+    /// Applies all changes to `env` in a single write txn, so either all or
+    /// none of them are written. Returns the edit so it can be undone.
+    pub fn commit(&self, env: &heed::Env) -> Result<Edit> {
+        let mut wtxn = env.write_txn().context("Failed to create write txn")?;
+        let mut edit = Edit::default();
+        for (db_name, change) in &self.changes {
+            let db = env
+                .open_database::<ByteSlice, ByteSlice>(&wtxn, db_name.as_deref())?
+                .with_context(|| format!("Database `{:?}` not found", db_name))?;
+            let value = match change {
+                PendingChange::Put { value, .. } => Some(value.as_slice()),
+                PendingChange::Delete { .. } => None,
+            };
+            match change.duplicate() {
+                Some(duplicate) => edit.write_duplicate(
+                    &mut wtxn,
+                    db,
+                    db_name.as_deref(),
+                    change.key(),
+                    duplicate,
+                    value,
+                )?,
+                None => edit.write(&mut wtxn, db, db_name.as_deref(), change.key(), value)?,
+            }
+        }
+        wtxn.commit().context("Failed to commit write txn")?;
+
+        Ok(edit)
+    }
+
+    /// Saves the changes as the ones staged for the env at `env_path`, so
+    /// they can be restored if the app closes before they are committed.
+    /// Saving no changes removes the saved ones.
+    pub fn save(&self, env_path: &Path) -> Result<()> {
+        let path = file_path(env_path);
+
+        if self.changes.is_empty() {
+            return match fs::remove_file(&path) {
+                Err(err) if err.kind() != ErrorKind::NotFound => Err(err.into()),
+                _ => Ok(()),
+            };
+        }
+
+        let changes = self
+            .changes
+            .iter()
+            .map(|(db_name, change)| {
+                let value = match change {
+                    PendingChange::Put { value, .. } => Some(to_hex(value)),
+                    PendingChange::Delete { .. } => None,
+                };
+                json!({
+                    "db": db_name,
+                    "key": to_hex(change.key()),
+                    "value": value,
+                    "duplicate": change.duplicate().map(to_hex),
+                })
+            })
+            .collect::<Vec<_>>();
+        let saved = json!({
+            "env_path": env_path.to_string_lossy(),
+            "changes": changes,
+        });
+
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(&path, serde_json::to_vec_pretty(&saved)?)?;
+
+        Ok(())
+    }
+
+    /// Loads the changes saved for the env at `env_path`, which are empty if
+    /// none were saved.
+    pub fn load_saved(env_path: &Path) -> Result<Self> {
+        let bytes = match fs::read(file_path(env_path)) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => return Err(err.into()),
+        };
+        let saved = serde_json::from_slice::<Value>(&bytes)?;
+
+        let mut this = Self::default();
+        for change in saved["changes"].as_array().context("No changes")? {
+            let db_name = change["db"].as_str();
+            let key = FieldEncoding::Hex.decode(change["key"].as_str().context("No key")?)?;
+            let duplicate = change["duplicate"]
+                .as_str()
+                .map(|duplicate| FieldEncoding::Hex.decode(duplicate))
+                .transpose()?;
+            let change = match change["value"].as_str() {
+                Some(value) => PendingChange::Put {
+                    key,
+                    value: FieldEncoding::Hex.decode(value)?,
+                    duplicate,
+                },
+                None => PendingChange::Delete { key, duplicate },
+            };
+            this.push(db_name, change);
+        }
+
+        Ok(this)
+    }
+}
+
+/// Returns the path of the file the changes staged for the env at
+/// `env_path` are saved to.
+fn file_path(env_path: &Path) -> PathBuf {
+    let env_hash = glib::compute_checksum_for_data(
+        glib::ChecksumType::Sha256,
+        env_path.to_string_lossy().as_bytes(),
+    )
+    .unwrap();
+    glib::user_data_dir()
+        .join(APP_ID)
+        .join("pending-changes")
+        .join(format!("{}.json", env_hash))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut hex, byte| {
+        write!(hex, "{:02x}", byte).unwrap();
+        hex
+    })
+}
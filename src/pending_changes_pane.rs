@@ -0,0 +1,72 @@
+use adw::prelude::*;
+use gettextrs::gettext;
+use gtk::{glib, subclass::prelude::*};
+
+use crate::{
+    diff::DiffKind,
+    pending_changes::{PendingChange, PendingChanges},
+};
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, gtk::CompositeTemplate)]
+    #[template(resource = "/io/github/seadve/LmdbViewer/ui/pending_changes_pane.ui")]
+    pub struct PendingChangesPane {
+        #[template_child]
+        pub(super) list_box: TemplateChild<gtk::ListBox>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for PendingChangesPane {
+        const NAME: &'static str = "LvPendingChangesPane";
+        type Type = super::PendingChangesPane;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for PendingChangesPane {
+        fn dispose(&self) {
+            self.dispose_template();
+        }
+    }
+
+    impl WidgetImpl for PendingChangesPane {}
+}
+
+glib::wrapper! {
+    pub struct PendingChangesPane(ObjectSubclass<imp::PendingChangesPane>)
+        @extends gtk::Widget;
+}
+
+impl PendingChangesPane {
+    /// Lists `changes`, displaying their keys with `key_text`.
+    pub fn update(&self, changes: &PendingChanges, key_text: impl Fn(&[u8]) -> String) {
+        let imp = self.imp();
+
+        imp.list_box.remove_all();
+
+        for (db_name, change) in changes.iter() {
+            let (kind, kind_label) = match change {
+                PendingChange::Put { .. } => (DiffKind::Changed, gettext("Put")),
+                PendingChange::Delete { .. } => (DiffKind::Removed, gettext("Delete")),
+            };
+            let db_name = db_name.map_or_else(|| gettext("Unnamed"), |name| name.to_string());
+
+            let row = adw::ActionRow::builder()
+                .title(key_text(change.key()))
+                .subtitle(format!("{} · {}", db_name, kind_label))
+                .use_markup(false)
+                .build();
+            row.add_css_class(kind.css_class().unwrap());
+            imp.list_box.append(&row);
+        }
+    }
+}
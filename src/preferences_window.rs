@@ -0,0 +1,265 @@
+use adw::{prelude::*, subclass::prelude::*};
+use gtk::{gio, glib, pango};
+
+use crate::config::APP_ID;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, gtk::CompositeTemplate)]
+    #[template(resource = "/io/github/seadve/LmdbViewer/ui/preferences_window.ui")]
+    pub struct PreferencesWindow {
+        #[template_child]
+        pub(super) monospace_font_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub(super) preview_length_row: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        pub(super) max_cell_lines_row: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        pub(super) page_size_row: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        pub(super) key_display_mode_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub(super) non_printable_display_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub(super) light_color_scheme_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub(super) dark_color_scheme_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub(super) key_separator_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub(super) use_system_font_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub(super) custom_font_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub(super) custom_font_button: TemplateChild<gtk::FontDialogButton>,
+        #[template_child]
+        pub(super) value_wrap_mode_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub(super) tab_width_row: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        pub(super) show_whitespace_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub(super) auto_reload_interval_row: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        pub(super) snapshot_renewal_interval_row: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        pub(super) monitor_interval_row: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        pub(super) confirm_delete_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub(super) open_in_write_mode_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub(super) open_no_lock_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub(super) open_no_read_ahead_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub(super) show_advanced_tools_row: TemplateChild<adw::SwitchRow>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for PreferencesWindow {
+        const NAME: &'static str = "LvPreferencesWindow";
+        type Type = super::PreferencesWindow;
+        type ParentType = adw::PreferencesWindow;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for PreferencesWindow {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let settings = gio::Settings::new(APP_ID);
+            settings
+                .bind("monospace-font", &*self.monospace_font_row, "active")
+                .build();
+            settings
+                .bind("preview-length", &*self.preview_length_row, "value")
+                .build();
+            settings
+                .bind("max-cell-lines", &*self.max_cell_lines_row, "value")
+                .build();
+            settings
+                .bind("page-size", &*self.page_size_row, "value")
+                .build();
+            bind_combo_row(
+                &settings,
+                "key-display-mode",
+                &self.key_display_mode_row,
+                &["auto", "text", "little-endian", "big-endian"],
+            );
+            bind_combo_row(
+                &settings,
+                "non-printable-display",
+                &self.non_printable_display_row,
+                &["escape", "control-pictures", "hex"],
+            );
+            for (key, row) in [
+                ("light-color-scheme", &self.light_color_scheme_row),
+                ("dark-color-scheme", &self.dark_color_scheme_row),
+            ] {
+                bind_style_scheme_row(&settings, key, row);
+            }
+            settings
+                .bind("key-separator", &*self.key_separator_row, "text")
+                .build();
+            settings
+                .bind("use-system-font", &*self.use_system_font_row, "active")
+                .build();
+            settings
+                .bind("use-system-font", &*self.custom_font_row, "sensitive")
+                .invert_boolean()
+                .build();
+            settings
+                .bind("custom-font", &*self.custom_font_button, "font-desc")
+                .mapping(|variant, _| {
+                    let font_desc = pango::FontDescription::from_string(variant.str()?);
+                    Some(font_desc.to_value())
+                })
+                .set_mapping(|value, _| {
+                    let font_desc = value.get::<pango::FontDescription>().ok()?;
+                    Some(font_desc.to_str().to_variant())
+                })
+                .build();
+            bind_combo_row(
+                &settings,
+                "value-wrap-mode",
+                &self.value_wrap_mode_row,
+                &["none", "char", "word", "word-char"],
+            );
+            settings
+                .bind("tab-width", &*self.tab_width_row, "value")
+                .build();
+            settings
+                .bind("show-whitespace", &*self.show_whitespace_row, "active")
+                .build();
+            settings
+                .bind(
+                    "auto-reload-interval",
+                    &*self.auto_reload_interval_row,
+                    "value",
+                )
+                .build();
+            settings
+                .bind(
+                    "snapshot-renewal-interval",
+                    &*self.snapshot_renewal_interval_row,
+                    "value",
+                )
+                .build();
+            settings
+                .bind("monitor-interval", &*self.monitor_interval_row, "value")
+                .build();
+            settings
+                .bind("confirm-delete", &*self.confirm_delete_row, "active")
+                .build();
+            settings
+                .bind(
+                    "open-in-write-mode",
+                    &*self.open_in_write_mode_row,
+                    "active",
+                )
+                .build();
+            settings
+                .bind("open-no-lock", &*self.open_no_lock_row, "active")
+                .build();
+            settings
+                .bind(
+                    "open-no-read-ahead",
+                    &*self.open_no_read_ahead_row,
+                    "active",
+                )
+                .build();
+            settings
+                .bind(
+                    "show-advanced-tools",
+                    &*self.show_advanced_tools_row,
+                    "active",
+                )
+                .build();
+        }
+    }
+
+    impl WidgetImpl for PreferencesWindow {}
+    impl WindowImpl for PreferencesWindow {}
+    impl AdwWindowImpl for PreferencesWindow {}
+    impl PreferencesWindowImpl for PreferencesWindow {}
+}
+
+glib::wrapper! {
+    pub struct PreferencesWindow(ObjectSubclass<imp::PreferencesWindow>)
+        @extends gtk::Widget, gtk::Window, adw::Window, adw::PreferencesWindow;
+}
+
+impl PreferencesWindow {
+    pub fn new(transient_for: &impl IsA<gtk::Window>) -> Self {
+        glib::Object::builder()
+            .property("transient-for", transient_for)
+            .build()
+    }
+}
+
+/// Lists the installed style schemes in `row`, binding the selected one to
+/// `key`, which holds the id of a scheme.
+fn bind_style_scheme_row(settings: &gio::Settings, key: &str, row: &adw::ComboRow) {
+    let manager = sourceview::StyleSchemeManager::default();
+    let schemes = manager
+        .scheme_ids()
+        .iter()
+        .filter_map(|id| manager.scheme(id))
+        .collect::<Vec<_>>();
+    let names = schemes
+        .iter()
+        .map(|scheme| scheme.name())
+        .collect::<Vec<_>>();
+    row.set_model(Some(&gtk::StringList::new(
+        &names.iter().map(|name| name.as_str()).collect::<Vec<_>>(),
+    )));
+
+    let ids = schemes
+        .iter()
+        .map(|scheme| scheme.id().to_string())
+        .collect::<Vec<_>>();
+    let set_ids = ids.clone();
+    settings
+        .bind(key, row, "selected")
+        .mapping(move |variant, _| {
+            let id = variant.str()?;
+            let position = ids.iter().position(|other| other == id)?;
+            Some((position as u32).to_value())
+        })
+        .set_mapping(move |value, _| {
+            let position = value.get::<u32>().ok()?;
+            set_ids.get(position as usize).map(|id| id.to_variant())
+        })
+        .build();
+}
+
+/// Binds the selected item of `row` to an enum `key`, where `nicks` are the
+/// enum values in the order of the row's items.
+fn bind_combo_row(
+    settings: &gio::Settings,
+    key: &str,
+    row: &adw::ComboRow,
+    nicks: &'static [&'static str],
+) {
+    settings
+        .bind(key, row, "selected")
+        .mapping(move |variant, _| {
+            let nick = variant.str()?;
+            let position = nicks.iter().position(|n| *n == nick)?;
+            Some((position as u32).to_value())
+        })
+        .set_mapping(move |value, _| {
+            let position = value.get::<u32>().ok()?;
+            nicks.get(position as usize).map(|nick| nick.to_variant())
+        })
+        .build();
+}
@@ -0,0 +1,233 @@
+use gettextrs::gettext;
+use gtk::{
+    cairo,
+    glib::{self, clone},
+    prelude::*,
+};
+
+use std::{cell::Cell, rc::Rc};
+
+use crate::utils::gettext_f;
+
+const FONT_FAMILY: &str = "Sans";
+const FONT_SIZE: f64 = 9.0;
+const TITLE_FONT_SIZE: f64 = 14.0;
+
+/// Height of a line of text, including the spacing below it
+const LINE_HEIGHT: f64 = FONT_SIZE * 1.6;
+const TITLE_LINE_HEIGHT: f64 = TITLE_FONT_SIZE * 1.6;
+
+/// Space between the text of adjacent columns
+const COLUMN_SPACING: f64 = 8.0;
+
+/// Fractions of the page width taken by the key, value, key size, and value
+/// size columns
+const COLUMN_WIDTHS: [f64; 4] = [0.32, 0.48, 0.1, 0.1];
+
+/// Number of characters of a cell kept before fitting it to its column, as
+/// the rest wouldn't fit anyway
+const MAX_CELL_CHARS: usize = 500;
+
+/// An entry as printed in a report.
+#[derive(Debug, Clone)]
+pub struct ReportRow {
+    pub key: String,
+    pub value: String,
+    pub key_size: usize,
+    pub value_size: usize,
+}
+
+/// Rows of a view to print, under a header with the metadata of where they
+/// are from.
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub title: String,
+    /// Labels and values shown below the title of every page
+    pub metadata: Vec<(String, String)>,
+    pub rows: Vec<ReportRow>,
+}
+
+impl Report {
+    /// Creates an operation that prints the report, paginated to fit the
+    /// page size picked when it runs.
+    pub fn print_operation(self) -> gtk::PrintOperation {
+        let operation = gtk::PrintOperation::builder()
+            .job_name(&self.title)
+            .embed_page_setup(true)
+            .build();
+
+        let report = Rc::new(self);
+        let rows_per_page = Rc::new(Cell::new(1));
+        operation.connect_begin_print(
+            clone!(@strong report, @strong rows_per_page => move |operation, context| {
+                rows_per_page.set(report.rows_per_page(context.height()));
+                operation.set_n_pages(report.n_pages(rows_per_page.get()) as i32);
+            }),
+        );
+        operation.connect_draw_page(move |_, context, page| {
+            if let Err(err) = report.draw_page(context, page as usize, rows_per_page.get()) {
+                tracing::error!("Failed to draw page {}: {:?}", page, &err);
+            }
+        });
+
+        operation
+    }
+
+    fn header_height(&self) -> f64 {
+        // The metadata is followed by an empty line, then the column titles
+        TITLE_LINE_HEIGHT + LINE_HEIGHT * (self.metadata.len() + 2) as f64
+    }
+
+    fn rows_per_page(&self, page_height: f64) -> usize {
+        let footer_height = LINE_HEIGHT * 2.0;
+        let rows_height = page_height - self.header_height() - footer_height;
+        ((rows_height / LINE_HEIGHT) as usize).max(1)
+    }
+
+    fn n_pages(&self, rows_per_page: usize) -> usize {
+        self.rows.len().div_ceil(rows_per_page).max(1)
+    }
+
+    fn draw_page(
+        &self,
+        context: &gtk::PrintContext,
+        page: usize,
+        rows_per_page: usize,
+    ) -> Result<(), cairo::Error> {
+        let cr = context.cairo_context();
+        let width = context.width();
+        cr.set_source_rgb(0.0, 0.0, 0.0);
+
+        let mut y = TITLE_FONT_SIZE;
+        set_font(&cr, TITLE_FONT_SIZE, cairo::FontWeight::Bold);
+        show_text(&cr, 0.0, y, &self.title, width)?;
+        y += TITLE_LINE_HEIGHT;
+
+        for (label, value) in &self.metadata {
+            set_font(&cr, FONT_SIZE, cairo::FontWeight::Bold);
+            let label = format!("{}: ", label);
+            let label_width = cr.text_extents(&label)?.x_advance();
+            show_text(&cr, 0.0, y, &label, width)?;
+            set_font(&cr, FONT_SIZE, cairo::FontWeight::Normal);
+            show_text(&cr, label_width, y, value, width - label_width)?;
+            y += LINE_HEIGHT;
+        }
+        y += LINE_HEIGHT;
+
+        set_font(&cr, FONT_SIZE, cairo::FontWeight::Bold);
+        let titles = [
+            gettext("Key"),
+            gettext("Value"),
+            gettext("Key Size"),
+            gettext("Value Size"),
+        ];
+        self.show_row(&cr, width, y, titles.each_ref().map(String::as_str))?;
+        cr.set_line_width(0.5);
+        cr.move_to(0.0, y + FONT_SIZE * 0.4);
+        cr.line_to(width, y + FONT_SIZE * 0.4);
+        cr.stroke()?;
+        y += LINE_HEIGHT;
+
+        set_font(&cr, FONT_SIZE, cairo::FontWeight::Normal);
+        for row in self
+            .rows
+            .iter()
+            .skip(page * rows_per_page)
+            .take(rows_per_page)
+        {
+            let key_size = glib::format_size(row.key_size as u64);
+            let value_size = glib::format_size(row.value_size as u64);
+            self.show_row(
+                &cr,
+                width,
+                y,
+                [&row.key, &row.value, &key_size, &value_size],
+            )?;
+            y += LINE_HEIGHT;
+        }
+
+        let footer = gettext_f(
+            "Page {page} of {n_pages}",
+            &[
+                ("page", &(page + 1).to_string()),
+                ("n_pages", &self.n_pages(rows_per_page).to_string()),
+            ],
+        );
+        let footer_width = cr.text_extents(&footer)?.x_advance();
+        cr.move_to(width - footer_width, context.height() - FONT_SIZE * 0.4);
+        cr.show_text(&footer)?;
+
+        Ok(())
+    }
+
+    /// Shows the texts of the cells of a row with its baseline at `y`, with
+    /// the sizes aligned to the right.
+    fn show_row(
+        &self,
+        cr: &cairo::Context,
+        width: f64,
+        y: f64,
+        cells: [&str; 4],
+    ) -> Result<(), cairo::Error> {
+        let mut x = 0.0;
+        for (index, (cell, fraction)) in cells.into_iter().zip(COLUMN_WIDTHS).enumerate() {
+            let column_width = width * fraction;
+            let max_width = column_width - COLUMN_SPACING;
+            if index < 2 {
+                show_text(cr, x, y, cell, max_width)?;
+            } else {
+                let text = fit_text(cr, cell, max_width)?;
+                let text_width = cr.text_extents(&text)?.x_advance();
+                cr.move_to(x + column_width - text_width, y);
+                cr.show_text(&text)?;
+            }
+            x += column_width;
+        }
+        Ok(())
+    }
+}
+
+fn set_font(cr: &cairo::Context, size: f64, weight: cairo::FontWeight) {
+    cr.select_font_face(FONT_FAMILY, cairo::FontSlant::Normal, weight);
+    cr.set_font_size(size);
+}
+
+/// Shows `text` on one line with its baseline at `y`, ellipsized to fit in
+/// `max_width`.
+fn show_text(
+    cr: &cairo::Context,
+    x: f64,
+    y: f64,
+    text: &str,
+    max_width: f64,
+) -> Result<(), cairo::Error> {
+    cr.move_to(x, y);
+    cr.show_text(&fit_text(cr, text, max_width)?)
+}
+
+/// Returns `text` on one line, ellipsized so it is no wider than `max_width`.
+fn fit_text(cr: &cairo::Context, text: &str, max_width: f64) -> Result<String, cairo::Error> {
+    let chars = text
+        .chars()
+        .take(MAX_CELL_CHARS)
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect::<Vec<_>>();
+
+    let text = chars.iter().collect::<String>();
+    if cr.text_extents(&text)?.x_advance() <= max_width {
+        return Ok(text);
+    }
+
+    // Longest prefix that fits with the ellipsis
+    let (mut low, mut high) = (0, chars.len());
+    while low < high {
+        let mid = (low + high).div_ceil(2);
+        let candidate = chars[..mid].iter().chain(['…'].iter()).collect::<String>();
+        if cr.text_extents(&candidate)?.x_advance() <= max_width {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    Ok(chars[..low].iter().chain(['…'].iter()).collect())
+}
@@ -0,0 +1,892 @@
+use serde_json::Value;
+
+use std::{
+    cell::{Cell, OnceCell},
+    cmp::Ordering,
+    fmt,
+    iter::Peekable,
+    str::CharIndices,
+};
+
+/// A query that couldn't be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// Byte offset in the query where the problem was found
+    pub offset: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    fn new(offset: usize, message: impl Into<String>) -> Self {
+        Self {
+            offset,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at column {}", self.message, self.offset + 1)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Key,
+    Value,
+}
+
+impl Field {
+    fn get<'a>(self, key: &'a [u8], value: &'a [u8]) -> &'a [u8] {
+        match self {
+            Self::Key => key,
+            Self::Value => value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BytesOp {
+    Prefix,
+    Suffix,
+    Contains,
+    Eq,
+    Ne,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl CompareOp {
+    /// Returns whether operands ordered as `ordering`, or `None` if they
+    /// can't be ordered, satisfy this.
+    fn holds(self, ordering: Option<Ordering>) -> bool {
+        match (self, ordering) {
+            (Self::Ne, None) => true,
+            (_, None) => false,
+            (Self::Eq, Some(ordering)) => ordering.is_eq(),
+            (Self::Ne, Some(ordering)) => ordering.is_ne(),
+            (Self::Lt, Some(ordering)) => ordering.is_lt(),
+            (Self::Le, Some(ordering)) => ordering.is_le(),
+            (Self::Gt, Some(ordering)) => ordering.is_gt(),
+            (Self::Ge, Some(ordering)) => ordering.is_ge(),
+        }
+    }
+}
+
+/// A step of a path into a decoded value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathSegment {
+    /// The member with this name of an object
+    Member(String),
+    /// The element at this index of an array
+    Index(usize),
+    /// Every member of an object or element of an array
+    Wildcard,
+}
+
+/// What the values at a path of a decoded value are tested for.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodedTest {
+    Exists,
+    Compare(CompareOp, Value),
+    /// Whether a string has a substring, or an array has an element
+    Contains(Value),
+}
+
+/// A filter on entries, parsed from text such as
+/// `key prefix "user:" && value.len > 1024 && value contains "error"`.
+///
+/// Predicates compare the key or value bytes with a string, using `prefix`,
+/// `suffix`, `contains`, `==`, or `!=`, or their `.len` with a number, using
+/// `==`, `!=`, `<`, `<=`, `>`, or `>=`. They are combined with `!`, `&&`,
+/// `||`, and parentheses, where `&&` binds tighter than `||`. Strings accept
+/// `\"`, `\\`, `\n`, `\r`, `\t`, `\0`, and `\xNN` escapes.
+///
+/// Fields of the decoded value are referenced with JSONPath-like paths, such
+/// as `$.status == "failed"`, `$.tags[*] == "urgent"`, or `$["user id"]` to
+/// test that a field exists. A test holds if any value at the path passes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Query {
+    And(Box<Query>, Box<Query>),
+    Or(Box<Query>, Box<Query>),
+    Not(Box<Query>),
+    Bytes {
+        field: Field,
+        op: BytesOp,
+        operand: Vec<u8>,
+    },
+    Len {
+        field: Field,
+        op: CompareOp,
+        operand: usize,
+    },
+    Decoded {
+        path: Vec<PathSegment>,
+        test: DecodedTest,
+    },
+}
+
+impl Query {
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        let mut parser = Parser {
+            tokens: tokenize(text)?,
+            position: 0,
+            end: text.len(),
+        };
+        let query = parser.parse_or()?;
+        match parser.peek() {
+            None => Ok(query),
+            Some((offset, _)) => Err(ParseError::new(offset, "Expected “&&” or “||”")),
+        }
+    }
+
+    /// Returns whether the entry with `key` and `value` matches, where
+    /// `decode` returns the decoded value, or `None` if it can't be decoded.
+    ///
+    /// The value is only decoded if the query references its fields, and at
+    /// most once.
+    pub fn matches(
+        &self,
+        key: &[u8],
+        value: &[u8],
+        decode: impl FnOnce() -> Option<Value>,
+    ) -> bool {
+        let entry = Entry {
+            key,
+            value,
+            decoded: OnceCell::new(),
+            decode: Cell::new(Some(decode)),
+        };
+        self.eval(&entry)
+    }
+
+    /// Returns whether the query references fields of the decoded value.
+    pub fn tests_decoded_value(&self) -> bool {
+        match self {
+            Self::And(a, b) | Self::Or(a, b) => a.tests_decoded_value() || b.tests_decoded_value(),
+            Self::Not(query) => query.tests_decoded_value(),
+            Self::Bytes { .. } | Self::Len { .. } => false,
+            Self::Decoded { .. } => true,
+        }
+    }
+
+    fn eval<F: FnOnce() -> Option<Value>>(&self, entry: &Entry<'_, F>) -> bool {
+        match self {
+            Self::And(a, b) => a.eval(entry) && b.eval(entry),
+            Self::Or(a, b) => a.eval(entry) || b.eval(entry),
+            Self::Not(query) => !query.eval(entry),
+            Self::Bytes { field, op, operand } => {
+                let bytes = field.get(entry.key, entry.value);
+                match op {
+                    BytesOp::Prefix => bytes.starts_with(operand),
+                    BytesOp::Suffix => bytes.ends_with(operand),
+                    BytesOp::Contains => {
+                        operand.is_empty()
+                            || bytes
+                                .windows(operand.len())
+                                .any(|window| window == operand.as_slice())
+                    }
+                    BytesOp::Eq => bytes == operand.as_slice(),
+                    BytesOp::Ne => bytes != operand.as_slice(),
+                }
+            }
+            Self::Len { field, op, operand } => {
+                let len = field.get(entry.key, entry.value).len();
+                op.holds(Some(len.cmp(operand)))
+            }
+            Self::Decoded { path, test } => {
+                let Some(decoded) = entry.decoded() else {
+                    return false;
+                };
+
+                let values = values_at(decoded, path);
+                match test {
+                    DecodedTest::Exists => !values.is_empty(),
+                    DecodedTest::Compare(op, operand) => values
+                        .into_iter()
+                        .any(|value| op.holds(compare_values(value, operand))),
+                    DecodedTest::Contains(operand) => {
+                        values.into_iter().any(|value| match (value, operand) {
+                            (Value::String(string), Value::String(substring)) => {
+                                string.contains(substring.as_str())
+                            }
+                            (Value::Array(elements), operand) => elements.contains(operand),
+                            _ => false,
+                        })
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// An entry that a query is evaluated against.
+struct Entry<'a, F> {
+    key: &'a [u8],
+    value: &'a [u8],
+    decoded: OnceCell<Option<Value>>,
+    /// Decodes the value, taken the first time it is needed
+    decode: Cell<Option<F>>,
+}
+
+impl<F: FnOnce() -> Option<Value>> Entry<'_, F> {
+    fn decoded(&self) -> Option<&Value> {
+        self.decoded
+            .get_or_init(|| self.decode.take().and_then(|decode| decode()))
+            .as_ref()
+    }
+}
+
+/// Parses a path into a decoded value on its own, such as `$.tags[0]`.
+pub fn parse_path(text: &str) -> Result<Vec<PathSegment>, ParseError> {
+    match tokenize(text)?.as_slice() {
+        [(_, Token::Path(path))] => Ok(path.clone()),
+        _ => Err(ParseError::new(0, "Expected a path starting with “$”")),
+    }
+}
+
+/// Formats `path` so it is parsed back by [`parse_path`].
+pub fn format_path(path: &[PathSegment]) -> String {
+    let mut text = String::from("$");
+    for segment in path {
+        match segment {
+            PathSegment::Member(name)
+                if !name.is_empty()
+                    && name
+                        .chars()
+                        .all(|c| c.is_alphanumeric() || c == '_' || c == '-') =>
+            {
+                text.push('.');
+                text.push_str(name);
+            }
+            PathSegment::Member(name) => {
+                text.push_str("[\"");
+                for c in name.chars() {
+                    if c == '"' || c == '\\' {
+                        text.push('\\');
+                    }
+                    text.push(c);
+                }
+                text.push_str("\"]");
+            }
+            PathSegment::Index(index) => text.push_str(&format!("[{}]", index)),
+            PathSegment::Wildcard => text.push_str("[*]"),
+        }
+    }
+    text
+}
+
+/// Returns the values at `path` in `value`.
+pub fn values_at<'a>(value: &'a Value, path: &[PathSegment]) -> Vec<&'a Value> {
+    let mut values = vec![value];
+    for segment in path {
+        values = values
+            .into_iter()
+            .flat_map(|value| select(value, segment))
+            .collect();
+    }
+    values
+}
+
+/// Returns the paths to the members of the objects in `value` that aren't
+/// objects themselves, in order.
+pub fn field_paths(value: &Value) -> Vec<Vec<PathSegment>> {
+    fn collect(value: &Value, prefix: &mut Vec<PathSegment>, paths: &mut Vec<Vec<PathSegment>>) {
+        match value {
+            Value::Object(members) => {
+                for (name, member) in members {
+                    prefix.push(PathSegment::Member(name.clone()));
+                    collect(member, prefix, paths);
+                    prefix.pop();
+                }
+            }
+            _ if !prefix.is_empty() => paths.push(prefix.clone()),
+            _ => {}
+        }
+    }
+
+    let mut paths = Vec::new();
+    collect(value, &mut Vec::new(), &mut paths);
+    paths
+}
+
+/// Returns the values that `segment` leads to from `value`.
+fn select<'a>(value: &'a Value, segment: &PathSegment) -> Vec<&'a Value> {
+    match (value, segment) {
+        (Value::Object(members), PathSegment::Member(name)) => {
+            members.get(name).into_iter().collect()
+        }
+        (Value::Array(elements), PathSegment::Index(index)) => {
+            elements.get(*index).into_iter().collect()
+        }
+        (Value::Object(members), PathSegment::Wildcard) => members.values().collect(),
+        (Value::Array(elements), PathSegment::Wildcard) => elements.iter().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Orders numbers by value and strings lexicographically, or returns whether
+/// other values are equal.
+fn compare_values(a: &Value, b: &Value) -> Option<Ordering> {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64()?.partial_cmp(&b.as_f64()?),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        (a, b) => (a == b).then_some(Ordering::Equal),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    String(Vec<u8>),
+    /// Digits with an optional sign and fraction, parsed by the parser as
+    /// what it expects
+    Number(String),
+    Path(Vec<PathSegment>),
+    Symbol(&'static str),
+}
+
+const SYMBOLS: &[&str] = &["&&", "||", "==", "!=", "<=", ">=", "<", ">", "!", "(", ")"];
+
+fn tokenize(text: &str) -> Result<Vec<(usize, Token)>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+
+    while let Some(&(offset, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '"' {
+            chars.next();
+            tokens.push((offset, Token::String(tokenize_string(offset, &mut chars)?)));
+        } else if c.is_ascii_digit()
+            || (c == '-' && text[offset + 1..].starts_with(|c: char| c.is_ascii_digit()))
+        {
+            chars.next();
+            let digits = take_while(text, &mut chars, |c| c.is_ascii_digit() || c == '.');
+            let end = offset + c.len_utf8() + digits.len();
+            tokens.push((offset, Token::Number(text[offset..end].to_string())));
+        } else if c == '$' {
+            chars.next();
+            tokens.push((offset, Token::Path(tokenize_path(text, &mut chars)?)));
+        } else if c.is_alphabetic() {
+            let ident = take_while(text, &mut chars, |c| c.is_alphanumeric() || c == '.');
+            tokens.push((offset, Token::Ident(ident.to_string())));
+        } else if let Some(symbol) = SYMBOLS
+            .iter()
+            .find(|symbol| text[offset..].starts_with(**symbol))
+        {
+            for _ in 0..symbol.len() {
+                chars.next();
+            }
+            tokens.push((offset, Token::Symbol(symbol)));
+        } else {
+            return Err(ParseError::new(offset, format!("Unexpected “{}”", c)));
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Consumes the characters of a string up to and including its closing
+/// quote, given the `start` offset of its opening quote.
+fn tokenize_string(
+    start: usize,
+    chars: &mut Peekable<CharIndices<'_>>,
+) -> Result<Vec<u8>, ParseError> {
+    let mut bytes = Vec::new();
+
+    loop {
+        let Some((offset, c)) = chars.next() else {
+            return Err(ParseError::new(start, "Unterminated string"));
+        };
+
+        match c {
+            '"' => return Ok(bytes),
+            '\\' => {
+                let escaped = match chars.next().map(|(_, c)| c) {
+                    Some('"') => b'"',
+                    Some('\\') => b'\\',
+                    Some('n') => b'\n',
+                    Some('r') => b'\r',
+                    Some('t') => b'\t',
+                    Some('0') => b'\0',
+                    Some('x') => {
+                        let hex = chars.next().zip(chars.next()).and_then(|((_, a), (_, b))| {
+                            u8::from_str_radix(&format!("{}{}", a, b), 16).ok()
+                        });
+                        hex.ok_or_else(|| ParseError::new(offset, "Expected two hex digits"))?
+                    }
+                    _ => return Err(ParseError::new(offset, "Invalid escape")),
+                };
+                bytes.push(escaped);
+            }
+            c => bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+        }
+    }
+}
+
+/// Consumes the segments of a path after its `$`.
+fn tokenize_path(
+    text: &str,
+    chars: &mut Peekable<CharIndices<'_>>,
+) -> Result<Vec<PathSegment>, ParseError> {
+    let mut path = Vec::new();
+
+    loop {
+        if chars.next_if(|&(_, c)| c == '.').is_some() {
+            if chars.next_if(|&(_, c)| c == '*').is_some() {
+                path.push(PathSegment::Wildcard);
+                continue;
+            }
+
+            let offset = chars.peek().map_or(text.len(), |&(offset, _)| offset);
+            let name = take_while(text, chars, |c| c.is_alphanumeric() || c == '_' || c == '-');
+            if name.is_empty() {
+                return Err(ParseError::new(offset, "Expected a member name"));
+            }
+            path.push(PathSegment::Member(name.to_string()));
+        } else if let Some((offset, _)) = chars.next_if(|&(_, c)| c == '[') {
+            let segment = match chars.peek().copied() {
+                Some((_, '*')) => {
+                    chars.next();
+                    PathSegment::Wildcard
+                }
+                Some((string_offset, '"')) => {
+                    chars.next();
+                    let name = tokenize_string(string_offset, chars)?;
+                    PathSegment::Member(String::from_utf8_lossy(&name).into_owned())
+                }
+                Some((index_offset, c)) if c.is_ascii_digit() => {
+                    let digits = take_while(text, chars, |c| c.is_ascii_digit());
+                    let index = digits
+                        .parse()
+                        .map_err(|_| ParseError::new(index_offset, "Index is too large"))?;
+                    PathSegment::Index(index)
+                }
+                _ => {
+                    let message = "Expected an index, a quoted member name, or “*”";
+                    return Err(ParseError::new(offset, message));
+                }
+            };
+            if chars.next_if(|&(_, c)| c == ']').is_none() {
+                return Err(ParseError::new(offset, "Expected “]”"));
+            }
+            path.push(segment);
+        } else {
+            return Ok(path);
+        }
+    }
+}
+
+fn take_while<'a>(
+    text: &'a str,
+    chars: &mut Peekable<CharIndices<'_>>,
+    predicate: impl Fn(char) -> bool,
+) -> &'a str {
+    let start = chars.peek().map_or(text.len(), |&(offset, _)| offset);
+    while chars.next_if(|&(_, c)| predicate(c)).is_some() {}
+    let end = chars.peek().map_or(text.len(), |&(offset, _)| offset);
+    &text[start..end]
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+    /// Offset of the end of the query, where running out of tokens is reported
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<(usize, &Token)> {
+        self.tokens
+            .get(self.position)
+            .map(|(offset, token)| (*offset, token))
+    }
+
+    fn next(&mut self) -> Option<(usize, Token)> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat_symbol(&mut self, symbol: &str) -> bool {
+        let is_symbol = matches!(self.peek(), Some((_, Token::Symbol(s))) if *s == symbol);
+        if is_symbol {
+            self.position += 1;
+        }
+        is_symbol
+    }
+
+    fn parse_or(&mut self) -> Result<Query, ParseError> {
+        let mut query = self.parse_and()?;
+        while self.eat_symbol("||") {
+            query = Query::Or(Box::new(query), Box::new(self.parse_and()?));
+        }
+        Ok(query)
+    }
+
+    fn parse_and(&mut self) -> Result<Query, ParseError> {
+        let mut query = self.parse_unary()?;
+        while self.eat_symbol("&&") {
+            query = Query::And(Box::new(query), Box::new(self.parse_unary()?));
+        }
+        Ok(query)
+    }
+
+    fn parse_unary(&mut self) -> Result<Query, ParseError> {
+        if self.eat_symbol("!") {
+            return Ok(Query::Not(Box::new(self.parse_unary()?)));
+        }
+
+        if self.eat_symbol("(") {
+            let query = self.parse_or()?;
+            if !self.eat_symbol(")") {
+                return Err(self.error("Expected “)”"));
+            }
+            return Ok(query);
+        }
+
+        self.parse_predicate()
+    }
+
+    fn parse_predicate(&mut self) -> Result<Query, ParseError> {
+        let (offset, field_name) = match self.next() {
+            Some((offset, Token::Ident(ident))) => (offset, ident),
+            Some((_, Token::Path(path))) => return self.parse_decoded(path),
+            Some((offset, _)) => {
+                return Err(ParseError::new(
+                    offset,
+                    "Expected “key”, “value”, or a path",
+                ));
+            }
+            None => {
+                return Err(ParseError::new(
+                    self.end,
+                    "Expected “key”, “value”, or a path",
+                ))
+            }
+        };
+
+        let (field, is_len) = match field_name.as_str() {
+            "key" => (Field::Key, false),
+            "value" => (Field::Value, false),
+            "key.len" => (Field::Key, true),
+            "value.len" => (Field::Value, true),
+            _ => {
+                return Err(ParseError::new(
+                    offset,
+                    format!("Unknown field “{}”", field_name),
+                ))
+            }
+        };
+
+        if is_len {
+            let op = match self.next() {
+                Some((_, Token::Symbol(symbol))) => compare_op(symbol),
+                _ => None,
+            }
+            .ok_or_else(|| self.previous_error("Expected a comparison"))?;
+            let operand = match self.next() {
+                Some((offset, Token::Number(number))) => number
+                    .parse()
+                    .map_err(|_| ParseError::new(offset, "Expected a non-negative integer"))?,
+                _ => return Err(self.previous_error("Expected a number")),
+            };
+            Ok(Query::Len { field, op, operand })
+        } else {
+            let op = match self.next() {
+                Some((_, Token::Ident(ident))) if ident == "prefix" => BytesOp::Prefix,
+                Some((_, Token::Ident(ident))) if ident == "suffix" => BytesOp::Suffix,
+                Some((_, Token::Ident(ident))) if ident == "contains" => BytesOp::Contains,
+                Some((_, Token::Symbol("=="))) => BytesOp::Eq,
+                Some((_, Token::Symbol("!="))) => BytesOp::Ne,
+                _ => {
+                    let message = "Expected “prefix”, “suffix”, “contains”, “==”, or “!=”";
+                    return Err(self.previous_error(message));
+                }
+            };
+            let operand = match self.next() {
+                Some((_, Token::String(string))) => string,
+                _ => return Err(self.previous_error("Expected a string")),
+            };
+            Ok(Query::Bytes { field, op, operand })
+        }
+    }
+
+    /// Parses what the values at `path` are tested for, which is whether
+    /// they exist if no comparison follows.
+    fn parse_decoded(&mut self, path: Vec<PathSegment>) -> Result<Query, ParseError> {
+        let test = match self.peek() {
+            Some((_, Token::Ident(ident))) if ident == "contains" => {
+                self.position += 1;
+                DecodedTest::Contains(self.parse_literal()?)
+            }
+            Some((_, Token::Symbol(symbol))) => match compare_op(symbol) {
+                Some(op) => {
+                    self.position += 1;
+                    DecodedTest::Compare(op, self.parse_literal()?)
+                }
+                None => DecodedTest::Exists,
+            },
+            _ => DecodedTest::Exists,
+        };
+        Ok(Query::Decoded { path, test })
+    }
+
+    fn parse_literal(&mut self) -> Result<Value, ParseError> {
+        match self.next() {
+            Some((_, Token::String(string))) => {
+                Ok(Value::String(String::from_utf8_lossy(&string).into_owned()))
+            }
+            Some((offset, Token::Number(number))) => {
+                serde_json::from_str(&number).map_err(|_| ParseError::new(offset, "Invalid number"))
+            }
+            Some((_, Token::Ident(ident))) if ident == "true" => Ok(Value::Bool(true)),
+            Some((_, Token::Ident(ident))) if ident == "false" => Ok(Value::Bool(false)),
+            Some((_, Token::Ident(ident))) if ident == "null" => Ok(Value::Null),
+            _ => {
+                let message = "Expected a string, a number, “true”, “false”, or “null”";
+                Err(self.previous_error(message))
+            }
+        }
+    }
+
+    /// Returns an error at the next token, or at the end if there is none.
+    fn error(&self, message: &str) -> ParseError {
+        let offset = self.peek().map_or(self.end, |(offset, _)| offset);
+        ParseError::new(offset, message)
+    }
+
+    /// Returns an error at the token just consumed, or at the end if there
+    /// was none.
+    fn previous_error(&self, message: &str) -> ParseError {
+        let offset = self
+            .tokens
+            .get(self.position - 1)
+            .map_or(self.end, |(offset, _)| *offset);
+        ParseError::new(offset, message)
+    }
+}
+
+fn compare_op(symbol: &str) -> Option<CompareOp> {
+    match symbol {
+        "==" => Some(CompareOp::Eq),
+        "!=" => Some(CompareOp::Ne),
+        "<" => Some(CompareOp::Lt),
+        "<=" => Some(CompareOp::Le),
+        ">" => Some(CompareOp::Gt),
+        ">=" => Some(CompareOp::Ge),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns whether the entry matches `query`, decoding its value as JSON.
+    fn matches(query: &str, key: &str, value: &str) -> bool {
+        Query::parse(query)
+            .unwrap()
+            .matches(key.as_bytes(), value.as_bytes(), || {
+                serde_json::from_str(value).ok()
+            })
+    }
+
+    fn parse_error(query: &str) -> (usize, String) {
+        let error = Query::parse(query).unwrap_err();
+        (error.offset, error.message)
+    }
+
+    fn bytes(field: Field, op: BytesOp, operand: &[u8]) -> Query {
+        Query::Bytes {
+            field,
+            op,
+            operand: operand.to_vec(),
+        }
+    }
+
+    #[test]
+    fn parses_predicates() {
+        assert_eq!(
+            Query::parse(r#"key prefix "user:""#),
+            Ok(bytes(Field::Key, BytesOp::Prefix, b"user:"))
+        );
+        assert_eq!(
+            Query::parse("value.len >= 1024"),
+            Ok(Query::Len {
+                field: Field::Value,
+                op: CompareOp::Ge,
+                operand: 1024,
+            })
+        );
+        assert_eq!(
+            Query::parse(r#"$.status != "failed""#),
+            Ok(Query::Decoded {
+                path: vec![PathSegment::Member("status".into())],
+                test: DecodedTest::Compare(CompareOp::Ne, Value::from("failed")),
+            })
+        );
+        assert_eq!(
+            Query::parse(r#"$["user id"]"#),
+            Ok(Query::Decoded {
+                path: vec![PathSegment::Member("user id".into())],
+                test: DecodedTest::Exists,
+            })
+        );
+    }
+
+    #[test]
+    fn parses_string_escapes() {
+        assert_eq!(
+            Query::parse(r#"value == "a\"\\\n\r\t\0\x7f\xFF""#),
+            Ok(bytes(Field::Value, BytesOp::Eq, b"a\"\\\n\r\t\0\x7f\xff"))
+        );
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert_eq!(
+            Query::parse(r#"key == "a" || !key == "b" && (value.len < 2)"#),
+            Ok(Query::Or(
+                Box::new(bytes(Field::Key, BytesOp::Eq, b"a")),
+                Box::new(Query::And(
+                    Box::new(Query::Not(Box::new(bytes(Field::Key, BytesOp::Eq, b"b")))),
+                    Box::new(Query::Len {
+                        field: Field::Value,
+                        op: CompareOp::Lt,
+                        operand: 2,
+                    }),
+                )),
+            ))
+        );
+    }
+
+    #[test]
+    fn reports_parse_errors_where_they_are() {
+        let expected_field = "Expected “key”, “value”, or a path".to_string();
+        assert_eq!(parse_error(""), (0, expected_field.clone()));
+        assert_eq!(parse_error("key == \"a\" &&"), (13, expected_field));
+        assert_eq!(
+            parse_error("key prefix \"abc"),
+            (11, "Unterminated string".into())
+        );
+        assert_eq!(
+            parse_error(r#"key == "\xzz""#),
+            (8, "Expected two hex digits".into())
+        );
+        assert_eq!(
+            parse_error(r#"key == "\x4""#),
+            (8, "Expected two hex digits".into())
+        );
+        assert_eq!(parse_error(r#"key == "\q""#), (8, "Invalid escape".into()));
+        assert_eq!(
+            parse_error("name == \"a\""),
+            (0, "Unknown field “name”".into())
+        );
+        assert_eq!(
+            parse_error("key.len > -1"),
+            (10, "Expected a non-negative integer".into())
+        );
+        assert_eq!(
+            parse_error("key.len > \"a\""),
+            (10, "Expected a number".into())
+        );
+        assert_eq!(parse_error("key @"), (4, "Unexpected “@”".into()));
+        assert_eq!(parse_error("(key == \"a\""), (11, "Expected “)”".into()));
+        assert_eq!(
+            parse_error("key == \"a\" key"),
+            (11, "Expected “&&” or “||”".into())
+        );
+        assert_eq!(
+            parse_error("$.a == ("),
+            (
+                7,
+                "Expected a string, a number, “true”, “false”, or “null”".into()
+            )
+        );
+    }
+
+    #[test]
+    fn parses_paths() {
+        let path = parse_path(r#"$.tags[0]["user id"][*].*"#).unwrap();
+        assert_eq!(
+            path,
+            [
+                PathSegment::Member("tags".into()),
+                PathSegment::Index(0),
+                PathSegment::Member("user id".into()),
+                PathSegment::Wildcard,
+                PathSegment::Wildcard,
+            ]
+        );
+        assert_eq!(parse_path(&format_path(&path)), Ok(path));
+    }
+
+    #[test]
+    fn reports_path_errors() {
+        let path_error = |text: &str| {
+            let error = parse_path(text).unwrap_err();
+            (error.offset, error.message)
+        };
+        assert_eq!(path_error("$."), (2, "Expected a member name".into()));
+        assert_eq!(
+            path_error("$.a["),
+            (3, "Expected an index, a quoted member name, or “*”".into())
+        );
+        assert_eq!(path_error("$.a[0"), (3, "Expected “]”".into()));
+        assert_eq!(
+            path_error("$[99999999999999999999999]"),
+            (2, "Index is too large".into())
+        );
+        assert_eq!(
+            path_error("key"),
+            (0, "Expected a path starting with “$”".into())
+        );
+    }
+
+    #[test]
+    fn matches_bytes_and_lengths() {
+        assert!(matches(r#"key prefix "user:""#, "user:1", ""));
+        assert!(!matches(r#"key suffix "user:""#, "user:1", ""));
+        assert!(matches(r#"value contains "rr""#, "k", "error"));
+        assert!(matches(r#"value contains """#, "k", ""));
+        assert!(matches("value.len > 4 && key.len == 1", "k", "error"));
+        assert!(!matches(r#"!(key != "k")"#, "j", ""));
+    }
+
+    #[test]
+    fn matches_decoded_values() {
+        let value = r#"{"status": "failed", "tags": ["urgent"], "n": 2.5}"#;
+        assert!(matches(r#"$.status == "failed""#, "k", value));
+        assert!(matches(r#"$.tags[*] == "urgent""#, "k", value));
+        assert!(matches(r#"$.tags contains "urgent""#, "k", value));
+        assert!(matches(r#"$.status contains "ail""#, "k", value));
+        assert!(matches("$.n >= 2 && $.n < 3", "k", value));
+        assert!(!matches("$.missing", "k", value));
+        assert!(!matches("$.n == null", "k", value));
+        // Values that can't be decoded have no fields
+        assert!(!matches("$.status", "k", "not json"));
+    }
+
+    #[test]
+    fn decodes_only_when_needed() {
+        let query = Query::parse(r#"key == "a" || $.b"#).unwrap();
+        assert!(query.tests_decoded_value());
+        assert!(query.matches(b"a", b"", || panic!("decoded needlessly")));
+
+        let query = Query::parse(r#"key == "a""#).unwrap();
+        assert!(!query.tests_decoded_value());
+    }
+}
@@ -0,0 +1,78 @@
+use anyhow::{Context, Result};
+use gtk::{gio, glib, prelude::*};
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    fs,
+    hash::{Hash, Hasher},
+    path::PathBuf,
+    sync::atomic::{self, AtomicU64},
+};
+
+use crate::config::APP_ID;
+
+const SYNC_ATTRIBUTES: &str = "standard::size,time::modified";
+
+/// Returns the local directory that the env at `uri` is cached in.
+pub fn cache_dir(uri: &str) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    uri.hash(&mut hasher);
+
+    glib::user_cache_dir()
+        .join(APP_ID)
+        .join("remote")
+        .join(format!("{:016x}", hasher.finish()))
+}
+
+/// Downloads the data file of the env at `uri`, which must be on a mounted
+/// location, into its cache directory, counting the downloaded bytes in
+/// `n_bytes` out of `n_total_bytes`.
+///
+/// The download is skipped if the cached file has the same size and
+/// modification time. Returns the cache directory and whether it downloaded.
+pub fn sync(uri: &str, n_bytes: &AtomicU64, n_total_bytes: &AtomicU64) -> Result<(PathBuf, bool)> {
+    let remote_file = gio::File::for_uri(uri).child("data.mdb");
+    let remote_info = remote_file
+        .query_info(
+            SYNC_ATTRIBUTES,
+            gio::FileQueryInfoFlags::NONE,
+            gio::Cancellable::NONE,
+        )
+        .with_context(|| format!("Failed to query `{}`", remote_file.uri()))?;
+    n_total_bytes.store(remote_info.size() as u64, atomic::Ordering::Relaxed);
+
+    let cache_dir = cache_dir(uri);
+    fs::create_dir_all(&cache_dir)?;
+    let cache_path = cache_dir.join("data.mdb");
+
+    let cache_file = gio::File::for_path(&cache_path);
+    if let Ok(cache_info) = cache_file.query_info(
+        SYNC_ATTRIBUTES,
+        gio::FileQueryInfoFlags::NONE,
+        gio::Cancellable::NONE,
+    ) {
+        if cache_info.size() == remote_info.size()
+            && cache_info.modification_date_time() == remote_info.modification_date_time()
+        {
+            n_bytes.store(remote_info.size() as u64, atomic::Ordering::Relaxed);
+            return Ok((cache_dir, false));
+        }
+    }
+
+    // Download next to the cached file, so an open env keeps its old file
+    // until the new one is complete
+    let part_path = cache_dir.join("data.mdb.part");
+    remote_file
+        .copy(
+            &gio::File::for_path(&part_path),
+            gio::FileCopyFlags::OVERWRITE | gio::FileCopyFlags::ALL_METADATA,
+            gio::Cancellable::NONE,
+            Some(&mut |n_copied_bytes, _| {
+                n_bytes.store(n_copied_bytes as u64, atomic::Ordering::Relaxed);
+            }),
+        )
+        .with_context(|| format!("Failed to download `{}`", remote_file.uri()))?;
+    fs::rename(&part_path, &cache_path)?;
+
+    Ok((cache_dir, true))
+}
@@ -0,0 +1,58 @@
+use gtk::{gio, prelude::*};
+
+use std::{collections::HashMap, path::Path};
+
+use crate::config::APP_ID;
+
+/// Saved searches of each env as `(name, pattern)`, keyed by env path
+type EnvSavedSearches = HashMap<String, Vec<(String, String)>>;
+
+/// A named search pattern, kept to be reused on the same env.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SavedSearch {
+    pub name: String,
+    /// Text that the displayed keys are searched for
+    pub pattern: String,
+}
+
+impl SavedSearch {
+    /// Loads the saved searches of the env at `env_path` from the settings.
+    pub fn load_all(env_path: &Path) -> Vec<Self> {
+        let settings = gio::Settings::new(APP_ID);
+
+        settings
+            .value("saved-searches")
+            .get::<EnvSavedSearches>()
+            .unwrap()
+            .remove(env_path.to_string_lossy().as_ref())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(name, pattern)| Self { name, pattern })
+            .collect()
+    }
+
+    /// Saves `saved_searches` as the saved searches of the env at `env_path`.
+    pub fn save_all(env_path: &Path, saved_searches: &[Self]) {
+        let settings = gio::Settings::new(APP_ID);
+
+        let mut env_saved_searches = settings
+            .value("saved-searches")
+            .get::<EnvSavedSearches>()
+            .unwrap();
+        let env_path = env_path.to_string_lossy().to_string();
+
+        if saved_searches.is_empty() {
+            env_saved_searches.remove(&env_path);
+        } else {
+            let saved_searches = saved_searches
+                .iter()
+                .map(|saved_search| (saved_search.name.clone(), saved_search.pattern.clone()))
+                .collect();
+            env_saved_searches.insert(env_path, saved_searches);
+        }
+
+        if let Err(err) = settings.set_value("saved-searches", &env_saved_searches.to_variant()) {
+            tracing::warn!("Failed to save saved searches: {:?}", &err);
+        }
+    }
+}
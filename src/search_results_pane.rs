@@ -0,0 +1,338 @@
+use anyhow::Result;
+use gtk::{
+    gio,
+    glib::{self, clone},
+    prelude::*,
+    subclass::prelude::*,
+};
+
+use std::{sync::Arc, time::Duration};
+
+use crate::{
+    full_search::{Match, ScanProgress},
+    utils::{gettext_f, ngettext_f},
+};
+
+/// Interval at which the matches found by a running scan are shown
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use super::*;
+
+    #[derive(Debug, Default, gtk::CompositeTemplate)]
+    #[template(resource = "/io/github/seadve/LmdbViewer/ui/search_results_pane.ui")]
+    pub struct SearchResultsPane {
+        #[template_child]
+        pub(super) status_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) list_view: TemplateChild<gtk::ListView>,
+        #[template_child]
+        pub(super) selection_model: TemplateChild<gtk::NoSelection>,
+
+        /// Progress of the scan whose results are shown
+        pub(super) progress: RefCell<Option<Arc<ScanProgress>>>,
+        pub(super) poll_source_id: RefCell<Option<glib::SourceId>>,
+        pub(super) n_entries: Cell<u32>,
+        /// Number of databases scanned, if the scan spans the env
+        pub(super) n_dbs: Cell<Option<usize>>,
+        /// Factory of the headers of the matches of each database, which are
+        /// only shown for scans spanning the env
+        pub(super) header_factory: gtk::SignalListItemFactory,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for SearchResultsPane {
+        const NAME: &'static str = "LvSearchResultsPane";
+        type Type = super::SearchResultsPane;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+
+            klass.install_action("search-results-pane.cancel", None, |obj, _, _| {
+                obj.cancel();
+            });
+
+            klass.install_action("search-results-pane.close", None, |obj, _, _| {
+                obj.close();
+            });
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for SearchResultsPane {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let obj = self.obj();
+
+            // Groups the matches of scans spanning the env by database
+            let section_sorter = gtk::CustomSorter::new(|a, b| {
+                let db_name = |item: &glib::Object| {
+                    let item = item.downcast_ref::<glib::BoxedAnyObject>().unwrap();
+                    let db_name = item.borrow::<Match>().db_name.clone();
+                    db_name
+                };
+                db_name(a).cmp(&db_name(b)).into()
+            });
+            let sort_model = gtk::SortListModel::builder()
+                .model(&gio::ListStore::new::<glib::BoxedAnyObject>())
+                .section_sorter(&section_sorter)
+                .build();
+            self.selection_model.set_model(Some(&sort_model));
+            obj.setup_view();
+
+            obj.action_set_enabled("search-results-pane.cancel", false);
+        }
+
+        fn dispose(&self) {
+            self.obj().cancel();
+
+            self.dispose_template();
+        }
+    }
+
+    impl WidgetImpl for SearchResultsPane {}
+}
+
+glib::wrapper! {
+    pub struct SearchResultsPane(ObjectSubclass<imp::SearchResultsPane>)
+        @extends gtk::Widget;
+}
+
+impl SearchResultsPane {
+    /// Shows the pane and the matches of the scan with `progress` as they
+    /// are found, stopping any previous scan. `n_dbs` is the number of
+    /// databases scanned if the scan spans the env, whose matches are then
+    /// grouped by database.
+    pub fn start(&self, progress: Arc<ScanProgress>, n_entries: u32, n_dbs: Option<usize>) {
+        let imp = self.imp();
+
+        self.cancel();
+        self.matches().remove_all();
+
+        imp.progress.replace(Some(progress));
+        imp.n_entries.set(n_entries);
+        imp.n_dbs.set(n_dbs);
+        imp.list_view
+            .set_header_factory(n_dbs.is_some().then_some(&imp.header_factory));
+        self.set_visible(true);
+        self.action_set_enabled("search-results-pane.cancel", true);
+        self.update_status(None);
+
+        let source_id = glib::timeout_add_local(
+            POLL_INTERVAL,
+            clone!(@weak self as obj => @default-return glib::ControlFlow::Break, move || {
+                obj.take_new_matches();
+                obj.update_status(None);
+                glib::ControlFlow::Continue
+            }),
+        );
+        imp.poll_source_id.replace(Some(source_id));
+    }
+
+    /// Shows the last matches and how the scan with `progress` ended, unless
+    /// it was replaced by another scan.
+    pub fn finish(&self, progress: &Arc<ScanProgress>, result: &Result<()>) {
+        let imp = self.imp();
+
+        let is_shown = imp
+            .progress
+            .borrow()
+            .as_ref()
+            .is_some_and(|shown| Arc::ptr_eq(shown, progress));
+        if !is_shown {
+            return;
+        }
+
+        if let Some(source_id) = imp.poll_source_id.take() {
+            source_id.remove();
+        }
+        self.action_set_enabled("search-results-pane.cancel", false);
+
+        self.take_new_matches();
+        self.update_status(Some(result));
+    }
+
+    /// Returns whether the matches shown are of a scan spanning the env,
+    /// rather than of the selected database.
+    pub fn is_env_wide(&self) -> bool {
+        self.imp().n_dbs.get().is_some()
+    }
+
+    /// Stops the scan and hides the pane.
+    pub fn close(&self) {
+        self.cancel();
+        self.set_visible(false);
+    }
+
+    fn cancel(&self) {
+        if let Some(progress) = self.imp().progress.borrow().as_ref() {
+            progress.cancel();
+        }
+    }
+
+    fn matches(&self) -> gio::ListStore {
+        self.imp()
+            .selection_model
+            .model()
+            .and_downcast::<gtk::SortListModel>()
+            .unwrap()
+            .model()
+            .and_downcast::<gio::ListStore>()
+            .unwrap()
+    }
+
+    fn take_new_matches(&self) {
+        let Some(progress) = self.imp().progress.borrow().clone() else {
+            return;
+        };
+
+        let new_matches = progress
+            .take_new_matches()
+            .into_iter()
+            .map(glib::BoxedAnyObject::new)
+            .collect::<Vec<_>>();
+        let matches = self.matches();
+        matches.splice(matches.n_items(), 0, &new_matches);
+    }
+
+    /// Shows the number of matches and of scanned entries, and how the scan
+    /// ended if given its `result`.
+    fn update_status(&self, result: Option<&Result<()>>) {
+        let imp = self.imp();
+
+        let Some(progress) = imp.progress.borrow().clone() else {
+            return;
+        };
+
+        let n_matches = self.matches().n_items();
+        let matches = ngettext_f(
+            "{n} match",
+            "{n} matches",
+            n_matches,
+            &[("n", &n_matches.to_string())],
+        );
+        let n_scanned = progress.n_scanned().to_string();
+        let status = match (result, imp.n_dbs.get()) {
+            (None, Some(n_dbs)) => gettext_f(
+                "{matches}, scanning “{db_name}”, {n_scanned_dbs} of {n_dbs} databases done",
+                &[
+                    ("matches", &matches),
+                    ("db_name", &progress.current_db().unwrap_or_default()),
+                    ("n_scanned_dbs", &progress.n_scanned_dbs().to_string()),
+                    ("n_dbs", &n_dbs.to_string()),
+                ],
+            ),
+            (None, None) => gettext_f(
+                "{matches}, scanned {n_scanned} of {n_entries} entries",
+                &[
+                    ("matches", &matches),
+                    ("n_scanned", &n_scanned),
+                    ("n_entries", &imp.n_entries.get().to_string()),
+                ],
+            ),
+            (Some(Err(err)), _) => gettext_f(
+                "{matches}, failed after {n_scanned} entries: {error}",
+                &[
+                    ("matches", &matches),
+                    ("n_scanned", &n_scanned),
+                    ("error", &err.to_string()),
+                ],
+            ),
+            (Some(Ok(())), _) if progress.is_cancelled() => gettext_f(
+                "{matches}, stopped after {n_scanned} entries",
+                &[("matches", &matches), ("n_scanned", &n_scanned)],
+            ),
+            (Some(Ok(())), Some(n_dbs)) => gettext_f(
+                "{matches} in {n_scanned} entries of {n_dbs} databases",
+                &[
+                    ("matches", &matches),
+                    ("n_scanned", &n_scanned),
+                    ("n_dbs", &n_dbs.to_string()),
+                ],
+            ),
+            (Some(Ok(())), None) => gettext_f(
+                "{matches} in {n_scanned} entries",
+                &[("matches", &matches), ("n_scanned", &n_scanned)],
+            ),
+        };
+        imp.status_label.set_label(&status);
+    }
+
+    fn setup_view(&self) {
+        let imp = self.imp();
+
+        let factory = gtk::SignalListItemFactory::new();
+        factory.connect_setup(|_, list_item| {
+            let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
+            let label = gtk::Label::builder()
+                .xalign(0.0)
+                .ellipsize(gtk::pango::EllipsizeMode::End)
+                .build();
+            list_item.set_child(Some(&label));
+        });
+        factory.connect_bind(|_, list_item| {
+            let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
+            let label = list_item.child().and_downcast::<gtk::Label>().unwrap();
+            let item = list_item
+                .item()
+                .and_downcast::<glib::BoxedAnyObject>()
+                .unwrap();
+            let key_text = &item.borrow::<Match>().key_text;
+
+            label.set_label(key_text);
+            label.set_tooltip_text(Some(key_text));
+        });
+        imp.list_view.set_factory(Some(&factory));
+
+        imp.header_factory.connect_setup(|_, list_header| {
+            let list_header = list_header.downcast_ref::<gtk::ListHeader>().unwrap();
+            let label = gtk::Label::builder()
+                .xalign(0.0)
+                .ellipsize(gtk::pango::EllipsizeMode::End)
+                .css_classes(["heading"])
+                .build();
+            list_header.set_child(Some(&label));
+        });
+        imp.header_factory.connect_bind(|_, list_header| {
+            let list_header = list_header.downcast_ref::<gtk::ListHeader>().unwrap();
+            let label = list_header.child().and_downcast::<gtk::Label>().unwrap();
+            let item = list_header
+                .item()
+                .and_downcast::<glib::BoxedAnyObject>()
+                .unwrap();
+            let db_name = item.borrow::<Match>().db_name.clone().unwrap_or_default();
+
+            label.set_label(&db_name);
+        });
+
+        imp.list_view
+            .connect_activate(clone!(@weak self as obj => move |_, position| {
+                let Some(item) = obj
+                    .imp()
+                    .selection_model
+                    .item(position)
+                    .and_downcast::<glib::BoxedAnyObject>()
+                else {
+                    return;
+                };
+                let Match { db_name, key, .. } = item.borrow::<Match>().clone();
+
+                let result = match db_name {
+                    Some(db_name) => {
+                        obj.activate_action("win.select-db-key", Some(&(db_name, key).to_variant()))
+                    }
+                    None => obj.activate_action("win.select-key", Some(&key.to_variant())),
+                };
+                if let Err(err) = result {
+                    tracing::error!("Failed to activate select action: {:?}", &err);
+                }
+            }));
+    }
+}
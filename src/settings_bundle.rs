@@ -0,0 +1,175 @@
+use anyhow::{Context, Result};
+use gtk::{gio, prelude::*};
+use serde_json::{json, Value};
+
+use std::{collections::HashMap, path::Path};
+
+use crate::{
+    bookmark::Bookmark,
+    config::APP_ID,
+    env_profile::EnvProfile,
+    highlight_rule::{HighlightColor, HighlightRule},
+    import::FieldEncoding,
+    non_printable_display,
+    saved_search::SavedSearch,
+};
+
+/// Version of the format, increased when it changes incompatibly
+const FORMAT_VERSION: u64 = 1;
+
+/// Decoder ids of each database, keyed by database name, keyed by env path
+type EnvDecoders = HashMap<String, HashMap<String, String>>;
+
+/// Returns the settings of the env at `env_path` as JSON: its profile, the
+/// decoders of its databases, bookmarks, highlight rules, and saved
+/// searches. The path isn't included, so they can be imported for a copy of
+/// the env elsewhere.
+pub fn export(env_path: &Path) -> Value {
+    let profile = EnvProfile::load(env_path);
+    let decoders = gio::Settings::new(APP_ID)
+        .value("database-decoders")
+        .get::<EnvDecoders>()
+        .unwrap()
+        .remove(env_path.to_string_lossy().as_ref())
+        .unwrap_or_default();
+
+    json!({
+        "version": FORMAT_VERSION,
+        "profile": {
+            "selected_db": profile.selected_db,
+            "display_settings": profile.display_settings,
+            "column_widths": profile.column_widths,
+            "hidden_columns": profile.hidden_columns,
+            "pinned_fields": profile.pinned_fields,
+            "map_size": profile.map_size,
+        },
+        "decoders": decoders,
+        "bookmarks": Bookmark::load_all(env_path)
+            .into_iter()
+            .map(|bookmark| json!({
+                "db": bookmark.db_name,
+                "key": bookmark.key.as_deref().map(non_printable_display::to_hex),
+                "label": bookmark.label,
+            }))
+            .collect::<Vec<_>>(),
+        "highlight_rules": HighlightRule::load_all(env_path)
+            .into_iter()
+            .map(|rule| json!({
+                "query": rule.query_text,
+                "color": rule.color.id(),
+                "badge": rule.badge,
+            }))
+            .collect::<Vec<_>>(),
+        "saved_searches": SavedSearch::load_all(env_path)
+            .into_iter()
+            .map(|saved_search| json!({
+                "name": saved_search.name,
+                "pattern": saved_search.pattern,
+            }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+/// Imports `settings` exported by [`export`] for the env at `env_path`.
+///
+/// The profile and the decoders of the databases in `settings` replace the
+/// current ones, while bookmarks, highlight rules, and saved searches are
+/// added to the current ones, skipping those already there.
+pub fn import(env_path: &Path, settings: &Value) -> Result<()> {
+    let version = settings["version"].as_u64().context("No format version")?;
+    anyhow::ensure!(
+        version <= FORMAT_VERSION,
+        "Settings are from a newer version of the app (format {})",
+        version
+    );
+
+    if let Some(profile) = settings.get("profile") {
+        let field = |name: &str| profile.get(name).cloned().unwrap_or_default();
+        EnvProfile {
+            selected_db: serde_json::from_value(field("selected_db"))?,
+            display_settings: serde_json::from_value(field("display_settings")).unwrap_or_default(),
+            column_widths: serde_json::from_value(field("column_widths")).unwrap_or_default(),
+            hidden_columns: serde_json::from_value(field("hidden_columns")).unwrap_or_default(),
+            pinned_fields: serde_json::from_value(field("pinned_fields")).unwrap_or_default(),
+            map_size: serde_json::from_value(field("map_size"))?,
+        }
+        .save(env_path);
+    }
+
+    if let Some(decoders) = settings.get("decoders") {
+        let decoders = serde_json::from_value::<HashMap<String, String>>(decoders.clone())
+            .context("Invalid decoders")?;
+        let gsettings = gio::Settings::new(APP_ID);
+        let mut env_decoders = gsettings
+            .value("database-decoders")
+            .get::<EnvDecoders>()
+            .unwrap();
+        env_decoders
+            .entry(env_path.to_string_lossy().to_string())
+            .or_default()
+            .extend(decoders);
+        gsettings.set_value("database-decoders", &env_decoders.to_variant())?;
+    }
+
+    let mut bookmarks = Bookmark::load_all(env_path);
+    for bookmark in array(settings, "bookmarks")? {
+        let bookmark = Bookmark {
+            db_name: string(bookmark, "db")?,
+            key: bookmark["key"]
+                .as_str()
+                .map(|key| FieldEncoding::Hex.decode(key))
+                .transpose()?,
+            label: string(bookmark, "label")?,
+        };
+        if !bookmarks.contains(&bookmark) {
+            bookmarks.push(bookmark);
+        }
+    }
+    Bookmark::save_all(env_path, &bookmarks);
+
+    let mut rules = HighlightRule::load_all(env_path);
+    for rule in array(settings, "highlight_rules")? {
+        let query_text = string(rule, "query")?;
+        let color = HighlightColor::from_id(&string(rule, "color")?).unwrap_or_default();
+        let rule = HighlightRule::new(&query_text, color, &string(rule, "badge")?)?;
+        if !rules
+            .iter()
+            .any(|other| other.query_text == rule.query_text && other.badge == rule.badge)
+        {
+            rules.push(rule);
+        }
+    }
+    HighlightRule::save_all(env_path, &rules);
+
+    let mut saved_searches = SavedSearch::load_all(env_path);
+    for saved_search in array(settings, "saved_searches")? {
+        let saved_search = SavedSearch {
+            name: string(saved_search, "name")?,
+            pattern: string(saved_search, "pattern")?,
+        };
+        if !saved_searches.contains(&saved_search) {
+            saved_searches.push(saved_search);
+        }
+    }
+    SavedSearch::save_all(env_path, &saved_searches);
+
+    Ok(())
+}
+
+/// Returns the items of the array field called `name`, which are none if it
+/// is missing.
+fn array<'a>(object: &'a Value, name: &str) -> Result<&'a [Value]> {
+    match object.get(name) {
+        Some(value) => Ok(value
+            .as_array()
+            .with_context(|| format!("`{}` is not an array", name))?),
+        None => Ok(&[]),
+    }
+}
+
+fn string(object: &Value, name: &str) -> Result<String> {
+    object[name]
+        .as_str()
+        .map(str::to_owned)
+        .with_context(|| format!("`{}` is not a string", name))
+}
@@ -0,0 +1,89 @@
+use anyhow::{Context, Result};
+use heed::types::ByteSlice;
+
+use std::collections::BTreeMap;
+
+/// How many keys or values there are of each length.
+#[derive(Debug, Default, Clone)]
+pub struct SizeDistribution {
+    /// Number of occurrences of each length
+    counts: BTreeMap<u64, u64>,
+    n_sizes: u64,
+}
+
+impl SizeDistribution {
+    pub fn add(&mut self, size: u64) {
+        *self.counts.entry(size).or_default() += 1;
+        self.n_sizes += 1;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n_sizes == 0
+    }
+
+    pub fn max(&self) -> u64 {
+        self.counts.keys().next_back().copied().unwrap_or(0)
+    }
+
+    /// Returns the smallest size that at least `fraction` of the sizes are
+    /// less than or equal to.
+    pub fn percentile(&self, fraction: f64) -> u64 {
+        let rank = (fraction * self.n_sizes as f64).ceil().max(1.0) as u64;
+
+        let mut n_seen = 0;
+        for (size, count) in &self.counts {
+            n_seen += count;
+            if n_seen >= rank {
+                return *size;
+            }
+        }
+
+        self.max()
+    }
+
+    /// Returns the number of sizes in each bucket, where bucket 0 holds empty
+    /// ones and bucket `i` holds sizes from `2^(i-1)` to `2^i - 1`.
+    pub fn buckets(&self) -> Vec<u64> {
+        let mut buckets = vec![0; bucket_index(self.max()) + 1];
+        for (size, count) in &self.counts {
+            buckets[bucket_index(*size)] += count;
+        }
+        buckets
+    }
+}
+
+/// Returns the smallest and largest size that fall in bucket `index` of
+/// [`SizeDistribution::buckets`].
+pub fn bucket_range(index: usize) -> (u64, u64) {
+    if index == 0 {
+        (0, 0)
+    } else {
+        (1 << (index - 1), (1 << index) - 1)
+    }
+}
+
+fn bucket_index(size: u64) -> usize {
+    (u64::BITS - size.leading_zeros()) as usize
+}
+
+/// Streams the entries of the database called `db_name` with a cursor,
+/// returning the distribution of its key and value sizes.
+pub fn collect(
+    env: &heed::Env,
+    db_name: Option<&str>,
+) -> Result<(SizeDistribution, SizeDistribution)> {
+    let rtxn = env.read_txn().context("Failed to create read txn")?;
+    let db = env
+        .open_database::<ByteSlice, ByteSlice>(&rtxn, db_name)?
+        .context("Database not found")?;
+
+    let mut key_sizes = SizeDistribution::default();
+    let mut value_sizes = SizeDistribution::default();
+    for item in db.iter(&rtxn)? {
+        let (key, value) = item?;
+        key_sizes.add(key.len() as u64);
+        value_sizes.add(value.len() as u64);
+    }
+
+    Ok((key_sizes, value_sizes))
+}
@@ -0,0 +1,252 @@
+use anyhow::{anyhow, Context, Result};
+use heed::types::ByteSlice;
+
+use std::{
+    fmt,
+    panic::{self, AssertUnwindSafe},
+    sync::{mpsc, Arc},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use crate::database;
+
+/// How long the window has to go without input to be considered idle
+const IDLE_DURATION: Duration = Duration::from_secs(10);
+
+/// A read run in the pinned txn of a snapshot, borrowing for `'a`
+type ScopedJob<'a> = Box<dyn FnOnce(&heed::Env, &heed::RoTxn<'_>) + Send + 'a>;
+type Job = ScopedJob<'static>;
+
+/// A committed state of an env, identified by the id of the txn that wrote
+/// it. The state is pinned by a read txn kept open until the last clone of
+/// the snapshot is dropped, so reads that should agree with what is shown
+/// go through the snapshot the shown entries were loaded from.
+///
+/// LMDB txns can't be used from several threads at once, so the txn is
+/// owned by a thread of its own that runs the reads one at a time.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    env: heed::Env,
+    txn_id: u64,
+    txn: Arc<PinnedTxn>,
+}
+
+impl Snapshot {
+    /// Pins the latest state of `env`.
+    pub fn latest(env: &heed::Env) -> Result<Self> {
+        let (txn_id_sender, txn_id_receiver) = mpsc::sync_channel(1);
+        let (job_sender, job_receiver) = mpsc::channel::<Job>();
+
+        let thread_env = env.clone();
+        let thread = thread::Builder::new()
+            .name("snapshot-txn".into())
+            .spawn(move || {
+                let env = thread_env;
+                let rtxn = match begin_pinned_txn(&env) {
+                    Ok((txn_id, rtxn)) => {
+                        let _ = txn_id_sender.send(Ok(txn_id));
+                        rtxn
+                    }
+                    Err(err) => {
+                        let _ = txn_id_sender.send(Err(err));
+                        return;
+                    }
+                };
+                // Ends once all snapshot clones are dropped, aborting the txn
+                for job in job_receiver {
+                    if panic::catch_unwind(AssertUnwindSafe(|| job(&env, &rtxn))).is_err() {
+                        tracing::error!("Read in snapshot txn panicked");
+                    }
+                }
+            })
+            .context("Failed to spawn snapshot thread")?;
+
+        let txn = PinnedTxn {
+            job_sender: Some(job_sender),
+            thread: Some(thread),
+        };
+        let txn_id = txn_id_receiver
+            .recv()
+            .map_err(|_| anyhow!("Snapshot thread panicked"))??;
+
+        Ok(Self {
+            env: env.clone(),
+            txn_id,
+            txn: Arc::new(txn),
+        })
+    }
+
+    pub fn env(&self) -> &heed::Env {
+        &self.env
+    }
+
+    pub fn txn_id(&self) -> u64 {
+        self.txn_id
+    }
+
+    /// Returns the names of the databases in this snapshot, like
+    /// [`database::names`], and the names that aren't valid UTF-8.
+    pub fn names(&self) -> Result<(Vec<Option<String>>, Vec<String>)> {
+        self.read(database::scan_names)
+    }
+
+    /// Runs `read` with the pinned txn of this snapshot, waiting for other
+    /// reads of it to finish first.
+    pub fn read<T: Send>(
+        &self,
+        read: impl FnOnce(&heed::Env, &heed::RoTxn<'_>) -> Result<T> + Send,
+    ) -> Result<T> {
+        let (result_sender, result_receiver) = mpsc::sync_channel(1);
+        let job: ScopedJob<'_> = Box::new(move |env, rtxn| {
+            let _ = result_sender.send(read(env, rtxn));
+        });
+        // SAFETY: This only waits below until the job is run or dropped, as
+        // either drops its result sender, so what it borrows outlives it
+        let job = unsafe {
+            std::mem::transmute::<Box<dyn FnOnce(&heed::Env, &heed::RoTxn<'_>) + Send + '_>, Job>(
+                job,
+            )
+        };
+
+        self.txn
+            .job_sender
+            .as_ref()
+            .unwrap()
+            .send(job)
+            .map_err(|_| anyhow!("Snapshot thread stopped"))?;
+        result_receiver
+            .recv()
+            .map_err(|_| anyhow!("Read in snapshot txn panicked"))?
+    }
+}
+
+/// Thread running reads in a txn that pins a state of an env.
+struct PinnedTxn {
+    job_sender: Option<mpsc::Sender<Job>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl fmt::Debug for PinnedTxn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PinnedTxn").finish_non_exhaustive()
+    }
+}
+
+impl Drop for PinnedTxn {
+    fn drop(&mut self) {
+        // The env is only closed once the thread drops its handle, so this
+        // waits for it
+        drop(self.job_sender.take());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Opens a read txn on the latest state of `env`, returning it with the id of
+/// the txn that wrote that state.
+fn begin_pinned_txn(env: &heed::Env) -> Result<(u64, heed::RoTxn<'_>)> {
+    // LMDB doesn't expose the id of a read txn, so it is taken from the env
+    // before and after the txn starts, until no write commits between
+    loop {
+        let txn_id = env.info().last_txn_id as u64;
+        open_databases(env)?;
+        let rtxn = env.read_txn().context("Failed to create read txn")?;
+        if env.info().last_txn_id as u64 == txn_id {
+            return Ok((txn_id, rtxn));
+        }
+    }
+}
+
+/// Opens the handles of the named databases of `env` in a committed txn.
+/// Handles opened in the pinned txn would be closed when it ends, and ones
+/// opened after it starts can't be used in it.
+fn open_databases(env: &heed::Env) -> Result<()> {
+    let names = database::names(env)?;
+    let rtxn = env.read_txn().context("Failed to create read txn")?;
+    for name in names.iter().flatten() {
+        // Envs without room for more handles can still be read, if not all
+        // their databases
+        if let Err(err) = env.open_database::<ByteSlice, ByteSlice>(&rtxn, Some(name)) {
+            tracing::warn!("Failed to open db `{}`: {:?}", name, err);
+        }
+    }
+    rtxn.commit().context("Failed to commit read txn")?;
+    Ok(())
+}
+
+/// Decides when the snapshot of the shown entries is renewed, so an old
+/// snapshot isn't kept forever once the env changed. Renewing while the
+/// window is in use would move rows under the pointer, so it waits for the
+/// window to be idle, but no longer than the staleness window.
+#[derive(Debug)]
+pub struct SnapshotRenewal {
+    /// How long a stale snapshot is kept while the window is in use
+    max_stale_duration: Duration,
+    last_activity: Instant,
+    /// When the snapshot was first seen to be stale
+    stale_since: Option<Instant>,
+    /// Snapshot that is kept as asked, until another is shown
+    kept: Option<Snapshot>,
+}
+
+impl Default for SnapshotRenewal {
+    fn default() -> Self {
+        Self {
+            max_stale_duration: Duration::ZERO,
+            last_activity: Instant::now(),
+            stale_since: None,
+            kept: None,
+        }
+    }
+}
+
+impl SnapshotRenewal {
+    pub fn set_max_stale_duration(&mut self, max_stale_duration: Duration) {
+        self.max_stale_duration = max_stale_duration;
+    }
+
+    /// Records that the window is in use.
+    pub fn record_activity(&mut self) {
+        self.last_activity = Instant::now();
+    }
+
+    /// Keeps `snapshot` instead of renewing it, until another one is shown.
+    /// Its txn stays open meanwhile, so the pages it reads aren't reused by
+    /// writers and the env may grow.
+    pub fn keep(&mut self, snapshot: &Snapshot) {
+        self.kept = Some(snapshot.clone());
+        self.stale_since = None;
+    }
+
+    /// Returns whether `snapshot` should be renewed now, which is when the
+    /// env changed since, and the window has been idle for a while or the
+    /// snapshot has been stale for longer than the staleness window.
+    pub fn should_renew(&mut self, snapshot: &Snapshot) -> bool {
+        if self
+            .kept
+            .as_ref()
+            .is_some_and(|kept| kept.txn_id() == snapshot.txn_id())
+        {
+            return false;
+        }
+        self.kept = None;
+
+        let latest_txn_id = snapshot.env().info().last_txn_id as u64;
+        if latest_txn_id == snapshot.txn_id() {
+            self.stale_since = None;
+            return false;
+        }
+
+        let stale_since = *self.stale_since.get_or_insert_with(Instant::now);
+        if self.last_activity.elapsed() >= IDLE_DURATION
+            || stale_since.elapsed() >= self.max_stale_duration
+        {
+            self.stale_since = None;
+            true
+        } else {
+            false
+        }
+    }
+}
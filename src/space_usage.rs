@@ -0,0 +1,56 @@
+use anyhow::{Context, Result};
+
+use crate::{meta_page, utils};
+
+/// Number of meta pages at the start of the data file
+const N_META_PAGES: u64 = 2;
+
+/// How the pages of an env are used, which helps diagnose a full map.
+#[derive(Debug, Clone, Copy)]
+pub struct SpaceUsage {
+    pub page_size: u64,
+    /// Pages that fit in the map
+    pub n_map_pages: u64,
+    /// Pages written to the data file so far, which only grows
+    pub n_allocated_pages: u64,
+    /// Pages holding the entries of the databases
+    pub n_used_pages: u64,
+    /// Pages holding the freelist itself
+    pub n_freelist_pages: u64,
+    /// Freelist entries, one for each txn whose freed pages aren't reused yet
+    pub n_freelist_entries: u64,
+}
+
+impl SpaceUsage {
+    /// Reads the usage of `env`, with the freelist stats taken from the
+    /// latest meta page of its data file, as heed doesn't expose them.
+    pub fn read(env: &heed::Env) -> Result<Self> {
+        let info = env.info();
+        let meta_pages = meta_page::read_meta_pages(&utils::data_path(env.path()))
+            .context("Failed to read meta pages")?;
+        let meta_page = meta_pages[meta_page::current_index(&meta_pages)];
+
+        let page_size = meta_page.page_size();
+        Ok(Self {
+            page_size,
+            n_map_pages: info.map_size as u64 / page_size,
+            n_allocated_pages: info.last_page_number as u64 + 1,
+            n_used_pages: env.non_free_pages_size()? / page_size,
+            n_freelist_pages: meta_page.free_db.n_pages(),
+            n_freelist_entries: meta_page.free_db.n_entries,
+        })
+    }
+
+    /// Pages that were freed and can be reused without growing the file
+    pub fn n_free_pages(&self) -> u64 {
+        self.n_allocated_pages
+            .saturating_sub(N_META_PAGES + self.n_used_pages + self.n_freelist_pages)
+    }
+
+    /// Fraction of the map that is allocated but not free, which is how full
+    /// the map is
+    pub fn map_utilization(&self) -> f64 {
+        let n_pages = self.n_allocated_pages - self.n_free_pages();
+        (n_pages as f64 / self.n_map_pages as f64).min(1.0)
+    }
+}
@@ -0,0 +1,74 @@
+use anyhow::{Context, Result};
+use heed::types::ByteSlice;
+use rusqlite::{params, Connection};
+
+use std::{
+    fs,
+    path::Path,
+    sync::atomic::{self, AtomicUsize},
+};
+
+use crate::snapshot::Snapshot;
+
+/// Number of rows inserted per SQLite transaction
+const BATCH_LEN: usize = 10_000;
+
+/// Writes the entries of the databases called `db_names` to a new SQLite
+/// file at `path`, as rows of an `entries (db TEXT, key BLOB, value BLOB)`
+/// table, where `db` is `NULL` for the unnamed database.
+///
+/// `n_total` is set to the number of entries to write, and `n_written` is
+/// incremented after each batch, so progress can be shown.
+pub fn export(
+    snapshot: &Snapshot,
+    db_names: &[Option<String>],
+    path: &Path,
+    n_total: &AtomicUsize,
+    n_written: &AtomicUsize,
+) -> Result<()> {
+    if path.exists() {
+        fs::remove_file(path).with_context(|| format!("Failed to replace `{}`", path.display()))?;
+    }
+
+    let mut connection = Connection::open(path)?;
+    connection.execute_batch(
+        "PRAGMA journal_mode = OFF;
+        PRAGMA synchronous = OFF;
+        CREATE TABLE entries (db TEXT, key BLOB NOT NULL, value BLOB NOT NULL);",
+    )?;
+
+    snapshot.read(|env, rtxn| {
+        let mut dbs = Vec::new();
+        for name in db_names {
+            let db = env
+                .open_database::<ByteSlice, ByteSlice>(rtxn, name.as_deref())?
+                .with_context(|| format!("Database `{:?}` not found", name))?;
+            n_total.fetch_add(db.len(rtxn)? as usize, atomic::Ordering::Relaxed);
+            dbs.push((name, db));
+        }
+
+        for (name, db) in dbs {
+            let mut entries = db.iter(rtxn)?.peekable();
+            while entries.peek().is_some() {
+                let tx = connection.transaction()?;
+                let mut n_batch_written = 0;
+                {
+                    let mut statement = tx.prepare_cached(
+                        "INSERT INTO entries (db, key, value) VALUES (?1, ?2, ?3)",
+                    )?;
+                    for item in entries.by_ref().take(BATCH_LEN) {
+                        let (key, value) = item?;
+                        statement.execute(params![name, key, value])?;
+                        n_batch_written += 1;
+                    }
+                }
+                tx.commit()?;
+                n_written.fetch_add(n_batch_written, atomic::Ordering::Relaxed);
+            }
+        }
+
+        connection.execute_batch("CREATE INDEX entries_db_key ON entries (db, key);")?;
+
+        Ok(())
+    })
+}
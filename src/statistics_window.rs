@@ -0,0 +1,393 @@
+use adw::{prelude::*, subclass::prelude::*};
+use gettextrs::gettext;
+use gtk::{
+    gio,
+    glib::{self, clone},
+};
+use heed::Env;
+
+use crate::{
+    db_stats::DbStats,
+    db_stats_item::DbStatsItem,
+    error_details,
+    size_distribution::{self, SizeDistribution},
+    space_usage::SpaceUsage,
+    utils::gettext_f,
+    window::Window,
+};
+
+/// Height of the size histograms
+const HISTOGRAM_HEIGHT: i32 = 160;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, gtk::CompositeTemplate)]
+    #[template(resource = "/io/github/seadve/LmdbViewer/ui/statistics_window.ui")]
+    pub struct StatisticsWindow {
+        #[template_child]
+        pub(super) toast_overlay: TemplateChild<adw::ToastOverlay>,
+        #[template_child]
+        pub(super) stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        pub(super) column_view: TemplateChild<gtk::ColumnView>,
+        #[template_child]
+        pub(super) sort_model: TemplateChild<gtk::SortListModel>,
+        #[template_child]
+        pub(super) sizes_page: TemplateChild<adw::ViewStackPage>,
+        #[template_child]
+        pub(super) sizes_stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        pub(super) sizes_box: TemplateChild<gtk::Box>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for StatisticsWindow {
+        const NAME: &'static str = "LvStatisticsWindow";
+        type Type = super::StatisticsWindow;
+        type ParentType = adw::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for StatisticsWindow {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            self.obj().setup_view();
+        }
+    }
+
+    impl WidgetImpl for StatisticsWindow {}
+    impl WindowImpl for StatisticsWindow {}
+    impl AdwWindowImpl for StatisticsWindow {}
+}
+
+glib::wrapper! {
+    pub struct StatisticsWindow(ObjectSubclass<imp::StatisticsWindow>)
+        @extends gtk::Widget, gtk::Window, adw::Window;
+}
+
+impl StatisticsWindow {
+    /// Creates a window showing how much storage each named database in
+    /// `env` takes, and the key and value sizes of the database called
+    /// `db_name`, if any.
+    pub fn new(parent: &Window, env: &Env, db_name: Option<&str>) -> Self {
+        let this = glib::Object::builder::<Self>()
+            .property("transient-for", parent)
+            .build();
+
+        this.load_db_stats(env.clone());
+        if let Some(db_name) = db_name {
+            this.load_sizes(env.clone(), db_name.to_string());
+        }
+
+        this
+    }
+
+    fn add_error_toast(&self, message: &str, err: &anyhow::Error) {
+        let toast = error_details::error_toast(self, message, err);
+        self.imp().toast_overlay.add_toast(toast);
+    }
+
+    /// Collects the stats of the databases in `env` in a separate thread.
+    fn load_db_stats(&self, env: Env) {
+        let imp = self.imp();
+
+        imp.stack.set_visible_child_name("loading");
+
+        glib::spawn_future_local(clone!(@weak self as obj => async move {
+            let imp = obj.imp();
+
+            let result = gio::spawn_blocking(move || {
+                let page_size = SpaceUsage::read(&env)?.page_size;
+                let all_stats = DbStats::collect(&env)?;
+                Ok::<_, anyhow::Error>((page_size, all_stats))
+            })
+            .await
+            .unwrap();
+
+            match result {
+                Ok((page_size, all_stats)) => {
+                    let max_size = all_stats
+                        .iter()
+                        .map(|stats| stats.n_pages() * page_size)
+                        .max()
+                        .unwrap_or(0);
+                    let items = all_stats
+                        .iter()
+                        .map(|stats| DbStatsItem::new(stats, page_size, max_size))
+                        .collect::<Vec<_>>();
+                    let store = gio::ListStore::new::<DbStatsItem>();
+                    store.extend_from_slice(&items);
+
+                    imp.stack.set_visible_child_name(if store.n_items() == 0 {
+                        "empty"
+                    } else {
+                        "databases"
+                    });
+                    imp.sort_model.set_model(Some(&store));
+                }
+                Err(err) => {
+                    tracing::error!("Failed to collect db stats: {:?}", &err);
+                    obj.add_error_toast(&gettext("Failed to collect statistics"), &err);
+                    imp.stack.set_visible_child_name("empty");
+                }
+            }
+        }));
+    }
+
+    /// Collects the key and value sizes of the database called `db_name` in
+    /// a separate thread.
+    fn load_sizes(&self, env: Env, db_name: String) {
+        let imp = self.imp();
+
+        imp.sizes_page.set_visible(true);
+        imp.sizes_stack.set_visible_child_name("loading");
+
+        glib::spawn_future_local(clone!(@weak self as obj => async move {
+            let imp = obj.imp();
+
+            let result = gio::spawn_blocking(clone!(@strong db_name => move || {
+                size_distribution::collect(&env, Some(&db_name))
+            }))
+            .await
+            .unwrap();
+
+            match result {
+                Ok((key_sizes, value_sizes)) => {
+                    if value_sizes.is_empty() {
+                        imp.sizes_stack.set_visible_child_name("empty");
+                        return;
+                    }
+
+                    imp.sizes_box.append(&size_section(
+                        &gettext_f("Value Sizes in “{name}”", &[("name", &db_name)]),
+                        &value_sizes,
+                    ));
+                    imp.sizes_box.append(&size_section(
+                        &gettext_f("Key Sizes in “{name}”", &[("name", &db_name)]),
+                        &key_sizes,
+                    ));
+                    imp.sizes_stack.set_visible_child_name("sizes");
+                }
+                Err(err) => {
+                    tracing::error!("Failed to collect sizes: {:?}", &err);
+                    obj.add_error_toast(&gettext("Failed to collect sizes"), &err);
+                    imp.sizes_stack.set_visible_child_name("empty");
+                }
+            }
+        }));
+    }
+
+    fn setup_view(&self) {
+        let imp = self.imp();
+
+        let name_sorter = gtk::StringSorter::new(Some(property_expression("name")));
+        let name_column =
+            self.append_column(&gettext("Database"), &name_sorter, |item| item.name());
+        name_column.set_expand(true);
+        let size_column = self.append_size_column();
+        self.append_column(&gettext("Entries"), &numeric_sorter("n-entries"), |item| {
+            item.n_entries().to_string()
+        });
+        self.append_column(
+            &gettext("Branch Pages"),
+            &numeric_sorter("n-branch-pages"),
+            |item| item.n_branch_pages().to_string(),
+        );
+        self.append_column(
+            &gettext("Leaf Pages"),
+            &numeric_sorter("n-leaf-pages"),
+            |item| item.n_leaf_pages().to_string(),
+        );
+        self.append_column(
+            &gettext("Overflow Pages"),
+            &numeric_sorter("n-overflow-pages"),
+            |item| item.n_overflow_pages().to_string(),
+        );
+        self.append_column(
+            &gettext("Key Bytes"),
+            &numeric_sorter("key-bytes"),
+            |item| glib::format_size(item.key_bytes()).to_string(),
+        );
+        self.append_column(
+            &gettext("Value Bytes"),
+            &numeric_sorter("value-bytes"),
+            |item| glib::format_size(item.value_bytes()).to_string(),
+        );
+
+        imp.sort_model.set_sorter(imp.column_view.sorter().as_ref());
+        imp.column_view
+            .sort_by_column(Some(&size_column), gtk::SortType::Descending);
+    }
+
+    /// Appends a column showing the size of each database with a bar
+    /// relative to the largest one.
+    fn append_size_column(&self) -> gtk::ColumnViewColumn {
+        let factory = gtk::SignalListItemFactory::new();
+        factory.connect_setup(|_, list_item| {
+            let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
+            let level_bar = gtk::LevelBar::builder()
+                .width_request(120)
+                .valign(gtk::Align::Center)
+                .build();
+            // Keep the bar a single color regardless of its value
+            level_bar.remove_offset_value(Some(gtk::LEVEL_BAR_OFFSET_LOW));
+            level_bar.remove_offset_value(Some(gtk::LEVEL_BAR_OFFSET_HIGH));
+            let label = gtk::Label::builder().xalign(0.0).build();
+            let hbox = gtk::Box::builder().spacing(6).build();
+            hbox.append(&level_bar);
+            hbox.append(&label);
+            list_item.set_child(Some(&hbox));
+        });
+        factory.connect_bind(|_, list_item| {
+            let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
+            let hbox = list_item.child().and_downcast::<gtk::Box>().unwrap();
+            let level_bar = hbox.first_child().and_downcast::<gtk::LevelBar>().unwrap();
+            let label = hbox.last_child().and_downcast::<gtk::Label>().unwrap();
+            let item = list_item.item().and_downcast::<DbStatsItem>().unwrap();
+
+            level_bar.set_value(item.share());
+            label.set_label(&glib::format_size(item.size()));
+        });
+
+        let column = gtk::ColumnViewColumn::new(Some(&gettext("Size")), Some(factory));
+        column.set_resizable(true);
+        column.set_sorter(Some(&numeric_sorter("size")));
+        self.imp().column_view.append_column(&column);
+
+        column
+    }
+
+    /// Appends a column sorted by `sorter`, whose cells show the text returned
+    /// by `cell_text` for each item.
+    fn append_column(
+        &self,
+        title: &str,
+        sorter: &impl IsA<gtk::Sorter>,
+        cell_text: impl Fn(&DbStatsItem) -> String + 'static,
+    ) -> gtk::ColumnViewColumn {
+        let factory = gtk::SignalListItemFactory::new();
+        factory.connect_setup(|_, list_item| {
+            let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
+            let label = gtk::Label::builder()
+                .xalign(0.0)
+                .ellipsize(gtk::pango::EllipsizeMode::End)
+                .single_line_mode(true)
+                .build();
+            list_item.set_child(Some(&label));
+        });
+        factory.connect_bind(move |_, list_item| {
+            let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
+            let label = list_item.child().and_downcast::<gtk::Label>().unwrap();
+            let item = list_item.item().and_downcast::<DbStatsItem>().unwrap();
+
+            label.set_label(&cell_text(&item));
+        });
+
+        let column = gtk::ColumnViewColumn::new(Some(title), Some(factory));
+        column.set_resizable(true);
+        column.set_sorter(Some(sorter));
+        self.imp().column_view.append_column(&column);
+
+        column
+    }
+}
+
+fn numeric_sorter(property_name: &str) -> gtk::NumericSorter {
+    gtk::NumericSorter::new(Some(property_expression(property_name)))
+}
+
+fn property_expression(property_name: &str) -> gtk::PropertyExpression {
+    gtk::PropertyExpression::new(
+        DbStatsItem::static_type(),
+        gtk::Expression::NONE,
+        property_name,
+    )
+}
+
+/// Creates a histogram of `sizes` with their percentiles below it.
+fn size_section(title: &str, sizes: &SizeDistribution) -> gtk::Widget {
+    let title_label = gtk::Label::builder()
+        .label(title)
+        .xalign(0.0)
+        .css_classes(["heading"])
+        .build();
+
+    let buckets = sizes.buckets();
+    let drawing_area = gtk::DrawingArea::builder()
+        .content_height(HISTOGRAM_HEIGHT)
+        .has_tooltip(true)
+        .build();
+    drawing_area.set_draw_func(
+        clone!(@strong buckets => move |drawing_area, cr, width, height| {
+            let max_count = buckets.iter().copied().max().unwrap_or(0).max(1);
+            let bar_width = width as f64 / buckets.len() as f64;
+
+            let color = drawing_area.color();
+            cr.set_source_rgba(
+                color.red() as f64,
+                color.green() as f64,
+                color.blue() as f64,
+                0.6,
+            );
+            for (index, count) in buckets.iter().enumerate() {
+                let bar_height = height as f64 * *count as f64 / max_count as f64;
+                cr.rectangle(
+                    index as f64 * bar_width + 1.0,
+                    height as f64 - bar_height,
+                    bar_width - 2.0,
+                    bar_height,
+                );
+            }
+            if let Err(err) = cr.fill() {
+                tracing::warn!("Failed to draw histogram: {:?}", err);
+            }
+        }),
+    );
+    drawing_area.connect_query_tooltip(move |drawing_area, x, _, _, tooltip| {
+        let bar_width = drawing_area.width() as f64 / buckets.len() as f64;
+        let index = ((x as f64 / bar_width) as usize).min(buckets.len() - 1);
+        let (min, max) = size_distribution::bucket_range(index);
+        tooltip.set_text(Some(&gettext_f(
+            "{min} to {max}: {count}",
+            &[
+                ("min", &glib::format_size(min)),
+                ("max", &glib::format_size(max)),
+                ("count", &buckets[index].to_string()),
+            ],
+        )));
+        true
+    });
+
+    let percentiles_label = gtk::Label::builder()
+        .label(gettext_f(
+            "Median {median} · 90th percentile {p90} · 99th percentile {p99} · Largest {max}",
+            &[
+                ("median", &glib::format_size(sizes.percentile(0.5))),
+                ("p90", &glib::format_size(sizes.percentile(0.9))),
+                ("p99", &glib::format_size(sizes.percentile(0.99))),
+                ("max", &glib::format_size(sizes.max())),
+            ],
+        ))
+        .xalign(0.0)
+        .wrap(true)
+        .css_classes(["dim-label"])
+        .build();
+
+    let vbox = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(6)
+        .build();
+    vbox.append(&title_label);
+    vbox.append(&drawing_area);
+    vbox.append(&percentiles_label);
+    vbox.upcast()
+}
@@ -0,0 +1,40 @@
+use gtk::glib;
+
+use std::borrow::Cow;
+
+/// Character encoding used to render bytes as text.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, glib::Enum)]
+#[enum_type(name = "LvTextEncoding")]
+pub enum TextEncoding {
+    #[default]
+    #[enum_value(name = "UTF-8", nick = "utf-8")]
+    Utf8,
+    #[enum_value(name = "UTF-16LE", nick = "utf-16le")]
+    Utf16Le,
+    #[enum_value(name = "UTF-16BE", nick = "utf-16be")]
+    Utf16Be,
+    #[enum_value(name = "Latin-1", nick = "iso-8859-1")]
+    Latin1,
+    #[enum_value(name = "Shift_JIS", nick = "shift_jis")]
+    ShiftJis,
+    #[enum_value(name = "Windows-1252", nick = "windows-1252")]
+    Windows1252,
+}
+
+impl TextEncoding {
+    /// Decodes `bytes`, replacing malformed sequences with U+FFFD.
+    pub fn decode(self, bytes: &[u8]) -> Cow<'_, str> {
+        let encoding = match self {
+            Self::Utf8 => return String::from_utf8_lossy(bytes),
+            // encoding_rs treats ISO-8859-1 as Windows-1252, per the WHATWG
+            // spec, but the real thing maps every byte to the same code point.
+            Self::Latin1 => return Cow::Owned(bytes.iter().map(|&byte| byte as char).collect()),
+            Self::Utf16Le => encoding_rs::UTF_16LE,
+            Self::Utf16Be => encoding_rs::UTF_16BE,
+            Self::ShiftJis => encoding_rs::SHIFT_JIS,
+            Self::Windows1252 => encoding_rs::WINDOWS_1252,
+        };
+
+        encoding.decode_without_bom_handling(bytes).0
+    }
+}
@@ -0,0 +1,119 @@
+use gettextrs::gettext;
+use gtk::glib;
+
+use std::ops::RangeInclusive;
+
+/// Range of microseconds since the UNIX epoch, from 1980 to 2100, outside
+/// which a reading is unlikely to be the meant one
+const PLAUSIBLE_UNIX_MICROS: RangeInclusive<i64> = 315_532_800_000_000..=4_102_444_800_000_000;
+
+/// Microseconds from the Windows FILETIME epoch, in 1601, to the UNIX epoch
+const FILETIME_EPOCH_OFFSET_MICROS: i64 = 11_644_473_600_000_000;
+
+/// A reading of bytes as a point in time.
+#[derive(Debug, Clone)]
+pub struct Timestamp {
+    /// How the bytes were read, e.g., "UNIX Seconds (Little Endian)"
+    pub label: String,
+    pub date_time: glib::DateTime,
+}
+
+impl Timestamp {
+    /// Returns the time in the local time zone, followed by the offset.
+    pub fn to_local_string(&self) -> String {
+        self.date_time
+            .to_local()
+            .and_then(|date_time| date_time.format("%Y-%m-%d %H:%M:%S %:z"))
+            .map(|string| string.to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// Returns the plausible times `bytes` can be read as, from binary integers
+/// of 4 or 8 bytes, decimal text, or ISO 8601 text.
+pub fn interpret(bytes: &[u8]) -> Vec<Timestamp> {
+    let mut timestamps = Vec::new();
+
+    let text = std::str::from_utf8(bytes).ok().map(|text| text.trim());
+
+    if let Some(text) = text.filter(|text| !text.is_empty()) {
+        if let Ok(n) = text.parse::<i64>() {
+            push_integer(&mut timestamps, n, None);
+        } else if let Ok(date_time) = glib::DateTime::from_iso8601(text, None) {
+            timestamps.push(Timestamp {
+                label: gettext("ISO 8601"),
+                date_time,
+            });
+        }
+    }
+
+    match bytes.len() {
+        4 => {
+            let bytes = bytes.try_into().unwrap();
+            push_integer(
+                &mut timestamps,
+                u32::from_le_bytes(bytes) as i64,
+                Some(gettext("Little Endian")),
+            );
+            push_integer(
+                &mut timestamps,
+                u32::from_be_bytes(bytes) as i64,
+                Some(gettext("Big Endian")),
+            );
+        }
+        8 => {
+            let bytes = bytes.try_into().unwrap();
+            push_integer(
+                &mut timestamps,
+                i64::from_le_bytes(bytes),
+                Some(gettext("Little Endian")),
+            );
+            push_integer(
+                &mut timestamps,
+                i64::from_be_bytes(bytes),
+                Some(gettext("Big Endian")),
+            );
+        }
+        _ => {}
+    }
+
+    timestamps
+}
+
+/// Adds the plausible times `n` can be, read in each unit, labeled with
+/// `byte_order` if it came from binary.
+fn push_integer(timestamps: &mut Vec<Timestamp>, n: i64, byte_order: Option<String>) {
+    let readings = [
+        (gettext("UNIX Seconds"), n.checked_mul(1_000_000)),
+        (gettext("UNIX Milliseconds"), n.checked_mul(1_000)),
+        (gettext("UNIX Microseconds"), Some(n)),
+        (gettext("UNIX Nanoseconds"), Some(n / 1_000)),
+        (
+            gettext("Windows FILETIME"),
+            Some(n / 10 - FILETIME_EPOCH_OFFSET_MICROS),
+        ),
+    ];
+
+    for (unit, unix_micros) in readings {
+        let Some(date_time) = unix_micros
+            .filter(|unix_micros| PLAUSIBLE_UNIX_MICROS.contains(unix_micros))
+            .and_then(date_time_from_unix_micros)
+        else {
+            continue;
+        };
+
+        timestamps.push(Timestamp {
+            label: match &byte_order {
+                Some(byte_order) => format!("{} ({})", unit, byte_order),
+                None => unit,
+            },
+            date_time,
+        });
+    }
+}
+
+fn date_time_from_unix_micros(unix_micros: i64) -> Option<glib::DateTime> {
+    glib::DateTime::from_unix_utc(unix_micros.div_euclid(1_000_000))
+        .and_then(|date_time| date_time.add(glib::TimeSpan(unix_micros.rem_euclid(1_000_000))))
+        .ok()
+}
@@ -0,0 +1,336 @@
+use anyhow::{Context, Result};
+use gettextrs::{gettext, ngettext};
+use gtk::{gio, glib, prelude::*};
+use heed::{types::ByteSlice, EnvFlags};
+
+use std::{
+    fs::{self, File},
+    io,
+    os::fd::AsRawFd,
+    path::{Path, PathBuf},
+    process::Command,
+    time::Duration,
+};
+
+use crate::{backend::BackendKind, config::APP_ID, env_profile::EnvProfile, mdbx};
+
+/// Map size used unless a larger one is remembered for the env
+const DEFAULT_MAP_SIZE: usize = 100 * 1024 * 1024; // 100 MiB
+
+/// Number of named dbs that can be opened in an env that doesn't have more
+const DEFAULT_MAX_DBS: u32 = 100;
+
+/// `MDB_PREVSNAPSHOT`, which heed doesn't name, to open an env at the
+/// transaction before its last one
+pub const PREVIOUS_SNAPSHOT: EnvFlags = EnvFlags::from_bits_retain(0x2000000);
+
+/// Size of the start of a data file that holds the LMDB magic number, after
+/// the page header of pgno (size_t), then pad, flags, lower and upper (u16)
+const LMDB_HEADER_LEN: usize = std::mem::size_of::<usize>() + 4 * 2 + 4;
+
+/// Suffix LMDB adds to the path of an env made of a single data file to name
+/// its lock file
+const LOCK_SUFFIX: &str = "-lock";
+
+/// Like `gettext`, but replaces named variables, e.g. `{name}`, with the
+/// given values, so translators can reorder them.
+pub fn gettext_f(format: &str, args: &[(&str, &str)]) -> String {
+    let mut string = gettext(format);
+
+    for (key, value) in args {
+        string = string.replace(&format!("{{{}}}", key), value);
+    }
+
+    string
+}
+
+/// Like `gettext_f`, but picks the singular or plural form based on `n`.
+pub fn ngettext_f(msgid: &str, msgid_plural: &str, n: u32, args: &[(&str, &str)]) -> String {
+    let mut string = ngettext(msgid, msgid_plural, n);
+
+    for (key, value) in args {
+        string = string.replace(&format!("{{{}}}", key), value);
+    }
+
+    string
+}
+
+/// Opens the env at `path`, read-write if `write_mode` is true, with the
+/// flags chosen in the preferences.
+///
+/// Read-only envs are opened with locking when possible, so reads are
+/// consistent, and fall back to no locking if allowed, e.g., when the lock
+/// file can't be written. Read-write envs never fall back, as writing
+/// without locking may corrupt the env if another process writes too.
+pub fn open_env(path: &Path, write_mode: bool) -> heed::Result<heed::Env> {
+    let settings = gio::Settings::new(APP_ID);
+
+    let mut flags = if write_mode {
+        EnvFlags::empty()
+    } else {
+        EnvFlags::READ_ONLY
+    };
+    if settings.boolean("open-no-read-ahead") {
+        flags |= EnvFlags::NO_READ_AHEAD;
+    }
+
+    let map_size = EnvProfile::load(path)
+        .map_size
+        .map_or(DEFAULT_MAP_SIZE, |map_size| {
+            (map_size as usize).max(DEFAULT_MAP_SIZE)
+        });
+
+    match open_env_with_options(path, flags, map_size) {
+        Err(heed::Error::Io(err)) if !write_mode && settings.boolean("open-no-lock") => {
+            tracing::debug!("Failed to open env with locking, retrying without: {}", err);
+            open_env_with_options(path, flags | EnvFlags::NO_LOCK, map_size)
+        }
+        result => result,
+    }
+}
+
+/// Returns whether `env` was opened without locking, in which case reads may
+/// be torn while another process writes.
+pub fn is_no_lock(env: &heed::Env) -> heed::Result<bool> {
+    Ok(EnvFlags::from_bits_truncate(env.raw_flags()?).contains(EnvFlags::NO_LOCK))
+}
+
+/// Returns whether another process has the env at `path` open with locking,
+/// which LMDB marks with a shared lock on the first byte of the lock file.
+pub fn is_env_in_use(path: &Path) -> io::Result<bool> {
+    let file = match File::open(lock_path(path)) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(err) => return Err(err),
+    };
+
+    // SAFETY: `flock` is plain data, and is only read after `fcntl` fills it
+    let mut lock = unsafe { std::mem::zeroed::<libc::flock>() };
+    lock.l_type = libc::F_WRLCK as libc::c_short;
+    lock.l_whence = libc::SEEK_SET as libc::c_short;
+    lock.l_start = 0;
+    lock.l_len = 1;
+    // SAFETY: the fd is valid while `file` is alive and `lock` is a valid `flock`
+    if unsafe { libc::fcntl(file.as_raw_fd(), libc::F_GETLK, &mut lock) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(lock.l_type != libc::F_UNLCK as libc::c_short)
+}
+
+/// Opens the env at `path` with `flags`.
+pub fn open_env_with_flags(path: &Path, flags: EnvFlags) -> heed::Result<heed::Env> {
+    open_env_with_options(path, flags, DEFAULT_MAP_SIZE)
+}
+
+/// Creates a new env in `dir`, which must not have one yet, with a map of
+/// at least `map_size` bytes.
+pub fn create_env(dir: &Path, map_size: usize) -> Result<heed::Env> {
+    anyhow::ensure!(
+        !dir.join("data.mdb").exists(),
+        "`{}` already has an env",
+        dir.display()
+    );
+
+    let env = open_env_with_options(dir, EnvFlags::empty(), map_size.max(DEFAULT_MAP_SIZE))?;
+    Ok(env)
+}
+
+/// Opens the env at `path` with `flags` and a map of at least `map_size`
+/// bytes.
+///
+/// Envs with more named dbs than [`DEFAULT_MAX_DBS`] are reopened with room
+/// for all of them, plus as many more for the ones created afterwards.
+///
+/// Envs that are already open, such as in another window, are shared, and
+/// can't be reopened until all their users close them, so they are kept as is.
+fn open_env_with_options(path: &Path, flags: EnvFlags, map_size: usize) -> heed::Result<heed::Env> {
+    let is_shared =
+        fs::canonicalize(path).is_ok_and(|path| heed::env_closing_event(path).is_some());
+    let env = open_env_with_max_dbs(path, flags, map_size, DEFAULT_MAX_DBS)?;
+    if is_shared {
+        return Ok(env);
+    }
+
+    // Every key of the unnamed db of an env with named dbs is a name, so this
+    // is at least the number of named dbs
+    let n_names = {
+        let rtxn = env.read_txn()?;
+        match env.open_database::<ByteSlice, ByteSlice>(&rtxn, None)? {
+            Some(unnamed_db) => unnamed_db.len(&rtxn)?,
+            None => 0,
+        }
+    };
+    if n_names <= DEFAULT_MAX_DBS as u64 {
+        return Ok(env);
+    }
+
+    tracing::debug!("Reopening env with {} names", n_names);
+    // Another window may have opened it in the meantime
+    if !env
+        .prepare_for_closing()
+        .wait_timeout(Duration::from_secs(5))
+    {
+        return Err(heed::Error::Io(io::Error::new(
+            io::ErrorKind::TimedOut,
+            "Timed out waiting for env to close before reopening it",
+        )));
+    }
+    open_env_with_max_dbs(path, flags, map_size, n_names as u32 + DEFAULT_MAX_DBS)
+}
+
+fn open_env_with_max_dbs(
+    path: &Path,
+    flags: EnvFlags,
+    map_size: usize,
+    max_dbs: u32,
+) -> heed::Result<heed::Env> {
+    let flags = if path.is_file() {
+        flags | EnvFlags::NO_SUB_DIR
+    } else {
+        flags
+    };
+    unsafe {
+        heed::EnvOpenOptions::new()
+            .map_size(map_size)
+            .max_dbs(max_dbs)
+            .flags(flags)
+            .open(path)
+    }
+}
+
+/// Returns the permission error that caused `err`, if any.
+pub fn permission_error(err: &anyhow::Error) -> Option<&io::Error> {
+    err.chain()
+        .find_map(|cause| {
+            cause
+                .downcast_ref::<io::Error>()
+                .or_else(|| match cause.downcast_ref() {
+                    Some(heed::Error::Io(err)) => Some(err),
+                    _ => None,
+                })
+        })
+        .filter(|err| err.kind() == io::ErrorKind::PermissionDenied)
+}
+
+/// Returns the LMDB error that caused `err`, if any.
+pub fn mdb_error(err: &anyhow::Error) -> Option<&heed::MdbError> {
+    err.chain().find_map(|cause| match cause.downcast_ref() {
+        Some(heed::Error::Mdb(err)) => Some(err),
+        _ => None,
+    })
+}
+
+/// Copies the data file of the env at `path` to a new temporary directory,
+/// reading it as an administrator with `pkexec` if `as_admin` is true, and
+/// returns the directory.
+pub fn copy_env_to_temp(path: &Path, as_admin: bool) -> Result<PathBuf> {
+    let copy_dir = glib::mkdtemp(glib::tmp_dir().join("lmdb-viewer-XXXXXX"))
+        .context("Failed to create temporary directory")?;
+    let data_path = BackendKind::data_path(path);
+    let copy_path = copy_dir.join("data.mdb");
+
+    if as_admin {
+        let status = Command::new("pkexec")
+            .arg("cat")
+            .arg(&data_path)
+            .stdout(File::create(&copy_path)?)
+            .status()
+            .context("Failed to run pkexec")?;
+        anyhow::ensure!(status.success(), "pkexec exited with {}", status);
+    } else {
+        fs::copy(&data_path, &copy_path)
+            .with_context(|| format!("Failed to copy `{}`", data_path.display()))?;
+    }
+
+    // The format may only be readable from the copy
+    if let Some(BackendKind::Mdbx) = BackendKind::detect(&copy_path)? {
+        fs::rename(&copy_path, copy_dir.join(mdbx::DATA_FILE_NAME))?;
+    }
+
+    Ok(copy_dir)
+}
+
+/// Writes `bytes` to a file called `file_name` in a new temporary directory,
+/// and returns the directory.
+pub fn write_to_temp_dir(bytes: &[u8], file_name: &str) -> Result<PathBuf> {
+    let dir = glib::mkdtemp(glib::tmp_dir().join("lmdb-viewer-XXXXXX"))
+        .context("Failed to create temporary directory")?;
+    let path = dir.join(file_name);
+    fs::write(&path, bytes).with_context(|| format!("Failed to write `{}`", path.display()))?;
+    Ok(dir)
+}
+
+/// Returns the path the env at `path` is opened from, checking that it has a
+/// data file of a known format. This is the env directory if `path` is the
+/// directory or one of its files, e.g., `data.mdb` or `mdbx.dat`, or else the
+/// file itself for an env made of a single data file.
+pub fn env_path(path: &Path) -> Result<PathBuf> {
+    let env_path = match path.file_name().and_then(|name| name.to_str()) {
+        _ if path.is_dir() => path.to_path_buf(),
+        Some("data.mdb" | "lock.mdb" | mdbx::DATA_FILE_NAME | mdbx::LOCK_FILE_NAME) => {
+            path.parent().context("File has no parent")?.to_path_buf()
+        }
+        Some(name) if name.ends_with(LOCK_SUFFIX) => {
+            path.with_file_name(name.trim_end_matches(LOCK_SUFFIX))
+        }
+        Some(name) if name.ends_with(mdbx::LOCK_SUFFIX) => {
+            path.with_file_name(name.trim_end_matches(mdbx::LOCK_SUFFIX))
+        }
+        _ => path.to_path_buf(),
+    };
+
+    let data_path = BackendKind::data_path(&env_path);
+    match BackendKind::detect(&data_path) {
+        Ok(kind) => anyhow::ensure!(
+            kind.is_some(),
+            "`{}` is not an LMDB or MDBX data file",
+            data_path.display()
+        ),
+        // Let opening the env report it, so a copy can be offered
+        Err(err) if err.kind() == io::ErrorKind::PermissionDenied => {}
+        Err(err) => {
+            return Err(err).with_context(|| format!("Failed to read `{}`", data_path.display()))
+        }
+    }
+
+    Ok(env_path)
+}
+
+/// Returns the path of the data file of the env at `path`, which is the path
+/// itself for an env made of a single file.
+pub fn data_path(path: &Path) -> PathBuf {
+    if path.is_file() {
+        path.to_path_buf()
+    } else {
+        path.join("data.mdb")
+    }
+}
+
+/// Returns the path of the lock file of the env at `path`, which is next to
+/// the data file for an env made of a single file.
+pub fn lock_path(path: &Path) -> PathBuf {
+    if path.is_file() {
+        let mut lock_path = path.as_os_str().to_owned();
+        lock_path.push(LOCK_SUFFIX);
+        PathBuf::from(lock_path)
+    } else {
+        path.join("lock.mdb")
+    }
+}
+
+/// Returns whether `path` was shared through the document portal, which
+/// only gives access to the chosen file and not to the others next to it.
+pub fn is_document_portal_path(path: &Path) -> bool {
+    path.starts_with(glib::user_runtime_dir().join("doc"))
+}
+
+/// Returns whether `bytes` start like an LMDB data file, e.g., a value that
+/// holds a nested env.
+pub fn is_lmdb_data(bytes: &[u8]) -> bool {
+    const MAGIC: u32 = 0xBEEFC0DE;
+
+    bytes
+        .get(LMDB_HEADER_LEN - 4..LMDB_HEADER_LEN)
+        .is_some_and(|magic| u32::from_ne_bytes(magic.try_into().unwrap()) == MAGIC)
+}
@@ -0,0 +1,274 @@
+use adw::prelude::*;
+use gettextrs::gettext;
+use gtk::{
+    gdk,
+    glib::{self, clone},
+    subclass::prelude::*,
+};
+
+use crate::{
+    line_diff::{self, LineChange},
+    utils::ngettext_f,
+};
+
+mod imp {
+    use std::cell::Cell;
+
+    use super::*;
+
+    #[derive(Debug, Default, glib::Properties, gtk::CompositeTemplate)]
+    #[properties(wrapper_type = super::ValueDiffView)]
+    #[template(resource = "/io/github/seadve/LmdbViewer/ui/value_diff_view.ui")]
+    pub struct ValueDiffView {
+        /// Whether the texts are shown next to each other rather than as one
+        /// text with the removed and added lines in between
+        #[property(get, set = Self::set_side_by_side, explicit_notify)]
+        pub(super) side_by_side: Cell<bool>,
+
+        #[template_child]
+        pub(super) summary_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) side_by_side_button: TemplateChild<gtk::ToggleButton>,
+        #[template_child]
+        pub(super) stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        pub(super) unified_view: TemplateChild<gtk::TextView>,
+        #[template_child]
+        pub(super) before_title_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) after_title_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) before_view: TemplateChild<gtk::TextView>,
+        #[template_child]
+        pub(super) after_view: TemplateChild<gtk::TextView>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ValueDiffView {
+        const NAME: &'static str = "LvValueDiffView";
+        type Type = super::ValueDiffView;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for ValueDiffView {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let obj = self.obj();
+
+            for text_view in [&self.unified_view, &self.before_view, &self.after_view] {
+                let buffer = text_view.buffer();
+                buffer.create_tag(
+                    Some("removed"),
+                    &[(
+                        "paragraph-background-rgba",
+                        &gdk::RGBA::new(0.88, 0.11, 0.14, 0.15),
+                    )],
+                );
+                buffer.create_tag(
+                    Some("added"),
+                    &[(
+                        "paragraph-background-rgba",
+                        &gdk::RGBA::new(0.18, 0.76, 0.49, 0.15),
+                    )],
+                );
+                buffer.create_tag(
+                    Some("filler"),
+                    &[(
+                        "paragraph-background-rgba",
+                        &gdk::RGBA::new(0.5, 0.5, 0.5, 0.1),
+                    )],
+                );
+            }
+
+            self.side_by_side_button
+                .connect_active_notify(clone!(@weak obj => move |button| {
+                    obj.set_side_by_side(button.is_active());
+                }));
+        }
+
+        fn dispose(&self) {
+            self.dispose_template();
+        }
+    }
+
+    impl WidgetImpl for ValueDiffView {}
+
+    impl ValueDiffView {
+        fn set_side_by_side(&self, side_by_side: bool) {
+            if side_by_side == self.side_by_side.get() {
+                return;
+            }
+
+            self.side_by_side.set(side_by_side);
+
+            let obj = self.obj();
+            self.side_by_side_button.set_active(side_by_side);
+            self.stack.set_visible_child_name(if side_by_side {
+                "side-by-side"
+            } else {
+                "unified"
+            });
+            obj.notify_side_by_side();
+        }
+    }
+}
+
+glib::wrapper! {
+    pub struct ValueDiffView(ObjectSubclass<imp::ValueDiffView>)
+        @extends gtk::Widget;
+}
+
+impl ValueDiffView {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    /// Presents a window on top of `transient_for` showing how the text
+    /// `before` changed into `after`, each given with its title.
+    pub fn present(
+        transient_for: &impl IsA<gtk::Window>,
+        before: (&str, &str),
+        after: (&str, &str),
+    ) {
+        let view = Self::new();
+        view.set_texts(before, after);
+
+        let toolbar_view = adw::ToolbarView::new();
+        toolbar_view.add_top_bar(&adw::HeaderBar::new());
+        toolbar_view.set_content(Some(&view));
+
+        let window = adw::Window::builder()
+            .title(gettext("Compare Values"))
+            .default_width(800)
+            .default_height(500)
+            .content(&toolbar_view)
+            .transient_for(transient_for)
+            .modal(true)
+            .build();
+        window.present();
+    }
+
+    /// Shows how the text `before` changed into `after`, with the titles of
+    /// both shown above them side by side.
+    pub fn set_texts(
+        &self,
+        (before_title, before): (&str, &str),
+        (after_title, after): (&str, &str),
+    ) {
+        let imp = self.imp();
+
+        imp.before_title_label.set_label(before_title);
+        imp.before_title_label.set_tooltip_text(Some(before_title));
+        imp.after_title_label.set_label(after_title);
+        imp.after_title_label.set_tooltip_text(Some(after_title));
+
+        let lines = line_diff::diff(before, after);
+
+        let unified_buffer = imp.unified_view.buffer();
+        let before_buffer = imp.before_view.buffer();
+        let after_buffer = imp.after_view.buffer();
+        for buffer in [&unified_buffer, &before_buffer, &after_buffer] {
+            buffer.set_text("");
+        }
+
+        let (mut n_removed, mut n_added) = (0, 0);
+        for (change, line) in &lines {
+            let (prefix, tag) = match change {
+                LineChange::Unchanged => ("  ", None),
+                LineChange::Removed => ("- ", Some("removed")),
+                LineChange::Added => ("+ ", Some("added")),
+            };
+            append_line(&unified_buffer, &format!("{}{}", prefix, line), tag);
+            match change {
+                LineChange::Unchanged => {}
+                LineChange::Removed => n_removed += 1,
+                LineChange::Added => n_added += 1,
+            }
+        }
+
+        // Removed and added lines in a row are shown next to each other, with
+        // blank lines filling the shorter side
+        let mut index = 0;
+        while index < lines.len() {
+            if lines[index].0 == LineChange::Unchanged {
+                append_line(&before_buffer, lines[index].1, None);
+                append_line(&after_buffer, lines[index].1, None);
+                index += 1;
+                continue;
+            }
+
+            let end = lines[index..]
+                .iter()
+                .position(|(change, _)| *change == LineChange::Unchanged)
+                .map_or(lines.len(), |len| index + len);
+            let removed = lines[index..end]
+                .iter()
+                .filter(|(change, _)| *change == LineChange::Removed)
+                .map(|(_, line)| *line)
+                .collect::<Vec<_>>();
+            let added = lines[index..end]
+                .iter()
+                .filter(|(change, _)| *change == LineChange::Added)
+                .map(|(_, line)| *line)
+                .collect::<Vec<_>>();
+            for row in 0..removed.len().max(added.len()) {
+                match removed.get(row) {
+                    Some(line) => append_line(&before_buffer, line, Some("removed")),
+                    None => append_line(&before_buffer, "", Some("filler")),
+                }
+                match added.get(row) {
+                    Some(line) => append_line(&after_buffer, line, Some("added")),
+                    None => append_line(&after_buffer, "", Some("filler")),
+                }
+            }
+            index = end;
+        }
+
+        imp.summary_label
+            .set_label(&if n_removed == 0 && n_added == 0 {
+                gettext("No differences")
+            } else {
+                format!(
+                    "{}, {}",
+                    ngettext_f(
+                        "{n} line removed",
+                        "{n} lines removed",
+                        n_removed,
+                        &[("n", &n_removed.to_string())]
+                    ),
+                    ngettext_f(
+                        "{n} line added",
+                        "{n} lines added",
+                        n_added,
+                        &[("n", &n_added.to_string())]
+                    ),
+                )
+            });
+    }
+}
+
+impl Default for ValueDiffView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Appends `line` to `buffer` with a line break, tagged with `tag`.
+fn append_line(buffer: &gtk::TextBuffer, line: &str, tag: Option<&str>) {
+    let mut end = buffer.end_iter();
+    let line = format!("{}\n", line);
+    match tag {
+        Some(tag) => buffer.insert_with_tags_by_name(&mut end, &line, &[tag]),
+        None => buffer.insert(&mut end, &line),
+    }
+}
@@ -0,0 +1,118 @@
+use gettextrs::gettext;
+
+use crate::{
+    compression::Compression,
+    decoder::{Decoder, MessagePack, ProtobufWireFormat},
+};
+
+/// Best guess of how a value is encoded, based on its bytes alone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueFormat {
+    Empty,
+    Text,
+    Json,
+    MessagePack,
+    Protobuf,
+    Compressed(Compression),
+    Png,
+    Jpeg,
+    Webp,
+    Gif,
+    Integer,
+    Binary,
+}
+
+impl ValueFormat {
+    pub fn detect(bytes: &[u8]) -> Self {
+        if bytes.is_empty() {
+            return Self::Empty;
+        }
+
+        if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+            return Self::Png;
+        }
+        if bytes.starts_with(&[0xff, 0xd8, 0xff]) {
+            return Self::Jpeg;
+        }
+        if bytes.len() >= 12 && bytes.starts_with(b"RIFF") && &bytes[8..12] == b"WEBP" {
+            return Self::Webp;
+        }
+        if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+            return Self::Gif;
+        }
+        if let Some(compression) = Compression::detect(bytes) {
+            return Self::Compressed(compression);
+        }
+
+        if let Ok(text) = std::str::from_utf8(bytes) {
+            let trimmed = text.trim_start();
+            if (trimmed.starts_with('{') || trimmed.starts_with('['))
+                && serde_json::from_str::<serde_json::Value>(text).is_ok()
+            {
+                return Self::Json;
+            }
+
+            if text
+                .chars()
+                .all(|c| !c.is_control() || matches!(c, '\n' | '\r' | '\t'))
+            {
+                return Self::Text;
+            }
+        }
+
+        if matches!(bytes.len(), 4 | 8) {
+            return Self::Integer;
+        }
+
+        // Only containers are considered, as almost any byte is a valid
+        // MessagePack scalar.
+        if matches!(bytes[0], 0x80..=0x9f | 0xdc..=0xdf) && MessagePack.decode(bytes).is_ok() {
+            return Self::MessagePack;
+        }
+
+        if ProtobufWireFormat.decode(bytes).is_ok() {
+            return Self::Protobuf;
+        }
+
+        Self::Binary
+    }
+
+    /// Short name shown as a badge next to values.
+    pub fn label(self) -> String {
+        match self {
+            Self::Empty => gettext("Empty"),
+            Self::Text => gettext("Text"),
+            Self::Json => "JSON".to_string(),
+            Self::MessagePack => "MessagePack".to_string(),
+            Self::Protobuf => "Protobuf".to_string(),
+            Self::Compressed(compression) => compression.name().to_string(),
+            Self::Png => "PNG".to_string(),
+            Self::Jpeg => "JPEG".to_string(),
+            Self::Webp => "WebP".to_string(),
+            Self::Gif => "GIF".to_string(),
+            Self::Integer => gettext("Integer"),
+            Self::Binary => gettext("Binary"),
+        }
+    }
+
+    /// File extension of image formats, or `None` if this is not an image.
+    pub fn image_extension(self) -> Option<&'static str> {
+        match self {
+            Self::Png => Some("png"),
+            Self::Jpeg => Some("jpg"),
+            Self::Webp => Some("webp"),
+            Self::Gif => Some("gif"),
+            _ => None,
+        }
+    }
+
+    /// Id of the decoder that best renders this format, if any.
+    pub fn decoder_id(self) -> Option<&'static str> {
+        match self {
+            Self::Json => Some("json"),
+            Self::MessagePack => Some("msgpack"),
+            Self::Protobuf => Some("protobuf-wire-format"),
+            _ => None,
+        }
+    }
+}
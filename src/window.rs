@@ -1,37 +1,335 @@
 use adw::{prelude::*, subclass::prelude::*};
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use gettextrs::gettext;
 use gtk::{
-    gio,
+    gdk, gio,
     glib::{self, clone, closure},
 };
-use heed::{Env, EnvFlags};
+use heed::{CompactionOption, DatabaseFlags, Env, EnvFlags};
 
-use std::cell::RefCell;
+use std::{
+    cell::{Cell, RefCell},
+    collections::{BTreeSet, HashMap, HashSet},
+    fs, io,
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::{
+        atomic::{self, AtomicBool, AtomicU64, AtomicUsize},
+        Arc,
+    },
+    time::Duration,
+};
 
 use crate::{
+    annotations::Annotations,
     application::Application,
+    backend::{Backend, BackendKind},
+    bookmark::Bookmark,
+    cell_rendering::{self, CellRendering, CellWrapMode},
+    command_palette::{Command, CommandPalette},
+    compare_window::CompareWindow,
     config::{APP_ID, PROFILE},
-    database::Database,
+    database::{self, Database},
     database_item::DatabaseItem,
+    database_pane::DatabasePane,
+    detail_pane::DetailPane,
+    diff::DiffKind,
+    duplicates,
+    edit_history::{Edit, EditHistory},
+    env_profile::{self, EnvProfile},
+    env_subset::{self, Subset},
+    error_details,
+    export::{self, ExportDestination, ExportEntry, ExportFormat, ExportOptions, ExportWriter},
+    full_search::{self, ScanProgress},
+    highlight_rule::{HighlightColor, HighlightRule, HighlightRules},
+    import::{self, FieldEncoding, ImportFormat, ImportOptions},
+    key_collation::KeyCollation,
+    key_display_mode::KeyDisplayMode,
+    key_history::KeyHistory,
+    key_tree_node::KeyTree,
+    key_tree_pane::KeyTreePane,
+    key_watch::{self, KeyWatches, WatchedChange},
+    known_locations_window::KnownLocationsWindow,
+    mdbx,
+    meta_page::{self, MetaPage},
+    monitor_pane::MonitorPane,
+    non_printable_display::{self, NonPrintableDisplay},
+    page::{NodeValue, Page, PageKind},
+    parquet_export,
+    pending_changes::{PendingChange, PendingChanges},
+    pending_changes_pane::PendingChangesPane,
+    print_report::{Report, ReportRow},
+    query::{self, PathSegment, Query},
+    remote,
+    saved_search::SavedSearch,
+    search_results_pane::SearchResultsPane,
+    settings_bundle,
+    snapshot::{Snapshot, SnapshotRenewal},
+    space_usage::SpaceUsage,
+    sqlite_export,
+    statistics_window::StatisticsWindow,
+    text_encoding::TextEncoding,
+    utils::{self, gettext_f, ngettext_f},
+    value_diff_view::ValueDiffView,
+    value_format::ValueFormat,
 };
 
+/// Ids of the columns whose visibility is a global setting rather than part
+/// of the profile of each env
+const SETTINGS_COLUMN_IDS: &[&str] = &["checksum", "key-size", "value-size"];
+
+/// Number of entries above which a batch delete shows its progress
+const DELETE_PROGRESS_THRESHOLD: u32 = 10_000;
+
+/// Number of groups of duplicate values listed, as the rest waste the least
+const MAX_N_SHOWN_DUPLICATE_GROUPS: usize = 100;
+
+/// Number of random entries sampled by default, and at most
+const DEFAULT_N_SAMPLED_ENTRIES: u32 = 10;
+const MAX_N_SAMPLED_ENTRIES: u32 = 1000;
+
+/// Number of rows printed in a report, beyond which the rest are left out
+const MAX_N_PRINTED_ROWS: u32 = 10_000;
+
+/// Seconds between checks of whether the snapshot of the shown entries is
+/// stale
+const SNAPSHOT_CHECK_INTERVAL: u32 = 5;
+
+/// Seconds between polls of the values of watched keys
+const KEY_WATCH_INTERVAL: u32 = 2;
+
+/// Number of rows of a file shown before importing it
+const IMPORT_PREVIEW_LEN: usize = 5;
+
+/// Pause in typing after which type-ahead starts over with a new prefix
+const TYPE_AHEAD_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// A value being edited in a file by an external app.
+#[derive(Debug)]
+struct ExternalEdit {
+    db: Database,
+    key: Vec<u8>,
+    /// Value of the entry in `db`, which is the duplicate that is replaced
+    /// if `key` has others
+    value: RefCell<Vec<u8>>,
+    path: PathBuf,
+    /// Contents of the file when it was last written or asked about
+    last_value: RefCell<Vec<u8>>,
+    is_asking: Cell<bool>,
+}
+
 mod imp {
     use super::*;
 
-    #[derive(Debug, Default, gtk::CompositeTemplate)]
+    #[derive(Debug, Default, glib::Properties, gtk::CompositeTemplate)]
+    #[properties(wrapper_type = super::Window)]
     #[template(resource = "/io/github/seadve/LmdbViewer/ui/window.ui")]
     pub struct Window {
+        #[property(get, set, builder(KeyDisplayMode::default()))]
+        pub(super) key_display_mode: Cell<KeyDisplayMode>,
+        #[property(get, set, builder(TextEncoding::default()))]
+        pub(super) key_encoding: Cell<TextEncoding>,
+        #[property(get, set, builder(CellRendering::default()))]
+        pub(super) key_rendering: Cell<CellRendering>,
+        #[property(
+            get,
+            set = Self::set_key_collation,
+            explicit_notify,
+            builder(KeyCollation::default())
+        )]
+        pub(super) key_collation: Cell<KeyCollation>,
+        /// Separator at which keys are split into groups in the key tree
+        #[property(get, set = Self::set_key_separator, explicit_notify)]
+        pub(super) key_separator: RefCell<String>,
+        /// Whether keys are also shown grouped by prefix in the key tree
+        #[property(get, set = Self::set_show_key_tree, explicit_notify)]
+        pub(super) show_key_tree: Cell<bool>,
+        /// Whether another database of the env is shown next to the entries
+        #[property(get, set = Self::set_split_view, explicit_notify)]
+        pub(super) split_view: Cell<bool>,
+        #[property(get, set, builder(TextEncoding::default()))]
+        pub(super) value_encoding: Cell<TextEncoding>,
+        #[property(get, set, builder(CellRendering::default()))]
+        pub(super) value_rendering: Cell<CellRendering>,
+        #[property(get, set, builder(NonPrintableDisplay::default()))]
+        pub(super) non_printable_display: Cell<NonPrintableDisplay>,
+        /// How keys and values that don't fit their columns are wrapped
+        #[property(get, set, builder(CellWrapMode::default()))]
+        pub(super) cell_wrap_mode: Cell<CellWrapMode>,
+        /// Maximum number of lines shown in a key or value cell
+        #[property(get, set)]
+        pub(super) max_cell_lines: Cell<u32>,
+        /// Maximum number of value bytes shown in a cell
+        #[property(get, set)]
+        pub(super) preview_length: Cell<u32>,
+        /// Maximum number of rows shown at once
+        #[property(get, set = Self::set_page_size, explicit_notify)]
+        pub(super) page_size: Cell<u32>,
+        /// Whether keys and values are shown in a monospace font
+        #[property(get, set)]
+        pub(super) monospace: Cell<bool>,
+        /// Seconds between automatic reloads, or 0 to disable them
+        #[property(get, set = Self::set_auto_reload_interval, explicit_notify)]
+        pub(super) auto_reload_interval: Cell<u32>,
+        /// Whether rows changed since the previous reload are highlighted
+        #[property(get, set = Self::set_show_changes, explicit_notify)]
+        pub(super) show_changes: Cell<bool>,
+        /// Whether the env is polled and changes are listed in the monitor pane
+        #[property(get, set = Self::set_monitor, explicit_notify)]
+        pub(super) monitor: Cell<bool>,
+        /// Seconds between polls in monitor mode
+        #[property(get, set = Self::set_monitor_interval, explicit_notify)]
+        pub(super) monitor_interval: Cell<u32>,
+        /// Seconds a stale snapshot is kept while the window is in use, or 0
+        /// to keep it until reloaded
+        #[property(get, set = Self::set_snapshot_renewal_interval, explicit_notify)]
+        pub(super) snapshot_renewal_interval: Cell<u32>,
+        /// Whether edits are staged in the pending changes pane instead of
+        /// written right away
+        #[property(get, set)]
+        pub(super) stage_changes: Cell<bool>,
+        /// Rules by which rows of the env are highlighted
+        #[property(get)]
+        pub(super) highlight_rules: RefCell<HighlightRules>,
+        /// Whether the env is opened read-write
+        #[property(get, set = Self::set_write_mode, explicit_notify)]
+        pub(super) write_mode: Cell<bool>,
+        /// Whether the window is too narrow to show the details next to the
+        /// entries, in which case they are shown on their own page
+        #[property(get, set = Self::set_collapsed, explicit_notify)]
+        pub(super) collapsed: Cell<bool>,
+        /// Whether tools for inspecting the pages of the env are shown
+        #[property(get, set = Self::set_advanced_tools, explicit_notify)]
+        pub(super) advanced_tools: Cell<bool>,
+
         #[template_child]
         pub(super) toast_overlay: TemplateChild<adw::ToastOverlay>,
         #[template_child]
+        pub(super) navigation_view: TemplateChild<adw::NavigationView>,
+        #[template_child]
+        pub(super) collapsed_detail_bin: TemplateChild<adw::Bin>,
+        #[template_child]
+        pub(super) sync_remote_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub(super) no_lock_banner: TemplateChild<adw::Banner>,
+        #[template_child]
+        pub(super) env_label: TemplateChild<gtk::Label>,
+        #[template_child]
         pub(super) drop_down: TemplateChild<gtk::DropDown>,
         #[template_child]
+        pub(super) filter_separator: TemplateChild<gtk::Image>,
+        #[template_child]
+        pub(super) filter_chips_box: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub(super) snapshot_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) reload_stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        pub(super) bookmarks_button: TemplateChild<gtk::MenuButton>,
+        #[template_child]
+        pub(super) bookmarks_list_box: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub(super) query_entry: TemplateChild<gtk::Entry>,
+        #[template_child]
+        pub(super) saved_searches_button: TemplateChild<gtk::MenuButton>,
+        #[template_child]
+        pub(super) saved_searches_list_box: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub(super) key_tree_pane: TemplateChild<KeyTreePane>,
+        #[template_child]
+        pub(super) split_pane: TemplateChild<DatabasePane>,
+        #[template_child]
         pub(super) column_view: TemplateChild<gtk::ColumnView>,
         #[template_child]
-        pub(super) column_view_model: TemplateChild<gtk::NoSelection>,
+        pub(super) column_view_model: TemplateChild<gtk::MultiSelection>,
+        #[template_child]
+        pub(super) slice_model: TemplateChild<gtk::SliceListModel>,
+        #[template_child]
+        pub(super) sort_model: TemplateChild<gtk::SortListModel>,
+        #[template_child]
+        pub(super) filter_model: TemplateChild<gtk::FilterListModel>,
+        #[template_child]
+        pub(super) search_bar: TemplateChild<gtk::SearchBar>,
+        #[template_child]
+        pub(super) search_entry: TemplateChild<gtk::SearchEntry>,
+        #[template_child]
+        pub(super) entries_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) matching_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) count_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) selection_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) load_duration_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) paging_box: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub(super) page_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub(super) env_size_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) side_paned: TemplateChild<gtk::Paned>,
+        #[template_child]
+        pub(super) detail_pane: TemplateChild<DetailPane>,
+        #[template_child]
+        pub(super) monitor_pane: TemplateChild<MonitorPane>,
+        #[template_child]
+        pub(super) pending_changes_pane: TemplateChild<PendingChangesPane>,
+        #[template_child]
+        pub(super) search_results_pane: TemplateChild<SearchResultsPane>,
+        #[template_child]
+        pub(super) open_menu: TemplateChild<gio::MenuModel>,
+        #[template_child]
+        pub(super) reload_menu: TemplateChild<gio::MenuModel>,
+        #[template_child]
+        pub(super) primary_menu: TemplateChild<gio::MenuModel>,
+        #[template_child]
+        pub(super) row_menu: TemplateChild<gio::MenuModel>,
+        #[template_child]
+        pub(super) column_menu: TemplateChild<gio::MenuModel>,
+        #[template_child]
+        pub(super) key_column_menu: TemplateChild<gio::MenuModel>,
+        #[template_child]
+        pub(super) value_column_menu: TemplateChild<gio::MenuModel>,
 
         pub(super) env: RefCell<Option<Env>>,
+        /// The open env whatever its format, which is also `env` if it is
+        /// LMDB, as only LMDB envs can be written and snapshotted
+        pub(super) backend: RefCell<Option<Arc<dyn Backend>>>,
+        /// URI of the remote env that the current env is a cached copy of
+        pub(super) remote_uri: RefCell<Option<String>>,
+        pub(super) bookmarks: RefCell<Vec<Bookmark>>,
+        pub(super) saved_searches: RefCell<Vec<SavedSearch>>,
+        /// Parsed text of the query entry, or `None` if it is empty or invalid
+        pub(super) query: RefCell<Option<Query>>,
+        /// Progress of the count of the entries matching the search and the
+        /// query shown in the status bar
+        pub(super) count_progress: RefCell<Option<Arc<ScanProgress>>>,
+        /// Paths of the decoded fields shown as columns, keyed by database name
+        pub(super) pinned_fields: RefCell<HashMap<String, Vec<String>>>,
+        pub(super) field_columns: RefCell<Vec<gtk::ColumnViewColumn>>,
+        /// Monitors of the files that values are being edited in externally
+        pub(super) external_edit_monitors: RefCell<Vec<gio::FileMonitor>>,
+        pub(super) annotations: RefCell<Annotations>,
+        pub(super) pending_changes: RefCell<PendingChanges>,
+        pub(super) edit_history: RefCell<EditHistory>,
+        pub(super) auto_reload_source_id: RefCell<Option<glib::SourceId>>,
+        pub(super) monitor_source_id: RefCell<Option<glib::SourceId>>,
+        pub(super) snapshot_renewal: RefCell<SnapshotRenewal>,
+        pub(super) snapshot_renewal_source_id: RefCell<Option<glib::SourceId>>,
+        pub(super) key_watches: RefCell<KeyWatches>,
+        pub(super) key_watch_source_id: RefCell<Option<glib::SourceId>>,
+        pub(super) key_history: RefCell<KeyHistory>,
+        /// Windows showing entries of the env, such as compare windows, which
+        /// keep it open, closed with it
+        pub(super) env_windows: RefCell<Vec<glib::WeakRef<gtk::Window>>>,
+        pub(super) type_ahead_prefix: RefCell<String>,
+        pub(super) type_ahead_source_id: RefCell<Option<glib::SourceId>>,
+        /// Handlers that update key and value cells, disconnected when their
+        /// cells are torn down
+        pub(super) cell_handler_ids:
+            RefCell<HashMap<gtk::ListItem, Vec<(glib::Object, glib::SignalHandlerId)>>>,
     }
 
     #[glib::object_subclass]
@@ -41,6 +339,12 @@ mod imp {
         type ParentType = adw::ApplicationWindow;
 
         fn class_init(klass: &mut Self::Class) {
+            DetailPane::ensure_type();
+            MonitorPane::ensure_type();
+            PendingChangesPane::ensure_type();
+            SearchResultsPane::ensure_type();
+            KeyTreePane::ensure_type();
+
             klass.bind_template();
 
             klass.install_action_async("win.open-env", None, |obj, _, _| async move {
@@ -50,211 +354,6775 @@ mod imp {
                         .is_some_and(|error| error.matches(gtk::DialogError::Dismissed))
                     {
                         tracing::error!("Failed to open env: {:?}", &err);
-                        obj.add_message_toast(&gettext("Failed to open env"));
+                        obj.add_error_toast(&gettext("Failed to open env"), &err);
                     }
                 }
             });
 
-            klass.install_action("win.reload-env", None, move |obj, _, _| {
-                let imp = obj.imp();
-
-                if let Some(model) = imp.drop_down.model() {
-                    let db = model.downcast_ref::<Database>().unwrap();
+            klass.install_action("win.show-known-locations", None, |obj, _, _| {
+                let window = KnownLocationsWindow::new(obj);
+                window.present();
+            });
 
-                    if let Err(err) = db.reload() {
-                        tracing::error!("Failed to reload env on drop down: {:?}", &err);
-                    }
+            klass.install_action_async("win.open-remote", None, |obj, _, _| async move {
+                if let Err(err) = obj.open_remote().await {
+                    tracing::error!("Failed to open remote env: {:?}", &err);
+                    obj.add_error_toast(&gettext("Failed to open remote env"), &err);
                 }
+            });
 
-                if let Some(model) = imp.column_view_model.model() {
-                    let db = model.downcast_ref::<Database>().unwrap();
-
-                    if let Err(err) = db.reload() {
-                        tracing::error!("Failed to reload env on view: {:?}", &err);
-                    }
+            klass.install_action_async("win.sync-remote", None, |obj, _, _| async move {
+                let Some(uri) = obj.imp().remote_uri.borrow().clone() else {
+                    return;
+                };
+                if let Err(err) = obj.sync_remote(uri).await {
+                    tracing::error!("Failed to sync remote env: {:?}", &err);
+                    obj.add_error_toast(&gettext("Failed to sync remote env"), &err);
                 }
             });
-        }
-
-        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
-            obj.init_template();
-        }
-    }
 
-    impl ObjectImpl for Window {
-        fn constructed(&self) {
-            self.parent_constructed();
-            let obj = self.obj();
+            klass.install_action_async("win.reload-db", None, |obj, _, _| async move {
+                obj.reload_in_background(false).await;
+            });
 
-            if PROFILE == "Devel" {
-                obj.add_css_class("devel");
-            }
+            klass.install_action_async("win.reload-env", None, |obj, _, _| async move {
+                obj.reload_in_background(true).await;
+            });
 
-            obj.setup_view();
+            klass.install_action_async("win.bookmark-database", None, |obj, _, _| async move {
+                if let Err(err) = obj.add_bookmark(false).await {
+                    tracing::error!("Failed to bookmark database: {:?}", &err);
+                    obj.add_error_toast(&gettext("Failed to bookmark database"), &err);
+                }
+            });
 
-            obj.load_window_size();
-        }
-    }
+            klass.install_action_async("win.bookmark-key", None, |obj, _, _| async move {
+                if let Err(err) = obj.add_bookmark(true).await {
+                    tracing::error!("Failed to bookmark key: {:?}", &err);
+                    obj.add_error_toast(&gettext("Failed to bookmark key"), &err);
+                }
+            });
 
-    impl WidgetImpl for Window {}
+            klass.install_action("win.watch-key", None, |obj, _, _| {
+                if let Err(err) = obj.watch_selected_key() {
+                    tracing::error!("Failed to watch key: {:?}", &err);
+                    obj.add_error_toast(&gettext("Failed to watch key"), &err);
+                }
+            });
 
-    impl WindowImpl for Window {
-        fn close_request(&self) -> glib::Propagation {
-            if let Err(err) = self.obj().save_window_size() {
-                tracing::warn!("Failed to save window state: {:?}", &err);
-            }
+            klass.install_action("win.unwatch-key", None, |obj, _, _| {
+                obj.unwatch_selected_key();
+            });
 
-            self.parent_close_request()
-        }
-    }
+            klass.install_action("win.open-in-new-window", None, |obj, _, _| {
+                obj.open_selected_in_new_window();
+            });
 
-    impl ApplicationWindowImpl for Window {}
-    impl AdwApplicationWindowImpl for Window {}
-}
+            klass.install_action("win.compare-values", None, |obj, _, _| {
+                obj.compare_selected_values();
+            });
 
-glib::wrapper! {
-    pub struct Window(ObjectSubclass<imp::Window>)
-        @extends gtk::Widget, gtk::Window, gtk::ApplicationWindow, adw::ApplicationWindow,
-        @implements gio::ActionMap, gio::ActionGroup, gtk::Root;
-}
+            klass.install_action("win.reset-column-widths", None, |obj, _, _| {
+                for column in obj.columns() {
+                    column.set_fixed_width(-1);
+                }
+            });
 
-impl Window {
-    pub fn new(app: &Application) -> Self {
-        glib::Object::builder().property("application", app).build()
-    }
+            klass.install_action("win.select-all", None, |obj, _, _| {
+                obj.imp().column_view_model.select_all();
+            });
 
-    fn add_message_toast(&self, message: &str) {
-        let toast = adw::Toast::new(message);
-        self.imp().toast_overlay.add_toast(toast);
-    }
+            klass.install_action("win.copy-selected", None, |obj, _, _| {
+                obj.copy_selected();
+            });
 
-    async fn open_env(&self) -> Result<()> {
-        let imp = self.imp();
+            klass.install_action_async("win.export", None, |obj, _, _| async move {
+                if let Err(err) = obj.export().await {
+                    if !err
+                        .downcast_ref::<glib::Error>()
+                        .is_some_and(|error| error.matches(gtk::DialogError::Dismissed))
+                    {
+                        tracing::error!("Failed to export: {:?}", &err);
+                        obj.add_error_toast(&gettext("Failed to export entries"), &err);
+                    }
+                }
+            });
 
-        let dialog = gtk::FileDialog::builder()
-            .title("Open Database")
-            .modal(true)
-            .build();
+            klass.install_action("win.print", None, |obj, _, _| {
+                if let Err(err) = obj.print_report(None) {
+                    tracing::error!("Failed to print: {:?}", &err);
+                    obj.add_error_toast(&gettext("Failed to print"), &err);
+                }
+            });
 
-        let folder = dialog.select_folder_future(Some(self)).await?;
+            klass.install_action_async("win.export-pdf", None, |obj, _, _| async move {
+                if let Err(err) = obj.export_pdf().await {
+                    if !err
+                        .downcast_ref::<glib::Error>()
+                        .is_some_and(|error| error.matches(gtk::DialogError::Dismissed))
+                    {
+                        tracing::error!("Failed to export PDF: {:?}", &err);
+                        obj.add_error_toast(&gettext("Failed to export PDF"), &err);
+                    }
+                }
+            });
 
-        let env = unsafe {
-            heed::EnvOpenOptions::new()
-                .map_size(100 * 1024 * 1024) // 100 MiB
-                .max_dbs(100)
-                .flags(EnvFlags::READ_ONLY | EnvFlags::NO_LOCK)
-                .open(folder.path().expect("file must have a path"))
-                .with_context(|| format!("Failed to open env at `{}`", folder.uri()))?
-        };
-        tracing::debug!("Opened env at `{}`", folder.uri());
+            klass.install_action_async("win.export-sqlite", None, |obj, _, _| async move {
+                if let Err(err) = obj.export_sqlite().await {
+                    if !err
+                        .downcast_ref::<glib::Error>()
+                        .is_some_and(|error| error.matches(gtk::DialogError::Dismissed))
+                    {
+                        tracing::error!("Failed to export to SQLite: {:?}", &err);
+                        obj.add_error_toast(&gettext("Failed to export to SQLite"), &err);
+                    }
+                }
+            });
 
-        let db = Database::load(&env, None).context("Failed to load unnamed db")?;
-        imp.drop_down.set_model(Some(&db));
+            klass.install_action_async("win.export-parquet", None, |obj, _, _| async move {
+                if let Err(err) = obj.export_parquet().await {
+                    if !err
+                        .downcast_ref::<glib::Error>()
+                        .is_some_and(|error| error.matches(gtk::DialogError::Dismissed))
+                    {
+                        tracing::error!("Failed to export to Parquet: {:?}", &err);
+                        obj.add_error_toast(&gettext("Failed to export to Parquet"), &err);
+                    }
+                }
+            });
 
-        imp.env.replace(Some(env));
+            klass.install_action_async("win.delete-selected", None, |obj, _, _| async move {
+                if let Err(err) = obj.delete_selected().await {
+                    tracing::error!("Failed to delete selected: {:?}", &err);
+                    obj.add_error_toast(&gettext("Failed to delete selected entries"), &err);
+                }
+            });
 
-        Ok(())
-    }
+            klass.install_action_async("win.create-database", None, |obj, _, _| async move {
+                if let Err(err) = obj.create_database().await {
+                    tracing::error!("Failed to create database: {:?}", &err);
+                    obj.add_error_toast(&gettext("Failed to create database"), &err);
+                }
+            });
 
-    fn save_window_size(&self) -> Result<(), glib::BoolError> {
-        let settings = gio::Settings::new(APP_ID);
+            klass.install_action_async("win.duplicate-database", None, |obj, _, _| async move {
+                if let Err(err) = obj.duplicate_database().await {
+                    tracing::error!("Failed to duplicate database: {:?}", &err);
+                    obj.add_error_toast(&gettext("Failed to duplicate database"), &err);
+                }
+            });
 
-        let (width, height) = self.default_size();
+            klass.install_action_async("win.clear-database", None, |obj, _, _| async move {
+                if let Err(err) = obj.clear_database().await {
+                    tracing::error!("Failed to clear database: {:?}", &err);
+                    obj.add_error_toast(&gettext("Failed to clear database"), &err);
+                }
+            });
 
-        settings.set_int("window-width", width)?;
-        settings.set_int("window-height", height)?;
+            klass.install_action_async("win.delete-range", None, |obj, _, _| async move {
+                if let Err(err) = obj.delete_range().await {
+                    tracing::error!("Failed to delete range: {:?}", &err);
+                    obj.add_error_toast(&gettext("Failed to delete entries"), &err);
+                }
+            });
 
-        settings.set_boolean("is-maximized", self.is_maximized())?;
+            klass.install_action_async("win.import", None, |obj, _, _| async move {
+                if let Err(err) = obj.import().await {
+                    tracing::error!("Failed to import entries: {:?}", &err);
+                    obj.add_error_toast(&gettext("Failed to import entries"), &err);
+                }
+            });
 
-        Ok(())
-    }
+            klass.install_action_async("win.export-settings", None, |obj, _, _| async move {
+                if let Err(err) = obj.export_settings().await {
+                    if !err
+                        .downcast_ref::<glib::Error>()
+                        .is_some_and(|error| error.matches(gtk::DialogError::Dismissed))
+                    {
+                        tracing::error!("Failed to export settings: {:?}", &err);
+                        obj.add_error_toast(&gettext("Failed to export settings"), &err);
+                    }
+                }
+            });
 
-    fn load_window_size(&self) {
-        let settings = gio::Settings::new(APP_ID);
+            klass.install_action_async("win.import-settings", None, |obj, _, _| async move {
+                if let Err(err) = obj.import_settings().await {
+                    if !err
+                        .downcast_ref::<glib::Error>()
+                        .is_some_and(|error| error.matches(gtk::DialogError::Dismissed))
+                    {
+                        tracing::error!("Failed to import settings: {:?}", &err);
+                        obj.add_error_toast(&gettext("Failed to import settings"), &err);
+                    }
+                }
+            });
 
-        let width = settings.int("window-width");
-        let height = settings.int("window-height");
-        let is_maximized = settings.boolean("is-maximized");
+            klass.install_action("win.commit-changes", None, |obj, _, _| {
+                if let Err(err) = obj.commit_changes() {
+                    tracing::error!("Failed to commit changes: {:?}", &err);
+                    obj.add_error_toast(&gettext("Failed to commit changes"), &err);
+                }
+            });
 
-        self.set_default_size(width, height);
+            klass.install_action("win.discard-changes", None, |obj, _, _| {
+                obj.discard_changes();
+            });
 
-        if is_maximized {
-            self.maximize();
-        }
-    }
+            klass.install_action("win.undo", None, |obj, _, _| {
+                if let Err(err) = obj.undo() {
+                    tracing::error!("Failed to undo: {:?}", &err);
+                    obj.add_error_toast(&gettext("Failed to undo"), &err);
+                }
+            });
 
-    fn setup_view(&self) {
-        let imp = self.imp();
+            klass.install_action("win.redo", None, |obj, _, _| {
+                if let Err(err) = obj.redo() {
+                    tracing::error!("Failed to redo: {:?}", &err);
+                    obj.add_error_toast(&gettext("Failed to redo"), &err);
+                }
+            });
 
-        let key_column_factory = gtk::SignalListItemFactory::new();
-        key_column_factory.connect_setup(|_, list_item| {
-            let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
-            let buffer = gtk::TextBuffer::builder().build();
-            let text_view = gtk::TextView::builder().buffer(&buffer).monospace(true).build();
-            list_item.connect_item_notify(clone!(@weak buffer => move |item| {
-                if let Some(item) = item.item() {
-                    let item = item.downcast_ref::<DatabaseItem>().unwrap();
-                    buffer.set_text(&String::from_utf8_lossy(item.key().as_ref()).replace('\x00', "0"));
-                } else {
-                    buffer.set_text("<None>");
+            klass.install_action_async("win.copy-env", Some("b"), |obj, _, compact| async move {
+                let compact = compact.and_then(|compact| compact.get::<bool>()).unwrap();
+                if let Err(err) = obj.copy_env(compact).await {
+                    if !err
+                        .downcast_ref::<glib::Error>()
+                        .is_some_and(|error| error.matches(gtk::DialogError::Dismissed))
+                    {
+                        tracing::error!("Failed to copy env: {:?}", &err);
+                        obj.add_error_toast(&gettext("Failed to copy env"), &err);
+                    }
                 }
-            }));
-            list_item.set_child(Some(&text_view));
-        });
-        let key_column = gtk::ColumnViewColumn::new(Some("Key"), Some(key_column_factory));
-        key_column.set_resizable(true);
-        key_column.set_expand(true);
-        imp.column_view.insert_column(0, &key_column);
+            });
 
-        let val_column_factory = gtk::SignalListItemFactory::new();
-        val_column_factory.connect_setup(|_, list_item| {
-                let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
-                let buffer = gtk::TextBuffer::builder().build();
-                let text_view = gtk::TextView::builder().buffer(&buffer).monospace(true).build();
-                list_item.connect_item_notify(clone!(@weak buffer => move |item| {
-                    if let Some(item) = item.item() {
-                        let item = item.downcast_ref::<DatabaseItem>().unwrap();
-                        buffer.set_text(&String::from_utf8_lossy(item.data().as_ref()).replace('\x00', "0"));
-                    } else {
-                        buffer.set_text("<None>");
+            klass.install_action_async("win.copy-subset", None, |obj, _, _| async move {
+                if let Err(err) = obj.copy_subset().await {
+                    if !err
+                        .downcast_ref::<glib::Error>()
+                        .is_some_and(|error| error.matches(gtk::DialogError::Dismissed))
+                    {
+                        tracing::error!("Failed to copy subset: {:?}", &err);
+                        obj.add_error_toast(&gettext("Failed to copy subset"), &err);
                     }
-                }));
-                list_item.set_child(Some(&text_view));
+                }
             });
-        let val_column = gtk::ColumnViewColumn::new(Some("Value"), Some(val_column_factory));
-        val_column.set_resizable(true);
-        val_column.set_expand(true);
-        imp.column_view.insert_column(1, &val_column);
 
-        imp.drop_down
-            .set_expression(Some(&gtk::ClosureExpression::new::<glib::GString>(
-                &[] as &[gtk::Expression],
-                closure!(|list_item: DatabaseItem| {
-                    String::from_utf8_lossy(list_item.key().as_ref()).to_string()
-                }),
-            )));
-        imp.drop_down
-            .connect_selected_item_notify(clone!(@weak self as obj => move |drop_down| {
-                let imp = obj.imp();
-                let env = imp.env.borrow();
+            klass.install_action_async("win.show-env-info", None, |obj, _, _| async move {
+                if let Err(err) = obj.show_env_info().await {
+                    tracing::error!("Failed to show env info: {:?}", &err);
+                    obj.add_error_toast(&gettext("Failed to read env info"), &err);
+                }
+            });
 
-                if let Some(env) = env.as_ref() {
-                    let selected_item = drop_down.selected_item();
+            klass.install_action_async("win.show-meta-pages", None, |obj, _, _| async move {
+                if let Err(err) = obj.show_meta_pages().await {
+                    tracing::error!("Failed to show meta pages: {:?}", &err);
+                    obj.add_error_toast(&gettext("Failed to read meta pages"), &err);
+                }
+            });
 
-                    imp.column_view_model.set_model(gtk::SelectionModel::NONE);
+            klass.install_action_async("win.inspect-page", None, |obj, _, _| async move {
+                if let Err(err) = obj.inspect_page().await {
+                    tracing::error!("Failed to inspect page: {:?}", &err);
+                    obj.add_error_toast(&gettext("Failed to inspect page"), &err);
+                }
+            });
+
+            klass.install_action("win.show-statistics", None, |obj, _, _| {
+                if let Err(err) = obj.show_statistics() {
+                    tracing::error!("Failed to show statistics: {:?}", &err);
+                }
+            });
+
+            klass.install_action_async("win.find-duplicates", None, |obj, _, _| async move {
+                if let Err(err) = obj.find_duplicates().await {
+                    tracing::error!("Failed to find duplicates: {:?}", &err);
+                    obj.add_error_toast(&gettext("Failed to find duplicate values"), &err);
+                }
+            });
+
+            klass.install_action_async("win.verify-database", None, |obj, _, _| async move {
+                if let Err(err) = obj.verify_database().await {
+                    tracing::error!("Failed to verify database: {:?}", &err);
+                    obj.add_error_toast(&gettext("Failed to verify database"), &err);
+                }
+            });
+
+            klass.install_action("win.select-key", Some("ay"), |obj, _, key| {
+                let key = key.and_then(|key| key.get::<Vec<u8>>()).unwrap();
+                obj.select_key(&key);
+            });
+
+            klass.install_action("win.scroll-to-key", Some("ay"), |obj, _, key| {
+                let key = key.and_then(|key| key.get::<Vec<u8>>()).unwrap();
+                obj.scroll_to_key(&key);
+            });
+
+            klass.install_action("win.select-db-key", Some("(say)"), |obj, _, param| {
+                let (db_name, key) = param
+                    .and_then(|param| param.get::<(String, Vec<u8>)>())
+                    .unwrap();
+                if !obj.select_database(&db_name) {
+                    obj.add_message_toast(&gettext_f(
+                        "Database “{name}” no longer exists",
+                        &[("name", &db_name)],
+                    ));
+                } else if !obj.select_key(&key) {
+                    obj.add_message_toast(&gettext("Key no longer exists"));
+                }
+            });
+
+            klass.install_action("win.select-database", Some("s"), |obj, _, db_name| {
+                let db_name = db_name.and_then(|db_name| db_name.get::<String>()).unwrap();
+                if !obj.select_database(&db_name) {
+                    obj.add_message_toast(&gettext_f(
+                        "Database “{name}” no longer exists",
+                        &[("name", &db_name)],
+                    ));
+                }
+            });
+
+            klass.install_action("win.switch-database", None, |obj, _, _| {
+                if let Err(err) = obj.show_database_switcher() {
+                    tracing::error!("Failed to show database switcher: {:?}", &err);
+                    obj.add_error_toast(&gettext("Failed to list databases"), &err);
+                }
+            });
+
+            klass.install_action("win.show-command-palette", None, |obj, _, _| {
+                obj.show_command_palette();
+            });
+
+            klass.install_action("win.compare", None, |obj, _, _| {
+                if let Err(err) = obj.compare() {
+                    tracing::error!("Failed to compare: {:?}", &err);
+                    obj.add_error_toast(&gettext("Select a database to compare first"), &err);
+                }
+            });
+
+            klass.install_action("win.search", None, |obj, _, _| {
+                let search_bar = &obj.imp().search_bar;
+                search_bar.set_search_mode(!search_bar.is_search_mode());
+            });
+
+            klass.install_action_async("win.save-search", None, |obj, _, _| async move {
+                if let Err(err) = obj.save_search().await {
+                    tracing::error!("Failed to save search: {:?}", &err);
+                    obj.add_error_toast(&gettext("Failed to save search"), &err);
+                }
+            });
+
+            klass.install_action_async("win.edit-highlight-rules", None, |obj, _, _| async move {
+                if let Err(err) = obj.edit_highlight_rules().await {
+                    tracing::error!("Failed to edit highlight rules: {:?}", &err);
+                    obj.add_error_toast(&gettext("Failed to edit highlight rules"), &err);
+                }
+            });
+
+            klass.install_action_async("win.pin-fields", None, |obj, _, _| async move {
+                if let Err(err) = obj.pin_fields().await {
+                    tracing::error!("Failed to pin fields: {:?}", &err);
+                    obj.add_error_toast(&gettext("Failed to pin fields"), &err);
+                }
+            });
+
+            klass.install_action_async("win.search-entire-db", None, |obj, _, _| async move {
+                if let Err(err) = obj.search_entire_db().await {
+                    tracing::error!("Failed to search database: {:?}", &err);
+                    obj.add_error_toast(&gettext("Failed to search database"), &err);
+                }
+            });
+
+            klass.install_action_async("win.count-matching", None, |obj, _, _| async move {
+                if let Err(err) = obj.count_matching().await {
+                    tracing::error!("Failed to count matching entries: {:?}", &err);
+                    obj.add_error_toast(&gettext("Failed to count matching entries"), &err);
+                }
+            });
+
+            klass.install_action_async("win.search-all-dbs", None, |obj, _, _| async move {
+                if let Err(err) = obj.search_all_dbs().await {
+                    tracing::error!("Failed to search all databases: {:?}", &err);
+                    obj.add_error_toast(&gettext("Failed to search all databases"), &err);
+                }
+            });
+
+            klass.install_action_async("win.go-to-key", None, |obj, _, _| async move {
+                obj.go_to_key().await;
+            });
+
+            klass.install_action("win.next-database", None, |obj, _, _| {
+                obj.select_database_by_offset(1);
+            });
+
+            klass.install_action("win.previous-database", None, |obj, _, _| {
+                obj.select_database_by_offset(-1);
+            });
+
+            klass.install_action("win.previous-page", None, |obj, _, _| {
+                obj.set_page(obj.page().saturating_sub(1));
+            });
+
+            klass.install_action("win.next-page", None, |obj, _, _| {
+                obj.set_page(obj.page() + 1);
+            });
+
+            klass.install_action_async("win.go-to-page", None, |obj, _, _| async move {
+                obj.go_to_page().await;
+            });
+
+            klass.install_action_async("win.go-to-row", None, |obj, _, _| async move {
+                obj.go_to_row().await;
+            });
+
+            klass.install_action_async("win.random-sample", None, |obj, _, _| async move {
+                obj.random_sample().await;
+            });
+
+            klass.install_property_action("win.write-mode", "write-mode");
+            klass.install_property_action("win.show-changes", "show-changes");
+            klass.install_property_action("win.monitor", "monitor");
+            klass.install_property_action("win.stage-changes", "stage-changes");
+            klass.install_property_action("win.show-key-tree", "show-key-tree");
+            klass.install_property_action("win.split-view", "split-view");
+
+            klass.install_action_async("win.save-key", None, |obj, _, _| async move {
+                if let Err(err) = obj.save_selected(true).await {
+                    if !err
+                        .downcast_ref::<glib::Error>()
+                        .is_some_and(|error| error.matches(gtk::DialogError::Dismissed))
+                    {
+                        tracing::error!("Failed to save key: {:?}", &err);
+                        obj.add_error_toast(&gettext("Failed to save key"), &err);
+                    }
+                }
+            });
+
+            klass.install_action_async("win.save-value", None, |obj, _, _| async move {
+                if let Err(err) = obj.save_selected(false).await {
+                    if !err
+                        .downcast_ref::<glib::Error>()
+                        .is_some_and(|error| error.matches(gtk::DialogError::Dismissed))
+                    {
+                        tracing::error!("Failed to save value: {:?}", &err);
+                        obj.add_error_toast(&gettext("Failed to save value"), &err);
+                    }
+                }
+            });
+
+            klass.install_action_async("win.rename-key", None, |obj, _, _| async move {
+                if let Err(err) = obj.rename_selected_key().await {
+                    tracing::error!("Failed to rename key: {:?}", &err);
+                    obj.add_error_toast(&gettext("Failed to rename key"), &err);
+                }
+            });
+
+            klass.install_action_async("win.load-value", None, |obj, _, _| async move {
+                if let Err(err) = obj.load_selected_value().await {
+                    if !err
+                        .downcast_ref::<glib::Error>()
+                        .is_some_and(|error| error.matches(gtk::DialogError::Dismissed))
+                    {
+                        tracing::error!("Failed to load value: {:?}", &err);
+                        obj.add_error_toast(&gettext("Failed to load value"), &err);
+                    }
+                }
+            });
+
+            klass.install_action_async("win.edit-externally", None, |obj, _, _| async move {
+                if let Err(err) = obj.edit_externally().await {
+                    tracing::error!("Failed to edit value externally: {:?}", &err);
+                    obj.add_error_toast(&gettext("Failed to open value in editor"), &err);
+                }
+            });
+
+            klass.install_action("win.show-details", None, |obj, _, _| {
+                obj.imp().navigation_view.push_by_tag("details");
+            });
+
+            klass.install_action("win.show-row-menu", None, |obj, _, _| {
+                obj.show_row_menu();
+            });
+
+            klass.install_action("win.set-value", Some("ay"), |obj, _, value| {
+                let value = value.unwrap().get::<Vec<u8>>().unwrap();
+                if let Err(err) = obj.put_selected_value(value) {
+                    tracing::error!("Failed to set value: {:?}", &err);
+                    obj.add_error_toast(&gettext("Failed to set value"), &err);
+                }
+            });
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for Window {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+
+            if PROFILE == "Devel" {
+                obj.add_css_class("devel");
+            }
+
+            let settings = gio::Settings::new(APP_ID);
+            settings
+                .bind("key-display-mode", &*obj, "key-display-mode")
+                .build();
+            obj.add_action(&settings.create_action("key-display-mode"));
+            settings.bind("key-encoding", &*obj, "key-encoding").build();
+            obj.add_action(&settings.create_action("key-encoding"));
+            settings
+                .bind("key-rendering", &*obj, "key-rendering")
+                .build();
+            obj.add_action(&settings.create_action("key-rendering"));
+            settings
+                .bind("key-collation", &*obj, "key-collation")
+                .build();
+            obj.add_action(&settings.create_action("key-collation"));
+            settings
+                .bind("key-separator", &*obj, "key-separator")
+                .build();
+            settings
+                .bind("value-encoding", &*obj, "value-encoding")
+                .build();
+            obj.add_action(&settings.create_action("value-encoding"));
+            settings
+                .bind("value-rendering", &*obj, "value-rendering")
+                .build();
+            obj.add_action(&settings.create_action("value-rendering"));
+            settings
+                .bind("cell-wrap-mode", &*obj, "cell-wrap-mode")
+                .build();
+            obj.add_action(&settings.create_action("cell-wrap-mode"));
+            obj.add_action(&settings.create_action("detail-encoding"));
+            settings
+                .bind("non-printable-display", &*obj, "non-printable-display")
+                .build();
+            obj.add_action(&settings.create_action("non-printable-display"));
+            settings
+                .bind("preview-length", &*obj, "preview-length")
+                .build();
+            settings
+                .bind("max-cell-lines", &*obj, "max-cell-lines")
+                .build();
+            settings.bind("page-size", &*obj, "page-size").build();
+            settings.bind("monospace-font", &*obj, "monospace").build();
+            settings
+                .bind("auto-reload-interval", &*obj, "auto-reload-interval")
+                .build();
+            settings
+                .bind("monitor-interval", &*obj, "monitor-interval")
+                .build();
+            settings
+                .bind(
+                    "snapshot-renewal-interval",
+                    &*obj,
+                    "snapshot-renewal-interval",
+                )
+                .build();
+            settings
+                .bind("show-advanced-tools", &*obj, "advanced-tools")
+                .build();
+
+            obj.bind_property("monitor", &*self.monitor_pane, "visible")
+                .sync_create()
+                .build();
+            obj.bind_property("show-key-tree", &*self.key_tree_pane, "visible")
+                .sync_create()
+                .build();
+            obj.bind_property("split-view", &*self.split_pane, "visible")
+                .sync_create()
+                .build();
+            for property in [
+                "key-encoding",
+                "value-encoding",
+                "non-printable-display",
+                "preview-length",
+            ] {
+                obj.bind_property(property, &*self.split_pane, property)
+                    .sync_create()
+                    .build();
+            }
+
+            for pane in [
+                self.monitor_pane.upcast_ref::<gtk::Widget>(),
+                self.pending_changes_pane.upcast_ref(),
+                self.search_results_pane.upcast_ref(),
+            ] {
+                pane.connect_visible_notify(clone!(@weak obj => move |_| {
+                    obj.update_side_paned_visibility();
+                }));
+            }
+
+            obj.connect_stage_changes_notify(|obj| {
+                obj.update_pending_changes();
+            });
+            obj.update_pending_changes();
+            obj.update_edit_history_actions();
+            obj.update_remote_actions();
+
+            obj.setup_view();
+
+            // Any input means the window is in use, so the snapshot isn't
+            // renewed under the pointer
+            let activity_controller = gtk::EventControllerLegacy::new();
+            activity_controller.set_propagation_phase(gtk::PropagationPhase::Capture);
+            activity_controller.connect_event(
+                clone!(@weak obj => @default-return glib::Propagation::Proceed, move |_, _| {
+                    obj.imp().snapshot_renewal.borrow_mut().record_activity();
+                    glib::Propagation::Proceed
+                }),
+            );
+            obj.add_controller(activity_controller);
+
+            obj.load_window_size();
+
+            obj.set_write_mode(settings.boolean("open-in-write-mode"));
+        }
+    }
+
+    impl WidgetImpl for Window {}
+
+    impl WindowImpl for Window {
+        fn close_request(&self) -> glib::Propagation {
+            if let Err(err) = self.obj().save_window_size() {
+                tracing::warn!("Failed to save window state: {:?}", &err);
+            }
+            self.obj().save_profile();
+            self.obj().close_env_windows();
+
+            self.parent_close_request()
+        }
+    }
+
+    impl ApplicationWindowImpl for Window {}
+    impl AdwApplicationWindowImpl for Window {}
+
+    impl Window {
+        fn set_write_mode(&self, write_mode: bool) {
+            if write_mode == self.write_mode.get() {
+                return;
+            }
+
+            self.write_mode.set(write_mode);
+
+            let obj = self.obj();
+
+            let path = self
+                .env
+                .borrow()
+                .as_ref()
+                .map(|env| env.path().to_path_buf());
+            if let Some(path) = path {
+                // Kept, as they are staged for the same env
+                let pending_changes = self.pending_changes.take();
+                match obj.load_env(&path) {
+                    Ok(()) if write_mode => obj.warn_if_env_in_use(&path),
+                    Ok(()) => {}
+                    Err(err) if write_mode => obj.fall_back_to_read_only(&path, err),
+                    Err(err) => {
+                        tracing::error!("Failed to reopen env: {:?}", &err);
+                        obj.add_error_toast(&gettext("Failed to reopen env"), &err);
+                    }
+                }
+                self.pending_changes.replace(pending_changes);
+                obj.update_pending_changes();
+            }
+
+            obj.update_selection_actions();
+            obj.notify_write_mode();
+        }
+
+        fn set_collapsed(&self, collapsed: bool) {
+            if collapsed == self.collapsed.get() {
+                return;
+            }
+
+            self.collapsed.set(collapsed);
+
+            let obj = self.obj();
+            obj.update_layout();
+            obj.update_selection_actions();
+            obj.notify_collapsed();
+        }
+
+        fn set_show_changes(&self, show_changes: bool) {
+            if show_changes == self.show_changes.get() {
+                return;
+            }
+
+            self.show_changes.set(show_changes);
+
+            let obj = self.obj();
+
+            if let Some(db) = obj.selected_db() {
+                db.set_show_changes(show_changes);
+            }
+
+            obj.notify_show_changes();
+        }
+
+        fn set_monitor(&self, monitor: bool) {
+            if monitor == self.monitor.get() {
+                return;
+            }
+
+            self.monitor.set(monitor);
+
+            let obj = self.obj();
+            obj.restart_monitor();
+            obj.notify_monitor();
+        }
+
+        fn set_advanced_tools(&self, advanced_tools: bool) {
+            if advanced_tools == self.advanced_tools.get() {
+                return;
+            }
+
+            self.advanced_tools.set(advanced_tools);
+
+            let obj = self.obj();
+            obj.update_selection_actions();
+            obj.notify_advanced_tools();
+        }
+
+        fn set_monitor_interval(&self, interval: u32) {
+            if interval == self.monitor_interval.get() {
+                return;
+            }
+
+            self.monitor_interval.set(interval);
+
+            let obj = self.obj();
+            obj.restart_monitor();
+            obj.notify_monitor_interval();
+        }
+
+        fn set_snapshot_renewal_interval(&self, interval: u32) {
+            if interval == self.snapshot_renewal_interval.get() {
+                return;
+            }
+
+            self.snapshot_renewal_interval.set(interval);
+            self.snapshot_renewal
+                .borrow_mut()
+                .set_max_stale_duration(Duration::from_secs(interval as u64));
+
+            let obj = self.obj();
+
+            if let Some(source_id) = self.snapshot_renewal_source_id.take() {
+                source_id.remove();
+            }
+
+            if interval > 0 {
+                let source_id = glib::timeout_add_seconds_local(
+                    SNAPSHOT_CHECK_INTERVAL,
+                    clone!(@weak obj => @default-return glib::ControlFlow::Break, move || {
+                        obj.check_snapshot();
+                        glib::ControlFlow::Continue
+                    }),
+                );
+                self.snapshot_renewal_source_id.replace(Some(source_id));
+            }
+
+            obj.notify_snapshot_renewal_interval();
+        }
+
+        fn set_key_collation(&self, key_collation: KeyCollation) {
+            if key_collation == self.key_collation.get() {
+                return;
+            }
+
+            self.key_collation.set(key_collation);
+
+            let obj = self.obj();
+            obj.update_sorter();
+            obj.notify_key_collation();
+        }
+
+        fn set_key_separator(&self, key_separator: String) {
+            if key_separator == *self.key_separator.borrow() {
+                return;
+            }
+
+            self.key_separator.replace(key_separator);
+
+            let obj = self.obj();
+            obj.update_key_tree();
+            obj.notify_key_separator();
+        }
+
+        fn set_show_key_tree(&self, show_key_tree: bool) {
+            if show_key_tree == self.show_key_tree.get() {
+                return;
+            }
+
+            self.show_key_tree.set(show_key_tree);
+
+            let obj = self.obj();
+            obj.update_key_tree();
+            obj.notify_show_key_tree();
+        }
+
+        fn set_split_view(&self, split_view: bool) {
+            if split_view == self.split_view.get() {
+                return;
+            }
+
+            self.split_view.set(split_view);
+
+            let obj = self.obj();
+            // Only keep the entries of the other database loaded while shown
+            let env = self.env.borrow().clone().filter(|_| split_view);
+            if let Err(err) = self.split_pane.set_env(env.as_ref()) {
+                tracing::error!("Failed to show split view: {:?}", &err);
+                obj.add_error_toast(&gettext("Failed to show split view"), &err);
+            }
+            obj.notify_split_view();
+        }
+
+        fn set_page_size(&self, page_size: u32) {
+            if page_size == self.page_size.get() {
+                return;
+            }
+
+            // Keep showing the first row of the current page
+            let offset = self.slice_model.offset();
+            self.page_size.set(page_size);
+            self.slice_model.set_size(page_size);
+
+            let obj = self.obj();
+            obj.set_page(offset / page_size);
+            obj.notify_page_size();
+        }
+
+        fn set_auto_reload_interval(&self, interval: u32) {
+            if interval == self.auto_reload_interval.get() {
+                return;
+            }
+
+            self.auto_reload_interval.set(interval);
+
+            let obj = self.obj();
+
+            if let Some(source_id) = self.auto_reload_source_id.take() {
+                source_id.remove();
+            }
+
+            if interval > 0 {
+                let source_id = glib::timeout_add_seconds_local(
+                    interval,
+                    clone!(@weak obj => @default-return glib::ControlFlow::Break, move || {
+                        obj.reload_env();
+                        glib::ControlFlow::Continue
+                    }),
+                );
+                self.auto_reload_source_id.replace(Some(source_id));
+            }
+
+            obj.notify_auto_reload_interval();
+        }
+    }
+}
+
+glib::wrapper! {
+    pub struct Window(ObjectSubclass<imp::Window>)
+        @extends gtk::Widget, gtk::Window, gtk::ApplicationWindow, adw::ApplicationWindow,
+        @implements gio::ActionMap, gio::ActionGroup, gtk::Root;
+}
+
+impl Window {
+    pub fn new(app: &Application) -> Self {
+        glib::Object::builder().property("application", app).build()
+    }
+
+    pub fn add_message_toast(&self, message: &str) {
+        let toast = adw::Toast::new(message);
+        self.imp().toast_overlay.add_toast(toast);
+    }
+
+    /// Shows `message` in a toast that can show the details of `err`.
+    pub fn add_error_toast(&self, message: &str, err: &anyhow::Error) {
+        let toast = error_details::error_toast(self, message, err);
+        self.imp().toast_overlay.add_toast(toast);
+    }
+
+    /// Asks for the data file of an env to open, which is either `data.mdb`
+    /// or `mdbx.dat` in the env directory, or the file of a single-file env.
+    ///
+    /// A file chosen through the document portal comes without the files
+    /// next to it, so access to its folder is asked for too, as LMDB needs
+    /// the lock file to read it safely.
+    async fn open_env(&self) -> Result<()> {
+        let filter = gtk::FileFilter::new();
+        filter.set_name(Some(&gettext("LMDB and MDBX Data Files")));
+        filter.add_pattern("*.mdb");
+        filter.add_pattern("*.dat");
+        let all_filter = gtk::FileFilter::new();
+        all_filter.set_name(Some(&gettext("All Files")));
+        all_filter.add_pattern("*");
+        let filters = gio::ListStore::new::<gtk::FileFilter>();
+        filters.append(&filter);
+        filters.append(&all_filter);
+
+        let dialog = gtk::FileDialog::builder()
+            .title(gettext("Open Database"))
+            .filters(&filters)
+            .modal(true)
+            .build();
+
+        let file = dialog.open_future(Some(self)).await?;
+        let path = file.path().expect("file must have a path");
+
+        let env_path = if utils::is_document_portal_path(&path) {
+            self.choose_env_folder(&path).await?
+        } else {
+            Some(utils::env_path(&path)?)
+        };
+        let Some(env_path) = env_path else {
+            return Ok(());
+        };
+
+        self.load_env_or_copy(&env_path)
+            .await
+            .with_context(|| format!("Failed to open env at `{}`", env_path.display()))?;
+        tracing::debug!("Opened env at `{}`", env_path.display());
+
+        Ok(())
+    }
+
+    /// Asks for the folder of the data file at `data_path`, so the portal
+    /// gives access to the lock file next to it, and returns the path of the
+    /// env in it, or `None` if cancelled.
+    async fn choose_env_folder(&self, data_path: &Path) -> Result<Option<PathBuf>> {
+        let file_name = data_path.file_name().context("File has no name")?;
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("Allow Access to Folder"))
+            .body(gettext_f(
+                "Opening “{name}” safely also needs its lock file, which is in the same \
+                 folder. Choose the folder that has “{name}” to allow access to both.",
+                &[("name", &file_name.to_string_lossy())],
+            ))
+            .transient_for(self)
+            .modal(true)
+            .default_response("choose")
+            .close_response("cancel")
+            .build();
+        dialog.add_responses(&[
+            ("cancel", &gettext("_Cancel")),
+            ("choose", &gettext("C_hoose Folder…")),
+        ]);
+        dialog.set_response_appearance("choose", adw::ResponseAppearance::Suggested);
+
+        if dialog.choose_future().await != "choose" {
+            return Ok(None);
+        }
+
+        let file_dialog = gtk::FileDialog::builder()
+            .title(gettext("Choose Folder"))
+            .modal(true)
+            .build();
+        let folder = file_dialog.select_folder_future(Some(self)).await?;
+        let folder_path = folder.path().expect("file must have a path");
+
+        let chosen_data_path = folder_path.join(file_name);
+        anyhow::ensure!(
+            chosen_data_path.is_file(),
+            "`{}` has no `{}`",
+            folder_path.display(),
+            file_name.to_string_lossy()
+        );
+
+        utils::env_path(&chosen_data_path).map(Some)
+    }
+
+    /// Asks for the location of a remote env, e.g., on an SFTP server, and
+    /// opens a local copy of it.
+    async fn open_remote(&self) -> Result<()> {
+        let entry = gtk::Entry::builder()
+            .placeholder_text("sftp://example.com/path/to/env")
+            .activates_default(true)
+            .build();
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("Open Remote Environment"))
+            .body(gettext(
+                "The environment is downloaded to a local cache and opened read-only.",
+            ))
+            .transient_for(self)
+            .modal(true)
+            .extra_child(&entry)
+            .default_response("open")
+            .close_response("cancel")
+            .build();
+        dialog.add_responses(&[("cancel", &gettext("_Cancel")), ("open", &gettext("_Open"))]);
+        dialog.set_response_appearance("open", adw::ResponseAppearance::Suggested);
+
+        if dialog.choose_future().await != "open" {
+            return Ok(());
+        }
+
+        let uri = entry.text().trim_end_matches('/').to_string();
+        if uri.is_empty() {
+            return Ok(());
+        }
+
+        self.sync_remote(uri).await
+    }
+
+    /// Downloads the env at `uri` if it changed since the last sync, then
+    /// opens the local copy read-only.
+    async fn sync_remote(&self, uri: String) -> Result<()> {
+        let imp = self.imp();
+
+        let file = gio::File::for_uri(&uri);
+        if !file.is_native() {
+            let mount_operation = gtk::MountOperation::new(Some(self));
+            if let Err(err) = file
+                .mount_enclosing_volume_future(gio::MountMountFlags::NONE, Some(&mount_operation))
+                .await
+            {
+                if !err.matches(gio::IOErrorEnum::AlreadyMounted) {
+                    return Err(err).with_context(|| format!("Failed to mount `{}`", uri));
+                }
+            }
+        }
+
+        let n_bytes = Arc::new(AtomicU64::new(0));
+        let n_total_bytes = Arc::new(AtomicU64::new(0));
+        let progress_dialog = self.present_progress_dialog(
+            &gettext("Downloading Environment…"),
+            clone!(@strong n_bytes, @strong n_total_bytes => move || {
+                let n_total_bytes = n_total_bytes.load(atomic::Ordering::Relaxed);
+                (n_total_bytes > 0).then(|| {
+                    n_bytes.load(atomic::Ordering::Relaxed) as f64 / n_total_bytes as f64
+                })
+            }),
+        );
+        let result = gio::spawn_blocking(clone!(@strong uri => move || {
+            remote::sync(&uri, &n_bytes, &n_total_bytes)
+        }))
+        .await
+        .map_err(|_| anyhow::anyhow!("sync thread panicked"))?;
+        progress_dialog.close();
+        let (cache_dir, downloaded) = result?;
+
+        self.set_write_mode(false);
+        self.load_env(&cache_dir)?;
+        imp.remote_uri.replace(Some(uri));
+        self.update_remote_actions();
+        self.update_breadcrumb();
+
+        if !downloaded {
+            self.add_message_toast(&gettext("Already up to date"));
+        }
+
+        Ok(())
+    }
+
+    fn update_remote_actions(&self) {
+        let is_remote = self.imp().remote_uri.borrow().is_some();
+
+        self.action_set_enabled("win.sync-remote", is_remote);
+        self.imp().sync_remote_button.set_visible(is_remote);
+    }
+
+    /// Opens the env at `path`, read-write if in write mode, replacing the
+    /// current one. MDBX envs are opened with [`Self::load_mdbx_env`].
+    pub fn load_env(&self, path: &Path) -> Result<()> {
+        if let Ok(Some(BackendKind::Mdbx)) = BackendKind::detect(&BackendKind::data_path(path)) {
+            return self.load_mdbx_env(path);
+        }
+
+        self.close_env()?;
+
+        let env = utils::open_env(path, self.write_mode())?;
+        self.set_env(env)
+    }
+
+    /// Asks whether to stay in write mode if another process has the env at
+    /// `path` open, as it may write too.
+    fn warn_if_env_in_use(&self, path: &Path) {
+        let is_in_use = utils::is_env_in_use(path).unwrap_or_else(|err| {
+            tracing::warn!("Failed to check if env is in use: {:?}", &err);
+            false
+        });
+        if !is_in_use {
+            return;
+        }
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("Environment In Use"))
+            .body(gettext_f(
+                "Another process has “{path}” open. Only one process can write at a time, \
+                 so writes here wait for its writes to finish. If it opened the environment \
+                 without locking, writing here may corrupt it.",
+                &[("path", &path.display().to_string())],
+            ))
+            .transient_for(self)
+            .modal(true)
+            .default_response("read-only")
+            .close_response("read-only")
+            .build();
+        dialog.add_responses(&[
+            ("read-only", &gettext("_Stay Read-Only")),
+            ("write", &gettext("_Write Anyway")),
+        ]);
+        dialog.set_response_appearance("write", adw::ResponseAppearance::Destructive);
+
+        glib::spawn_future_local(clone!(@weak self as obj => async move {
+            if dialog.choose_future().await != "write" {
+                obj.set_write_mode(false);
+            }
+        }));
+    }
+
+    /// Reopens the env at `path` read-only after `err` kept it from being
+    /// opened for writing, explaining why write mode was left.
+    fn fall_back_to_read_only(&self, path: &Path, err: anyhow::Error) {
+        tracing::error!("Failed to reopen env for writing: {:?}", &err);
+
+        self.imp().write_mode.set(false);
+        if let Err(err) = self.load_env(path) {
+            tracing::error!("Failed to reopen env: {:?}", &err);
+            self.add_error_toast(&gettext("Failed to reopen env"), &err);
+            return;
+        }
+
+        let path_display = path.display().to_string();
+        let body = if utils::permission_error(&err).is_some() {
+            gettext_f(
+                "“{path}” or its lock file can't be written with your permissions, so it was \
+                 left open read-only.",
+                &[("path", &path_display)],
+            )
+        } else {
+            gettext_f(
+                "“{path}” couldn't be opened for writing with locking, so it was left open \
+                 read-only rather than risk writing without it: {error}",
+                &[("path", &path_display), ("error", &format!("{:#}", err))],
+            )
+        };
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("Opened Read-Only"))
+            .body(body)
+            .transient_for(self)
+            .modal(true)
+            .default_response("close")
+            .close_response("close")
+            .build();
+        dialog.add_response("close", &gettext("_Close"));
+        dialog.present();
+    }
+
+    /// Opens the env at `path` read-only with the extra `flags`, to get around
+    /// what keeps it from being opened normally.
+    fn load_env_with_flags(&self, path: &Path, flags: EnvFlags) -> Result<()> {
+        // Closed first so that leaving write mode doesn't reopen it
+        self.close_env()?;
+        self.set_write_mode(false);
+
+        let env = utils::open_env_with_flags(path, EnvFlags::READ_ONLY | flags)?;
+        self.set_env(env)
+    }
+
+    /// Opens the MDBX env at `path`, replacing the current one. It can only
+    /// be read, so write mode is left, and tools that need an LMDB env, such
+    /// as exports and statistics, are disabled while it is open.
+    fn load_mdbx_env(&self, path: &Path) -> Result<()> {
+        // Closed first so that leaving write mode doesn't reopen it
+        self.close_env()?;
+        self.set_write_mode(false);
+
+        let backend: Arc<dyn Backend> = Arc::new(mdbx::Env::open(path)?);
+        let db = Database::load_from(&backend, None).context("Failed to load unnamed db")?;
+        self.imp().backend.replace(Some(backend));
+        self.imp().drop_down.set_model(Some(&db));
+
+        self.update_breadcrumb();
+        self.update_selection_actions();
+
+        Ok(())
+    }
+
+    fn set_env(&self, env: Env) -> Result<()> {
+        let imp = self.imp();
+
+        imp.env.replace(Some(env.clone()));
+        imp.backend.replace(Some(Arc::new(env.clone())));
+        self.update_no_lock_banner(&env)?;
+
+        let annotations = Annotations::load(env.path()).unwrap_or_else(|err| {
+            tracing::warn!("Failed to load annotations: {:?}", &err);
+            Annotations::default()
+        });
+        imp.annotations.replace(annotations);
+        self.clear_key_watches();
+        imp.key_history.borrow_mut().clear();
+
+        let db = Database::load(&env, None).context("Failed to load unnamed db")?;
+        imp.drop_down.set_model(Some(&db));
+        self.restore_profile(&env);
+
+        imp.bookmarks.replace(Bookmark::load_all(env.path()));
+        self.update_bookmarks_list();
+        imp.saved_searches
+            .replace(SavedSearch::load_all(env.path()));
+        self.update_saved_searches_list();
+        self.replace_highlight_rules(HighlightRule::load_all(env.path()));
+
+        self.update_breadcrumb();
+        if self.split_view() {
+            imp.split_pane
+                .set_env(Some(&env))
+                .context("Failed to load split view")?;
+        }
+
+        glib::spawn_future_local(clone!(@weak self as obj => async move {
+            obj.offer_saved_changes(&env).await;
+        }));
+
+        Ok(())
+    }
+
+    /// Opens the env at `path` like [`Self::load_env`], but if it can't be
+    /// read due to permissions, explains why and offers to open a read-only
+    /// copy instead. Corrupted or incompatible envs are explained, and
+    /// offered ways around it.
+    pub async fn load_env_or_copy(&self, path: &Path) -> Result<()> {
+        let Err(err) = self.load_env(path) else {
+            return Ok(());
+        };
+        if utils::mdb_error(&err).is_some() {
+            return self.recover_env(path, err).await;
+        }
+        let Some(permission_error) = utils::permission_error(&err) else {
+            return Err(err);
+        };
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("Can't Read Environment"))
+            .body(gettext_f(
+                "Opening “{path}” failed: {error}. A read-only copy can be opened instead.",
+                &[
+                    ("path", &path.display().to_string()),
+                    ("error", &permission_error.to_string()),
+                ],
+            ))
+            .transient_for(self)
+            .modal(true)
+            .default_response("copy-as-admin")
+            .close_response("cancel")
+            .build();
+        dialog.add_responses(&[
+            ("cancel", &gettext("_Cancel")),
+            ("copy", &gettext("_Copy")),
+            ("copy-as-admin", &gettext("Copy as _Administrator")),
+        ]);
+        dialog.set_response_appearance("copy-as-admin", adw::ResponseAppearance::Suggested);
+
+        let as_admin = match dialog.choose_future().await.as_str() {
+            "copy" => false,
+            "copy-as-admin" => true,
+            _ => return Err(err),
+        };
+
+        let path = path.to_path_buf();
+        let copy_dir = gio::spawn_blocking(move || utils::copy_env_to_temp(&path, as_admin))
+            .await
+            .map_err(|_| anyhow::anyhow!("copy thread panicked"))??;
+
+        self.set_write_mode(false);
+        self.load_env(&copy_dir)?;
+
+        self.add_message_toast(&gettext_f(
+            "Opened a copy in “{path}”",
+            &[("path", &copy_dir.display().to_string())],
+        ));
+
+        Ok(())
+    }
+
+    /// Explains the LMDB error that kept the env at `path` from being opened
+    /// with what may fix it, and offers to open it in a way that avoids the
+    /// error where there is one. `err` is returned if that isn't taken.
+    async fn recover_env(&self, path: &Path, err: anyhow::Error) -> Result<()> {
+        let Some(mdb_error) = utils::mdb_error(&err) else {
+            return Err(err);
+        };
+
+        let path_display = path.display().to_string();
+        let (heading, body, recovery) = match mdb_error {
+            heed::MdbError::Corrupted | heed::MdbError::Problem => (
+                gettext("Corrupted Environment"),
+                gettext_f(
+                    "“{path}” is damaged, possibly by a crash while it was being written. \
+                     Its previous snapshot, which lacks the last committed transaction, may \
+                     still be intact. Otherwise, restore it from a backup, or salvage what \
+                     is readable with “mdb_dump -a”.",
+                    &[("path", &path_display)],
+                ),
+                Some((gettext("Open _Previous Snapshot"), utils::PREVIOUS_SNAPSHOT)),
+            ),
+            heed::MdbError::Invalid => (
+                gettext("Invalid Environment"),
+                gettext_f(
+                    "The headers of “{path}” aren't those of an LMDB environment. The file \
+                     may be truncated, overwritten, or not an environment at all. If it was \
+                     an environment, restore it from a backup.",
+                    &[("path", &path_display)],
+                ),
+                None,
+            ),
+            heed::MdbError::VersionMismatch => (
+                gettext("Incompatible Environment"),
+                gettext_f(
+                    "“{path}” was created by a different version of LMDB. Its lock file may \
+                     be the only incompatible part, in which case it can be opened without \
+                     locking while nothing else writes to it. Otherwise, convert it with \
+                     “mdb_dump” of the version that created it and “mdb_load”.",
+                    &[("path", &path_display)],
+                ),
+                Some((gettext("Open Without _Locking"), EnvFlags::NO_LOCK)),
+            ),
+            _ => return Err(err),
+        };
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(heading)
+            .body(body)
+            .transient_for(self)
+            .modal(true)
+            .default_response("cancel")
+            .close_response("cancel")
+            .build();
+        dialog.add_response("cancel", &gettext("_Cancel"));
+        if let Some((label, _)) = &recovery {
+            dialog.add_response("recover", label);
+            dialog.set_response_appearance("recover", adw::ResponseAppearance::Suggested);
+            dialog.set_default_response(Some("recover"));
+        }
+
+        let Some((_, flags)) = recovery else {
+            dialog.choose_future().await;
+            return Ok(());
+        };
+        if dialog.choose_future().await != "recover" {
+            return Ok(());
+        }
+
+        self.load_env_with_flags(path, flags)?;
+
+        self.add_message_toast(&if flags == utils::PREVIOUS_SNAPSHOT {
+            gettext("Opened the previous snapshot read-only")
+        } else {
+            gettext("Opened read-only without locking")
+        });
+
+        Ok(())
+    }
+
+    /// Remembers the view of the current env, so it is restored when the env
+    /// is reopened.
+    fn save_profile(&self) {
+        let imp = self.imp();
+
+        let Some(env) = imp.env.borrow().clone() else {
+            return;
+        };
+
+        let settings = gio::Settings::new(APP_ID);
+        let columns = self.columns();
+        let profile = EnvProfile {
+            selected_db: self
+                .selected_db()
+                .and_then(|db| db.name().map(|name| name.to_string())),
+            display_settings: env_profile::DISPLAY_SETTINGS
+                .iter()
+                .map(|key| (key.to_string(), settings.string(key).to_string()))
+                .collect(),
+            column_widths: columns
+                .iter()
+                .filter(|column| column.fixed_width() > 0)
+                .filter_map(|column| Some((column.id()?.to_string(), column.fixed_width())))
+                .collect(),
+            hidden_columns: columns
+                .iter()
+                .filter(|column| !column.is_visible())
+                .filter_map(|column| Some(column.id()?.to_string()))
+                .filter(|id| !SETTINGS_COLUMN_IDS.contains(&id.as_str()))
+                .collect(),
+            pinned_fields: imp.pinned_fields.borrow().clone(),
+            map_size: Some(env.info().map_size as u64),
+        };
+        profile.save(env.path());
+    }
+
+    /// Restores the view that `env` had when it was last closed.
+    fn restore_profile(&self, env: &Env) {
+        let profile = EnvProfile::load(env.path());
+
+        let settings = gio::Settings::new(APP_ID);
+        for (key, value) in &profile.display_settings {
+            if !env_profile::DISPLAY_SETTINGS.contains(&key.as_str()) {
+                continue;
+            }
+
+            if let Err(err) = settings.set_string(key, value) {
+                tracing::warn!("Failed to restore `{}`: {:?}", key, &err);
+            }
+        }
+
+        for column in self.columns() {
+            let id = column.id().unwrap_or_default();
+            column.set_fixed_width(
+                profile
+                    .column_widths
+                    .get(id.as_str())
+                    .copied()
+                    .unwrap_or(-1),
+            );
+            if !SETTINGS_COLUMN_IDS.contains(&id.as_str()) {
+                column.set_visible(!profile.hidden_columns.iter().any(|hidden| *hidden == id));
+            }
+        }
+
+        self.imp().pinned_fields.replace(profile.pinned_fields);
+        self.update_field_columns();
+
+        if let Some(db_name) = &profile.selected_db {
+            self.select_database(db_name);
+        }
+    }
+
+    /// Writes the settings of the current env to a JSON file, so they can be
+    /// imported elsewhere.
+    async fn export_settings(&self) -> Result<()> {
+        let env = self.imp().env.borrow().clone().context("No env set")?;
+
+        // Includes the current view, not only what was saved on last close
+        self.save_profile();
+        let settings = settings_bundle::export(env.path());
+
+        let file_dialog = gtk::FileDialog::builder()
+            .title(gettext("Export Settings"))
+            .initial_name("settings.json")
+            .modal(true)
+            .build();
+        let file = file_dialog.save_future(Some(self)).await?;
+        let path = file.path().context("File must have a path")?;
+        fs::write(&path, serde_json::to_string_pretty(&settings)?)
+            .with_context(|| format!("Failed to write `{}`", path.display()))?;
+
+        self.add_message_toast(&gettext("Settings exported"));
+
+        Ok(())
+    }
+
+    /// Imports the settings in a JSON file, written by
+    /// [`Self::export_settings`], for the current env and applies them.
+    async fn import_settings(&self) -> Result<()> {
+        let imp = self.imp();
+
+        let env = imp.env.borrow().clone().context("No env set")?;
+
+        let file_dialog = gtk::FileDialog::builder()
+            .title(gettext("Import Settings"))
+            .modal(true)
+            .build();
+        let file = file_dialog.open_future(Some(self)).await?;
+        let path = file.path().context("File must have a path")?;
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read `{}`", path.display()))?;
+        let settings = serde_json::from_str(&contents).context("Invalid JSON")?;
+        settings_bundle::import(env.path(), &settings)?;
+
+        imp.bookmarks.replace(Bookmark::load_all(env.path()));
+        self.update_bookmarks_list();
+        imp.saved_searches
+            .replace(SavedSearch::load_all(env.path()));
+        self.update_saved_searches_list();
+        self.replace_highlight_rules(HighlightRule::load_all(env.path()));
+        self.restore_profile(&env);
+        if let Some(db) = self.selected_db() {
+            imp.detail_pane
+                .set_decoder_id(self.load_decoder_id(env.path(), &db));
+        }
+
+        self.add_message_toast(&gettext("Settings imported"));
+
+        Ok(())
+    }
+
+    /// Moves the detail pane to its own page while collapsed, or back next
+    /// to the entries otherwise.
+    fn update_layout(&self) {
+        let imp = self.imp();
+
+        let detail_pane = imp.detail_pane.get();
+        if self.collapsed() {
+            imp.side_paned.set_start_child(gtk::Widget::NONE);
+            imp.collapsed_detail_bin.set_child(Some(&detail_pane));
+        } else {
+            imp.navigation_view.pop_to_tag("entries");
+            imp.collapsed_detail_bin.set_child(gtk::Widget::NONE);
+            imp.side_paned.set_start_child(Some(&detail_pane));
+        }
+
+        self.update_side_paned_visibility();
+    }
+
+    /// Hides the panes below the entries while collapsed if none of them is
+    /// shown, as the paned would otherwise take space for nothing.
+    fn update_side_paned_visibility(&self) {
+        let imp = self.imp();
+
+        let has_visible_pane = imp.monitor_pane.is_visible()
+            || imp.pending_changes_pane.is_visible()
+            || imp.search_results_pane.is_visible();
+        imp.side_paned
+            .set_visible(!self.collapsed() || has_visible_pane);
+    }
+
+    fn columns(&self) -> Vec<gtk::ColumnViewColumn> {
+        let columns = self.imp().column_view.columns();
+        (0..columns.n_items())
+            .filter_map(|position| columns.item(position).and_downcast())
+            .collect()
+    }
+
+    /// Warns that reads may be torn if `env` had to be opened without
+    /// locking, more strongly if another process has it open.
+    fn update_no_lock_banner(&self, env: &Env) -> Result<()> {
+        let banner = &self.imp().no_lock_banner;
+
+        if !utils::is_no_lock(env)? {
+            banner.set_revealed(false);
+            return Ok(());
+        }
+
+        let is_in_use = utils::is_env_in_use(env.path()).unwrap_or_else(|err| {
+            tracing::warn!("Failed to check if env is in use: {:?}", &err);
+            true
+        });
+        banner.set_title(&if is_in_use {
+            gettext("Unsafe snapshot: read without locking while another process has it open")
+        } else {
+            gettext("Unsafe snapshot: read without locking, so other writers may cause torn reads")
+        });
+        banner.set_revealed(true);
+
+        Ok(())
+    }
+
+    fn reload_env(&self) {
+        let imp = self.imp();
+
+        if let Some(model) = imp.drop_down.model() {
+            let db = model.downcast_ref::<Database>().unwrap();
+
+            if let Err(err) = db.reload() {
+                tracing::error!("Failed to reload env on drop down: {:?}", &err);
+            }
+        }
+
+        if let Some(db) = self.selected_db() {
+            if let Err(err) = db.reload() {
+                tracing::error!("Failed to reload env on view: {:?}", &err);
+            }
+        }
+
+        if self.monitor() {
+            self.record_changes();
+        }
+
+        self.update_status_bar();
+    }
+
+    /// Like [`Self::reload_env`], but reads the entries in a separate thread
+    /// while a spinner is shown on the reload button. The list of databases
+    /// is only reloaded if `include_db_list` is set.
+    async fn reload_in_background(&self, include_db_list: bool) {
+        let imp = self.imp();
+
+        self.action_set_enabled("win.reload-db", false);
+        self.action_set_enabled("win.reload-env", false);
+        imp.reload_stack.set_visible_child_name("spinner");
+
+        if include_db_list {
+            if let Some(model) = imp.drop_down.model() {
+                let db = model.downcast::<Database>().unwrap();
+
+                if let Err(err) = db.reload_in_background().await {
+                    tracing::error!("Failed to reload env on drop down: {:?}", &err);
+                }
+            }
+        }
+
+        if let Some(db) = self.selected_db() {
+            if let Err(err) = db.reload_in_background().await {
+                tracing::error!("Failed to reload env on view: {:?}", &err);
+                self.add_error_toast(&gettext("Failed to reload database"), &err);
+            }
+        }
+
+        if self.monitor() {
+            self.record_changes();
+        }
+
+        self.update_status_bar();
+
+        imp.reload_stack.set_visible_child_name("icon");
+        self.action_set_enabled("win.reload-db", true);
+        self.action_set_enabled("win.reload-env", true);
+    }
+
+    /// Polls the env at the monitor interval while in monitor mode.
+    fn restart_monitor(&self) {
+        let imp = self.imp();
+
+        if let Some(source_id) = imp.monitor_source_id.take() {
+            source_id.remove();
+        }
+
+        if self.monitor() {
+            let source_id = glib::timeout_add_seconds_local(
+                self.monitor_interval(),
+                clone!(@weak self as obj => @default-return glib::ControlFlow::Break, move || {
+                    obj.reload_env();
+                    glib::ControlFlow::Continue
+                }),
+            );
+            imp.monitor_source_id.replace(Some(source_id));
+        }
+    }
+
+    /// Renews the snapshot of the selected database if it is stale and the
+    /// window is idle or has kept it long enough.
+    fn check_snapshot(&self) {
+        let imp = self.imp();
+
+        // Only databases of LMDB envs are pinned to a snapshot
+        let Some((db, snapshot)) = self
+            .selected_db()
+            .and_then(|db| db.snapshot().ok().map(|snapshot| (db, snapshot)))
+        else {
+            return;
+        };
+
+        // Renewing would change what is being read or edited in a dialog
+        let has_dialog = gtk::Window::list_toplevels()
+            .into_iter()
+            .filter_map(|toplevel| toplevel.downcast::<gtk::Window>().ok())
+            .any(|window| {
+                window.is_visible() && window.transient_for().as_ref() == Some(self.upcast_ref())
+            });
+        if has_dialog || !imp.snapshot_renewal.borrow_mut().should_renew(&snapshot) {
+            return;
+        }
+
+        let saved_items = db.save_items();
+        if let Err(err) = db.reload() {
+            tracing::error!("Failed to renew snapshot: {:?}", &err);
+            return;
+        }
+        if self.monitor() {
+            self.record_changes();
+        }
+        self.update_status_bar();
+
+        let toast = adw::Toast::builder()
+            .title(gettext_f(
+                "Snapshot refreshed to transaction {id}",
+                &[("id", &db.txn_id().to_string())],
+            ))
+            .button_label(gettext("Keep Old View"))
+            .build();
+        let renewed_txn_id = db.txn_id();
+        // The saved items keep the old snapshot, and so its txn, open
+        let saved_items = Rc::new(RefCell::new(Some(saved_items)));
+        toast.connect_dismissed(clone!(@strong saved_items => move |_| {
+            saved_items.take();
+        }));
+        toast.connect_button_clicked(clone!(@weak self as obj, @weak db => move |_| {
+            let Some(saved_items) = saved_items.take() else {
+                return;
+            };
+            // Only restore if the database is still shown as renewed
+            if obj.selected_db().as_ref() != Some(&db) || db.txn_id() != renewed_txn_id {
+                return;
+            }
+            db.restore_items(saved_items);
+            if let Ok(snapshot) = db.snapshot() {
+                obj.imp().snapshot_renewal.borrow_mut().keep(&snapshot);
+            }
+            obj.update_status_bar();
+        }));
+        imp.toast_overlay.add_toast(toast);
+    }
+
+    /// Adds the changes in the selected database from the previous reload to
+    /// the monitor pane, notifying if any matches the watched prefix.
+    fn record_changes(&self) {
+        let imp = self.imp();
+
+        let Some(db) = self.selected_db() else {
+            return;
+        };
+
+        let watched_prefix = imp.monitor_pane.watched_prefix();
+        let mut has_watched_change = false;
+
+        for item in db.changes() {
+            let key_text = self.key_text(Some(item.clone().upcast()));
+            imp.monitor_pane.add_change(item.change(), &key_text);
+
+            if !watched_prefix.is_empty() && key_text.starts_with(watched_prefix.as_str()) {
+                has_watched_change = true;
+            }
+        }
+
+        if has_watched_change {
+            self.add_message_toast(&gettext_f(
+                "Keys starting with “{prefix}” changed",
+                &[("prefix", &watched_prefix)],
+            ));
+        }
+    }
+
+    /// Closes the current env, if any, so it can be reopened with different
+    /// flags.
+    fn close_env(&self) -> Result<()> {
+        let imp = self.imp();
+
+        self.save_profile();
+
+        imp.filter_model.set_model(gio::ListModel::NONE);
+        imp.drop_down.set_model(gio::ListModel::NONE);
+        imp.no_lock_banner.set_revealed(false);
+        imp.remote_uri.replace(None);
+        self.update_remote_actions();
+
+        imp.bookmarks.replace(Vec::new());
+        self.update_bookmarks_list();
+        imp.saved_searches.replace(Vec::new());
+        self.update_saved_searches_list();
+        self.replace_highlight_rules(Vec::new());
+        imp.pinned_fields.replace(HashMap::new());
+        self.update_field_columns();
+        imp.annotations.replace(Annotations::default());
+        imp.pending_changes.borrow_mut().clear();
+        self.update_pending_changes();
+        imp.edit_history.borrow_mut().clear();
+        self.update_edit_history_actions();
+        for monitor in imp.external_edit_monitors.take() {
+            monitor.cancel();
+        }
+        self.clear_key_watches();
+        imp.key_history.borrow_mut().clear();
+
+        imp.backend.take();
+        let Some(env) = imp.env.take() else {
+            self.update_breadcrumb();
+            return Ok(());
+        };
+        self.update_breadcrumb();
+        imp.split_pane.set_env(None)?;
+        self.close_env_windows();
+
+        if !env
+            .prepare_for_closing()
+            .wait_timeout(Duration::from_secs(5))
+        {
+            anyhow::bail!("Timed out waiting for env to close");
+        }
+
+        Ok(())
+    }
+
+    /// Starts watching the selected key, polling the env for changes to its
+    /// value.
+    fn watch_selected_key(&self) -> Result<()> {
+        let imp = self.imp();
+
+        let env = imp.env.borrow().clone().context("No env set")?;
+        let db = self.selected_db().context("No selected db")?;
+        let item = self.selected_item().context("No selected item")?;
+
+        imp.key_watches
+            .borrow_mut()
+            .add(&env, db.name(), &item.key())?;
+
+        if imp.key_watch_source_id.borrow().is_none() {
+            let source_id = glib::timeout_add_seconds_local(
+                KEY_WATCH_INTERVAL,
+                clone!(@weak self as obj => @default-return glib::ControlFlow::Break, move || {
+                    obj.poll_key_watches();
+                    glib::ControlFlow::Continue
+                }),
+            );
+            imp.key_watch_source_id.replace(Some(source_id));
+        }
+
+        self.update_selection_actions();
+        self.add_message_toast(&gettext_f(
+            "Watching “{key}” for changes",
+            &[("key", &self.key_text(Some(item.upcast())))],
+        ));
+
+        Ok(())
+    }
+
+    fn unwatch_selected_key(&self) {
+        let imp = self.imp();
+
+        let Some(item) = self.selected_item() else {
+            return;
+        };
+        let db_name = self
+            .selected_db()
+            .and_then(|db| db.name().map(str::to_owned));
+
+        let is_empty = {
+            let mut key_watches = imp.key_watches.borrow_mut();
+            key_watches.remove(db_name.as_deref(), &item.key());
+            key_watches.is_empty()
+        };
+        if is_empty {
+            if let Some(source_id) = imp.key_watch_source_id.take() {
+                source_id.remove();
+            }
+        }
+
+        self.update_selection_actions();
+    }
+
+    fn clear_key_watches(&self) {
+        let imp = self.imp();
+
+        imp.key_watches.borrow_mut().clear();
+        if let Some(source_id) = imp.key_watch_source_id.take() {
+            source_id.remove();
+        }
+    }
+
+    /// Checks the watched keys for changes, alerting about each one.
+    fn poll_key_watches(&self) {
+        let Some(env) = self.imp().env.borrow().clone() else {
+            return;
+        };
+
+        let changes = match self.imp().key_watches.borrow_mut().poll(&env) {
+            Ok(changes) => changes,
+            Err(err) => {
+                tracing::error!("Failed to poll watched keys: {:?}", &err);
+                return;
+            }
+        };
+
+        for change in changes {
+            self.alert_key_change(change);
+        }
+    }
+
+    /// Shows a toast for a change of a watched key, with a button to see the
+    /// value before and after, and also sends a desktop notification if the
+    /// window isn't focused.
+    fn alert_key_change(&self, change: WatchedChange) {
+        let key_text = self
+            .non_printable_display()
+            .render(&change.key, self.key_encoding());
+        let message = match (&change.before, &change.after) {
+            (None, _) => gettext_f("Watched key “{key}” was added", &[("key", &key_text)]),
+            (_, None) => gettext_f("Watched key “{key}” was deleted", &[("key", &key_text)]),
+            _ => gettext_f("Watched key “{key}” changed", &[("key", &key_text)]),
+        };
+        let (before_text, after_text) = self.changed_value_texts(&change);
+        let db_text = change
+            .db_name
+            .clone()
+            .unwrap_or_else(|| gettext("Unnamed Database"));
+
+        if !self.is_active() {
+            if let Some(app) = self.application() {
+                let notification = gio::Notification::new(&message);
+                notification.set_body(Some(&format!("− {}\n+ {}", before_text, after_text)));
+                app.send_notification(None, &notification);
+            }
+        }
+
+        let toast = adw::Toast::builder()
+            .title(&message)
+            .button_label(gettext("Show Change"))
+            .build();
+        toast.connect_button_clicked(clone!(@weak self as obj => move |_| {
+            let texts = [&message, &db_text, &before_text, &after_text].map(String::clone);
+            glib::spawn_future_local(clone!(@weak obj => async move {
+                obj.show_key_change(texts).await;
+            }));
+        }));
+        self.imp().toast_overlay.add_toast(toast);
+    }
+
+    /// Returns the parts of the value of a watched key before and after
+    /// `change` that differ, as text.
+    fn changed_value_texts(&self, change: &WatchedChange) -> (String, String) {
+        let render = |value: &Option<Vec<u8>>| {
+            value.as_ref().map_or_else(
+                || gettext("(none)"),
+                |value| {
+                    self.non_printable_display()
+                        .render(value, self.value_encoding())
+                },
+            )
+        };
+        match (&change.before, &change.after) {
+            (Some(_), Some(_)) => {
+                key_watch::changed_parts(&render(&change.before), &render(&change.after))
+            }
+            _ => (render(&change.before), render(&change.after)),
+        }
+    }
+
+    /// Shows the change of a watched key, from the alert `message`, the
+    /// database name, and the value texts before and after.
+    async fn show_key_change(&self, [message, db_text, before_text, after_text]: [String; 4]) {
+        let grid = gtk::Grid::builder()
+            .row_spacing(6)
+            .column_spacing(12)
+            .build();
+        let rows = [
+            (gettext("Before"), &before_text, "diff-removed"),
+            (gettext("After"), &after_text, "diff-added"),
+        ];
+        for (row, (title, text, css_class)) in rows.into_iter().enumerate() {
+            let title_label = gtk::Label::builder()
+                .label(title)
+                .xalign(1.0)
+                .yalign(0.0)
+                .css_classes(["dim-label"])
+                .build();
+            let text_label = gtk::Label::builder()
+                .label(text)
+                .xalign(0.0)
+                .wrap(true)
+                .wrap_mode(gtk::pango::WrapMode::WordChar)
+                .selectable(true)
+                .css_classes(["monospace", css_class])
+                .build();
+            grid.attach(&title_label, 0, row as i32, 1, 1);
+            grid.attach(&text_label, 1, row as i32, 1, 1);
+        }
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(message)
+            .body(gettext_f("In “{name}”", &[("name", &db_text)]))
+            .transient_for(self)
+            .modal(true)
+            .extra_child(&grid)
+            .default_response("close")
+            .close_response("close")
+            .build();
+        dialog.add_response("close", &gettext("_Close"));
+        dialog.choose_future().await;
+    }
+
+    /// Shows how the value of the first of the two selected items differs
+    /// from the value of the second.
+    fn compare_selected_values(&self) {
+        let [first, second] = &self.selected_items()[..] else {
+            return;
+        };
+
+        let (first_text, second_text) = self.value_diff_texts(
+            (&first.key(), &first.data()),
+            (&second.key(), &second.data()),
+        );
+        ValueDiffView::present(
+            self,
+            (&self.key_text(Some(first.clone().upcast())), &first_text),
+            (&self.key_text(Some(second.clone().upcast())), &second_text),
+        );
+    }
+
+    /// Returns the texts the values `before` and `after` of the given keys
+    /// are compared by, decoded as in the detail pane.
+    pub fn value_diff_texts(
+        &self,
+        before: (&[u8], &[u8]),
+        after: (&[u8], &[u8]),
+    ) -> (String, String) {
+        self.imp().detail_pane.value_diff_texts(before, after)
+    }
+
+    /// Returns the selected item if exactly one is selected.
+    fn selected_item(&self) -> Option<DatabaseItem> {
+        let model = &self.imp().column_view_model;
+
+        let selection = model.selection();
+        if selection.size() != 1 {
+            return None;
+        }
+
+        model.item(selection.nth(0)).and_downcast::<DatabaseItem>()
+    }
+
+    fn selected_items(&self) -> Vec<DatabaseItem> {
+        let model = &self.imp().column_view_model;
+
+        let selection = model.selection();
+        (0..selection.size() as u32)
+            .filter_map(|index| model.item(selection.nth(index)))
+            .map(|item| item.downcast::<DatabaseItem>().unwrap())
+            .collect()
+    }
+
+    /// Enables only the actions that apply to the current selection.
+    fn update_selection_actions(&self) {
+        let n_selected = self.imp().column_view_model.selection().size();
+        let write_mode = self.write_mode();
+
+        // Databases of other formats can only be browsed
+        let has_lmdb_db = self
+            .selected_db()
+            .is_some_and(|db| db.backend_kind() == BackendKind::Lmdb);
+        let is_read_only_env = self
+            .imp()
+            .backend
+            .borrow()
+            .as_ref()
+            .is_some_and(|backend| backend.kind() != BackendKind::Lmdb);
+        self.action_set_enabled("win.write-mode", !is_read_only_env);
+
+        self.action_set_enabled("win.bookmark-database", has_lmdb_db);
+        self.action_set_enabled(
+            "win.create-database",
+            write_mode && self.imp().env.borrow().is_some(),
+        );
+        let has_env = self.imp().env.borrow().is_some();
+        self.action_set_enabled("win.export-settings", has_env);
+        self.action_set_enabled("win.import-settings", has_env);
+        self.action_set_enabled("win.switch-database", has_env);
+        self.action_set_enabled(
+            "win.duplicate-database",
+            write_mode && self.selected_db().is_some_and(|db| db.name().is_some()),
+        );
+        self.action_set_enabled(
+            "win.clear-database",
+            write_mode && self.selected_db().is_some(),
+        );
+        self.action_set_enabled(
+            "win.delete-range",
+            write_mode && self.selected_db().is_some(),
+        );
+        self.action_set_enabled("win.import", write_mode && self.selected_db().is_some());
+        self.action_set_enabled("win.stage-changes", write_mode);
+        self.action_set_enabled("win.copy-env", self.imp().env.borrow().is_some());
+        self.action_set_enabled("win.copy-subset", has_lmdb_db);
+        self.action_set_enabled("win.show-env-info", self.imp().env.borrow().is_some());
+        self.action_set_enabled("win.show-meta-pages", self.imp().env.borrow().is_some());
+        self.action_set_enabled(
+            "win.inspect-page",
+            self.advanced_tools() && self.imp().env.borrow().is_some(),
+        );
+        self.action_set_enabled("win.show-statistics", self.imp().env.borrow().is_some());
+        self.action_set_enabled("win.find-duplicates", has_lmdb_db);
+        self.action_set_enabled("win.verify-database", has_lmdb_db);
+        self.action_set_enabled("win.bookmark-key", n_selected == 1);
+        let is_watched = self.selected_item().is_some_and(|item| {
+            self.imp().key_watches.borrow().contains(
+                self.selected_db()
+                    .and_then(|db| db.name().map(str::to_owned))
+                    .as_deref(),
+                &item.key(),
+            )
+        });
+        self.action_set_enabled("win.watch-key", n_selected == 1 && !is_watched);
+        self.action_set_enabled("win.unwatch-key", n_selected == 1 && is_watched);
+        self.action_set_enabled("win.save-key", n_selected == 1);
+        self.action_set_enabled("win.save-value", n_selected == 1);
+        self.action_set_enabled("win.compare-values", n_selected == 2);
+        self.action_set_enabled("win.open-in-new-window", n_selected == 1);
+        self.action_set_enabled("win.rename-key", write_mode && n_selected == 1);
+        self.action_set_enabled("win.load-value", write_mode && n_selected == 1);
+        self.action_set_enabled("win.set-value", write_mode && n_selected == 1);
+        self.action_set_enabled("win.edit-externally", write_mode && n_selected == 1);
+        self.action_set_enabled("win.copy-selected", n_selected > 0);
+        self.action_set_enabled("win.show-row-menu", n_selected > 0);
+        self.action_set_enabled("win.show-details", self.collapsed() && n_selected == 1);
+        self.action_set_enabled("win.export", has_lmdb_db);
+        self.action_set_enabled("win.export-sqlite", has_lmdb_db);
+        self.action_set_enabled("win.export-parquet", has_lmdb_db);
+        self.action_set_enabled("win.print", self.selected_db().is_some());
+        self.action_set_enabled("win.export-pdf", self.selected_db().is_some());
+        self.action_set_enabled("win.delete-selected", write_mode && n_selected > 0);
+
+        self.imp()
+            .detail_pane
+            .set_can_edit(write_mode && n_selected == 1);
+    }
+
+    fn handle_selection_changed(&self) {
+        let imp = self.imp();
+
+        let selected_item = self.selected_item();
+        let note = selected_item.as_ref().and_then(|item| self.note(item));
+        imp.detail_pane.set_item(selected_item.clone());
+        imp.detail_pane.set_note(note.unwrap_or_default());
+        self.update_key_history(selected_item.as_ref());
+        self.update_selection_actions();
+        self.update_status_bar();
+
+        if let Some(item) = &selected_item {
+            imp.split_pane.scroll_to_key(&item.key());
+        }
+    }
+
+    /// Records the value of the inspected `item` in the history of its key,
+    /// and shows the values it had before in the detail pane.
+    fn update_key_history(&self, item: Option<&DatabaseItem>) {
+        let imp = self.imp();
+
+        let (Some(item), Some(db)) = (item, self.selected_db()) else {
+            imp.detail_pane.set_versions(Vec::new());
+            return;
+        };
+        // Removed items aren't values the key has
+        if item.change() != DiffKind::Removed {
+            imp.key_history
+                .borrow_mut()
+                .inspect(db.name(), &item.key(), &item.data(), db.txn_id());
+        }
+        let versions = imp.key_history.borrow().versions(db.name(), &item.key());
+        imp.detail_pane.set_versions(versions);
+    }
+
+    /// Records the values of the keys with a history in `db`, as read from
+    /// the snapshot it shows.
+    fn record_key_history(&self, db: &Database) {
+        if let Err(err) = db.snapshot().and_then(|snapshot| {
+            self.imp()
+                .key_history
+                .borrow_mut()
+                .record_snapshot(&snapshot, db.name())
+        }) {
+            tracing::debug!("Failed to record key history: {:?}", &err);
+        }
+    }
+
+    /// Returns the note attached to `item` in the selected database.
+    fn note(&self, item: &DatabaseItem) -> Option<String> {
+        let db = self.selected_db()?;
+
+        self.imp()
+            .annotations
+            .borrow()
+            .get(db.name().unwrap_or_default(), &item.key())
+            .map(|note| note.to_string())
+    }
+
+    /// Attaches `note` to the item shown in the detail pane.
+    fn save_note(&self, note: &str) -> Result<()> {
+        let Some(item) = self.imp().detail_pane.item() else {
+            return Ok(());
+        };
+
+        let db = self.selected_db().context("No selected db")?;
+        self.save_key_note(db.name(), &item.key(), note)
+    }
+
+    /// Attaches `note` to `key` of the database called `db_name`.
+    fn save_key_note(&self, db_name: Option<&str>, key: &[u8], note: &str) -> Result<()> {
+        let mut annotations = self.imp().annotations.borrow_mut();
+
+        if annotations
+            .get(db_name.unwrap_or_default(), key)
+            .unwrap_or_default()
+            == note
+        {
+            return Ok(());
+        }
+
+        annotations.set(db_name.unwrap_or_default(), key, note)
+    }
+
+    /// Shows the selected item in a window of its own, which keeps showing it
+    /// while other rows are selected, so several can be looked at at once.
+    fn open_selected_in_new_window(&self) {
+        let imp = self.imp();
+
+        let (Some(item), Some(db)) = (self.selected_item(), self.selected_db()) else {
+            return;
+        };
+
+        let detail_pane = DetailPane::new();
+        detail_pane.set_decoder_id(imp.detail_pane.decoder_id());
+        detail_pane.set_item(Some(item.clone()));
+        detail_pane.set_note(self.note(&item).unwrap_or_default());
+        detail_pane.set_versions(imp.key_history.borrow().versions(db.name(), &item.key()));
+
+        let db_name = db.name().map(str::to_owned);
+        let key = item.key();
+        detail_pane.connect_note_notify(
+            clone!(@weak self as obj, @strong db_name, @strong key => move |detail_pane| {
+                let note = detail_pane.note();
+                if let Err(err) = obj.save_key_note(db_name.as_deref(), &key, &note) {
+                    tracing::error!("Failed to save note: {:?}", &err);
+                    obj.add_error_toast(&gettext("Failed to save note"), &err);
+                    return;
+                }
+
+                // Keep the note in sync if the key is also shown in this window
+                let imp = obj.imp();
+                let is_shown = imp.detail_pane.item().is_some_and(|item| item.key() == key)
+                    && obj.selected_db().is_some_and(|db| db.name() == db_name.as_deref());
+                if is_shown {
+                    imp.detail_pane.set_note(note);
+                }
+            }),
+        );
+
+        let key_text = self.key_text(Some(item.upcast()));
+        let header_bar = adw::HeaderBar::builder()
+            .title_widget(&adw::WindowTitle::new(
+                &key_text,
+                db_name.as_deref().unwrap_or_default(),
+            ))
+            .build();
+        let toast_overlay = adw::ToastOverlay::new();
+        toast_overlay.set_child(Some(&detail_pane));
+        let toolbar_view = adw::ToolbarView::new();
+        toolbar_view.add_top_bar(&header_bar);
+        toolbar_view.set_content(Some(&toast_overlay));
+
+        let window = adw::Window::builder()
+            .title(key_text)
+            .default_width(480)
+            .default_height(600)
+            .content(&toolbar_view)
+            .transient_for(self)
+            .build();
+        // The pane uses actions of the window it is in
+        let actions = self.detail_window_actions(&window, &toast_overlay, &detail_pane, db_name);
+        window.insert_action_group("win", Some(&actions));
+        window.present();
+        self.add_env_window(window.upcast_ref());
+    }
+
+    /// Returns the `win` actions used by `detail_pane` in `window`, acting on
+    /// the item it shows, of the database called `db_name`, rather than on
+    /// the selected one.
+    fn detail_window_actions(
+        &self,
+        window: &adw::Window,
+        toast_overlay: &adw::ToastOverlay,
+        detail_pane: &DetailPane,
+        db_name: Option<String>,
+    ) -> gio::SimpleActionGroup {
+        let add_error_toast = Rc::new(
+            clone!(@weak window, @weak toast_overlay => move |message: &str, err: &anyhow::Error| {
+                toast_overlay.add_toast(error_details::error_toast(&window, message, err));
+            }),
+        );
+
+        let save_value_action = gio::SimpleAction::new("save-value", None);
+        save_value_action.connect_activate(
+            clone!(@weak window, @weak detail_pane, @strong add_error_toast => move |_, _| {
+                let Some(item) = detail_pane.item() else {
+                    return;
+                };
+                glib::spawn_future_local(clone!(@weak window, @strong add_error_toast =>
+                async move {
+                    if let Err(err) = save_item(&window, &item, false).await {
+                        if !err
+                            .downcast_ref::<glib::Error>()
+                            .is_some_and(|error| error.matches(gtk::DialogError::Dismissed))
+                        {
+                            tracing::error!("Failed to save value: {:?}", &err);
+                            add_error_toast(&gettext("Failed to save value"), &err);
+                        }
+                    }
+                }));
+            }),
+        );
+
+        let edit_externally_action = gio::SimpleAction::new("edit-externally", None);
+        edit_externally_action.connect_activate(
+            clone!(@weak self as obj, @weak detail_pane, @strong db_name,
+            @strong add_error_toast => move |_, _| {
+                let Some(item) = detail_pane.item() else {
+                    return;
+                };
+                glib::spawn_future_local(
+                    clone!(@weak obj, @strong db_name, @strong add_error_toast => async move {
+                        let result = match obj.shown_db(db_name.as_deref()) {
+                            Ok(db) => obj.edit_item_externally(db, &item).await,
+                            Err(err) => Err(err),
+                        };
+                        if let Err(err) = result {
+                            tracing::error!("Failed to edit value externally: {:?}", &err);
+                            add_error_toast(&gettext("Failed to open value in editor"), &err);
+                        }
+                    }),
+                );
+            }),
+        );
+
+        let set_value_action =
+            gio::SimpleAction::new("set-value", Some(glib::VariantTy::BYTE_STRING));
+        set_value_action.connect_activate(
+            clone!(@weak self as obj, @weak detail_pane, @strong db_name,
+            @strong add_error_toast => move |_, value| {
+                let Some(item) = detail_pane.item() else {
+                    return;
+                };
+                let value = value.unwrap().get::<Vec<u8>>().unwrap();
+                let key = item.key();
+                let result = obj.shown_db(db_name.as_deref()).and_then(|db| {
+                    obj.put_value(&db, &key, &item.data(), value.clone())?;
+                    Ok(db)
+                });
+                match result {
+                    // Items are replaced on reload, so the pane is given the
+                    // one now shown for the key, with the new value as a
+                    // DUPSORT key may have others
+                    Ok(db) => {
+                        let new_item = db
+                            .iter::<DatabaseItem>()
+                            .filter_map(Result::ok)
+                            .find(|item| item.key() == key && item.data() == value);
+                        if new_item.is_some() {
+                            detail_pane.set_item(new_item);
+                        }
+                    }
+                    Err(err) => {
+                        tracing::error!("Failed to set value: {:?}", &err);
+                        add_error_toast(&gettext("Failed to set value"), &err);
+                    }
+                }
+            }),
+        );
+
+        for action in [&edit_externally_action, &set_value_action] {
+            self.bind_property("write-mode", action, "enabled")
+                .sync_create()
+                .build();
+        }
+
+        let actions = gio::SimpleActionGroup::new();
+        actions.add_action(&gio::Settings::new(APP_ID).create_action("detail-encoding"));
+        actions.add_action(&save_value_action);
+        actions.add_action(&edit_externally_action);
+        actions.add_action(&set_value_action);
+        actions
+    }
+
+    /// Returns the database called `db_name` if it is the selected one, as
+    /// the others aren't kept loaded.
+    fn shown_db(&self, db_name: Option<&str>) -> Result<Database> {
+        self.selected_db()
+            .filter(|db| db.name() == db_name)
+            .with_context(|| {
+                format!(
+                    "Database `{}` is no longer shown",
+                    db_name.unwrap_or_default()
+                )
+            })
+    }
+
+    /// Copies the selected rows as tab-separated keys and values.
+    fn copy_selected(&self) {
+        let text = self
+            .selected_items()
+            .iter()
+            .map(|item| {
+                let key = self.key_text(Some(item.clone().upcast()));
+                let value = self
+                    .non_printable_display()
+                    .render(item.data().as_ref(), self.value_encoding());
+                format!("{}\t{}", key, value)
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        self.clipboard().set_text(&text);
+    }
+
+    /// Asks whether to export all, the filtered, or the selected entries,
+    /// then saves them as a JSON array of key-value objects.
+    async fn export(&self) -> Result<()> {
+        let imp = self.imp();
+        let db = self.selected_db().context("No selected db")?;
+
+        let n_selected = imp.column_view_model.selection().size() as u32;
+        let is_filtered = self.is_filtered();
+
+        let all_button = gtk::CheckButton::with_label(&ngettext_f(
+            "All {n} Entry",
+            "All {n} Entries",
+            db.n_entries(),
+            &[("n", &db.n_entries().to_string())],
+        ));
+        let filtered_button = gtk::CheckButton::builder()
+            .label(ngettext_f(
+                "{n} Filtered Entry",
+                "{n} Filtered Entries",
+                imp.filter_model.n_items(),
+                &[("n", &imp.filter_model.n_items().to_string())],
+            ))
+            .group(&all_button)
+            .sensitive(is_filtered)
+            .build();
+        let selected_button = gtk::CheckButton::builder()
+            .label(ngettext_f(
+                "{n} Selected Entry",
+                "{n} Selected Entries",
+                n_selected,
+                &[("n", &n_selected.to_string())],
+            ))
+            .group(&all_button)
+            .sensitive(n_selected > 0)
+            .build();
+        let all_dbs_button = gtk::CheckButton::builder()
+            .label(gettext("All Databases"))
+            .group(&all_button)
+            .build();
+        if n_selected > 0 {
+            selected_button.set_active(true);
+        } else if is_filtered {
+            filtered_button.set_active(true);
+        } else {
+            all_button.set_active(true);
+        }
+
+        let separate_files_button = gtk::CheckButton::builder()
+            .label(gettext("One File per Database"))
+            .active(true)
+            .build();
+        let single_file_button = gtk::CheckButton::builder()
+            .label(gettext("Single File With a Database Field"))
+            .group(&separate_files_button)
+            .build();
+        let layout_box = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(6)
+            .margin_start(24)
+            .build();
+        layout_box.append(&separate_files_button);
+        layout_box.append(&single_file_button);
+        all_dbs_button
+            .bind_property("active", &layout_box, "sensitive")
+            .sync_create()
+            .build();
+
+        let scope_box = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(6)
+            .build();
+        scope_box.append(&all_button);
+        scope_box.append(&filtered_button);
+        scope_box.append(&selected_button);
+        scope_box.append(&all_dbs_button);
+        scope_box.append(&layout_box);
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("Export Entries"))
+            .body(gettext(
+                "Entries are saved as a JSON array of their keys and values, as displayed.",
+            ))
+            .transient_for(self)
+            .modal(true)
+            .extra_child(&scope_box)
+            .default_response("export")
+            .close_response("cancel")
+            .build();
+        dialog.add_responses(&[
+            ("cancel", &gettext("_Cancel")),
+            ("export", &gettext("_Export")),
+        ]);
+        dialog.set_response_appearance("export", adw::ResponseAppearance::Suggested);
+
+        if dialog.choose_future().await != "export" {
+            return Ok(());
+        }
+
+        if all_dbs_button.is_active() {
+            return self.export_all_dbs(single_file_button.is_active()).await;
+        }
+
+        let file_dialog = gtk::FileDialog::builder()
+            .title(gettext("Export Entries"))
+            .initial_name("export.json")
+            .modal(true)
+            .build();
+
+        let file = file_dialog.save_future(Some(self)).await?;
+        let stream = file
+            .replace_future(
+                None,
+                false,
+                gio::FileCreateFlags::NONE,
+                glib::Priority::default(),
+            )
+            .await?;
+
+        let mut writer = ExportWriter::new(
+            ExportFormat::Json,
+            db.name(),
+            db.flags(),
+            io::BufWriter::new(stream.into_write()),
+        )?;
+        let mut n_exported = 0;
+        let mut write_item = |item: &DatabaseItem| -> Result<()> {
+            let key = item.key();
+            let value = item.data();
+            writer.write_entry(&ExportEntry {
+                key: &key,
+                value: &value,
+                key_text: self.key_text(Some(item.clone().upcast())),
+                value_text: self
+                    .non_printable_display()
+                    .render(&value, self.value_encoding()),
+            })?;
+            n_exported += 1;
+            Ok(())
+        };
+
+        if selected_button.is_active() {
+            for item in self.selected_items() {
+                write_item(&item)?;
+            }
+        } else {
+            // Match entries against the search filter as they are written, rather
+            // than collecting the filtered rows first
+            let filter = imp
+                .filter_model
+                .filter()
+                .filter(|_| filtered_button.is_active());
+            for position in 0..db.n_items() {
+                let item = db.item(position).and_downcast::<DatabaseItem>().unwrap();
+                if item.change() != DiffKind::Removed
+                    && filter.as_ref().is_none_or(|filter| filter.match_(&item))
+                {
+                    write_item(&item)?;
+                }
+            }
+        }
+        writer.finish()?;
+
+        self.add_message_toast(&ngettext_f(
+            "Exported {n} entry",
+            "Exported {n} entries",
+            n_exported,
+            &[("n", &n_exported.to_string())],
+        ));
+
+        Ok(())
+    }
+
+    /// Asks for a file if `is_combined`, or else a folder for a file per
+    /// database, then writes the entries of all databases there as JSON in
+    /// the background.
+    async fn export_all_dbs(&self, is_combined: bool) -> Result<()> {
+        let snapshot = self.snapshot()?;
+
+        // The unnamed database only holds the others if there are any
+        let (mut db_names, non_utf8_names) = snapshot.names()?;
+        if db_names.len() > 1 {
+            db_names.retain(Option::is_some);
+        }
+        let n_skipped = non_utf8_names.len();
+
+        let destination = if is_combined {
+            let file_dialog = gtk::FileDialog::builder()
+                .title(gettext("Export All Databases"))
+                .initial_name("export.json")
+                .modal(true)
+                .build();
+            let file = file_dialog.save_future(Some(self)).await?;
+            ExportDestination::Combined(file.path().context("File must have a path")?)
+        } else {
+            let file_dialog = gtk::FileDialog::builder()
+                .title(gettext("Export All Databases"))
+                .modal(true)
+                .build();
+            let folder = file_dialog.select_folder_future(Some(self)).await?;
+            ExportDestination::Directory(folder.path().context("Folder must have a path")?)
+        };
+
+        let options = ExportOptions {
+            format: ExportFormat::Json,
+            non_printable_display: self.non_printable_display(),
+            key_encoding: self.key_encoding(),
+            value_encoding: self.value_encoding(),
+        };
+        let n_total = Arc::new(AtomicUsize::new(0));
+        let n_written = Arc::new(AtomicUsize::new(0));
+        let is_cancelled = Arc::new(AtomicBool::new(false));
+        let progress_dialog = self.present_progress_dialog(
+            &gettext("Exporting All Databases…"),
+            clone!(@strong n_total, @strong n_written => move || {
+                let n_total = n_total.load(atomic::Ordering::Relaxed);
+                let n_written = n_written.load(atomic::Ordering::Relaxed);
+                (n_total > 0).then(|| (n_written as f64 / n_total as f64).min(1.0))
+            }),
+        );
+        progress_dialog.add_response("cancel", &gettext("_Cancel"));
+        progress_dialog.set_close_response("cancel");
+        progress_dialog.connect_response(
+            None,
+            clone!(@strong is_cancelled => move |_, _| {
+                is_cancelled.store(true, atomic::Ordering::Relaxed);
+            }),
+        );
+        let result = gio::spawn_blocking(
+            clone!(@strong n_total, @strong n_written, @strong is_cancelled => move || {
+                export::export_dbs(
+                    &snapshot,
+                    &db_names,
+                    &destination,
+                    options,
+                    &n_total,
+                    &n_written,
+                    &is_cancelled,
+                )
+            }),
+        )
+        .await
+        .map_err(|_| anyhow!("export thread panicked"))?;
+        progress_dialog.close();
+        result?;
+
+        let n_written = n_written.load(atomic::Ordering::Relaxed);
+        self.add_message_toast(&if is_cancelled.load(atomic::Ordering::Relaxed) {
+            ngettext_f(
+                "Export cancelled after {n} entry",
+                "Export cancelled after {n} entries",
+                n_written as u32,
+                &[("n", &n_written.to_string())],
+            )
+        } else {
+            ngettext_f(
+                "Exported {n} entry",
+                "Exported {n} entries",
+                n_written as u32,
+                &[("n", &n_written.to_string())],
+            )
+        });
+        if n_skipped > 0 {
+            self.add_message_toast(&ngettext_f(
+                "Skipped {n} database whose name isn't valid UTF-8",
+                "Skipped {n} databases whose names aren't valid UTF-8",
+                n_skipped as u32,
+                &[("n", &n_skipped.to_string())],
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Asks for a file and saves the report of the shown rows to it as a PDF.
+    async fn export_pdf(&self) -> Result<()> {
+        let dialog = gtk::FileDialog::builder()
+            .title(gettext("Export as PDF"))
+            .initial_name("report.pdf")
+            .modal(true)
+            .build();
+        let file = dialog.save_future(Some(self)).await?;
+        let path = file.path().context("File has no path")?;
+
+        self.print_report(Some(&path))
+    }
+
+    /// Prints a report of the shown rows of the selected database, or saves
+    /// it as a PDF at `export_path` without asking.
+    fn print_report(&self, export_path: Option<&Path>) -> Result<()> {
+        let operation = self.report()?.print_operation();
+
+        let action = if let Some(export_path) = export_path {
+            operation.set_export_filename(export_path);
+            gtk::PrintOperationAction::Export
+        } else {
+            gtk::PrintOperationAction::PrintDialog
+        };
+        operation.run(action, Some(self))?;
+
+        Ok(())
+    }
+
+    /// Collects the shown rows of the selected database, in their order, with
+    /// where they are from and how they are filtered.
+    fn report(&self) -> Result<Report> {
+        let imp = self.imp();
+
+        let env = imp.env.borrow().clone().context("No env set")?;
+        let db = self.selected_db().context("No selected db")?;
+
+        let n_rows = imp.sort_model.n_items();
+        let rows = (0..n_rows.min(MAX_N_PRINTED_ROWS))
+            .filter_map(|position| imp.sort_model.item(position))
+            .map(|item| {
+                let database_item = item.downcast_ref::<DatabaseItem>().unwrap();
+                ReportRow {
+                    key: self.key_text(Some(item.clone())),
+                    value: self.value_text(Some(item.clone())),
+                    key_size: database_item.key().len(),
+                    value_size: database_item.data().len(),
+                }
+            })
+            .collect();
+
+        let db_name = db
+            .name()
+            .map_or_else(|| gettext("Unnamed Database"), |name| name.to_string());
+        let mut metadata = vec![
+            (gettext("Environment"), env.path().display().to_string()),
+            (gettext("Database"), db_name.clone()),
+            (gettext("Transaction"), db.txn_id().to_string()),
+            (
+                gettext("Entries"),
+                if self.is_filtered() {
+                    ngettext_f(
+                        "{n_rows} of {n_entries} entry match the filter",
+                        "{n_rows} of {n_entries} entries match the filter",
+                        db.n_entries(),
+                        &[
+                            ("n_rows", &n_rows.to_string()),
+                            ("n_entries", &db.n_entries().to_string()),
+                        ],
+                    )
+                } else {
+                    db.n_entries().to_string()
+                },
+            ),
+        ];
+        let search_text = imp.search_entry.text();
+        if !search_text.is_empty() {
+            metadata.push((gettext("Search"), search_text.to_string()));
+        }
+        if imp.query.borrow().is_some() {
+            metadata.push((gettext("Query"), imp.query_entry.text().to_string()));
+        }
+        if n_rows > MAX_N_PRINTED_ROWS {
+            metadata.push((
+                gettext("Printed"),
+                gettext_f(
+                    "The first {n} rows",
+                    &[("n", &MAX_N_PRINTED_ROWS.to_string())],
+                ),
+            ));
+        }
+        let now = glib::DateTime::now_local()?;
+        metadata.push((gettext("Date"), now.format("%c")?.to_string()));
+
+        Ok(Report {
+            title: db_name,
+            metadata,
+            rows,
+        })
+    }
+
+    /// Asks whether to export the selected or all databases, then writes their
+    /// entries to a SQLite file in the background.
+    async fn export_sqlite(&self) -> Result<()> {
+        let db = self.selected_db().context("No selected db")?;
+        let snapshot = db.snapshot()?;
+
+        let selected_button = gtk::CheckButton::builder()
+            .label(gettext("Selected Database"))
+            .active(true)
+            .build();
+        let all_button = gtk::CheckButton::builder()
+            .label(gettext("All Databases"))
+            .group(&selected_button)
+            .build();
+        let scope_box = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(6)
+            .build();
+        scope_box.append(&selected_button);
+        scope_box.append(&all_button);
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("Export to SQLite"))
+            .body(gettext(
+                "Entries are saved in an “entries” table with db, key, and value columns.",
+            ))
+            .transient_for(self)
+            .modal(true)
+            .extra_child(&scope_box)
+            .default_response("export")
+            .close_response("cancel")
+            .build();
+        dialog.add_responses(&[
+            ("cancel", &gettext("_Cancel")),
+            ("export", &gettext("_Export")),
+        ]);
+        dialog.set_response_appearance("export", adw::ResponseAppearance::Suggested);
+
+        if dialog.choose_future().await != "export" {
+            return Ok(());
+        }
+
+        let file_dialog = gtk::FileDialog::builder()
+            .title(gettext("Export to SQLite"))
+            .initial_name("export.sqlite")
+            .modal(true)
+            .build();
+        let file = file_dialog.save_future(Some(self)).await?;
+        let path = file.path().context("File must have a path")?;
+
+        let db_name = (!all_button.is_active()).then(|| db.name().map(str::to_owned));
+        let n_total = Arc::new(AtomicUsize::new(0));
+        let n_written = Arc::new(AtomicUsize::new(0));
+        let progress_dialog = self.present_progress_dialog(
+            &gettext("Exporting to SQLite…"),
+            clone!(@strong n_total, @strong n_written => move || {
+                let n_total = n_total.load(atomic::Ordering::Relaxed);
+                let n_written = n_written.load(atomic::Ordering::Relaxed);
+                (n_total > 0).then(|| (n_written as f64 / n_total as f64).min(1.0))
+            }),
+        );
+        let result = gio::spawn_blocking(clone!(@strong n_written => move || {
+            let db_names = match db_name {
+                Some(db_name) => vec![db_name],
+                None => snapshot.names()?.0,
+            };
+            sqlite_export::export(&snapshot, &db_names, &path, &n_total, &n_written)
+        }))
+        .await
+        .map_err(|_| anyhow!("SQLite export thread panicked"))?;
+        progress_dialog.close();
+        result?;
+
+        let n_written = n_written.load(atomic::Ordering::Relaxed);
+        self.add_message_toast(&ngettext_f(
+            "Exported {n} entry",
+            "Exported {n} entries",
+            n_written as u32,
+            &[("n", &n_written.to_string())],
+        ));
+
+        Ok(())
+    }
+
+    /// Writes the entries of the selected database to a Parquet file in the
+    /// background, optionally with a column of the values decoded as JSON.
+    async fn export_parquet(&self) -> Result<()> {
+        let db = self.selected_db().context("No selected db")?;
+        let snapshot = db.snapshot()?;
+
+        let decoded_check_button = gtk::CheckButton::builder()
+            .label(gettext("Include Values Decoded as JSON"))
+            .build();
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("Export to Parquet"))
+            .body(gettext(
+                "Entries are saved with key, value, key_len, and value_len columns.",
+            ))
+            .transient_for(self)
+            .modal(true)
+            .extra_child(&decoded_check_button)
+            .default_response("export")
+            .close_response("cancel")
+            .build();
+        dialog.add_responses(&[
+            ("cancel", &gettext("_Cancel")),
+            ("export", &gettext("_Export")),
+        ]);
+        dialog.set_response_appearance("export", adw::ResponseAppearance::Suggested);
+
+        if dialog.choose_future().await != "export" {
+            return Ok(());
+        }
+
+        let file_dialog = gtk::FileDialog::builder()
+            .title(gettext("Export to Parquet"))
+            .initial_name("export.parquet")
+            .modal(true)
+            .build();
+        let file = file_dialog.save_future(Some(self)).await?;
+        let path = file.path().context("File must have a path")?;
+
+        let db_name = db.name().map(str::to_owned);
+        let include_decoded = decoded_check_button.is_active();
+        let n_entries = db.n_entries();
+        let n_written = Arc::new(AtomicUsize::new(0));
+        let progress_dialog = self.present_progress_dialog(
+            &gettext("Exporting to Parquet…"),
+            clone!(@strong n_written => move || {
+                let n_written = n_written.load(atomic::Ordering::Relaxed);
+                (n_entries > 0).then(|| (n_written as f64 / n_entries as f64).min(1.0))
+            }),
+        );
+        let result = gio::spawn_blocking(clone!(@strong n_written => move || {
+            parquet_export::export(
+                &snapshot,
+                db_name.as_deref(),
+                &path,
+                include_decoded,
+                &n_written,
+            )
+        }))
+        .await
+        .map_err(|_| anyhow!("Parquet export thread panicked"))?;
+        progress_dialog.close();
+        result?;
+
+        let n_written = n_written.load(atomic::Ordering::Relaxed);
+        self.add_message_toast(&ngettext_f(
+            "Exported {n} entry",
+            "Exported {n} entries",
+            n_written as u32,
+            &[("n", &n_written.to_string())],
+        ));
+
+        Ok(())
+    }
+
+    /// Deletes the selected rows, asking first if `confirm-delete` is set.
+    async fn delete_selected(&self) -> Result<()> {
+        let items = self.selected_items();
+        let db = self.selected_db().context("No selected db")?;
+
+        let settings = gio::Settings::new(APP_ID);
+        if settings.boolean("confirm-delete") {
+            let n_items = items.len() as u32;
+            let dialog = adw::MessageDialog::builder()
+                .heading(ngettext_f(
+                    "Delete {n} Entry?",
+                    "Delete {n} Entries?",
+                    n_items,
+                    &[("n", &n_items.to_string())],
+                ))
+                .body(gettext("Deleted entries can't be recovered."))
+                .transient_for(self)
+                .modal(true)
+                .default_response("cancel")
+                .close_response("cancel")
+                .build();
+            dialog.add_responses(&[
+                ("cancel", &gettext("_Cancel")),
+                ("delete", &gettext("_Delete")),
+            ]);
+            dialog.set_response_appearance("delete", adw::ResponseAppearance::Destructive);
+
+            if dialog.choose_future().await != "delete" {
+                return Ok(());
+            }
+        }
+
+        if self.stage_changes() {
+            let is_dup_sort = db.flags().contains(DatabaseFlags::DUP_SORT);
+            self.stage(
+                &db,
+                items.iter().map(|item| PendingChange::Delete {
+                    key: item.key().to_vec(),
+                    duplicate: is_dup_sort.then(|| item.data().to_vec()),
+                }),
+            );
+            return Ok(());
+        }
+
+        let edit = db.delete(&items)?;
+        self.record_edit(edit);
+
+        Ok(())
+    }
+
+    /// Asks for a name and flags, then creates an empty database and selects
+    /// it.
+    async fn create_database(&self) -> Result<()> {
+        let env = self.imp().env.borrow().clone().context("No env set")?;
+
+        let name_entry = gtk::Entry::builder()
+            .placeholder_text(gettext("Name"))
+            .activates_default(true)
+            .build();
+        let flag_check_buttons = [
+            (gettext("Sorted Duplicate Keys"), DatabaseFlags::DUP_SORT),
+            (gettext("Integer Keys"), DatabaseFlags::INTEGER_KEY),
+            (gettext("Reverse Keys"), DatabaseFlags::REVERSE_KEY),
+        ]
+        .map(|(label, flag)| (gtk::CheckButton::with_label(&label), flag));
+
+        let vbox = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(6)
+            .build();
+        vbox.append(&name_entry);
+        for (check_button, _) in &flag_check_buttons {
+            vbox.append(check_button);
+        }
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("New Database"))
+            .transient_for(self)
+            .modal(true)
+            .extra_child(&vbox)
+            .default_response("create")
+            .close_response("cancel")
+            .build();
+        dialog.add_responses(&[
+            ("cancel", &gettext("_Cancel")),
+            ("create", &gettext("C_reate")),
+        ]);
+        dialog.set_response_appearance("create", adw::ResponseAppearance::Suggested);
+        dialog.set_response_enabled("create", false);
+        name_entry.connect_changed(clone!(@weak dialog => move |entry| {
+            dialog.set_response_enabled("create", !entry.text().is_empty());
+        }));
+
+        if dialog.choose_future().await != "create" {
+            return Ok(());
+        }
+
+        let name = name_entry.text();
+        let flags = flag_check_buttons
+            .iter()
+            .filter(|(check_button, _)| check_button.is_active())
+            .fold(DatabaseFlags::empty(), |flags, (_, flag)| flags | *flag);
+        database::create(&env, &name, flags)?;
+
+        self.reload_env();
+        self.select_database(&name);
+
+        Ok(())
+    }
+
+    /// Asks for a name, then copies the selected database to a new one with
+    /// that name in a single write txn in the background, which can be
+    /// cancelled.
+    async fn duplicate_database(&self) -> Result<()> {
+        let env = self.imp().env.borrow().clone().context("No env set")?;
+        let db = self.selected_db().context("No selected db")?;
+        let db_name = db
+            .name()
+            .context("Unnamed db can't be duplicated")?
+            .to_string();
+
+        let db_names = database::names(&env)?;
+        let name_entry = gtk::Entry::builder()
+            .placeholder_text(gettext("Name"))
+            .text(format!("{}-copy", db_name))
+            .activates_default(true)
+            .build();
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext_f("Duplicate “{name}”", &[("name", &db_name)]))
+            .body(gettext(
+                "All entries are copied to a new database with the same flags.",
+            ))
+            .transient_for(self)
+            .modal(true)
+            .extra_child(&name_entry)
+            .default_response("duplicate")
+            .close_response("cancel")
+            .build();
+        dialog.add_responses(&[
+            ("cancel", &gettext("_Cancel")),
+            ("duplicate", &gettext("_Duplicate")),
+        ]);
+        dialog.set_response_appearance("duplicate", adw::ResponseAppearance::Suggested);
+        let update_response = clone!(@weak dialog => move |entry: &gtk::Entry| {
+            let name = entry.text();
+            let is_taken = db_names.iter().flatten().any(|db_name| *db_name == name);
+            dialog.set_response_enabled("duplicate", !name.is_empty() && !is_taken);
+        });
+        update_response(&name_entry);
+        name_entry.connect_changed(update_response);
+
+        if dialog.choose_future().await != "duplicate" {
+            return Ok(());
+        }
+
+        let new_name = name_entry.text().to_string();
+        let n_entries = db.n_entries();
+        let n_copied = Arc::new(AtomicUsize::new(0));
+        let is_cancelled = Arc::new(AtomicBool::new(false));
+        let progress_dialog = self.present_progress_dialog(
+            &gettext("Duplicating Database…"),
+            clone!(@strong n_copied => move || {
+                let n_copied = n_copied.load(atomic::Ordering::Relaxed);
+                (n_entries > 0).then(|| (n_copied as f64 / n_entries as f64).min(1.0))
+            }),
+        );
+        progress_dialog.add_response("cancel", &gettext("_Cancel"));
+        progress_dialog.set_close_response("cancel");
+        progress_dialog.connect_response(
+            None,
+            clone!(@strong is_cancelled => move |_, _| {
+                is_cancelled.store(true, atomic::Ordering::Relaxed);
+            }),
+        );
+        let result = gio::spawn_blocking(
+            clone!(@strong new_name, @strong n_copied, @strong is_cancelled => move || {
+                database::duplicate(&env, &db_name, &new_name, &n_copied, &is_cancelled)
+            }),
+        )
+        .await
+        .map_err(|_| anyhow!("duplicate thread panicked"))?;
+        progress_dialog.close();
+
+        if !result? {
+            self.add_message_toast(&gettext("Duplicate cancelled"));
+            return Ok(());
+        }
+
+        self.reload_env();
+        self.select_database(&new_name);
+
+        let n_copied = n_copied.load(atomic::Ordering::Relaxed);
+        self.add_message_toast(&ngettext_f(
+            "Copied {n} entry to “{name}”",
+            "Copied {n} entries to “{name}”",
+            n_copied as u32,
+            &[("n", &n_copied.to_string()), ("name", &new_name)],
+        ));
+
+        Ok(())
+    }
+
+    /// Deletes all entries of the selected database after confirmation.
+    async fn clear_database(&self) -> Result<()> {
+        let db = self.selected_db().context("No selected db")?;
+        let db_name = db.name().unwrap_or_default();
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext_f("Clear “{name}”?", &[("name", db_name)]))
+            .body(gettext(
+                "All entries of the database will be deleted and can't be recovered.",
+            ))
+            .transient_for(self)
+            .modal(true)
+            .default_response("cancel")
+            .close_response("cancel")
+            .build();
+        dialog.add_responses(&[
+            ("cancel", &gettext("_Cancel")),
+            ("clear", &gettext("C_lear")),
+        ]);
+        dialog.set_response_appearance("clear", adw::ResponseAppearance::Destructive);
+
+        if dialog.choose_future().await != "clear" {
+            return Ok(());
+        }
+
+        if self.stage_changes() {
+            let keys = db.keys_in_range(&database::KeyRange::Prefix(Vec::new()));
+            self.stage(
+                &db,
+                keys.iter().map(|key| PendingChange::Delete {
+                    key: key.to_vec(),
+                    duplicate: None,
+                }),
+            );
+            return Ok(());
+        }
+
+        let edit = db.clear()?;
+        self.record_edit(edit);
+
+        Ok(())
+    }
+
+    /// Asks for a key prefix or range, previewing how many entries it matches,
+    /// then deletes them all in a single write txn. Progress is shown for
+    /// large ranges.
+    async fn delete_range(&self) -> Result<()> {
+        let db = self.selected_db().context("No selected db")?;
+
+        let mode_drop_down = gtk::DropDown::from_strings(&[
+            &gettext("Keys Starting With"),
+            &gettext("Keys Between"),
+        ]);
+        let prefix_entry = gtk::Entry::builder()
+            .placeholder_text(gettext("Prefix"))
+            .activates_default(true)
+            .build();
+        let start_entry = gtk::Entry::builder()
+            .placeholder_text(gettext("First Key"))
+            .activates_default(true)
+            .build();
+        let end_entry = gtk::Entry::builder()
+            .placeholder_text(gettext("Last Key"))
+            .activates_default(true)
+            .build();
+        let preview_label = gtk::Label::builder()
+            .wrap(true)
+            .css_classes(["dim-label"])
+            .build();
+
+        let range_box = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(6)
+            .build();
+        range_box.append(&start_entry);
+        range_box.append(&end_entry);
+        mode_drop_down
+            .bind_property("selected", &prefix_entry, "visible")
+            .transform_to(|_, selected: u32| Some(selected == 0))
+            .sync_create()
+            .build();
+        mode_drop_down
+            .bind_property("selected", &range_box, "visible")
+            .transform_to(|_, selected: u32| Some(selected == 1))
+            .sync_create()
+            .build();
+
+        let vbox = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(12)
+            .build();
+        vbox.append(&mode_drop_down);
+        vbox.append(&prefix_entry);
+        vbox.append(&range_box);
+        vbox.append(&preview_label);
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("Delete Range"))
+            .body(gettext("Deleted entries can't be recovered."))
+            .transient_for(self)
+            .modal(true)
+            .extra_child(&vbox)
+            .default_response("delete")
+            .close_response("cancel")
+            .build();
+        dialog.add_responses(&[
+            ("cancel", &gettext("_Cancel")),
+            ("delete", &gettext("_Delete")),
+        ]);
+        dialog.set_response_appearance("delete", adw::ResponseAppearance::Destructive);
+
+        let key_range = {
+            let widgets = (
+                mode_drop_down.downgrade(),
+                prefix_entry.downgrade(),
+                start_entry.downgrade(),
+                end_entry.downgrade(),
+            );
+            move || {
+                let (mode_drop_down, prefix_entry, start_entry, end_entry) = &widgets;
+                if mode_drop_down.upgrade()?.selected() == 0 {
+                    Some(database::KeyRange::Prefix(
+                        prefix_entry.upgrade()?.text().as_bytes().to_vec(),
+                    ))
+                } else {
+                    Some(database::KeyRange::Between {
+                        start: start_entry.upgrade()?.text().as_bytes().to_vec(),
+                        end: end_entry.upgrade()?.text().as_bytes().to_vec(),
+                    })
+                }
+            }
+        };
+        let update_preview = clone!(@weak dialog, @weak preview_label, @strong db,
+        @strong key_range => move || {
+            let n_keys = key_range()
+                .map_or(0, |key_range| db.keys_in_range(&key_range).len() as u32);
+            preview_label.set_label(&ngettext_f(
+                "{n} entry will be deleted",
+                "{n} entries will be deleted",
+                n_keys,
+                &[("n", &n_keys.to_string())],
+            ));
+            dialog.set_response_enabled("delete", n_keys > 0);
+        });
+        update_preview();
+        for entry in [&prefix_entry, &start_entry, &end_entry] {
+            entry.connect_changed(clone!(@strong update_preview => move |_| update_preview()));
+        }
+        mode_drop_down.connect_selected_notify(move |_| update_preview());
+
+        if dialog.choose_future().await != "delete" {
+            return Ok(());
+        }
+
+        let keys = db.keys_in_range(&key_range().context("Dialog widgets were dropped")?);
+
+        if self.stage_changes() {
+            self.stage(
+                &db,
+                keys.iter().map(|key| PendingChange::Delete {
+                    key: key.to_vec(),
+                    duplicate: None,
+                }),
+            );
+            return Ok(());
+        }
+
+        let n_keys = keys.len() as u32;
+        let n_deleted = Arc::new(AtomicUsize::new(0));
+
+        let progress_dialog = (n_keys > DELETE_PROGRESS_THRESHOLD).then(|| {
+            self.present_progress_dialog(
+                &gettext("Deleting Entries…"),
+                clone!(@strong n_deleted => move || {
+                    let n_deleted = n_deleted.load(atomic::Ordering::Relaxed);
+                    Some(n_deleted as f64 / n_keys as f64)
+                }),
+            )
+        });
+
+        let result = db.delete_in_background(keys, n_deleted).await;
+
+        if let Some(progress_dialog) = progress_dialog {
+            progress_dialog.close();
+        }
+        self.record_edit(result?);
+
+        self.add_message_toast(&ngettext_f(
+            "Deleted {n} entry",
+            "Deleted {n} entries",
+            n_keys,
+            &[("n", &n_keys.to_string())],
+        ));
+
+        Ok(())
+    }
+
+    /// Asks for a CSV, JSON Lines, or JSON file, then for where its keys and
+    /// values are and how they are encoded, previewing the first rows as they
+    /// would be imported. The entries are then written to the selected database in
+    /// batches in the background.
+    async fn import(&self) -> Result<()> {
+        let db = self.selected_db().context("No selected db")?;
+
+        let file_dialog = gtk::FileDialog::builder()
+            .title(gettext("Import Entries"))
+            .modal(true)
+            .build();
+        let file = file_dialog.open_future(Some(self)).await?;
+        let path = file.path().context("File must have a path")?;
+
+        let format_drop_down = gtk::DropDown::from_strings(&[
+            &gettext("CSV"),
+            &gettext("JSON Lines"),
+            &gettext("JSON"),
+        ]);
+        format_drop_down.set_selected(match ImportFormat::for_path(&path) {
+            ImportFormat::Csv => 0,
+            ImportFormat::JsonLines => 1,
+            ImportFormat::Json => 2,
+        });
+        let header_button = gtk::CheckButton::builder()
+            .label(gettext("First Row Names the Columns"))
+            .active(true)
+            .build();
+        format_drop_down
+            .bind_property("selected", &header_button, "visible")
+            .transform_to(|_, selected: u32| Some(selected == 0))
+            .sync_create()
+            .build();
+        let key_entry = gtk::Entry::builder()
+            .text("key")
+            .hexpand(true)
+            .activates_default(true)
+            .build();
+        let value_entry = gtk::Entry::builder()
+            .text("value")
+            .hexpand(true)
+            .activates_default(true)
+            .build();
+        let encodings = [gettext("Text"), gettext("Hex"), gettext("Base64")];
+        let encodings = encodings.each_ref().map(String::as_str);
+        let key_encoding_drop_down = gtk::DropDown::from_strings(&encodings);
+        let value_encoding_drop_down = gtk::DropDown::from_strings(&encodings);
+
+        let grid = gtk::Grid::builder()
+            .row_spacing(6)
+            .column_spacing(12)
+            .build();
+        let rows = [
+            (
+                gettext("Format"),
+                format_drop_down.upcast_ref::<gtk::Widget>(),
+                None,
+            ),
+            (
+                gettext("Key Column"),
+                key_entry.upcast_ref(),
+                Some(&key_encoding_drop_down),
+            ),
+            (
+                gettext("Value Column"),
+                value_entry.upcast_ref(),
+                Some(&value_encoding_drop_down),
+            ),
+        ];
+        for (row, (title, widget, encoding_drop_down)) in rows.into_iter().enumerate() {
+            let title_label = gtk::Label::builder()
+                .label(title)
+                .xalign(1.0)
+                .css_classes(["dim-label"])
+                .build();
+            grid.attach(&title_label, 0, row as i32, 1, 1);
+            match encoding_drop_down {
+                Some(encoding_drop_down) => {
+                    grid.attach(widget, 1, row as i32, 1, 1);
+                    grid.attach(encoding_drop_down, 2, row as i32, 1, 1);
+                }
+                None => grid.attach(widget, 1, row as i32, 2, 1),
+            }
+        }
+
+        let preview_view = gtk::TextView::builder()
+            .editable(false)
+            .monospace(true)
+            .wrap_mode(gtk::WrapMode::WordChar)
+            .top_margin(6)
+            .bottom_margin(6)
+            .left_margin(6)
+            .right_margin(6)
+            .build();
+        let scrolled_window = gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .min_content_height(120)
+            .max_content_height(240)
+            .propagate_natural_height(true)
+            .css_classes(["card"])
+            .child(&preview_view)
+            .build();
+
+        let vbox = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(12)
+            .build();
+        vbox.append(&grid);
+        vbox.append(&header_button);
+        vbox.append(&scrolled_window);
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("Import Entries"))
+            .body(gettext_f(
+                "Entries of “{file}” are written to the database, replacing the values of \
+                existing keys.",
+                &[(
+                    "file",
+                    &path.file_name().unwrap_or_default().to_string_lossy(),
+                )],
+            ))
+            .transient_for(self)
+            .modal(true)
+            .extra_child(&vbox)
+            .default_response("import")
+            .close_response("cancel")
+            .build();
+        dialog.add_responses(&[
+            ("cancel", &gettext("_Cancel")),
+            ("import", &gettext("_Import")),
+        ]);
+        dialog.set_response_appearance("import", adw::ResponseAppearance::Suggested);
+
+        let options = {
+            let widgets = (
+                format_drop_down.downgrade(),
+                header_button.downgrade(),
+                key_entry.downgrade(),
+                value_entry.downgrade(),
+                key_encoding_drop_down.downgrade(),
+                value_encoding_drop_down.downgrade(),
+            );
+            move || {
+                let (format, header, key, value, key_encoding, value_encoding) = &widgets;
+                let encoding = |drop_down: &glib::WeakRef<gtk::DropDown>| {
+                    Some(match drop_down.upgrade()?.selected() {
+                        1 => FieldEncoding::Hex,
+                        2 => FieldEncoding::Base64,
+                        _ => FieldEncoding::Text,
+                    })
+                };
+                Some(ImportOptions {
+                    format: match format.upgrade()?.selected() {
+                        1 => ImportFormat::JsonLines,
+                        2 => ImportFormat::Json,
+                        _ => ImportFormat::Csv,
+                    },
+                    has_header: header.upgrade()?.is_active(),
+                    key_column: key.upgrade()?.text().to_string(),
+                    value_column: value.upgrade()?.text().to_string(),
+                    key_encoding: encoding(key_encoding)?,
+                    value_encoding: encoding(value_encoding)?,
+                })
+            }
+        };
+        let update_preview = clone!(@weak self as obj, @weak dialog, @weak preview_view,
+        @strong path, @strong options => move || {
+            let Some(options) = options() else {
+                return;
+            };
+            let (text, is_valid) = match import::preview(&path, options, IMPORT_PREVIEW_LEN) {
+                Ok(entries) if entries.is_empty() => (gettext("No rows to import"), false),
+                Ok(entries) => {
+                    let is_valid = entries.iter().all(|entry| entry.is_ok());
+                    let lines = entries
+                        .iter()
+                        .map(|entry| match entry {
+                            Ok((key, value)) => format!(
+                                "{} → {}",
+                                obj.non_printable_display().render(key, obj.key_encoding()),
+                                obj.non_printable_display().render(value, obj.value_encoding()),
+                            ),
+                            Err(err) => format!("{:#}", err),
+                        })
+                        .collect::<Vec<_>>();
+                    (lines.join("\n"), is_valid)
+                }
+                Err(err) => (format!("{:#}", err), false),
+            };
+            preview_view.buffer().set_text(&text);
+            dialog.set_response_enabled("import", is_valid);
+        });
+        update_preview();
+        for entry in [&key_entry, &value_entry] {
+            entry.connect_changed(clone!(@strong update_preview => move |_| update_preview()));
+        }
+        for drop_down in [
+            &format_drop_down,
+            &key_encoding_drop_down,
+            &value_encoding_drop_down,
+        ] {
+            drop_down.connect_selected_notify(
+                clone!(@strong update_preview => move |_| update_preview()),
+            );
+        }
+        header_button.connect_toggled(move |_| update_preview());
+
+        if dialog.choose_future().await != "import" {
+            return Ok(());
+        }
+
+        let options = options().context("Dialog widgets were dropped")?;
+
+        if self.stage_changes() {
+            let entries = gio::spawn_blocking(clone!(@strong path => move || {
+                let mut reader = import::EntryReader::open(&path, options, Arc::default())?;
+                let mut entries = Vec::new();
+                while let Some(entry) = reader.next_entry()? {
+                    entries.push(entry);
+                }
+                Ok::<_, anyhow::Error>(entries)
+            }))
+            .await
+            .map_err(|_| anyhow!("import thread panicked"))??;
+            self.stage(
+                &db,
+                entries.into_iter().map(|(key, value)| PendingChange::Put {
+                    key,
+                    value,
+                    duplicate: None,
+                }),
+            );
+            return Ok(());
+        }
+
+        let file_size = fs::metadata(&path)?.len() as usize;
+        let n_read = Arc::new(AtomicUsize::new(0));
+        let progress_dialog = self.present_progress_dialog(
+            &gettext("Importing Entries…"),
+            clone!(@strong n_read => move || {
+                let n_read = n_read.load(atomic::Ordering::Relaxed);
+                (file_size > 0).then(|| (n_read as f64 / file_size as f64).min(1.0))
+            }),
+        );
+        let env = self.imp().env.borrow().clone().context("No env set")?;
+        let db_name = db.name().map(|name| name.to_string());
+        let result = gio::spawn_blocking(move || {
+            import::import(&env, db_name.as_deref(), &path, options, n_read)
+        })
+        .await
+        .map_err(|_| anyhow!("import thread panicked"));
+        progress_dialog.close();
+        let import = result??;
+
+        db.reload()?;
+        if !import.edit.is_empty() {
+            self.record_edit(import.edit);
+        }
+
+        if let Some(err) = import.error {
+            return Err(err.context(format!(
+                "Stopped after importing {} entries",
+                import.n_imported
+            )));
+        }
+
+        self.add_message_toast(&ngettext_f(
+            "Imported {n} entry",
+            "Imported {n} entries",
+            import.n_imported as u32,
+            &[("n", &import.n_imported.to_string())],
+        ));
+
+        Ok(())
+    }
+
+    /// Asks for a folder, then copies the env into it, omitting free pages if
+    /// `compact` is set, and reports the size before and after.
+    async fn copy_env(&self, compact: bool) -> Result<()> {
+        let env = self.imp().env.borrow().clone().context("No env set")?;
+
+        let dialog = gtk::FileDialog::builder()
+            .title(if compact {
+                gettext("Compact Environment To")
+            } else {
+                gettext("Copy Environment To")
+            })
+            .modal(true)
+            .build();
+        let folder = dialog.select_folder_future(Some(self)).await?;
+        let path = folder
+            .path()
+            .expect("file must have a path")
+            .join("data.mdb");
+
+        let prev_size = env.real_disk_size()?;
+
+        let progress_dialog = self.present_progress_dialog(
+            &if compact {
+                gettext("Compacting Environment…")
+            } else {
+                gettext("Copying Environment…")
+            },
+            || None,
+        );
+        let result = gio::spawn_blocking(move || {
+            let option = if compact {
+                CompactionOption::Enabled
+            } else {
+                CompactionOption::Disabled
+            };
+            let file = env
+                .copy_to_file(&path, option)
+                .with_context(|| format!("Failed to copy env to `{}`", path.display()))?;
+            Ok::<_, anyhow::Error>(file.metadata()?.len())
+        })
+        .await
+        .map_err(|_| anyhow::anyhow!("copy thread panicked"))?;
+        progress_dialog.close();
+        let new_size = result?;
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(if compact {
+                gettext("Environment Compacted")
+            } else {
+                gettext("Environment Copied")
+            })
+            .body(gettext_f(
+                "The environment took {prev_size} and its copy takes {new_size}.",
+                &[
+                    ("prev_size", &glib::format_size(prev_size)),
+                    ("new_size", &glib::format_size(new_size)),
+                ],
+            ))
+            .transient_for(self)
+            .modal(true)
+            .default_response("ok")
+            .close_response("ok")
+            .build();
+        dialog.add_response("ok", &gettext("_OK"));
+        dialog.choose_future().await;
+
+        Ok(())
+    }
+
+    /// Asks whether to copy the filtered or selected entries of the selected
+    /// database, or whole databases, then copies them to a new env in a chosen
+    /// folder, offering to open it.
+    async fn copy_subset(&self) -> Result<()> {
+        let imp = self.imp();
+        let env = imp.env.borrow().clone().context("No env set")?;
+        let db = self.selected_db().context("No selected db")?;
+
+        let n_filtered = imp.filter_model.n_items();
+        let n_selected = imp.column_view_model.selection().size() as u32;
+
+        let filtered_button = gtk::CheckButton::builder()
+            .label(ngettext_f(
+                "{n} Filtered Entry",
+                "{n} Filtered Entries",
+                n_filtered,
+                &[("n", &n_filtered.to_string())],
+            ))
+            .active(true)
+            .build();
+        let selected_button = gtk::CheckButton::builder()
+            .label(ngettext_f(
+                "{n} Selected Entry",
+                "{n} Selected Entries",
+                n_selected,
+                &[("n", &n_selected.to_string())],
+            ))
+            .group(&filtered_button)
+            .sensitive(n_selected > 0)
+            .build();
+        let databases_button = gtk::CheckButton::builder()
+            .label(gettext("These Databases"))
+            .group(&filtered_button)
+            .build();
+
+        let databases_box = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .margin_start(24)
+            .build();
+        databases_button
+            .bind_property("active", &databases_box, "sensitive")
+            .sync_create()
+            .build();
+        let db_check_buttons = database::names(&env)?
+            .into_iter()
+            .map(|db_name| {
+                let check_button = gtk::CheckButton::builder()
+                    .label(match &db_name {
+                        Some(db_name) => db_name.clone(),
+                        None => gettext("Unnamed Database"),
+                    })
+                    .active(db_name.as_deref() == db.name())
+                    .build();
+                databases_box.append(&check_button);
+                (check_button, db_name)
+            })
+            .collect::<Vec<_>>();
+        for db_name in database::non_utf8_names(&env)? {
+            databases_box.append(
+                &gtk::CheckButton::builder()
+                    .label(db_name)
+                    .tooltip_text(gettext("The name isn't valid UTF-8, so it can't be opened"))
+                    .sensitive(false)
+                    .build(),
+            );
+        }
+
+        let scope_box = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(6)
+            .build();
+        scope_box.append(&filtered_button);
+        scope_box.append(&selected_button);
+        scope_box.append(&databases_button);
+        scope_box.append(
+            &gtk::ScrolledWindow::builder()
+                .hscrollbar_policy(gtk::PolicyType::Never)
+                .propagate_natural_height(true)
+                .max_content_height(240)
+                .child(&databases_box)
+                .build(),
+        );
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("Copy Subset to New Environment"))
+            .body(gettext(
+                "Entries are copied to a new environment, keeping their database flags.",
+            ))
+            .transient_for(self)
+            .modal(true)
+            .extra_child(&scope_box)
+            .default_response("copy")
+            .close_response("cancel")
+            .build();
+        dialog.add_responses(&[("cancel", &gettext("_Cancel")), ("copy", &gettext("_Copy"))]);
+        dialog.set_response_appearance("copy", adw::ResponseAppearance::Suggested);
+
+        if dialog.choose_future().await != "copy" {
+            return Ok(());
+        }
+
+        let subset = if databases_button.is_active() {
+            Subset::Databases(
+                db_check_buttons
+                    .into_iter()
+                    .filter(|(check_button, _)| check_button.is_active())
+                    .map(|(_, db_name)| db_name)
+                    .collect(),
+            )
+        } else {
+            let items = if selected_button.is_active() {
+                self.selected_items()
+            } else {
+                (0..imp.filter_model.n_items())
+                    .filter_map(|position| imp.filter_model.item(position).and_downcast())
+                    .collect::<Vec<DatabaseItem>>()
+            };
+            Subset::Entries {
+                db_name: db.name().map(str::to_owned),
+                entries: items
+                    .iter()
+                    .filter(|item| item.change() != DiffKind::Removed)
+                    .map(|item| (item.key(), item.data()))
+                    .collect(),
+            }
+        };
+
+        let file_dialog = gtk::FileDialog::builder()
+            .title(gettext("Copy Subset To"))
+            .modal(true)
+            .build();
+        let folder = file_dialog.select_folder_future(Some(self)).await?;
+        let dir = folder.path().context("Folder must have a path")?;
+
+        let n_copied = Arc::new(AtomicUsize::new(0));
+        let progress_dialog = self.present_progress_dialog(&gettext("Copying Subset…"), || None);
+        let result = gio::spawn_blocking(clone!(@strong n_copied, @strong dir => move || {
+            env_subset::copy_to_new_env(&env, subset, &dir, &n_copied)
+        }))
+        .await
+        .map_err(|_| anyhow!("copy thread panicked"))?;
+        progress_dialog.close();
+        result?;
+
+        let n_copied = n_copied.load(atomic::Ordering::Relaxed);
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("Subset Copied"))
+            .body(ngettext_f(
+                "Copied {n} entry to “{path}”.",
+                "Copied {n} entries to “{path}”.",
+                n_copied as u32,
+                &[
+                    ("n", &n_copied.to_string()),
+                    ("path", &dir.display().to_string()),
+                ],
+            ))
+            .transient_for(self)
+            .modal(true)
+            .default_response("open")
+            .close_response("close")
+            .build();
+        dialog.add_responses(&[("close", &gettext("_Close")), ("open", &gettext("_Open"))]);
+        dialog.set_response_appearance("open", adw::ResponseAppearance::Suggested);
+
+        if dialog.choose_future().await == "open" {
+            self.load_env_or_copy(&dir).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Shows the size of the env and how much of its map is used, including
+    /// the free pages that can be reused.
+    async fn show_env_info(&self) -> Result<()> {
+        let env = self.imp().env.borrow().clone().context("No env set")?;
+        let info = env.info();
+        let usage = SpaceUsage::read(&env)?;
+
+        let format_pages = |n_pages: u64| {
+            ngettext_f(
+                "{n} page ({size})",
+                "{n} pages ({size})",
+                n_pages as u32,
+                &[
+                    ("n", &n_pages.to_string()),
+                    ("size", &glib::format_size(n_pages * usage.page_size)),
+                ],
+            )
+        };
+        let rows = [
+            (gettext("Path"), env.path().display().to_string()),
+            (
+                gettext("Size on Disk"),
+                glib::format_size(env.real_disk_size()?).to_string(),
+            ),
+            (
+                gettext("Map Size"),
+                glib::format_size(info.map_size as u64).to_string(),
+            ),
+            (
+                gettext("Page Size"),
+                glib::format_size(usage.page_size).to_string(),
+            ),
+            (gettext("Allocated"), format_pages(usage.n_allocated_pages)),
+            (gettext("Used"), format_pages(usage.n_used_pages)),
+            (gettext("Free"), format_pages(usage.n_free_pages())),
+            (gettext("Freelist"), format_pages(usage.n_freelist_pages)),
+            (
+                gettext("Freelist Entries"),
+                usage.n_freelist_entries.to_string(),
+            ),
+            (
+                gettext("Readers"),
+                format!(
+                    "{}/{}",
+                    info.number_of_readers, info.maximum_number_of_readers
+                ),
+            ),
+        ];
+
+        let grid = gtk::Grid::builder()
+            .row_spacing(6)
+            .column_spacing(12)
+            .build();
+        for (row, (title, value)) in rows.iter().enumerate() {
+            let title_label = gtk::Label::builder()
+                .label(title)
+                .xalign(1.0)
+                .css_classes(["dim-label"])
+                .build();
+            let value_label = gtk::Label::builder()
+                .label(value)
+                .xalign(0.0)
+                .wrap(true)
+                .wrap_mode(gtk::pango::WrapMode::WordChar)
+                .selectable(true)
+                .build();
+            grid.attach(&title_label, 0, row as i32, 1, 1);
+            grid.attach(&value_label, 1, row as i32, 1, 1);
+        }
+
+        let utilization = usage.map_utilization();
+        let level_bar = gtk::LevelBar::builder()
+            .value(utilization)
+            .tooltip_text(gettext_f(
+                "{percent}% of the map is used",
+                &[("percent", &format!("{:.0}", utilization * 100.0))],
+            ))
+            .build();
+        level_bar.add_offset_value(gtk::LEVEL_BAR_OFFSET_LOW, 0.75);
+        level_bar.add_offset_value(gtk::LEVEL_BAR_OFFSET_HIGH, 0.9);
+        level_bar.add_offset_value(gtk::LEVEL_BAR_OFFSET_FULL, 1.0);
+
+        let vbox = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(12)
+            .build();
+        vbox.append(&level_bar);
+        vbox.append(&grid);
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("Environment Info"))
+            .transient_for(self)
+            .modal(true)
+            .extra_child(&vbox)
+            .default_response("close")
+            .close_response("close")
+            .build();
+        dialog.add_response("close", &gettext("_Close"));
+        dialog.choose_future().await;
+
+        Ok(())
+    }
+
+    /// Shows the fields of both meta pages of the env side by side, marking
+    /// the current one, which helps tell what a crash or torn write left.
+    async fn show_meta_pages(&self) -> Result<()> {
+        let env = self.imp().env.borrow().clone().context("No env set")?;
+        let meta_pages = meta_page::read_meta_pages(&utils::data_path(env.path()))?;
+        let current_index = meta_page::current_index(&meta_pages);
+
+        let row = |title: String, value: &dyn Fn(&MetaPage) -> String| {
+            (title, meta_pages.each_ref().map(value))
+        };
+        let format_root =
+            |root: Option<u64>| root.map_or_else(|| gettext("None"), |root| root.to_string());
+        let rows = [
+            row(gettext("Transaction ID"), &|meta| meta.txn_id.to_string()),
+            row(gettext("Magic Number"), &|meta| {
+                if meta.has_magic {
+                    gettext("Valid")
+                } else {
+                    gettext("Invalid")
+                }
+            }),
+            row(gettext("Version"), &|meta| meta.version.to_string()),
+            row(gettext("Map Size"), &|meta| {
+                glib::format_size(meta.map_size).to_string()
+            }),
+            row(gettext("Last Page"), &|meta| meta.last_page.to_string()),
+            row(gettext("Root Page"), &|meta| format_root(meta.main_db.root)),
+            row(gettext("Depth"), &|meta| meta.main_db.depth.to_string()),
+            row(gettext("Entries"), &|meta| {
+                meta.main_db.n_entries.to_string()
+            }),
+            row(gettext("Freelist Root Page"), &|meta| {
+                format_root(meta.free_db.root)
+            }),
+            row(gettext("Freelist Entries"), &|meta| {
+                meta.free_db.n_entries.to_string()
+            }),
+        ];
+
+        let grid = gtk::Grid::builder()
+            .row_spacing(6)
+            .column_spacing(12)
+            .build();
+        for index in 0..meta_pages.len() {
+            let title = if index == current_index {
+                gettext_f("Meta {n} (Current)", &[("n", &index.to_string())])
+            } else {
+                gettext_f("Meta {n}", &[("n", &index.to_string())])
+            };
+            let title_label = gtk::Label::builder()
+                .label(title)
+                .xalign(0.0)
+                .css_classes(["heading"])
+                .build();
+            grid.attach(&title_label, index as i32 + 1, 0, 1, 1);
+        }
+        for (row, (title, values)) in rows.iter().enumerate() {
+            let title_label = gtk::Label::builder()
+                .label(title)
+                .xalign(1.0)
+                .css_classes(["dim-label"])
+                .build();
+            grid.attach(&title_label, 0, row as i32 + 1, 1, 1);
+            for (index, value) in values.iter().enumerate() {
+                let value_label = gtk::Label::builder()
+                    .label(value)
+                    .xalign(0.0)
+                    .selectable(true)
+                    .build();
+                if values[0] != values[1] {
+                    value_label.add_css_class("accent");
+                }
+                grid.attach(&value_label, index as i32 + 1, row as i32 + 1, 1, 1);
+            }
+        }
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("Meta Pages"))
+            .body(gettext(
+                "LMDB writes the two meta pages alternately on commit. The one with the \
+                 latest transaction ID is current, and the other is the previous snapshot. \
+                 Fields that differ between them are highlighted.",
+            ))
+            .transient_for(self)
+            .modal(true)
+            .extra_child(&grid)
+            .default_response("close")
+            .close_response("close")
+            .build();
+        dialog.add_response("close", &gettext("_Close"));
+        dialog.choose_future().await;
+
+        Ok(())
+    }
+
+    /// Shows the header and the layout of the entries of a page of the data
+    /// file picked by its number, read as is from the file.
+    async fn inspect_page(&self) -> Result<()> {
+        let env = self.imp().env.borrow().clone().context("No env set")?;
+        let data_path = utils::data_path(env.path());
+        let meta_pages = meta_page::read_meta_pages(&data_path)?;
+        let page_size = meta_pages[0].page_size();
+        let n_pages = fs::metadata(&data_path)?.len() / page_size;
+
+        let spin_button = gtk::SpinButton::with_range(0.0, n_pages.saturating_sub(1) as f64, 1.0);
+        let text_view = gtk::TextView::builder()
+            .editable(false)
+            .monospace(true)
+            .top_margin(6)
+            .bottom_margin(6)
+            .left_margin(6)
+            .right_margin(6)
+            .build();
+        let scrolled_window = gtk::ScrolledWindow::builder()
+            .min_content_height(360)
+            .min_content_width(480)
+            .vexpand(true)
+            .css_classes(["card"])
+            .child(&text_view)
+            .build();
+
+        let number_box = gtk::Box::builder().spacing(12).build();
+        number_box.append(
+            &gtk::Label::builder()
+                .label(gettext("Page Number"))
+                .hexpand(true)
+                .xalign(0.0)
+                .build(),
+        );
+        number_box.append(&spin_button);
+
+        let vbox = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(12)
+            .build();
+        vbox.append(&number_box);
+        vbox.append(&scrolled_window);
+
+        let update_text = move |spin_button: &gtk::SpinButton| {
+            let number = spin_button.value() as u64;
+            let text = Page::read(&data_path, page_size, number)
+                .map(|page| describe_page(&page, number))
+                .unwrap_or_else(|err| format!("{:?}", err));
+            text_view.buffer().set_text(&text);
+        };
+        update_text(&spin_button);
+        spin_button.connect_value_changed(update_text);
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("Inspect Page"))
+            .body(gettext_f(
+                "The data file has {n_pages} pages of {page_size} bytes. Pages 0 and 1 are \
+                 the meta pages.",
+                &[
+                    ("n_pages", &n_pages.to_string()),
+                    ("page_size", &page_size.to_string()),
+                ],
+            ))
+            .transient_for(self)
+            .modal(true)
+            .resizable(true)
+            .extra_child(&vbox)
+            .default_response("close")
+            .close_response("close")
+            .build();
+        dialog.add_response("close", &gettext("_Close"));
+        dialog.choose_future().await;
+
+        Ok(())
+    }
+
+    /// Presents a dialog with a progress bar showing `fraction`, which is
+    /// polled until the dialog is closed. The bar pulses while `fraction`
+    /// returns `None`.
+    fn present_progress_dialog(
+        &self,
+        heading: &str,
+        fraction: impl Fn() -> Option<f64> + 'static,
+    ) -> adw::MessageDialog {
+        let progress_bar = gtk::ProgressBar::new();
+        let dialog = adw::MessageDialog::builder()
+            .heading(heading)
+            .transient_for(self)
+            .modal(true)
+            .extra_child(&progress_bar)
+            .build();
+        dialog.present();
+
+        glib::timeout_add_local(
+            Duration::from_millis(100),
+            clone!(@weak progress_bar => @default-return glib::ControlFlow::Break, move || {
+                match fraction() {
+                    Some(fraction) => progress_bar.set_fraction(fraction),
+                    None => progress_bar.pulse(),
+                }
+                glib::ControlFlow::Continue
+            }),
+        );
+
+        dialog
+    }
+
+    /// Adds an applied `edit` to the undo stack.
+    fn record_edit(&self, edit: Edit) {
+        self.imp().edit_history.borrow_mut().push(edit);
+        self.update_edit_history_actions();
+    }
+
+    /// Reverts the most recent edit with a compensating write txn.
+    fn undo(&self) -> Result<()> {
+        let imp = self.imp();
+
+        let env = imp.env.borrow().clone().context("No env set")?;
+        imp.edit_history.borrow_mut().undo(&env)?;
+        self.update_edit_history_actions();
+        self.reload_env();
+
+        Ok(())
+    }
+
+    /// Applies the most recently undone edit again.
+    fn redo(&self) -> Result<()> {
+        let imp = self.imp();
+
+        let env = imp.env.borrow().clone().context("No env set")?;
+        imp.edit_history.borrow_mut().redo(&env)?;
+        self.update_edit_history_actions();
+        self.reload_env();
+
+        Ok(())
+    }
+
+    fn update_edit_history_actions(&self) {
+        let edit_history = self.imp().edit_history.borrow();
+        let write_mode = self.write_mode();
+
+        self.action_set_enabled("win.undo", write_mode && edit_history.can_undo());
+        self.action_set_enabled("win.redo", write_mode && edit_history.can_redo());
+    }
+
+    /// Queues `changes` to the database `db` for the next commit.
+    fn stage(&self, db: &Database, changes: impl IntoIterator<Item = PendingChange>) {
+        let imp = self.imp();
+
+        let n_changes = {
+            let mut pending_changes = imp.pending_changes.borrow_mut();
+            let prev_len = pending_changes.len();
+            for change in changes {
+                pending_changes.push(db.name(), change);
+            }
+            (pending_changes.len() - prev_len) as u32
+        };
+        self.update_pending_changes();
+        self.save_pending_changes();
+
+        self.add_message_toast(&ngettext_f(
+            "Staged {n} change",
+            "Staged {n} changes",
+            n_changes,
+            &[("n", &n_changes.to_string())],
+        ));
+    }
+
+    /// Writes all pending changes in a single write txn, then reloads.
+    fn commit_changes(&self) -> Result<()> {
+        let imp = self.imp();
+
+        let env = imp.env.borrow().clone().context("No env set")?;
+        let n_changes = {
+            let mut pending_changes = imp.pending_changes.borrow_mut();
+            let edit = pending_changes.commit(&env)?;
+            imp.edit_history.borrow_mut().push(edit);
+            let n_changes = pending_changes.len() as u32;
+            pending_changes.clear();
+            n_changes
+        };
+        self.update_pending_changes();
+        self.save_pending_changes();
+        self.update_edit_history_actions();
+        self.reload_env();
+
+        self.add_message_toast(&ngettext_f(
+            "Committed {n} change",
+            "Committed {n} changes",
+            n_changes,
+            &[("n", &n_changes.to_string())],
+        ));
+
+        Ok(())
+    }
+
+    /// Drops all pending changes without writing them.
+    fn discard_changes(&self) {
+        self.imp().pending_changes.borrow_mut().clear();
+        self.update_pending_changes();
+        self.save_pending_changes();
+
+        self.add_message_toast(&gettext("Discarded pending changes"));
+    }
+
+    /// Saves the pending changes, so they can be restored if the app closes
+    /// before they are committed.
+    fn save_pending_changes(&self) {
+        let imp = self.imp();
+
+        let Some(env) = imp.env.borrow().clone() else {
+            return;
+        };
+        if let Err(err) = imp.pending_changes.borrow().save(env.path()) {
+            tracing::warn!("Failed to save pending changes: {:?}", &err);
+        }
+    }
+
+    /// Offers to restore the changes that were staged for `env` but not
+    /// committed before it was last closed, e.g., because the app crashed.
+    async fn offer_saved_changes(&self, env: &Env) {
+        let imp = self.imp();
+
+        // Already there if the env was only reopened
+        if !imp.pending_changes.borrow().is_empty() {
+            return;
+        }
+
+        let saved_changes = match PendingChanges::load_saved(env.path()) {
+            Ok(saved_changes) => saved_changes,
+            Err(err) => {
+                tracing::warn!("Failed to load saved pending changes: {:?}", &err);
+                return;
+            }
+        };
+        if saved_changes.is_empty() {
+            return;
+        }
+
+        let n_changes = saved_changes.len() as u32;
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("Restore Staged Changes?"))
+            .body(ngettext_f(
+                "{n} change staged for “{path}” wasn't committed before it was closed.",
+                "{n} changes staged for “{path}” weren't committed before it was closed.",
+                n_changes,
+                &[
+                    ("n", &n_changes.to_string()),
+                    ("path", &env.path().display().to_string()),
+                ],
+            ))
+            .transient_for(self)
+            .modal(true)
+            .default_response("restore")
+            .close_response("restore")
+            .build();
+        dialog.add_responses(&[
+            ("discard", &gettext("_Discard")),
+            ("restore", &gettext("_Restore")),
+        ]);
+        dialog.set_response_appearance("discard", adw::ResponseAppearance::Destructive);
+        dialog.set_response_appearance("restore", adw::ResponseAppearance::Suggested);
+
+        let response = dialog.choose_future().await;
+
+        // The env may have been closed meanwhile
+        if imp.env.borrow().as_ref().map(|env| env.path()) != Some(env.path()) {
+            return;
+        }
+        if response == "restore" {
+            imp.pending_changes.replace(saved_changes);
+            self.update_pending_changes();
+        } else {
+            self.save_pending_changes();
+        }
+    }
+
+    /// Refreshes the pending changes pane, which is shown while staging or
+    /// while there are changes left to review.
+    fn update_pending_changes(&self) {
+        let imp = self.imp();
+
+        let pending_changes = imp.pending_changes.borrow();
+        imp.pending_changes_pane.update(&pending_changes, |key| {
+            self.non_printable_display()
+                .render(key, self.key_encoding())
+        });
+        imp.pending_changes_pane
+            .set_visible(self.stage_changes() || !pending_changes.is_empty());
+
+        self.action_set_enabled("win.commit-changes", !pending_changes.is_empty());
+        self.action_set_enabled("win.discard-changes", !pending_changes.is_empty());
+    }
+
+    /// The snapshot of the env the selected database was loaded from, which
+    /// reads that should agree with the shown entries go through.
+    fn snapshot(&self) -> Result<Snapshot> {
+        self.selected_db().context("No selected db")?.snapshot()
+    }
+
+    fn selected_db(&self) -> Option<Database> {
+        self.imp().filter_model.model().and_downcast::<Database>()
+    }
+
+    /// Opens a window comparing the selected database with another one.
+    fn compare(&self) -> Result<()> {
+        let env = self.imp().env.borrow().clone().context("No env set")?;
+        let db = self.selected_db().context("No selected db")?;
+        let db_name = db.name().context("Selected db has no name")?;
+
+        let window = CompareWindow::new(self, &env, db_name);
+        window.present();
+        self.add_env_window(window.upcast_ref());
+
+        Ok(())
+    }
+
+    /// Closes `window` with the env.
+    fn add_env_window(&self, window: &gtk::Window) {
+        let mut env_windows = self.imp().env_windows.borrow_mut();
+        env_windows.retain(|window| window.upgrade().is_some());
+        env_windows.push(window.downgrade());
+    }
+
+    /// Closes the windows showing entries of the env, so they no longer keep
+    /// it open.
+    fn close_env_windows(&self) {
+        for window in self.imp().env_windows.take() {
+            if let Some(window) = window.upgrade() {
+                window.destroy();
+            }
+        }
+    }
+
+    /// Opens a window with the storage used by each database and the sizes
+    /// in the selected one.
+    fn show_statistics(&self) -> Result<()> {
+        let env = self.imp().env.borrow().clone().context("No env set")?;
+        let db = self.selected_db();
+
+        let window = StatisticsWindow::new(self, &env, db.as_ref().and_then(|db| db.name()));
+        window.present();
+
+        Ok(())
+    }
+
+    /// Scans the selected database for keys with byte-identical values and
+    /// lists them, with the groups that waste the most bytes first.
+    async fn find_duplicates(&self) -> Result<()> {
+        let db = self.selected_db().context("No selected db")?;
+        let snapshot = db.snapshot()?;
+        let db_name = db.name().map(str::to_owned);
+
+        let progress_dialog =
+            self.present_progress_dialog(&gettext("Finding Duplicates…"), || None);
+        let result = gio::spawn_blocking(move || duplicates::find(&snapshot, db_name.as_deref()))
+            .await
+            .map_err(|_| anyhow::anyhow!("duplicates thread panicked"))?;
+        progress_dialog.close();
+        let groups = result?;
+
+        let n_wasted_bytes = groups.iter().map(|group| group.wasted_bytes()).sum::<u64>();
+        let body = if groups.is_empty() {
+            gettext("No keys share a value.")
+        } else {
+            ngettext_f(
+                "{n} value is shared by several keys, wasting {size}.",
+                "{n} values are shared by several keys, wasting {size}.",
+                groups.len() as u32,
+                &[
+                    ("n", &groups.len().to_string()),
+                    ("size", &glib::format_size(n_wasted_bytes)),
+                ],
+            )
+        };
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("Duplicate Values"))
+            .body(body)
+            .transient_for(self)
+            .modal(true)
+            .default_response("close")
+            .close_response("close")
+            .build();
+        dialog.add_response("close", &gettext("_Close"));
+
+        if !groups.is_empty() {
+            let list_box = gtk::ListBox::builder()
+                .selection_mode(gtk::SelectionMode::None)
+                .css_classes(["boxed-list"])
+                .build();
+            for group in groups.iter().take(MAX_N_SHOWN_DUPLICATE_GROUPS) {
+                let row = adw::ExpanderRow::builder()
+                    .title(ngettext_f(
+                        "{n} key",
+                        "{n} keys",
+                        group.keys.len() as u32,
+                        &[("n", &group.keys.len().to_string())],
+                    ))
+                    .subtitle(gettext_f(
+                        "{value_size} each, {wasted_size} wasted",
+                        &[
+                            ("value_size", &glib::format_size(group.value_len)),
+                            ("wasted_size", &glib::format_size(group.wasted_bytes())),
+                        ],
+                    ))
+                    .build();
+                for key in &group.keys {
+                    let key_row = adw::ActionRow::builder()
+                        .title(glib::markup_escape_text(
+                            &self
+                                .non_printable_display()
+                                .render(key, self.key_encoding()),
+                        ))
+                        .activatable(true)
+                        .build();
+                    key_row.connect_activated(
+                        clone!(@weak self as obj, @weak dialog, @to-owned key => move |_| {
+                            obj.select_key(&key);
+                            dialog.close();
+                        }),
+                    );
+                    row.add_row(&key_row);
+                }
+                list_box.append(&row);
+            }
+
+            let scrolled_window = gtk::ScrolledWindow::builder()
+                .hscrollbar_policy(gtk::PolicyType::Never)
+                .propagate_natural_height(true)
+                .max_content_height(360)
+                .child(&list_box)
+                .build();
+            dialog.set_extra_child(Some(&scrolled_window));
+        }
+
+        dialog.choose_future().await;
+
+        Ok(())
+    }
+
+    /// Reads every entry of the selected database, like dumping it, and
+    /// reports how many could be read before any corruption.
+    async fn verify_database(&self) -> Result<()> {
+        let db = self.selected_db().context("No selected db")?;
+
+        let n_entries = db.n_entries();
+        let n_read = Arc::new(AtomicUsize::new(0));
+        let progress_dialog = self.present_progress_dialog(
+            &gettext("Verifying Database…"),
+            clone!(@strong n_read => move || {
+                let n_read = n_read.load(atomic::Ordering::Relaxed);
+                (n_entries > 0).then(|| (n_read as f64 / n_entries as f64).min(1.0))
+            }),
+        );
+        let result = db.verify_in_background(n_read).await;
+        progress_dialog.close();
+        let verification = result?;
+
+        let (heading, body) = match verification.failure {
+            None => (
+                gettext("Database Verified"),
+                ngettext_f(
+                    "Read {n} entry without errors.",
+                    "Read {n} entries without errors.",
+                    verification.n_read as u32,
+                    &[("n", &verification.n_read.to_string())],
+                ),
+            ),
+            Some(failure) => {
+                let n_read = ngettext_f(
+                    "Read {n} entry",
+                    "Read {n} entries",
+                    verification.n_read as u32,
+                    &[("n", &verification.n_read.to_string())],
+                );
+                let body = match failure.last_key {
+                    Some(last_key) => gettext_f(
+                        "{n_read} before failing after key “{key}”: {error}",
+                        &[
+                            ("n_read", &n_read),
+                            (
+                                "key",
+                                &self
+                                    .non_printable_display()
+                                    .render(&last_key, self.key_encoding()),
+                            ),
+                            ("error", &failure.message),
+                        ],
+                    ),
+                    None => gettext_f(
+                        "Failed before reading any entry: {error}",
+                        &[("error", &failure.message)],
+                    ),
+                };
+                (gettext("Verification Failed"), body)
+            }
+        };
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(heading)
+            .body(body)
+            .transient_for(self)
+            .modal(true)
+            .default_response("ok")
+            .close_response("ok")
+            .build();
+        dialog.add_response("ok", &gettext("_OK"));
+        dialog.choose_future().await;
+
+        Ok(())
+    }
+
+    /// Parses the query entry, marking it with the error if it is invalid.
+    fn update_query(&self) {
+        let imp = self.imp();
+
+        let text = imp.query_entry.text();
+        let result = (!text.trim().is_empty()).then(|| Query::parse(&text));
+
+        match &result {
+            Some(Err(err)) => {
+                imp.query_entry.add_css_class("error");
+                imp.query_entry
+                    .set_secondary_icon_name(Some("dialog-error-symbolic"));
+                imp.query_entry
+                    .set_secondary_icon_tooltip_text(Some(&err.to_string()));
+            }
+            _ => {
+                imp.query_entry.remove_css_class("error");
+                imp.query_entry.set_secondary_icon_name(None);
+                imp.query_entry.set_secondary_icon_tooltip_text(None);
+            }
+        }
+
+        imp.query.replace(result.and_then(Result::ok));
+    }
+
+    /// Shows where the shown entries are from in the header: the env, then
+    /// the database, then the search and the query narrowing them, if any,
+    /// as chips that can be removed.
+    fn update_breadcrumb(&self) {
+        let imp = self.imp();
+
+        let env_path = imp
+            .backend
+            .borrow()
+            .as_ref()
+            .map(|backend| backend.path().to_path_buf());
+        let location = imp.remote_uri.borrow().clone().or_else(|| {
+            env_path.map(|path| {
+                match path.strip_prefix(glib::home_dir()) {
+                    Ok(relative_path) => Path::new("~").join(relative_path),
+                    Err(_) => path,
+                }
+                .display()
+                .to_string()
+            })
+        });
+        imp.env_label.set_label(
+            &location
+                .clone()
+                .unwrap_or_else(|| gettext("No Environment")),
+        );
+        imp.env_label.set_tooltip_text(location.as_deref());
+
+        while let Some(child) = imp.filter_chips_box.first_child() {
+            imp.filter_chips_box.remove(&child);
+        }
+
+        let search_text = imp.search_entry.text();
+        if !search_text.is_empty() {
+            let chip = filter_chip(
+                &gettext_f("Search “{text}”", &[("text", &search_text)]),
+                clone!(@weak self as obj => move || {
+                    let imp = obj.imp();
+                    imp.search_bar.set_search_mode(true);
+                    imp.search_entry.grab_focus();
+                }),
+                clone!(@weak self as obj => move || {
+                    obj.imp().search_entry.set_text("");
+                }),
+            );
+            imp.filter_chips_box.append(&chip);
+        }
+
+        let query_text = imp.query_entry.text();
+        if !query_text.trim().is_empty() {
+            let chip = filter_chip(
+                &gettext_f("Query “{text}”", &[("text", &query_text)]),
+                clone!(@weak self as obj => move || {
+                    let imp = obj.imp();
+                    imp.search_bar.set_search_mode(true);
+                    imp.query_entry.grab_focus();
+                }),
+                clone!(@weak self as obj => move || {
+                    obj.imp().query_entry.set_text("");
+                }),
+            );
+            imp.filter_chips_box.append(&chip);
+        }
+
+        imp.filter_separator
+            .set_visible(imp.filter_chips_box.first_child().is_some());
+    }
+
+    /// Returns whether the rows are narrowed by the search or the query.
+    fn is_filtered(&self) -> bool {
+        let imp = self.imp();
+
+        !imp.search_entry.text().is_empty() || imp.query.borrow().is_some()
+    }
+
+    /// Scans the whole selected database in the background for keys
+    /// containing the search text, streaming them into the search results
+    /// pane, rather than filtering the loaded rows.
+    async fn search_entire_db(&self) -> Result<()> {
+        let imp = self.imp();
+
+        let db = self.selected_db().context("No selected db")?;
+        let snapshot = db.snapshot()?;
+        let db_name = db.name().map(str::to_owned);
+        let query = imp.search_entry.text().to_string();
+        let non_printable_display = self.non_printable_display();
+        let key_encoding = self.key_encoding();
+
+        let progress = Arc::new(ScanProgress::default());
+        imp.search_results_pane
+            .start(progress.clone(), db.n_entries(), None);
+        let result = gio::spawn_blocking(clone!(@strong progress => move || {
+            full_search::scan(
+                &snapshot,
+                db_name.as_deref(),
+                &query,
+                non_printable_display,
+                key_encoding,
+                &progress,
+            )
+        }))
+        .await
+        .map_err(|_| anyhow!("search thread panicked"))
+        .and_then(|result| result);
+        imp.search_results_pane.finish(&progress, &result);
+
+        result
+    }
+
+    /// Counts the entries of the selected database that match the search and
+    /// the query by walking it in the background, showing the count in the
+    /// status bar, without loading the entries as rows.
+    async fn count_matching(&self) -> Result<()> {
+        let imp = self.imp();
+
+        let db = self.selected_db().context("No selected db")?;
+        let snapshot = db.snapshot()?;
+        let db_name = db.name().map(str::to_owned);
+        let query = imp.query.borrow().clone();
+        anyhow::ensure!(
+            !query.as_ref().is_some_and(Query::tests_decoded_value),
+            "Fields of decoded values can only be tested on loaded rows"
+        );
+        let text = imp.search_entry.text().to_string();
+        let non_printable_display = self.non_printable_display();
+        let key_encoding = self.key_encoding();
+
+        self.cancel_count();
+        let progress = Arc::new(ScanProgress::default());
+        imp.count_progress.replace(Some(progress.clone()));
+
+        let n_entries = db.n_entries().to_string();
+        let update_label = clone!(@weak self as obj, @strong progress => move || {
+            obj.imp().count_label.set_label(&gettext_f(
+                "Counting, {n_scanned} of {n_entries} entries scanned",
+                &[
+                    ("n_scanned", &progress.n_scanned().to_string()),
+                    ("n_entries", &n_entries),
+                ],
+            ));
+        });
+        update_label();
+        imp.count_label.set_visible(true);
+        let source_id = glib::timeout_add_local(Duration::from_millis(100), move || {
+            update_label();
+            glib::ControlFlow::Continue
+        });
+
+        let result = gio::spawn_blocking(clone!(@strong progress => move || {
+            full_search::count(
+                &snapshot,
+                db_name.as_deref(),
+                query.as_ref(),
+                &text,
+                non_printable_display,
+                key_encoding,
+                &progress,
+            )
+        }))
+        .await
+        .map_err(|_| anyhow!("count thread panicked"))
+        .and_then(|result| result);
+        source_id.remove();
+
+        // Replaced by another count, or made stale by a change of the filter
+        if progress.is_cancelled() {
+            return Ok(());
+        }
+        imp.count_progress.replace(None);
+
+        match result {
+            Ok(n_matching) => {
+                imp.count_label.set_label(&ngettext_f(
+                    "{n} counted",
+                    "{n} counted",
+                    n_matching as u32,
+                    &[("n", &n_matching.to_string())],
+                ));
+                Ok(())
+            }
+            Err(err) => {
+                imp.count_label.set_visible(false);
+                Err(err)
+            }
+        }
+    }
+
+    /// Stops the count of matching entries, if any, and hides its result, as
+    /// it no longer applies.
+    fn cancel_count(&self) {
+        let imp = self.imp();
+
+        if let Some(progress) = imp.count_progress.take() {
+            progress.cancel();
+        }
+        imp.count_label.set_visible(false);
+    }
+
+    /// Scans every named database in the background for keys or values
+    /// containing the search text, streaming them into the search results
+    /// pane grouped by database.
+    async fn search_all_dbs(&self) -> Result<()> {
+        let imp = self.imp();
+
+        let snapshot = self.snapshot()?;
+        let db_names = snapshot
+            .names()?
+            .0
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+        let n_entries = full_search::count_entries(&snapshot, &db_names)?;
+        let query = imp.search_entry.text().to_string();
+        let non_printable_display = self.non_printable_display();
+        let key_encoding = self.key_encoding();
+        let value_encoding = self.value_encoding();
+
+        let progress = Arc::new(ScanProgress::default());
+        imp.search_results_pane.start(
+            progress.clone(),
+            n_entries.min(u32::MAX as u64) as u32,
+            Some(db_names.len()),
+        );
+        let result = gio::spawn_blocking(clone!(@strong progress => move || {
+            full_search::scan_env(
+                &snapshot,
+                &db_names,
+                &query,
+                non_printable_display,
+                key_encoding,
+                value_encoding,
+                &progress,
+            )
+        }))
+        .await
+        .map_err(|_| anyhow!("search thread panicked"))
+        .and_then(|result| result);
+        imp.search_results_pane.finish(&progress, &result);
+
+        result
+    }
+
+    /// Asks for a key and selects the first row whose key, as displayed, is
+    /// or starts with it.
+    async fn go_to_key(&self) {
+        let imp = self.imp();
+
+        let entry = gtk::Entry::builder().activates_default(true).build();
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("Go to Key"))
+            .transient_for(self)
+            .modal(true)
+            .extra_child(&entry)
+            .default_response("go")
+            .close_response("cancel")
+            .build();
+        dialog.add_responses(&[("cancel", &gettext("_Cancel")), ("go", &gettext("_Go"))]);
+        dialog.set_response_appearance("go", adw::ResponseAppearance::Suggested);
+
+        if dialog.choose_future().await != "go" {
+            return;
+        }
+
+        let query = entry.text();
+        let key_texts = (0..imp.sort_model.n_items())
+            .map(|position| self.key_text(imp.sort_model.item(position)))
+            .collect::<Vec<_>>();
+        let position = key_texts
+            .iter()
+            .position(|key_text| *key_text == query)
+            .or_else(|| {
+                key_texts
+                    .iter()
+                    .position(|key_text| key_text.starts_with(query.as_str()))
+            });
+
+        if let Some(position) = position {
+            self.select_position(position as u32);
+        } else {
+            self.add_message_toast(&gettext_f(
+                "No key starting with “{key}”",
+                &[("key", &query)],
+            ));
+        }
+    }
+
+    /// Appends `c` to the typed prefix and selects the first key starting with
+    /// it. The prefix starts over after a pause in typing.
+    fn type_ahead(&self, c: char) {
+        let imp = self.imp();
+
+        imp.type_ahead_prefix.borrow_mut().push(c);
+
+        if let Some(source_id) = imp.type_ahead_source_id.take() {
+            source_id.remove();
+        }
+        let source_id = glib::timeout_add_local_once(
+            TYPE_AHEAD_TIMEOUT,
+            clone!(@weak self as obj => move || {
+                let imp = obj.imp();
+                imp.type_ahead_prefix.borrow_mut().clear();
+                imp.type_ahead_source_id.replace(None);
+            }),
+        );
+        imp.type_ahead_source_id.replace(Some(source_id));
+
+        let prefix = imp.type_ahead_prefix.borrow().clone();
+        if let Some(position) = self.find_key_with_prefix(&prefix) {
+            self.select_position(position);
+        } else {
+            self.error_bell();
+        }
+    }
+
+    /// Returns the position of the first filtered row whose key, as displayed,
+    /// starts with `prefix`.
+    fn find_key_with_prefix(&self, prefix: &str) -> Option<u32> {
+        let imp = self.imp();
+        let db = self.selected_db()?;
+
+        let key_at = |position| {
+            imp.sort_model
+                .item(position)
+                .and_downcast::<DatabaseItem>()
+                .unwrap()
+                .key()
+        };
+        let n_items = imp.sort_model.n_items();
+
+        // Keys are sorted by their bytes in this case, so the first match can
+        // be found with a binary search on the ordered rows.
+        if self.key_collation().flags(db.flags()).is_empty()
+            && matches!(
+                self.key_display_mode(),
+                KeyDisplayMode::Auto | KeyDisplayMode::Text
+            )
+            && self.key_encoding() == TextEncoding::Utf8
+        {
+            let (mut low, mut high) = (0, n_items);
+            while low < high {
+                let mid = low + (high - low) / 2;
+                if key_at(mid).as_ref() < prefix.as_bytes() {
+                    low = mid + 1;
+                } else {
+                    high = mid;
+                }
+            }
+
+            return (low < n_items && key_at(low).starts_with(prefix.as_bytes())).then_some(low);
+        }
+
+        (0..n_items).find(|&position| {
+            self.key_text(imp.sort_model.item(position))
+                .starts_with(prefix)
+        })
+    }
+
+    /// Asks for an optional label and bookmarks the selected database, or the
+    /// selected key in it if `is_key` is true.
+    async fn add_bookmark(&self, is_key: bool) -> Result<()> {
+        let imp = self.imp();
+
+        let env = imp.env.borrow().clone().context("No env set")?;
+        let db = self.selected_db().context("No selected db")?;
+        let db_name = db.name().context("Selected db has no name")?.to_string();
+        let key = if is_key {
+            let item = self.selected_item().context("No selected item")?;
+            Some(item.key().to_vec())
+        } else {
+            None
+        };
+
+        let entry = gtk::Entry::builder()
+            .placeholder_text(gettext("Label (optional)"))
+            .activates_default(true)
+            .build();
+        let dialog = adw::MessageDialog::builder()
+            .heading(if is_key {
+                gettext("Bookmark Key")
+            } else {
+                gettext("Bookmark Database")
+            })
+            .transient_for(self)
+            .modal(true)
+            .extra_child(&entry)
+            .default_response("add")
+            .close_response("cancel")
+            .build();
+        dialog.add_responses(&[("cancel", &gettext("_Cancel")), ("add", &gettext("_Add"))]);
+        dialog.set_response_appearance("add", adw::ResponseAppearance::Suggested);
+
+        if dialog.choose_future().await != "add" {
+            return Ok(());
+        }
+
+        imp.bookmarks.borrow_mut().push(Bookmark {
+            db_name,
+            key,
+            label: entry.text().trim().to_string(),
+        });
+        Bookmark::save_all(env.path(), &imp.bookmarks.borrow());
+        self.update_bookmarks_list();
+
+        Ok(())
+    }
+
+    fn remove_bookmark(&self, index: usize) {
+        let imp = self.imp();
+
+        let Some(env) = imp.env.borrow().clone() else {
+            return;
+        };
+
+        imp.bookmarks.borrow_mut().remove(index);
+        Bookmark::save_all(env.path(), &imp.bookmarks.borrow());
+        self.update_bookmarks_list();
+    }
+
+    /// Selects the database of the bookmark at `index`, and its key if any.
+    fn go_to_bookmark(&self, index: usize) {
+        let imp = self.imp();
+
+        let Some(bookmark) = imp.bookmarks.borrow().get(index).cloned() else {
+            return;
+        };
+
+        imp.bookmarks_button.popdown();
+
+        if !self.select_database(&bookmark.db_name) {
+            self.add_message_toast(&gettext_f(
+                "Database “{name}” no longer exists",
+                &[("name", &bookmark.db_name)],
+            ));
+            return;
+        }
+
+        if let Some(key) = bookmark.key {
+            if !self.select_key(&key) {
+                self.add_message_toast(&gettext("Bookmarked key no longer exists"));
+            }
+        }
+    }
+
+    /// Shows `key` of the database called `db_name` in the env at `env_path`,
+    /// opening the env if it isn't already.
+    pub fn show_key(&self, env_path: &Path, db_name: &str, key: &[u8]) -> Result<()> {
+        let is_loaded = self.imp().env.borrow().as_ref().is_some_and(|env| {
+            env_path
+                .canonicalize()
+                .is_ok_and(|env_path| env_path == env.path())
+        });
+        if !is_loaded {
+            self.load_env(env_path)?;
+        }
+
+        anyhow::ensure!(
+            self.select_database(db_name),
+            "Database `{}` not found",
+            db_name
+        );
+        anyhow::ensure!(self.select_key(key), "Key not found");
+
+        Ok(())
+    }
+
+    /// Selects the database called `db_name`, returning whether it exists.
+    fn select_database(&self, db_name: &str) -> bool {
+        let imp = self.imp();
+
+        let position = imp.drop_down.model().and_then(|model| {
+            (0..model.n_items()).find(|&position| {
+                model
+                    .item(position)
+                    .and_downcast::<DatabaseItem>()
+                    .is_some_and(|item| item.key().as_ref() == db_name.as_bytes())
+            })
+        });
+
+        if let Some(position) = position {
+            imp.drop_down.set_selected(position);
+        }
+
+        position.is_some()
+    }
+
+    /// Selects and scrolls to the row of `key` in the selected database,
+    /// clearing the search if needed, returning whether it exists.
+    fn select_key(&self, key: &[u8]) -> bool {
+        let imp = self.imp();
+
+        // The key may be hidden by the search or the query
+        if self.is_filtered() {
+            imp.search_entry.set_text("");
+            imp.query_entry.set_text("");
+            if let Some(filter) = imp.filter_model.filter() {
+                filter.changed(gtk::FilterChange::LessStrict);
+            }
+        }
+
+        let position = (0..imp.sort_model.n_items()).find(|&position| {
+            imp.sort_model
+                .item(position)
+                .and_downcast::<DatabaseItem>()
+                .is_some_and(|item| item.key().as_ref() == key)
+        });
+
+        if let Some(position) = position {
+            self.select_position(position);
+        }
+
+        position.is_some()
+    }
+
+    /// Selects and scrolls to the row of `key` if it is shown, leaving the
+    /// search and the query as they are, unlike [`Self::select_key`].
+    fn scroll_to_key(&self, key: &[u8]) {
+        let imp = self.imp();
+
+        if self
+            .selected_item()
+            .is_some_and(|item| item.key().as_ref() == key)
+        {
+            return;
+        }
+
+        let position = (0..imp.sort_model.n_items()).find(|&position| {
+            imp.sort_model
+                .item(position)
+                .and_downcast::<DatabaseItem>()
+                .is_some_and(|item| item.key().as_ref() == key)
+        });
+        if let Some(position) = position {
+            self.select_position(position);
+        }
+    }
+
+    /// Shows the keys of the selected database grouped by the key separator
+    /// in the key tree, if it is shown.
+    fn update_key_tree(&self) {
+        let imp = self.imp();
+
+        let Some(db) = self.selected_db().filter(|_| self.show_key_tree()) else {
+            imp.key_tree_pane.clear();
+            return;
+        };
+
+        let mut keys = (0..db.n_items())
+            .filter_map(|position| db.item(position).and_downcast::<DatabaseItem>())
+            .filter(|item| item.change() != DiffKind::Removed)
+            .map(|item| item.key())
+            .collect::<Vec<_>>();
+        // Group by prefix regardless of the database's key order
+        keys.sort_unstable_by(|a, b| a.as_ref().cmp(b.as_ref()));
+
+        imp.key_tree_pane.set_tree(KeyTree {
+            keys,
+            separator: self.key_separator().into_bytes(),
+            non_printable_display: self.non_printable_display(),
+            encoding: self.key_encoding(),
+        });
+    }
+
+    fn update_bookmarks_list(&self) {
+        let imp = self.imp();
+
+        imp.bookmarks_list_box.remove_all();
+
+        for bookmark in imp.bookmarks.borrow().iter() {
+            let location = match &bookmark.key {
+                Some(key) => format!(
+                    "{} › {}",
+                    bookmark.db_name,
+                    self.non_printable_display()
+                        .render(key, self.key_encoding())
+                ),
+                None => bookmark.db_name.clone(),
+            };
+
+            let row = adw::ActionRow::builder()
+                .activatable(true)
+                .use_markup(false)
+                .build();
+            if bookmark.label.is_empty() {
+                row.set_title(&location);
+            } else {
+                row.set_title(&bookmark.label);
+                row.set_subtitle(&location);
+            }
+
+            let remove_button = gtk::Button::builder()
+                .icon_name("user-trash-symbolic")
+                .tooltip_text(gettext("Remove Bookmark"))
+                .valign(gtk::Align::Center)
+                .css_classes(["flat"])
+                .build();
+            remove_button.connect_clicked(clone!(@weak self as obj, @weak row => move |_| {
+                obj.remove_bookmark(row.index() as usize);
+            }));
+            row.add_suffix(&remove_button);
+
+            imp.bookmarks_list_box.append(&row);
+        }
+    }
+
+    /// Asks for a name and saves the search text under it, replacing any
+    /// saved search with the same name.
+    async fn save_search(&self) -> Result<()> {
+        let imp = self.imp();
+
+        let env = imp.env.borrow().clone().context("No env set")?;
+        let pattern = imp.search_entry.text().to_string();
+
+        let entry = gtk::Entry::builder()
+            .text(&pattern)
+            .activates_default(true)
+            .build();
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("Save Search"))
+            .body(gettext("Enter a name for the search"))
+            .transient_for(self)
+            .modal(true)
+            .extra_child(&entry)
+            .default_response("save")
+            .close_response("cancel")
+            .build();
+        dialog.add_responses(&[("cancel", &gettext("_Cancel")), ("save", &gettext("_Save"))]);
+        dialog.set_response_appearance("save", adw::ResponseAppearance::Suggested);
+
+        if dialog.choose_future().await != "save" {
+            return Ok(());
+        }
+
+        let name = entry.text().trim().to_string();
+        let name = if name.is_empty() {
+            pattern.clone()
+        } else {
+            name
+        };
+
+        {
+            let mut saved_searches = imp.saved_searches.borrow_mut();
+            saved_searches.retain(|saved_search| saved_search.name != name);
+            saved_searches.push(SavedSearch { name, pattern });
+        }
+        SavedSearch::save_all(env.path(), &imp.saved_searches.borrow());
+        self.update_saved_searches_list();
+
+        Ok(())
+    }
+
+    fn remove_saved_search(&self, index: usize) {
+        let imp = self.imp();
+
+        let Some(env) = imp.env.borrow().clone() else {
+            return;
+        };
+
+        imp.saved_searches.borrow_mut().remove(index);
+        SavedSearch::save_all(env.path(), &imp.saved_searches.borrow());
+        self.update_saved_searches_list();
+    }
+
+    /// Searches for the pattern of the saved search at `index`.
+    fn apply_saved_search(&self, index: usize) {
+        let imp = self.imp();
+
+        let Some(saved_search) = imp.saved_searches.borrow().get(index).cloned() else {
+            return;
+        };
+
+        imp.saved_searches_button.popdown();
+        imp.search_entry.set_text(&saved_search.pattern);
+        imp.search_entry.set_position(-1);
+    }
+
+    fn update_saved_searches_list(&self) {
+        let imp = self.imp();
+
+        imp.saved_searches_list_box.remove_all();
+
+        for saved_search in imp.saved_searches.borrow().iter() {
+            let row = adw::ActionRow::builder()
+                .title(&saved_search.name)
+                .activatable(true)
+                .use_markup(false)
+                .build();
+            if saved_search.name != saved_search.pattern {
+                row.set_subtitle(&saved_search.pattern);
+            }
+
+            let remove_button = gtk::Button::builder()
+                .icon_name("user-trash-symbolic")
+                .tooltip_text(gettext("Remove Saved Search"))
+                .valign(gtk::Align::Center)
+                .css_classes(["flat"])
+                .build();
+            remove_button.connect_clicked(clone!(@weak self as obj, @weak row => move |_| {
+                obj.remove_saved_search(row.index() as usize);
+            }));
+            row.add_suffix(&remove_button);
+
+            imp.saved_searches_list_box.append(&row);
+        }
+    }
+
+    fn replace_highlight_rules(&self, rules: Vec<HighlightRule>) {
+        self.imp().highlight_rules.replace(HighlightRules(rules));
+        self.notify_highlight_rules();
+    }
+
+    /// Lists the highlight rules of the env, letting them be removed or new
+    /// ones added.
+    async fn edit_highlight_rules(&self) -> Result<()> {
+        let imp = self.imp();
+
+        let env = imp.env.borrow().clone().context("No env set")?;
+
+        loop {
+            let dialog = adw::MessageDialog::builder()
+                .heading(gettext("Highlight Rules"))
+                .body(gettext(
+                    "Rows matching the query of a rule are shown in its color, by the first \
+                    matching rule.",
+                ))
+                .transient_for(self)
+                .modal(true)
+                .default_response("close")
+                .close_response("close")
+                .build();
+            dialog.add_responses(&[
+                ("add", &gettext("_Add Rule…")),
+                ("close", &gettext("_Close")),
+            ]);
+
+            let list_box = gtk::ListBox::builder()
+                .selection_mode(gtk::SelectionMode::None)
+                .css_classes(["boxed-list"])
+                .build();
+            for (index, rule) in imp.highlight_rules.borrow().0.iter().enumerate() {
+                let row = adw::ActionRow::builder()
+                    .title(&rule.query_text)
+                    .subtitle(if rule.badge.is_empty() {
+                        rule.color.label()
+                    } else {
+                        format!("{} · {}", rule.color.label(), rule.badge)
+                    })
+                    .use_markup(false)
+                    .build();
+
+                let swatch = gtk::Label::builder()
+                    .label(" ")
+                    .valign(gtk::Align::Center)
+                    .css_classes(["format-badge", rule.color.css_class()])
+                    .build();
+                row.add_prefix(&swatch);
+
+                let remove_button = gtk::Button::builder()
+                    .icon_name("user-trash-symbolic")
+                    .tooltip_text(gettext("Remove Rule"))
+                    .valign(gtk::Align::Center)
+                    .css_classes(["flat"])
+                    .build();
+                remove_button.connect_clicked(
+                    clone!(@weak self as obj, @weak list_box, @weak row, @strong env => move |_| {
+                        let mut rules = obj.highlight_rules().0;
+                        rules.remove(index);
+                        HighlightRule::save_all(env.path(), &rules);
+                        obj.replace_highlight_rules(rules);
+                        list_box.remove(&row);
+                    }),
+                );
+                row.add_suffix(&remove_button);
+
+                list_box.append(&row);
+            }
+            if list_box.first_child().is_some() {
+                let scrolled_window = gtk::ScrolledWindow::builder()
+                    .hscrollbar_policy(gtk::PolicyType::Never)
+                    .propagate_natural_height(true)
+                    .max_content_height(360)
+                    .child(&list_box)
+                    .build();
+                dialog.set_extra_child(Some(&scrolled_window));
+            }
+
+            if dialog.choose_future().await != "add" {
+                return Ok(());
+            }
+
+            self.add_highlight_rule(&env).await;
+        }
+    }
+
+    /// Asks for a query, a color, and a badge, and adds them as a highlight
+    /// rule of `env`.
+    async fn add_highlight_rule(&self, env: &Env) {
+        let imp = self.imp();
+
+        let query_entry = gtk::Entry::builder()
+            .text(imp.query_entry.text())
+            .placeholder_text(gettext("Query"))
+            .tooltip_text(imp.query_entry.tooltip_text().unwrap_or_default())
+            .activates_default(true)
+            .build();
+        let color_labels = HighlightColor::ALL.map(|color| color.label());
+        let color_drop_down = gtk::DropDown::from_strings(
+            &color_labels.iter().map(String::as_str).collect::<Vec<_>>(),
+        );
+        let badge_entry = gtk::Entry::builder()
+            .placeholder_text(gettext("Badge (optional)"))
+            .activates_default(true)
+            .build();
+
+        let vbox = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(6)
+            .build();
+        vbox.append(&query_entry);
+        vbox.append(&color_drop_down);
+        vbox.append(&badge_entry);
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("Add Highlight Rule"))
+            .transient_for(self)
+            .modal(true)
+            .extra_child(&vbox)
+            .default_response("add")
+            .close_response("cancel")
+            .build();
+        dialog.add_responses(&[("cancel", &gettext("_Cancel")), ("add", &gettext("_Add"))]);
+        dialog.set_response_appearance("add", adw::ResponseAppearance::Suggested);
+
+        if dialog.choose_future().await != "add" {
+            return;
+        }
+
+        let color = HighlightColor::ALL[color_drop_down.selected() as usize];
+        match HighlightRule::new(query_entry.text().trim(), color, badge_entry.text().trim()) {
+            Ok(rule) => {
+                let mut rules = self.highlight_rules().0;
+                rules.push(rule);
+                HighlightRule::save_all(env.path(), &rules);
+                self.replace_highlight_rules(rules);
+            }
+            Err(err) => self.add_message_toast(&gettext_f(
+                "Invalid query: {error}",
+                &[("error", &err.to_string())],
+            )),
+        }
+    }
+
+    /// Asks for a page number and shows that page.
+    async fn go_to_page(&self) {
+        let entry = gtk::Entry::builder()
+            .text((self.page() + 1).to_string())
+            .input_purpose(gtk::InputPurpose::Digits)
+            .activates_default(true)
+            .build();
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("Go to Page"))
+            .body(gettext_f(
+                "Enter a page number from 1 to {n_pages}",
+                &[("n_pages", &self.n_pages().to_string())],
+            ))
+            .transient_for(self)
+            .modal(true)
+            .extra_child(&entry)
+            .default_response("go")
+            .close_response("cancel")
+            .build();
+        dialog.add_responses(&[("cancel", &gettext("_Cancel")), ("go", &gettext("_Go"))]);
+        dialog.set_response_appearance("go", adw::ResponseAppearance::Suggested);
+
+        if dialog.choose_future().await != "go" {
+            return;
+        }
+
+        match entry.text().trim().parse::<u32>() {
+            Ok(page) if page > 0 => self.set_page(page - 1),
+            _ => self.add_message_toast(&gettext("Invalid page number")),
+        }
+    }
+
+    /// Asks for a row number among the filtered rows and selects that row.
+    async fn go_to_row(&self) {
+        let n_rows = self.imp().filter_model.n_items();
+
+        let entry = gtk::Entry::builder()
+            .input_purpose(gtk::InputPurpose::Digits)
+            .activates_default(true)
+            .build();
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("Go to Row"))
+            .body(gettext_f(
+                "Enter a row number from 1 to {n_rows}",
+                &[("n_rows", &n_rows.to_string())],
+            ))
+            .transient_for(self)
+            .modal(true)
+            .extra_child(&entry)
+            .default_response("go")
+            .close_response("cancel")
+            .build();
+        dialog.add_responses(&[("cancel", &gettext("_Cancel")), ("go", &gettext("_Go"))]);
+        dialog.set_response_appearance("go", adw::ResponseAppearance::Suggested);
+
+        if dialog.choose_future().await != "go" {
+            return;
+        }
+
+        match entry.text().trim().parse::<u32>() {
+            Ok(row) if row > 0 && row <= n_rows => self.select_position(row - 1),
+            _ => self.add_message_toast(&gettext("Invalid row number")),
+        }
+    }
+
+    /// Asks for a number of entries, then lists that many random rows of the
+    /// filtered rows, selecting one when activated.
+    async fn random_sample(&self) {
+        let entry = gtk::Entry::builder()
+            .text(DEFAULT_N_SAMPLED_ENTRIES.to_string())
+            .input_purpose(gtk::InputPurpose::Digits)
+            .activates_default(true)
+            .build();
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("Random Sample"))
+            .body(gettext_f(
+                "Enter a number of random entries to sample, up to {max}",
+                &[("max", &MAX_N_SAMPLED_ENTRIES.to_string())],
+            ))
+            .transient_for(self)
+            .modal(true)
+            .extra_child(&entry)
+            .default_response("sample")
+            .close_response("cancel")
+            .build();
+        dialog.add_responses(&[
+            ("cancel", &gettext("_Cancel")),
+            ("sample", &gettext("_Sample")),
+        ]);
+        dialog.set_response_appearance("sample", adw::ResponseAppearance::Suggested);
+
+        if dialog.choose_future().await != "sample" {
+            return;
+        }
+
+        let n_sampled = match entry.text().trim().parse::<u32>() {
+            Ok(n) if n > 0 && n <= MAX_N_SAMPLED_ENTRIES => n,
+            _ => {
+                self.add_message_toast(&gettext("Invalid number of entries"));
+                return;
+            }
+        };
+        loop {
+            let filter_model = &self.imp().filter_model;
+            let positions = sample_positions(filter_model.n_items(), n_sampled);
+
+            let dialog = adw::MessageDialog::builder()
+                .heading(gettext("Random Sample"))
+                .body(ngettext_f(
+                    "{n} of {n_rows} row",
+                    "{n} of {n_rows} rows",
+                    filter_model.n_items(),
+                    &[
+                        ("n", &positions.len().to_string()),
+                        ("n_rows", &filter_model.n_items().to_string()),
+                    ],
+                ))
+                .transient_for(self)
+                .modal(true)
+                .default_response("close")
+                .close_response("close")
+                .build();
+            dialog.add_responses(&[
+                ("sample-again", &gettext("Sample _Again")),
+                ("close", &gettext("_Close")),
+            ]);
+
+            if !positions.is_empty() {
+                let list_box = gtk::ListBox::builder()
+                    .selection_mode(gtk::SelectionMode::None)
+                    .css_classes(["boxed-list"])
+                    .build();
+                for position in positions {
+                    let Some(item) = filter_model.item(position).and_downcast::<DatabaseItem>()
+                    else {
+                        continue;
+                    };
+                    let row = adw::ActionRow::builder()
+                        .title(glib::markup_escape_text(
+                            &self
+                                .non_printable_display()
+                                .render(&item.key(), self.key_encoding()),
+                        ))
+                        .subtitle(gettext_f(
+                            "Row {row}",
+                            &[("row", &(position + 1).to_string())],
+                        ))
+                        .subtitle_lines(1)
+                        .activatable(true)
+                        .build();
+                    row.connect_activated(clone!(@weak self as obj, @weak dialog => move |_| {
+                        obj.select_position(position);
+                        dialog.close();
+                    }));
+                    list_box.append(&row);
+                }
+
+                let scrolled_window = gtk::ScrolledWindow::builder()
+                    .hscrollbar_policy(gtk::PolicyType::Never)
+                    .propagate_natural_height(true)
+                    .max_content_height(360)
+                    .child(&list_box)
+                    .build();
+                dialog.set_extra_child(Some(&scrolled_window));
+            }
+
+            if dialog.choose_future().await != "sample-again" {
+                return;
+            }
+        }
+    }
+
+    /// Sorts the rows by the key collation, leaving them in the database's own
+    /// order if it already matches.
+    fn update_sorter(&self) {
+        let imp = self.imp();
+
+        let Some(db) = self.selected_db() else {
+            imp.sort_model.set_sorter(gtk::Sorter::NONE);
+            return;
+        };
+
+        let db_flags = db.flags() & (DatabaseFlags::INTEGER_KEY | DatabaseFlags::REVERSE_KEY);
+        let flags = self.key_collation().flags(db.flags());
+        if flags == db_flags {
+            imp.sort_model.set_sorter(gtk::Sorter::NONE);
+            return;
+        }
+
+        let sorter = gtk::CustomSorter::new(move |a, b| {
+            let a = a.downcast_ref::<DatabaseItem>().unwrap().key();
+            let b = b.downcast_ref::<DatabaseItem>().unwrap().key();
+            database::compare_keys(flags, &a, &b).into()
+        });
+        imp.sort_model.set_sorter(Some(&sorter));
+    }
+
+    /// Returns the index of the shown page.
+    fn page(&self) -> u32 {
+        self.imp().slice_model.offset() / self.page_size()
+    }
+
+    fn n_pages(&self) -> u32 {
+        self.imp()
+            .filter_model
+            .n_items()
+            .div_ceil(self.page_size())
+            .max(1)
+    }
+
+    /// Shows the page at index `page`, or the last page if there are fewer.
+    fn set_page(&self, page: u32) {
+        let imp = self.imp();
+
+        let page = page.min(self.n_pages() - 1);
+        imp.slice_model.set_offset(page * self.page_size());
+
+        self.update_paging_controls();
+    }
+
+    fn update_paging_controls(&self) {
+        let imp = self.imp();
+
+        let page = self.page();
+        let n_pages = self.n_pages();
+
+        imp.paging_box.set_visible(n_pages > 1);
+        imp.page_button.set_label(&gettext_f(
+            "Page {page} of {n_pages}",
+            &[
+                ("page", &(page + 1).to_string()),
+                ("n_pages", &n_pages.to_string()),
+            ],
+        ));
+
+        self.action_set_enabled("win.previous-page", page > 0);
+        self.action_set_enabled("win.next-page", page + 1 < n_pages);
+        self.action_set_enabled("win.go-to-page", n_pages > 1);
+    }
+
+    /// Selects and scrolls to the row at `position` of the filtered rows,
+    /// showing its page first.
+    fn select_position(&self, position: u32) {
+        let page_size = self.page_size();
+
+        self.set_page(position / page_size);
+        self.imp().column_view.scroll_to(
+            position % page_size,
+            None,
+            gtk::ListScrollFlags::FOCUS | gtk::ListScrollFlags::SELECT,
+            None,
+        );
+    }
+
+    /// Selects the database `offset` positions away from the selected one,
+    /// wrapping around at either end.
+    fn select_database_by_offset(&self, offset: i64) {
+        let drop_down = &self.imp().drop_down;
+
+        let n_items = drop_down.model().map_or(0, |model| model.n_items());
+        if n_items == 0 {
+            return;
+        }
+
+        let position = match drop_down.selected() {
+            gtk::INVALID_LIST_POSITION => 0,
+            selected => (selected as i64 + offset).rem_euclid(n_items as i64) as u32,
+        };
+        drop_down.set_selected(position);
+    }
+
+    /// Saves the raw bytes of the selected item's key, or value if `is_key`
+    /// is false, to a file.
+    async fn save_selected(&self, is_key: bool) -> Result<()> {
+        let item = self.selected_item().context("No selected item")?;
+        save_item(self, &item, is_key).await
+    }
+
+    /// Replaces the selected item's value with the contents of a file.
+    /// Asks for a new key and moves the selected item's value to it.
+    async fn rename_selected_key(&self) -> Result<()> {
+        let item = self.selected_item().context("No selected item")?;
+        let db = self.selected_db().context("No selected db")?;
+
+        let key = item.key();
+        let entry = gtk::Entry::builder()
+            .text(String::from_utf8_lossy(&key))
+            .activates_default(true)
+            .build();
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("Rename Key"))
+            .body(gettext(
+                "The value is moved to the new key, which must not exist yet.",
+            ))
+            .transient_for(self)
+            .modal(true)
+            .extra_child(&entry)
+            .default_response("rename")
+            .close_response("cancel")
+            .build();
+        dialog.add_responses(&[
+            ("cancel", &gettext("_Cancel")),
+            ("rename", &gettext("_Rename")),
+        ]);
+        dialog.set_response_appearance("rename", adw::ResponseAppearance::Suggested);
+
+        if dialog.choose_future().await != "rename" {
+            return Ok(());
+        }
+
+        let new_key = entry.text();
+        if new_key.as_bytes() == key.as_ref() {
+            return Ok(());
+        }
+
+        if self.stage_changes() {
+            // Every duplicate of a DUPSORT key is moved
+            let puts = (0..db.n_items())
+                .filter_map(|position| db.item(position).and_downcast::<DatabaseItem>())
+                .filter(|item| item.change() != DiffKind::Removed && item.key() == key)
+                .map(|item| PendingChange::Put {
+                    key: new_key.as_bytes().to_vec(),
+                    value: item.data().to_vec(),
+                    duplicate: None,
+                })
+                .collect::<Vec<_>>();
+            self.stage(
+                &db,
+                puts.into_iter().chain([PendingChange::Delete {
+                    key: key.to_vec(),
+                    duplicate: None,
+                }]),
+            );
+            return Ok(());
+        }
+
+        let edit = db.rename(&key, new_key.as_bytes())?;
+        self.record_edit(edit);
+        self.select_key(new_key.as_bytes());
+
+        Ok(())
+    }
+
+    async fn load_selected_value(&self) -> Result<()> {
+        let dialog = gtk::FileDialog::builder()
+            .title(gettext("Load Value"))
+            .modal(true)
+            .build();
+
+        let file = dialog.open_future(Some(self)).await?;
+        let (contents, _) = file.load_contents_future().await?;
+
+        self.put_selected_value(contents.to_vec())
+    }
+
+    /// Opens the value of the selected item in the default app for its
+    /// format, offering to put it back whenever the app saves it.
+    async fn edit_externally(&self) -> Result<()> {
+        let item = self.selected_item().context("No selected item")?;
+        let db = self.selected_db().context("No selected db")?;
+        self.edit_item_externally(db, &item).await
+    }
+
+    /// Like [`Self::edit_externally`], but for `item` of `db`.
+    async fn edit_item_externally(&self, db: Database, item: &DatabaseItem) -> Result<()> {
+        let data = item.data();
+
+        // So the default app is an editor for the format
+        let extension = match ValueFormat::detect(&data) {
+            ValueFormat::Json => "json",
+            ValueFormat::Empty | ValueFormat::Text => "txt",
+            format => format.image_extension().unwrap_or("bin"),
+        };
+        let file_name = format!("value.{}", extension);
+        let path = utils::write_to_temp_dir(&data, &file_name)?.join(&file_name);
+        let file = gio::File::for_path(&path);
+
+        let monitor = file.monitor_file(gio::FileMonitorFlags::NONE, gio::Cancellable::NONE)?;
+        let external_edit = Rc::new(ExternalEdit {
+            db,
+            key: item.key().to_vec(),
+            value: RefCell::new(data.to_vec()),
+            path,
+            last_value: RefCell::new(data.to_vec()),
+            is_asking: Cell::new(false),
+        });
+        monitor.connect_changed(clone!(@weak self as obj => move |_, _, _, event| {
+            // Editors may replace the file instead of writing to it
+            if matches!(
+                event,
+                gio::FileMonitorEvent::ChangesDoneHint | gio::FileMonitorEvent::Created
+            ) {
+                glib::spawn_future_local(
+                    clone!(@weak obj, @strong external_edit => async move {
+                        obj.offer_external_edit(&external_edit).await;
+                    }),
+                );
+            }
+        }));
+        self.imp().external_edit_monitors.borrow_mut().push(monitor);
+
+        gtk::FileLauncher::new(Some(&file))
+            .launch_future(Some(self))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Asks whether to put the value saved in the file of `external_edit`,
+    /// unless it is unchanged or already being asked about.
+    async fn offer_external_edit(&self, external_edit: &ExternalEdit) {
+        if external_edit.is_asking.get() {
+            return;
+        }
+
+        let is_changed = fs::read(&external_edit.path)
+            .is_ok_and(|value| value != *external_edit.last_value.borrow());
+        if !is_changed {
+            return;
+        }
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("Apply External Edit?"))
+            .body(gettext_f(
+                "The value of “{key}” was saved in the editor",
+                &[(
+                    "key",
+                    &self
+                        .non_printable_display()
+                        .render(&external_edit.key, self.key_encoding()),
+                )],
+            ))
+            .transient_for(self)
+            .modal(true)
+            .default_response("apply")
+            .close_response("discard")
+            .build();
+        dialog.add_responses(&[
+            ("discard", &gettext("_Discard")),
+            ("apply", &gettext("_Apply")),
+        ]);
+        dialog.set_response_appearance("apply", adw::ResponseAppearance::Suggested);
+
+        external_edit.is_asking.set(true);
+        let response = dialog.choose_future().await;
+        external_edit.is_asking.set(false);
+
+        // Read again, as it may have been saved again while asking
+        let value = match fs::read(&external_edit.path) {
+            Ok(value) => value,
+            Err(err) => {
+                tracing::error!("Failed to read edited value: {:?}", &err);
+                self.add_error_toast(&gettext("Failed to read edited value"), &err.into());
+                return;
+            }
+        };
+        external_edit.last_value.replace(value.clone());
+
+        if response != "apply" {
+            return;
+        }
+
+        let old_value = external_edit.value.borrow().clone();
+        match self.put_value(
+            &external_edit.db,
+            &external_edit.key,
+            &old_value,
+            value.clone(),
+        ) {
+            Ok(()) => {
+                external_edit.value.replace(value);
+            }
+            Err(err) => {
+                tracing::error!("Failed to set value: {:?}", &err);
+                self.add_error_toast(&gettext("Failed to set value"), &err);
+            }
+        }
+    }
+
+    /// Replaces the value of the selected item, or stages it when staging
+    /// changes.
+    fn put_selected_value(&self, value: Vec<u8>) -> Result<()> {
+        let item = self.selected_item().context("No selected item")?;
+        let db = self.selected_db().context("No selected db")?;
+
+        self.put_value(&db, &item.key(), &item.data(), value)
+    }
+
+    /// Replaces `old_value` of `key` in `db`, which is only that duplicate of
+    /// a DUPSORT key, or stages it when staging changes.
+    fn put_value(&self, db: &Database, key: &[u8], old_value: &[u8], value: Vec<u8>) -> Result<()> {
+        if self.stage_changes() {
+            let is_dup_sort = db.flags().contains(DatabaseFlags::DUP_SORT);
+            self.stage(
+                db,
+                [PendingChange::Put {
+                    key: key.to_vec(),
+                    value,
+                    duplicate: is_dup_sort.then(|| old_value.to_vec()),
+                }],
+            );
+            return Ok(());
+        }
+
+        let edit = db.put(key, old_value, &value)?;
+        self.record_edit(edit);
+
+        Ok(())
+    }
+
+    fn save_window_size(&self) -> Result<(), glib::BoolError> {
+        let settings = gio::Settings::new(APP_ID);
+
+        let (width, height) = self.default_size();
+
+        settings.set_int("window-width", width)?;
+        settings.set_int("window-height", height)?;
+
+        settings.set_boolean("is-maximized", self.is_maximized())?;
+
+        Ok(())
+    }
+
+    fn load_window_size(&self) {
+        let settings = gio::Settings::new(APP_ID);
+
+        let width = settings.int("window-width");
+        let height = settings.int("window-height");
+        let is_maximized = settings.boolean("is-maximized");
+
+        self.set_default_size(width, height);
+
+        if is_maximized {
+            self.maximize();
+        }
+    }
+
+    fn key_text(&self, item: Option<glib::Object>) -> String {
+        let Some(item) = item else {
+            return "<None>".to_string();
+        };
+
+        let item = item.downcast_ref::<DatabaseItem>().unwrap();
+        if let Some(text) = self.key_rendering().render(&item.key()) {
+            return text;
+        }
+
+        let is_integer_key = self
+            .selected_db()
+            .is_some_and(|db| db.flags().contains(DatabaseFlags::INTEGER_KEY));
+        let key = item.key();
+        match self
+            .key_display_mode()
+            .integer(key.as_ref(), is_integer_key)
+        {
+            Some(integer) => integer.to_string(),
+            None => self
+                .non_printable_display()
+                .render(key.as_ref(), self.key_encoding()),
+        }
+    }
+
+    fn value_text(&self, item: Option<glib::Object>) -> String {
+        let Some(item) = item else {
+            return "<None>".to_string();
+        };
+
+        let item = item.downcast_ref::<DatabaseItem>().unwrap();
+        let data = item.data();
+        let preview_len = self.preview_length() as usize;
+
+        // Decoded values are only known to fit once decoded
+        if self.value_rendering() == CellRendering::Decoded {
+            if let Some(text) = self.value_rendering().render(&data) {
+                let mut chars = text.chars();
+                let mut preview = chars.by_ref().take(preview_len).collect::<String>();
+                if chars.next().is_some() {
+                    preview.push('…');
+                }
+                return preview;
+            }
+        }
+
+        let preview = &data[..data.len().min(preview_len)];
+        let mut text = match self.value_rendering() {
+            CellRendering::Hex => non_printable_display::to_hex(preview),
+            _ => self
+                .non_printable_display()
+                .render(preview, self.value_encoding()),
+        };
+        if preview.len() < data.len() {
+            text.push('…');
+        }
+        text
+    }
+
+    /// Updates the entry, matching and selection counts, the load duration, and
+    /// the env size.
+    fn update_status_bar(&self) {
+        let imp = self.imp();
+
+        let n_entries = self.selected_db().map_or(0, |db| db.n_entries());
+        imp.entries_label.set_label(&ngettext_f(
+            "{n} entry",
+            "{n} entries",
+            n_entries,
+            &[("n", &n_entries.to_string())],
+        ));
+
+        let n_matching = imp.filter_model.n_items();
+        imp.matching_label.set_label(&ngettext_f(
+            "{n} matching",
+            "{n} matching",
+            n_matching,
+            &[("n", &n_matching.to_string())],
+        ));
+        imp.matching_label.set_visible(self.is_filtered());
+
+        let n_selected = imp.column_view_model.selection().size() as u32;
+        imp.selection_label.set_label(&ngettext_f(
+            "{n} selected",
+            "{n} selected",
+            n_selected,
+            &[("n", &n_selected.to_string())],
+        ));
+
+        let txn_id = self.selected_db().map(|db| db.txn_id());
+        if let Some(txn_id) = txn_id {
+            imp.snapshot_label
+                .set_label(&gettext_f("Txn {id}", &[("id", &txn_id.to_string())]));
+            imp.snapshot_label.set_tooltip_text(Some(&gettext_f(
+                "Entries are shown as of transaction {id}. Searches and exports read the \
+                same transaction, and fail if the environment changed since.",
+                &[("id", &txn_id.to_string())],
+            )));
+        }
+        imp.snapshot_label.set_visible(txn_id.is_some());
+
+        let load_duration = self.selected_db().map(|db| db.load_duration());
+        imp.load_duration_label.set_label(
+            &load_duration
+                .map(|duration| {
+                    gettext_f(
+                        "Loaded in {duration} ms",
+                        &[("duration", &duration.as_millis().to_string())],
+                    )
+                })
+                .unwrap_or_default(),
+        );
+
+        let env_size = imp
+            .env
+            .borrow()
+            .as_ref()
+            .and_then(|env| env.real_disk_size().ok());
+        imp.env_size_label
+            .set_label(&env_size.map(glib::format_size).unwrap_or_default());
+    }
+
+    /// Shows `icon` with the note of `item` as its tooltip if it has one.
+    fn update_note_icon(&self, icon: &gtk::Image, item: Option<glib::Object>) {
+        let note = item
+            .and_downcast::<DatabaseItem>()
+            .and_then(|item| self.note(&item));
+        icon.set_tooltip_text(note.as_deref());
+        icon.set_visible(note.is_some());
+    }
+
+    /// Highlights `widget` based on how `item` changed since the previous
+    /// reload, if changes are shown.
+    fn update_change_highlight(&self, widget: &gtk::Widget, item: Option<glib::Object>) {
+        for kind in [DiffKind::Added, DiffKind::Removed, DiffKind::Changed] {
+            widget.remove_css_class(kind.css_class().unwrap());
+        }
+
+        if !self.show_changes() {
+            return;
+        }
+
+        if let Some(css_class) = item
+            .and_downcast::<DatabaseItem>()
+            .and_then(|item| item.change().css_class())
+        {
+            widget.add_css_class(css_class);
+        }
+    }
+
+    /// Colors `widget` by the first highlight rule that `item` matches,
+    /// returning the badge of the rule.
+    fn update_rule_highlight(
+        &self,
+        widget: &gtk::Widget,
+        item: Option<&DatabaseItem>,
+    ) -> Option<String> {
+        for color in HighlightColor::ALL {
+            widget.remove_css_class(color.css_class());
+        }
+
+        let item = item?;
+        let imp = self.imp();
+        let rules = imp.highlight_rules.borrow();
+        let (key, data) = (item.key(), item.data());
+        let rule = rules.find(&key, &data, || imp.detail_pane.decode(&key, &data))?;
+        widget.add_css_class(rule.color.css_class());
+        Some(rule.badge.clone())
+    }
+
+    /// Creates a column showing the size of the part of each entry that `len`
+    /// returns the length of.
+    fn size_column(
+        &self,
+        title: &str,
+        id: &str,
+        len: fn(&DatabaseItem) -> usize,
+    ) -> gtk::ColumnViewColumn {
+        let factory = gtk::SignalListItemFactory::new();
+        factory.connect_setup(clone!(@weak self as obj => move |_, list_item| {
+            let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
+            let label = gtk::Label::builder()
+                .xalign(1.0)
+                .valign(gtk::Align::Start)
+                .css_classes(["numeric", "dim-label"])
+                .build();
+            obj.setup_row_context_menu(label.upcast_ref(), list_item);
+            list_item.set_child(Some(&label));
+        }));
+        factory.connect_bind(move |_, list_item| {
+            let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
+            let label = list_item.child().and_downcast::<gtk::Label>().unwrap();
+            let item = list_item.item().and_downcast::<DatabaseItem>().unwrap();
+            label.set_label(&glib::format_size(len(&item) as u64));
+        });
+        let column = gtk::ColumnViewColumn::new(Some(title), Some(factory));
+        column.set_id(Some(id));
+        column.set_header_menu(Some(&*self.imp().column_menu));
+        column.set_resizable(true);
+        column
+    }
+
+    /// Replaces the columns of decoded fields with those pinned for the
+    /// selected database.
+    fn update_field_columns(&self) {
+        let imp = self.imp();
+
+        for column in imp.field_columns.take() {
+            imp.column_view.remove_column(&column);
+        }
+
+        let Some(db) = self.selected_db() else {
+            return;
+        };
+
+        let pinned_fields = imp.pinned_fields.borrow();
+        let Some(path_texts) = pinned_fields.get(db.name().unwrap_or_default()) else {
+            return;
+        };
+
+        let columns = path_texts
+            .iter()
+            .filter_map(|path_text| match query::parse_path(path_text) {
+                Ok(path) => Some(self.field_column(path_text, path)),
+                Err(err) => {
+                    tracing::warn!("Skipping invalid pinned field `{}`: {}", path_text, err);
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        for column in &columns {
+            imp.column_view.append_column(column);
+        }
+        imp.field_columns.replace(columns);
+    }
+
+    /// Creates a column showing the decoded field at `path`, whose text is
+    /// `path_text`.
+    fn field_column(&self, path_text: &str, path: Vec<PathSegment>) -> gtk::ColumnViewColumn {
+        let factory = gtk::SignalListItemFactory::new();
+        factory.connect_setup(clone!(@weak self as obj => move |_, list_item| {
+            let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
+            let label = gtk::Label::builder()
+                .xalign(0.0)
+                .valign(gtk::Align::Start)
+                .ellipsize(gtk::pango::EllipsizeMode::End)
+                .build();
+            obj.setup_row_context_menu(label.upcast_ref(), list_item);
+            list_item.set_child(Some(&label));
+        }));
+        factory.connect_bind(clone!(@weak self as obj => move |_, list_item| {
+            let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
+            let label = list_item.child().and_downcast::<gtk::Label>().unwrap();
+            let item = list_item.item().and_downcast::<DatabaseItem>().unwrap();
+            // Only decoded for the rows being shown
+            let text = obj
+                .imp()
+                .detail_pane
+                .decode(&item.key(), &item.data())
+                .map(|decoded| field_text(&query::values_at(&decoded, &path)))
+                .unwrap_or_default();
+            label.set_label(&text);
+        }));
+        let title = path_text.strip_prefix("$.").unwrap_or(path_text);
+        let column = gtk::ColumnViewColumn::new(Some(title), Some(factory));
+        column.set_id(Some(&format!("field:{}", path_text)));
+        column.set_header_menu(Some(&*self.imp().column_menu));
+        column.set_resizable(true);
+        column
+    }
+
+    /// Lists the fields of the decoded selected or first value, letting the
+    /// ones shown as columns be picked.
+    async fn pin_fields(&self) -> Result<()> {
+        let imp = self.imp();
+
+        let db = self.selected_db().context("No database selected")?;
+        let db_name = db.name().unwrap_or_default().to_string();
+
+        let item = self
+            .selected_item()
+            .or_else(|| imp.filter_model.item(0).and_downcast::<DatabaseItem>());
+        let decoded = item.and_then(|item| imp.detail_pane.decode(&item.key(), &item.data()));
+
+        let pinned = imp
+            .pinned_fields
+            .borrow()
+            .get(&db_name)
+            .cloned()
+            .unwrap_or_default();
+        let mut path_texts = pinned.clone();
+        for path in decoded.as_ref().map(query::field_paths).unwrap_or_default() {
+            let path_text = query::format_path(&path);
+            if !path_texts.contains(&path_text) {
+                path_texts.push(path_text);
+            }
+        }
+
+        let list_box = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(["boxed-list"])
+            .build();
+        let check_buttons = path_texts
+            .iter()
+            .map(|path_text| {
+                let check_button = gtk::CheckButton::builder()
+                    .active(pinned.contains(path_text))
+                    .valign(gtk::Align::Center)
+                    .build();
+                let row = adw::ActionRow::builder()
+                    .title(path_text)
+                    .use_markup(false)
+                    .activatable_widget(&check_button)
+                    .build();
+                row.add_prefix(&check_button);
+                list_box.append(&row);
+                check_button
+            })
+            .collect::<Vec<_>>();
+
+        let path_entry = gtk::Entry::builder()
+            .placeholder_text(gettext("Other path, e.g. $.items[0].id"))
+            .activates_default(true)
+            .build();
+
+        let vbox = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(12)
+            .build();
+        if !check_buttons.is_empty() {
+            let scrolled_window = gtk::ScrolledWindow::builder()
+                .hscrollbar_policy(gtk::PolicyType::Never)
+                .propagate_natural_height(true)
+                .max_content_height(360)
+                .child(&list_box)
+                .build();
+            vbox.append(&scrolled_window);
+        }
+        vbox.append(&path_entry);
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("Pin Decoded Fields"))
+            .body(if decoded.is_some() {
+                gettext("Pinned fields of the decoded values are shown as columns.")
+            } else {
+                gettext(
+                    "Pinned fields of the decoded values are shown as columns. Choose a decoder \
+                    for the database to list the fields of its values.",
+                )
+            })
+            .transient_for(self)
+            .modal(true)
+            .extra_child(&vbox)
+            .default_response("pin")
+            .close_response("cancel")
+            .build();
+        dialog.add_responses(&[("cancel", &gettext("_Cancel")), ("pin", &gettext("_Pin"))]);
+        dialog.set_response_appearance("pin", adw::ResponseAppearance::Suggested);
+
+        if dialog.choose_future().await != "pin" {
+            return Ok(());
+        }
+
+        let mut new_pinned = path_texts
+            .into_iter()
+            .zip(&check_buttons)
+            .filter(|(_, check_button)| check_button.is_active())
+            .map(|(path_text, _)| path_text)
+            .collect::<Vec<_>>();
+
+        let other_path_text = path_entry.text().trim().to_string();
+        if !other_path_text.is_empty() {
+            match query::parse_path(&other_path_text) {
+                Ok(path) => {
+                    let path_text = query::format_path(&path);
+                    if !new_pinned.contains(&path_text) {
+                        new_pinned.push(path_text);
+                    }
+                }
+                Err(err) => self.add_message_toast(&gettext_f(
+                    "Invalid path: {error}",
+                    &[("error", &err.to_string())],
+                )),
+            }
+        }
+
+        {
+            let mut pinned_fields = imp.pinned_fields.borrow_mut();
+            if new_pinned.is_empty() {
+                pinned_fields.remove(&db_name);
+            } else {
+                pinned_fields.insert(db_name, new_pinned);
+            }
+        }
+        self.update_field_columns();
+
+        Ok(())
+    }
+
+    /// Creates the label that shows the text of a key or value cell, which is
+    /// only selectable and editable in the detail pane.
+    fn cell_label(&self, list_item: &gtk::ListItem) -> gtk::Label {
+        let label = gtk::Label::builder()
+            .hexpand(true)
+            .xalign(0.0)
+            .yalign(0.0)
+            .ellipsize(gtk::pango::EllipsizeMode::End)
+            .build();
+
+        let update_style = |obj: &Self, label: &gtk::Label| {
+            if obj.monospace() {
+                label.add_css_class("monospace");
+            } else {
+                label.remove_css_class("monospace");
+            }
+            obj.cell_wrap_mode().apply(label, obj.max_cell_lines());
+        };
+        update_style(self, &label);
+        for property in ["monospace", "cell-wrap-mode", "max-cell-lines"] {
+            let handler_id = self.connect_notify_local(
+                Some(property),
+                clone!(@weak label => move |obj, _| update_style(obj, &label)),
+            );
+            self.track_cell_handler(list_item, self, handler_id);
+        }
+
+        label
+    }
+
+    /// Remembers that `handler_id` of `object` updates the cell of
+    /// `list_item`, so it is disconnected when the cell is torn down.
+    fn track_cell_handler(
+        &self,
+        list_item: &gtk::ListItem,
+        object: &impl IsA<glib::Object>,
+        handler_id: glib::SignalHandlerId,
+    ) {
+        self.imp()
+            .cell_handler_ids
+            .borrow_mut()
+            .entry(list_item.clone())
+            .or_default()
+            .push((object.clone().upcast(), handler_id));
+    }
+
+    /// Disconnects the handlers that update the cell of `list_item`.
+    fn teardown_cell(&self, list_item: &gtk::ListItem) {
+        let handler_ids = self.imp().cell_handler_ids.borrow_mut().remove(list_item);
+        for (object, handler_id) in handler_ids.into_iter().flatten() {
+            object.disconnect(handler_id);
+        }
+    }
+
+    /// Shows the key of the item of `list_item` in its cell, or clears the
+    /// cell if it has none.
+    fn update_key_cell(&self, list_item: &gtk::ListItem) {
+        let Some(hbox) = list_item.child() else {
+            return;
+        };
+        let label = hbox.first_child().and_downcast::<gtk::Label>().unwrap();
+        let badge = label.next_sibling().and_downcast::<gtk::Label>().unwrap();
+        let note_icon = hbox.last_child().and_downcast::<gtk::Image>().unwrap();
+
+        let item = list_item.item();
+        let key_text = item
+            .as_ref()
+            .map_or(String::new(), |item| self.key_text(Some(item.clone())));
+        label.set_label(&cell_rendering::clamp_lines(
+            &key_text,
+            self.max_cell_lines(),
+        ));
+        self.update_change_highlight(label.upcast_ref(), item.clone());
+        let badge_text = self
+            .update_rule_highlight(label.upcast_ref(), item.and_downcast_ref())
+            .unwrap_or_default();
+        badge.set_label(&badge_text);
+        badge.set_visible(!badge_text.is_empty());
+
+        // The row is announced by its key, then the rest of what the cells show
+        list_item.set_accessible_label(&key_text);
+        list_item.set_accessible_description(
+            &item
+                .and_downcast_ref::<DatabaseItem>()
+                .map_or(String::new(), |item| {
+                    self.row_description(item, &badge_text)
+                }),
+        );
+
+        self.update_note_icon(&note_icon, item);
+    }
+
+    /// Describes the value of `item` for screen readers, along with the
+    /// `badge` and whether it has a note, which are only shown as icons.
+    fn row_description(&self, item: &DatabaseItem, badge: &str) -> String {
+        let data = item.data();
+        let mut parts = vec![gettext_f(
+            "{format} value of {size}",
+            &[
+                ("format", &item.format().label()),
+                ("size", &glib::format_size(data.len() as u64)),
+            ],
+        )];
+        if !badge.is_empty() {
+            parts.push(badge.to_string());
+        }
+        if self.note(item).is_some() {
+            parts.push(gettext("has a note"));
+        }
+        parts.join(", ")
+    }
+
+    /// Shows the value of the item of `list_item` and its format in its cell,
+    /// or clears the cell if it has none.
+    fn update_value_cell(&self, list_item: &gtk::ListItem) {
+        let Some(hbox) = list_item.child() else {
+            return;
+        };
+        let label = hbox.first_child().and_downcast::<gtk::Label>().unwrap();
+        let format_badge = hbox.last_child().and_downcast::<gtk::Label>().unwrap();
+
+        let item = list_item.item().and_downcast::<DatabaseItem>();
+        label.set_label(&item.as_ref().map_or(String::new(), |item| {
+            cell_rendering::clamp_lines(
+                &self.value_text(Some(item.clone().upcast())),
+                self.max_cell_lines(),
+            )
+        }));
+        self.update_change_highlight(label.upcast_ref(), item.clone().map(|item| item.upcast()));
+        self.update_rule_highlight(label.upcast_ref(), item.as_ref());
+        format_badge.set_label(&item.map_or(String::new(), |item| item.format().label()));
+    }
+
+    fn setup_view(&self) {
+        let imp = self.imp();
+
+        let key_column_factory = gtk::SignalListItemFactory::new();
+        key_column_factory.connect_setup(clone!(@weak self as obj => move |_, list_item| {
+            let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
+            let label = obj.cell_label(list_item);
+            let badge = gtk::Label::builder()
+                .valign(gtk::Align::Start)
+                .visible(false)
+                .css_classes(["format-badge"])
+                .build();
+            let note_icon = gtk::Image::builder()
+                .icon_name("document-edit-symbolic")
+                .valign(gtk::Align::Start)
+                .visible(false)
+                .build();
+            let hbox = gtk::Box::builder().spacing(6).build();
+            hbox.append(&label);
+            hbox.append(&badge);
+            hbox.append(&note_icon);
+            // This runs after the note is saved, as the window connects first
+            let detail_pane = obj.imp().detail_pane.get();
+            let handler_id = detail_pane.connect_note_notify(
+                clone!(@weak obj, @weak list_item => move |_| {
+                    obj.update_key_cell(&list_item);
+                }),
+            );
+            obj.track_cell_handler(list_item, &detail_pane, handler_id);
+            for property in [
+                "show-changes",
+                "key-display-mode",
+                "key-encoding",
+                "key-rendering",
+                "non-printable-display",
+                "max-cell-lines",
+                "highlight-rules",
+            ] {
+                let handler_id = obj.connect_notify_local(
+                    Some(property),
+                    clone!(@weak list_item => move |obj, _| {
+                        obj.update_key_cell(&list_item);
+                    }),
+                );
+                obj.track_cell_handler(list_item, &obj, handler_id);
+            }
+            obj.setup_row_context_menu(label.upcast_ref(), list_item);
+            list_item.set_child(Some(&hbox));
+        }));
+        key_column_factory.connect_bind(clone!(@weak self as obj => move |_, list_item| {
+            obj.update_key_cell(list_item.downcast_ref().unwrap());
+        }));
+        key_column_factory.connect_unbind(|_, list_item| {
+            clear_cell(list_item.downcast_ref().unwrap());
+        });
+        key_column_factory.connect_teardown(clone!(@weak self as obj => move |_, list_item| {
+            obj.teardown_cell(list_item.downcast_ref().unwrap());
+        }));
+        let key_column = gtk::ColumnViewColumn::new(Some("Key"), Some(key_column_factory));
+        key_column.set_id(Some("key"));
+        key_column.set_header_menu(Some(&*imp.key_column_menu));
+        key_column.set_resizable(true);
+        key_column.set_expand(true);
+        imp.column_view.insert_column(0, &key_column);
+
+        let val_column_factory = gtk::SignalListItemFactory::new();
+        val_column_factory.connect_setup(clone!(@weak self as obj => move |_, list_item| {
+            let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
+            let label = obj.cell_label(list_item);
+            let format_badge = gtk::Label::builder()
+                .valign(gtk::Align::Start)
+                .css_classes(["format-badge"])
+                .build();
+            let hbox = gtk::Box::builder().spacing(6).build();
+            hbox.append(&label);
+            hbox.append(&format_badge);
+            for property in [
+                "show-changes",
+                "value-encoding",
+                "value-rendering",
+                "non-printable-display",
+                "preview-length",
+                "max-cell-lines",
+                "highlight-rules",
+            ] {
+                let handler_id = obj.connect_notify_local(
+                    Some(property),
+                    clone!(@weak list_item => move |obj, _| {
+                        obj.update_value_cell(&list_item);
+                    }),
+                );
+                obj.track_cell_handler(list_item, &obj, handler_id);
+            }
+            obj.setup_row_context_menu(hbox.upcast_ref(), list_item);
+            list_item.set_child(Some(&hbox));
+        }));
+        val_column_factory.connect_bind(clone!(@weak self as obj => move |_, list_item| {
+            obj.update_value_cell(list_item.downcast_ref().unwrap());
+        }));
+        val_column_factory.connect_unbind(|_, list_item| {
+            clear_cell(list_item.downcast_ref().unwrap());
+        });
+        val_column_factory.connect_teardown(clone!(@weak self as obj => move |_, list_item| {
+            obj.teardown_cell(list_item.downcast_ref().unwrap());
+        }));
+        let val_column = gtk::ColumnViewColumn::new(Some("Value"), Some(val_column_factory));
+        val_column.set_id(Some("value"));
+        val_column.set_header_menu(Some(&*imp.value_column_menu));
+        val_column.set_resizable(true);
+        val_column.set_expand(true);
+        imp.column_view.insert_column(1, &val_column);
+        self.add_action(&gio::PropertyAction::new(
+            "show-value-column",
+            &val_column,
+            "visible",
+        ));
+
+        let checksum_column_factory = gtk::SignalListItemFactory::new();
+        checksum_column_factory.connect_setup(clone!(@weak self as obj => move |_, list_item| {
+            let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
+            let label = gtk::Label::builder()
+                .xalign(0.0)
+                .valign(gtk::Align::Start)
+                .css_classes(["monospace", "dim-label"])
+                .build();
+            obj.setup_row_context_menu(label.upcast_ref(), list_item);
+            list_item.set_child(Some(&label));
+        }));
+        checksum_column_factory.connect_bind(|_, list_item| {
+            let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
+            let label = list_item.child().and_downcast::<gtk::Label>().unwrap();
+            let item = list_item.item().and_downcast::<DatabaseItem>().unwrap();
+            // Only computed for the rows being shown
+            label.set_label(&format!("{:08x}", item.checksum()));
+        });
+        let checksum_column =
+            gtk::ColumnViewColumn::new(Some("CRC32"), Some(checksum_column_factory));
+        checksum_column.set_id(Some("checksum"));
+        checksum_column.set_header_menu(Some(&*imp.column_menu));
+        checksum_column.set_resizable(true);
+        imp.column_view.insert_column(2, &checksum_column);
+        let settings = gio::Settings::new(APP_ID);
+        settings
+            .bind("show-checksum-column", &checksum_column, "visible")
+            .build();
+        self.add_action(&settings.create_action("show-checksum-column"));
+
+        let key_size_column = self.size_column("Key Size", "key-size", |item| item.key().len());
+        imp.column_view.insert_column(3, &key_size_column);
+        let value_size_column =
+            self.size_column("Value Size", "value-size", |item| item.data().len());
+        imp.column_view.insert_column(4, &value_size_column);
+        for column in [&key_size_column, &value_size_column] {
+            settings
+                .bind("show-size-columns", column, "visible")
+                .build();
+        }
+        self.add_action(&settings.create_action("show-size-columns"));
+
+        let filter = gtk::CustomFilter::new(
+            clone!(@weak self as obj => @default-return true, move |item| {
+                let imp = obj.imp();
+
+                let is_query_match = imp.query.borrow().as_ref().is_none_or(|query| {
+                    let item = item.downcast_ref::<DatabaseItem>().unwrap();
+                    let (key, data) = (item.key(), item.data());
+                    query.matches(&key, &data, || imp.detail_pane.decode(&key, &data))
+                });
+                let text = imp.search_entry.text().to_lowercase();
+                is_query_match
+                    && (text.is_empty()
+                        || obj.key_text(Some(item.clone())).to_lowercase().contains(&text))
+            }),
+        );
+        imp.filter_model.set_filter(Some(&filter));
+        imp.search_bar.connect_entry(&*imp.search_entry);
+        imp.search_entry.connect_search_changed(
+            clone!(@weak self as obj, @weak filter => move |search_entry| {
+                filter.changed(gtk::FilterChange::Different);
+                obj.set_page(0);
+                obj.update_status_bar();
+                let has_text = !search_entry.text().is_empty();
+                obj.action_set_enabled("win.search-entire-db", has_text);
+                obj.action_set_enabled("win.search-all-dbs", has_text);
+                obj.action_set_enabled("win.save-search", has_text);
+                obj.action_set_enabled("win.count-matching", obj.is_filtered());
+                obj.cancel_count();
+                obj.update_breadcrumb();
+            }),
+        );
+        self.action_set_enabled("win.search-entire-db", false);
+        self.action_set_enabled("win.search-all-dbs", false);
+        self.action_set_enabled("win.save-search", false);
+        self.action_set_enabled("win.count-matching", false);
+        imp.query_entry
+            .connect_changed(clone!(@weak self as obj, @weak filter => move |_| {
+                obj.update_query();
+                filter.changed(gtk::FilterChange::Different);
+                obj.set_page(0);
+                obj.update_status_bar();
+                obj.action_set_enabled("win.count-matching", obj.is_filtered());
+                obj.cancel_count();
+                obj.update_breadcrumb();
+            }));
+
+        imp.drop_down
+            .set_expression(Some(&gtk::ClosureExpression::new::<glib::GString>(
+                &[] as &[gtk::Expression],
+                closure!(|list_item: DatabaseItem| {
+                    String::from_utf8_lossy(list_item.key().as_ref()).to_string()
+                }),
+            )));
+        imp.drop_down
+            .connect_selected_item_notify(clone!(@weak self as obj => move |drop_down| {
+                let imp = obj.imp();
+                let backend = imp.backend.borrow().clone();
+
+                if let Some(backend) = backend {
+                    let selected_item = drop_down.selected_item();
+
+                    imp.filter_model.set_model(gio::ListModel::NONE);
+                    obj.cancel_count();
+                    // Matches of another database can't be selected, unless
+                    // they are of all databases
+                    if !imp.search_results_pane.is_env_wide() {
+                        imp.search_results_pane.close();
+                    }
 
                     if let Some(item) = selected_item {
                         let item = item.downcast_ref::<DatabaseItem>().unwrap();
                         let item_key = item.key();
-                        let db_name = std::str::from_utf8(&item_key).unwrap();
+                        let db = database::name_from_bytes(&item_key).and_then(|db_name| {
+                            // Only LMDB databases can be written and pinned
+                            match imp.env.borrow().as_ref() {
+                                Some(env) => Database::load(env, Some(db_name)),
+                                None => Database::load_from(&backend, Some(db_name)),
+                            }
+                        });
 
-                        match Database::load(env, Some(db_name)) {
+                        match db {
                             Ok(db) => {
-                                imp.column_view_model.set_model(Some(&db));
+                                db.set_show_changes(obj.show_changes());
+                                db.connect_items_changed(
+                                    clone!(@weak obj => move |db, _, _, _| {
+                                        obj.record_key_history(db);
+                                        obj.update_key_tree();
+                                    }),
+                                );
+                                imp.filter_model.set_model(Some(&db));
+                                obj.update_sorter();
+                                obj.set_page(0);
+                                obj.update_key_tree();
+                                imp.detail_pane
+                                    .set_decoder_id(obj.load_decoder_id(backend.path(), &db));
+                                obj.update_field_columns();
                             }
                             Err(err) => {
                                 tracing::error!("Failed to load db: {:?}", &err);
-                                obj.add_message_toast(&format!("Failed to load “{}”", db_name));
+                                let message = format!(
+                                    "Failed to load “{}”",
+                                    String::from_utf8_lossy(&item_key)
+                                );
+                                obj.add_error_toast(&message, &err);
                             }
                         }
                     }
@@ -262,5 +7130,661 @@ impl Window {
                     tracing::error!("No env set!");
                 }
             }));
+
+        imp.detail_pane
+            .connect_note_notify(clone!(@weak self as obj => move |detail_pane| {
+                if let Err(err) = obj.save_note(&detail_pane.note()) {
+                    tracing::error!("Failed to save note: {:?}", &err);
+                    obj.add_error_toast(&gettext("Failed to save note"), &err);
+                }
+            }));
+
+        imp.bookmarks_list_box
+            .connect_row_activated(clone!(@weak self as obj => move |_, row| {
+                obj.go_to_bookmark(row.index() as usize);
+            }));
+
+        imp.saved_searches_list_box.connect_row_activated(
+            clone!(@weak self as obj => move |_, row| {
+                obj.apply_saved_search(row.index() as usize);
+            }),
+        );
+
+        // Capture these so they aren't handled by the text views in the cells
+        let shortcut_controller = gtk::ShortcutController::new();
+        shortcut_controller.set_propagation_phase(gtk::PropagationPhase::Capture);
+        for (trigger, action_name) in [
+            ("<Control>a", "win.select-all"),
+            ("<Control>c", "win.copy-selected"),
+            ("Delete", "win.delete-selected"),
+            ("Menu", "win.show-row-menu"),
+            ("<Shift>F10", "win.show-row-menu"),
+        ] {
+            shortcut_controller.add_shortcut(gtk::Shortcut::new(
+                gtk::ShortcutTrigger::parse_string(trigger),
+                Some(gtk::NamedAction::new(action_name)),
+            ));
+        }
+        imp.column_view.add_controller(shortcut_controller);
+
+        let key_controller = gtk::EventControllerKey::new();
+        key_controller.set_propagation_phase(gtk::PropagationPhase::Capture);
+        key_controller.connect_key_pressed(
+            clone!(@weak self as obj => @default-return glib::Propagation::Proceed,
+                move |_, keyval, _, state| {
+                    if state.intersects(
+                        gdk::ModifierType::CONTROL_MASK
+                            | gdk::ModifierType::ALT_MASK
+                            | gdk::ModifierType::SUPER_MASK,
+                    ) {
+                        return glib::Propagation::Proceed;
+                    }
+
+                    let Some(c) = keyval.to_unicode().filter(|c| !c.is_control()) else {
+                        return glib::Propagation::Proceed;
+                    };
+
+                    // Let space toggle the selection unless it continues a prefix
+                    if c == ' ' && obj.imp().type_ahead_prefix.borrow().is_empty() {
+                        return glib::Propagation::Proceed;
+                    }
+
+                    obj.type_ahead(c);
+                    glib::Propagation::Stop
+                }
+            ),
+        );
+        imp.column_view.add_controller(key_controller);
+
+        imp.filter_model
+            .connect_items_changed(clone!(@weak self as obj => move |_, _, _, _| {
+                // Clamp to the last page in case the shown one no longer exists
+                obj.set_page(obj.page());
+            }));
+        imp.column_view_model.connect_items_changed(
+            clone!(@weak self as obj => move |_, _, _, _| {
+                obj.handle_selection_changed();
+            }),
+        );
+        imp.column_view_model.connect_selection_changed(
+            clone!(@weak self as obj => move |_, _, _| {
+                obj.handle_selection_changed();
+            }),
+        );
+        self.handle_selection_changed();
+
+        imp.column_view
+            .connect_activate(clone!(@weak self as obj => move |_, _| {
+                if obj.collapsed() {
+                    obj.imp().navigation_view.push_by_tag("details");
+                }
+            }));
+
+        imp.detail_pane
+            .connect_decoder_id_notify(clone!(@weak self as obj => move |detail_pane| {
+                let imp = obj.imp();
+                let backend = imp.backend.borrow();
+
+                if let (Some(backend), Some(db)) = (backend.as_ref(), obj.selected_db()) {
+                    obj.save_decoder_id(backend.path(), &db, &detail_pane.decoder_id());
+                }
+                obj.update_field_columns();
+
+                // Paths in the query reference fields of the decoded values
+                if imp.query.borrow().is_some() {
+                    if let Some(filter) = imp.filter_model.filter() {
+                        filter.changed(gtk::FilterChange::Different);
+                    }
+                }
+            }));
+    }
+
+    /// Shows the row menu when `widget`, the cell of `list_item`, is
+    /// right-clicked, selecting its row first.
+    fn setup_row_context_menu(&self, widget: &gtk::Widget, list_item: &gtk::ListItem) {
+        let gesture = gtk::GestureClick::builder()
+            .button(gdk::BUTTON_SECONDARY)
+            .build();
+        gesture.connect_pressed(
+            clone!(@weak self as obj, @weak widget, @weak list_item => move |gesture, _, x, y| {
+                gesture.set_state(gtk::EventSequenceState::Claimed);
+
+                let imp = obj.imp();
+                let position = list_item.position();
+                if !imp.column_view_model.is_selected(position) {
+                    imp.column_view_model.select_item(position, true);
+                }
+
+                obj.popup_row_menu(&widget, x, y);
+            }),
+        );
+        widget.add_controller(gesture);
+    }
+
+    /// Shows the row menu for the selected rows at the focused cell, so its
+    /// actions can be reached with the keyboard.
+    fn show_row_menu(&self) {
+        let column_view = self.imp().column_view.upcast_ref::<gtk::Widget>();
+
+        match gtk::prelude::GtkWindowExt::focus(self).filter(|focus| focus.is_ancestor(column_view))
+        {
+            Some(focus) => self.popup_row_menu(&focus, 0.0, focus.height() as f64),
+            None => self.popup_row_menu(column_view, 0.0, 0.0),
+        }
+    }
+
+    /// Shows a palette of the commands of the menus, and of the actions
+    /// without menu items, such as switching to each of the databases.
+    fn show_command_palette(&self) {
+        let imp = self.imp();
+
+        let mut commands = vec![
+            Command::new(&gettext("Open Environment…"), "win.open-env", None),
+            Command::new(&gettext("Search Keys"), "win.search", None),
+            Command::new(&gettext("Go to Key…"), "win.go-to-key", None),
+            Command::new(&gettext("Go to Page…"), "win.go-to-page", None),
+            Command::new(&gettext("Next Page"), "win.next-page", None),
+            Command::new(&gettext("Previous Page"), "win.previous-page", None),
+            Command::new(&gettext("Switch Database…"), "win.switch-database", None),
+            Command::new(&gettext("Next Database"), "win.next-database", None),
+            Command::new(&gettext("Previous Database"), "win.previous-database", None),
+            Command::new(&gettext("Write Mode"), "win.write-mode", None),
+            Command::new(&gettext("Undo"), "win.undo", None),
+            Command::new(&gettext("Redo"), "win.redo", None),
+            Command::new(&gettext("Commit Changes"), "win.commit-changes", None),
+            Command::new(&gettext("Discard Changes"), "win.discard-changes", None),
+        ];
+        commands.extend(Command::from_menu(&*imp.open_menu, None));
+        commands.extend(Command::from_menu(&*imp.reload_menu, None));
+        commands.extend(Command::from_menu(&*imp.primary_menu, None));
+        commands.extend(Command::from_menu(&*imp.row_menu, None));
+        commands.extend(Command::from_menu(
+            &*imp.key_column_menu,
+            Some(&gettext("Key Column")),
+        ));
+        commands.extend(Command::from_menu(
+            &*imp.value_column_menu,
+            Some(&gettext("Value Column")),
+        ));
+        commands.extend(Command::from_menu(&*imp.column_menu, None));
+
+        if let Some(model) = imp.drop_down.model() {
+            for item in model.iter::<DatabaseItem>().filter_map(Result::ok) {
+                let key = item.key();
+                let command = match database::name_from_bytes(&key) {
+                    Ok(db_name) => Command::new(
+                        &gettext_f("Switch to Database “{name}”", &[("name", db_name)]),
+                        "win.select-database",
+                        Some(db_name.to_variant()),
+                    ),
+                    Err(_) => Command::new(
+                        &gettext_f(
+                            "Switch to Database “{name}”",
+                            &[("name", &String::from_utf8_lossy(&key))],
+                        ),
+                        "win.select-database",
+                        None,
+                    )
+                    .with_detail(gettext("Name Not Valid UTF-8"))
+                    .insensitive(),
+                };
+                commands.push(command);
+            }
+        }
+
+        // The same items are in several of the menus
+        let mut seen = HashSet::new();
+        commands.retain(|command| {
+            seen.insert((
+                command.action_name.clone(),
+                command.target.as_ref().map(|target| target.print(true)),
+            ))
+        });
+
+        self.popup_command_palette(&gettext("Search Commands"), commands);
+    }
+
+    /// Shows a palette of the named databases with their number of entries,
+    /// switching to the one chosen.
+    fn show_database_switcher(&self) -> Result<()> {
+        let env = self.imp().env.borrow().clone().context("No env set")?;
+
+        let (n_entries, non_utf8_names) = database::n_entries_by_name(&env)?;
+        let commands = n_entries
+            .into_iter()
+            .map(|(db_name, n)| {
+                Command::new(&db_name, "win.select-database", Some(db_name.to_variant()))
+                    .with_detail(ngettext_f(
+                        "{n} entry",
+                        "{n} entries",
+                        n as u32,
+                        &[("n", &n.to_string())],
+                    ))
+            })
+            .chain(non_utf8_names.into_iter().map(|db_name| {
+                Command::new(&db_name, "win.select-database", None)
+                    .with_detail(gettext("Name Not Valid UTF-8"))
+                    .insensitive()
+            }))
+            .collect();
+        self.popup_command_palette(&gettext("Search Databases"), commands);
+
+        Ok(())
+    }
+
+    /// Shows a [`CommandPalette`] of `commands` at the top of the window.
+    fn popup_command_palette(&self, placeholder_text: &str, commands: Vec<Command>) {
+        let toast_overlay = self.imp().toast_overlay.get();
+        let palette = CommandPalette::new();
+        palette.set_parent(&toast_overlay);
+        palette.set_pointing_to(Some(&gdk::Rectangle::new(
+            toast_overlay.width() / 2,
+            0,
+            1,
+            1,
+        )));
+        palette.connect_closed(|palette| {
+            glib::idle_add_local_once(clone!(@weak palette => move || {
+                palette.unparent();
+            }));
+        });
+        palette.set_placeholder_text(placeholder_text);
+        palette.set_commands(commands);
+        palette.popup();
+    }
+
+    /// Shows the row menu pointing at `x` and `y` in `widget`.
+    fn popup_row_menu(&self, widget: &gtk::Widget, x: f64, y: f64) {
+        let popover = gtk::PopoverMenu::builder()
+            .menu_model(&*self.imp().row_menu)
+            .has_arrow(false)
+            .halign(gtk::Align::Start)
+            .pointing_to(&gdk::Rectangle::new(x as i32, y as i32, 1, 1))
+            .build();
+        popover.set_parent(widget);
+        popover.connect_closed(|popover| {
+            glib::idle_add_local_once(clone!(@weak popover => move || {
+                popover.unparent();
+            }));
+        });
+        popover.popup();
+    }
+
+    fn load_decoder_id(&self, env_path: &Path, db: &Database) -> String {
+        let settings = gio::Settings::new(APP_ID);
+
+        settings
+            .value("database-decoders")
+            .get::<HashMap<String, HashMap<String, String>>>()
+            .unwrap()
+            .get(env_path.to_string_lossy().as_ref())
+            .and_then(|decoders| decoders.get(db.name().unwrap_or_default()))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn save_decoder_id(&self, env_path: &Path, db: &Database, decoder_id: &str) {
+        let settings = gio::Settings::new(APP_ID);
+
+        let mut env_decoders = settings
+            .value("database-decoders")
+            .get::<HashMap<String, HashMap<String, String>>>()
+            .unwrap();
+        let decoders = env_decoders
+            .entry(env_path.to_string_lossy().to_string())
+            .or_default();
+        let db_name = db.name().unwrap_or_default().to_string();
+
+        if decoder_id.is_empty() {
+            decoders.remove(&db_name);
+        } else {
+            decoders.insert(db_name, decoder_id.to_string());
+        }
+
+        if let Err(err) = settings.set_value("database-decoders", &env_decoders.to_variant()) {
+            tracing::warn!("Failed to save database decoder: {:?}", &err);
+        }
+    }
+}
+
+/// Clears the text of a key or value cell, so rows that are scrolled away
+/// don't keep rendered text around.
+/// Describes the header of `page`, which was read as page `number`, and the
+/// offset, key, and value of each of its entries.
+fn describe_page(page: &Page, number: u64) -> String {
+    /// Number of bytes of keys and data shown, which is enough to tell them
+    /// apart
+    const MAX_SHOWN_LEN: usize = 32;
+
+    let escape = |bytes: &[u8]| {
+        let shown = bytes[..bytes.len().min(MAX_SHOWN_LEN)].escape_ascii();
+        if bytes.len() > MAX_SHOWN_LEN {
+            format!("\"{}\"…", shown)
+        } else {
+            format!("\"{}\"", shown)
+        }
+    };
+
+    let mut lines = vec![
+        gettext_f("Page: {number}", &[("number", &number.to_string())]),
+        gettext_f(
+            "Flags: {flags}",
+            &[("flags", &page.flag_names().join(" | "))],
+        ),
+    ];
+    if page.number() != number {
+        lines.push(gettext_f(
+            "Page number in header: {number}, which doesn't match, so this is likely a \
+             continuation of an overflow page or unused",
+            &[("number", &page.number().to_string())],
+        ));
+    }
+
+    match page.kind() {
+        PageKind::Meta => {
+            lines.push(gettext("Meta page, whose fields are shown in Meta Pages"));
+            return lines.join("\n");
+        }
+        PageKind::Overflow => {
+            lines.push(gettext_f(
+                "Overflow of {n} pages",
+                &[("n", &page.n_overflow_pages().to_string())],
+            ));
+            return lines.join("\n");
+        }
+        PageKind::Unknown => return lines.join("\n"),
+        PageKind::Branch | PageKind::Leaf | PageKind::Leaf2 => {}
+    }
+
+    lines.push(gettext_f(
+        "Free space: {size} bytes (lower {lower}, upper {upper})",
+        &[
+            ("size", &page.free_space().to_string()),
+            ("lower", &page.lower().to_string()),
+            ("upper", &page.upper().to_string()),
+        ],
+    ));
+    lines.push(gettext_f(
+        "Entries: {n}",
+        &[("n", &page.n_keys().to_string())],
+    ));
+    lines.push(String::new());
+
+    match page.nodes() {
+        Ok(nodes) => {
+            for (index, node) in nodes.iter().enumerate() {
+                let value = match node.value {
+                    NodeValue::Child(child) => format!("→ page {}", child),
+                    NodeValue::Data(data) => format!("= {} ({} B)", escape(data), data.len()),
+                    NodeValue::Overflow { page, len } => {
+                        format!("= overflow page {} ({} B)", page, len)
+                    }
+                    NodeValue::None => String::new(),
+                };
+                let flags = node.flag_names();
+                lines.push(format!(
+                    "#{} @{}: {} ({} B) {}{}",
+                    index,
+                    node.offset,
+                    escape(node.key),
+                    node.key.len(),
+                    value,
+                    if flags.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" [{}]", flags.join(" | "))
+                    },
+                ));
+            }
+        }
+        Err(err) => lines.push(format!("{:?}", err)),
+    }
+
+    lines.join("\n")
+}
+
+fn clear_cell(list_item: &gtk::ListItem) {
+    if let Some(label) = list_item
+        .child()
+        .and_then(|hbox| hbox.first_child())
+        .and_downcast::<gtk::Label>()
+    {
+        label.set_label("");
+    }
+}
+
+/// Picks `n` distinct positions out of `n_items` at random, in order, or all
+/// of them if there are no more than `n`.
+fn sample_positions(n_items: u32, n: u32) -> BTreeSet<u32> {
+    if n >= n_items {
+        return (0..n_items).collect();
+    }
+
+    // Floyd's algorithm, to not have to shuffle every position
+    let mut positions = BTreeSet::new();
+    for upper in n_items - n..n_items {
+        let position = glib::random_int_range(0, upper as i32 + 1) as u32;
+        if !positions.insert(position) {
+            positions.insert(upper);
+        }
+    }
+    positions
+}
+
+/// Returns the text shown for the `values` of a decoded field, with strings
+/// shown without quotes.
+fn field_text(values: &[&serde_json::Value]) -> String {
+    values
+        .iter()
+        .map(|value| match value {
+            serde_json::Value::String(string) => string.clone(),
+            value => value.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Returns a chip for a filter labeled `label`, calling `activated` when
+/// clicked, and `removed` when its remove button is clicked.
+fn filter_chip(
+    label: &str,
+    activated: impl Fn() + 'static,
+    removed: impl Fn() + 'static,
+) -> gtk::Widget {
+    let label = gtk::Label::builder()
+        .label(label)
+        .ellipsize(gtk::pango::EllipsizeMode::End)
+        .max_width_chars(20)
+        .build();
+    let button = gtk::Button::builder()
+        .child(&label)
+        .tooltip_text(label.label())
+        .build();
+    button.connect_clicked(move |_| activated());
+
+    let remove_button = gtk::Button::builder()
+        .icon_name("window-close-symbolic")
+        .tooltip_text(gettext("Remove Filter"))
+        .build();
+    remove_button.connect_clicked(move |_| removed());
+
+    let hbox = gtk::Box::builder().css_classes(["linked"]).build();
+    hbox.append(&button);
+    hbox.append(&remove_button);
+    hbox.upcast()
+}
+
+/// Saves the raw bytes of the key of `item`, or its value if `is_key` is
+/// false, to a file chosen in a dialog on `parent`.
+async fn save_item(
+    parent: &impl IsA<gtk::Window>,
+    item: &DatabaseItem,
+    is_key: bool,
+) -> Result<()> {
+    let (title, initial_name, bytes) = if is_key {
+        (gettext("Save Key"), "key.bin", item.key())
+    } else {
+        (gettext("Save Value"), "value.bin", item.data())
+    };
+
+    let dialog = gtk::FileDialog::builder()
+        .title(title)
+        .initial_name(initial_name)
+        .modal(true)
+        .build();
+
+    let file = dialog.save_future(Some(parent)).await?;
+    file.replace_contents_future(bytes, None, false, gio::FileCreateFlags::NONE)
+        .await
+        .map_err(|(_, err)| err)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{sync::Once, time::Instant};
+
+    use crate::config::RESOURCES_FILE;
+
+    /// Creates a window showing the items of `store` as its entries. Like the app, this
+    /// needs the resources and the settings schema to be installed.
+    fn window_showing(store: &gio::ListStore) -> Window {
+        static INIT: Once = Once::new();
+        INIT.call_once(|| {
+            // Keeps changes to the settings bound to the window from being saved
+            std::env::set_var("GSETTINGS_BACKEND", "memory");
+            adw::init().unwrap();
+            let resource = gio::Resource::load(RESOURCES_FILE).expect("Could not load resources");
+            gio::resources_register(&resource);
+        });
+
+        let window = glib::Object::new::<Window>();
+        window.imp().filter_model.set_model(Some(store));
+        window.present();
+        window
+    }
+
+    fn store_of(entries: &[(&str, &str)]) -> gio::ListStore {
+        let store = gio::ListStore::new::<DatabaseItem>();
+        store.extend_from_slice(&items_of(entries));
+        store
+    }
+
+    fn items_of(entries: &[(&str, &str)]) -> Vec<DatabaseItem> {
+        entries
+            .iter()
+            .map(|(key, value)| {
+                DatabaseItem::new(
+                    &glib::Bytes::from(key.as_bytes()),
+                    &glib::Bytes::from(value.as_bytes()),
+                )
+            })
+            .collect()
+    }
+
+    /// Runs the main loop until `condition` holds, so cells get bound.
+    fn run_until(condition: impl Fn() -> bool) {
+        let context = glib::MainContext::default();
+        let start = Instant::now();
+        while !condition() {
+            assert!(
+                start.elapsed() < Duration::from_secs(5),
+                "Timed out waiting for cells"
+            );
+            context.iteration(false);
+        }
+    }
+
+    /// Returns the keys of the items that cells are bound to, which has the
+    /// key of each item twice, once for its key cell and once for its value
+    /// cell.
+    fn bound_keys(window: &Window) -> Vec<String> {
+        let mut keys = window
+            .imp()
+            .cell_handler_ids
+            .borrow()
+            .keys()
+            .filter_map(|list_item| list_item.item().and_downcast::<DatabaseItem>())
+            .map(|item| String::from_utf8(item.key().to_vec()).unwrap())
+            .collect::<Vec<_>>();
+        keys.sort();
+        keys
+    }
+
+    fn keys_twice(keys: &[&str]) -> Vec<String> {
+        let mut keys = keys
+            .iter()
+            .chain(keys)
+            .map(|key| key.to_string())
+            .collect::<Vec<_>>();
+        keys.sort();
+        keys
+    }
+
+    /// Asserts that every key and value cell shows the item it is bound to,
+    /// and that unbound ones are empty.
+    fn assert_cells_show_their_items(window: &Window) {
+        for list_item in window.imp().cell_handler_ids.borrow().keys() {
+            let hbox = list_item.child().unwrap();
+            let label = hbox.first_child().and_downcast::<gtk::Label>().unwrap();
+            // Only key cells end with the note icon
+            let is_key_cell = hbox
+                .last_child()
+                .is_some_and(|child| child.is::<gtk::Image>());
+
+            let expected = list_item.item().map_or(String::new(), |item| {
+                let text = if is_key_cell {
+                    window.key_text(Some(item))
+                } else {
+                    window.value_text(Some(item))
+                };
+                cell_rendering::clamp_lines(&text, window.max_cell_lines())
+            });
+            assert_eq!(label.label(), expected);
+        }
+    }
+
+    #[gtk::test]
+    fn cells_follow_rebound_items() {
+        let store = store_of(&[("a", "1"), ("b", "2"), ("c", "3")]);
+        let window = window_showing(&store);
+
+        run_until(|| bound_keys(&window) == keys_twice(&["a", "b", "c"]));
+        assert_cells_show_their_items(&window);
+
+        // The cells are recycled for the new items
+        store.splice(0, 3, &items_of(&[("d", "4"), ("e", "5"), ("f", "6")]));
+        run_until(|| bound_keys(&window) == keys_twice(&["d", "e", "f"]));
+        assert_cells_show_their_items(&window);
+
+        store.splice(0, 3, &items_of(&[("g", "7")]));
+        run_until(|| bound_keys(&window) == keys_twice(&["g"]));
+        assert_cells_show_their_items(&window);
+
+        // Cells follow changes of how items are shown after being rebound
+        window.set_max_cell_lines(1);
+        window.set_non_printable_display(NonPrintableDisplay::Hex);
+        assert_cells_show_their_items(&window);
+
+        window.destroy();
+    }
+
+    #[gtk::test]
+    fn torn_down_cells_disconnect_handlers() {
+        let store = store_of(&[("a", "1"), ("b", "2")]);
+        let window = window_showing(&store);
+
+        run_until(|| bound_keys(&window) == keys_twice(&["a", "b"]));
+        assert!(!window.imp().cell_handler_ids.borrow().is_empty());
+
+        window.destroy();
+        run_until(|| window.imp().cell_handler_ids.borrow().is_empty());
+
+        // Would update the torn down cells if their handlers were connected
+        window.notify("max-cell-lines");
+        window.notify("non-printable-display");
     }
 }